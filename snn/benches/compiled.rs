@@ -0,0 +1,100 @@
+//! Compares `Network::forward` with `CompiledNetwork::forward_into` on the same networks.
+//! Run with `cargo bench --bench compiled`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use snn::neural::combinator::{
+    AdditionCombinatorNode, AverageCombinatorNode, MaxCombinatorNode, MinCombinatorNode,
+    XORCombinatorNode,
+};
+use snn::neural::mapper::MapperNode;
+use snn::neural::mapper_shared::SharedMapper;
+use snn::neural::network::Network;
+use snn::neural::node::SignalNode;
+
+const ITERATIONS: u32 = 20_000;
+
+/// Few wide dense layers: most of the time goes to table lookups.
+fn wide() -> Network {
+    let squash = SharedMapper::new(MapperNode::new_transformation(|x| (x / 2) as u8));
+    let threshold = SharedMapper::new(MapperNode::new().with_range(128..=255, 255));
+    Network::builder()
+        .input_width(64)
+        .layer_dense(32, |n| {
+            n.shared_mapper(squash.clone())
+                .combinator(Box::new(AverageCombinatorNode::new()))
+        })
+        .layer_dense(16, |n| {
+            n.combinator(Box::new(AdditionCombinatorNode::new()))
+                .activation(threshold.clone())
+        })
+        .layer_dense(8, |n| n.combinator(Box::new(MinCombinatorNode::new())))
+        .layer_dense(1, |n| n.combinator(Box::new(MaxCombinatorNode::new())))
+        .build()
+        .expect("the wide network is valid")
+}
+
+/// Many narrow sparse layers of two-input nodes: per-node overhead dominates.
+fn deep() -> Network {
+    let width = 16;
+    let rotate = SharedMapper::new(MapperNode::new_transformation(|x| (x as u8).rotate_left(1)));
+    let mut builder = Network::builder().input_width(width);
+    for _ in 0..12 {
+        let nodes = (0..width)
+            .map(|_| {
+                SignalNode::builder()
+                    .input_mapper(rotate.clone())
+                    .input_mapper(MapperNode::new_transformation(|x| x as u8 ^ 0x5A))
+                    .combinator(Box::new(XORCombinatorNode::new()))
+                    .build()
+                    .expect("a two-input xor node is valid")
+            })
+            .collect();
+        let wiring = (0..width).map(|i| vec![i, (i + 1) % width]).collect();
+        builder = builder.layer_sparse(nodes, wiring);
+    }
+    builder.build().expect("the deep network is valid")
+}
+
+fn time(width: usize, mut run: impl FnMut(&[u8])) -> Duration {
+    let inputs: Vec<Vec<u8>> = (0..64u8)
+        .map(|seed| {
+            (0..width)
+                .map(|i| seed.wrapping_mul(31).wrapping_add(i as u8))
+                .collect()
+        })
+        .collect();
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        run(&inputs[i as usize % inputs.len()]);
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn compare(name: &str, network: &Network) {
+    let width = network
+        .input_width()
+        .expect("benchmark networks have layers");
+    let interpreted = time(width, |input| {
+        black_box(network.forward(black_box(input)).unwrap());
+    });
+
+    let compiled = network.compile();
+    let (mut scratch, mut output) = (Vec::new(), Vec::new());
+    let flat = time(width, |input| {
+        compiled
+            .forward_into(black_box(input), &mut scratch, &mut output)
+            .unwrap();
+        black_box(&output);
+    });
+
+    println!(
+        "{name:<5} interpreted {interpreted:>10?}  compiled {flat:>10?}  speedup {:.2}x",
+        interpreted.as_secs_f64() / flat.as_secs_f64()
+    );
+}
+
+fn main() {
+    compare("wide", &wide());
+    compare("deep", &deep());
+}
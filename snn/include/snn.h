@@ -0,0 +1,50 @@
+/* C interface to the snn crate. Build the shared and static libraries with `make ffi`, which
+ * runs `cargo rustc -p snn --lib --release --features ffi --crate-type cdylib,staticlib`. */
+#ifndef SNN_H
+#define SNN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes. Every failure also sets the message returned by snn_last_error_message. */
+#define SNN_OK 0
+#define SNN_ERR_NULL_POINTER (-1)
+#define SNN_ERR_INVALID_LENGTH (-2)
+#define SNN_ERR_FORMAT (-3)
+#define SNN_ERR_EVAL (-4)
+#define SNN_ERR_PANIC (-5)
+
+typedef struct SnnMapper SnnMapper;
+typedef struct SnnNetwork SnnNetwork;
+
+/* Message of the last failure on this thread, or NULL. Valid until the next failure. */
+const char *snn_last_error_message(void);
+
+/* Creates a mapper from 256 table entries. Returns NULL on failure. */
+SnnMapper *snn_mapper_new_from(const uint8_t table[256]);
+/* Maps len bytes of input into output; the buffers may be the same. */
+int snn_mapper_transform_slice(const SnnMapper *mapper, const uint8_t *input, uint8_t *output,
+                               size_t len);
+/* Frees a mapper. NULL is ignored. */
+void snn_mapper_free(SnnMapper *mapper);
+
+/* Loads a network from the binary format. Returns NULL on failure. */
+SnnNetwork *snn_network_load_binary(const uint8_t *data, size_t len);
+/* Input and output widths, 0 for a network without layers, -1 for NULL. */
+ptrdiff_t snn_network_input_width(const SnnNetwork *network);
+ptrdiff_t snn_network_output_width(const SnnNetwork *network);
+/* Runs the input through the network; output_len must equal the output width. */
+int snn_network_forward(const SnnNetwork *network, const uint8_t *input, size_t input_len,
+                        uint8_t *output, size_t output_len);
+/* Frees a network. NULL is ignored. */
+void snn_network_free(SnnNetwork *network);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SNN_H */
@@ -0,0 +1,213 @@
+//! Runs a saved network over a byte stream, as a unix filter.
+//!
+//! ```text
+//! snn-run --network net.json [--input FILE] [--window N [--stride N] [--pad]] [--trace]
+//! ```
+//!
+//! Without `--window`, every input byte is fed on its own to a network with one input.
+//! With `--window N`, every window of `N` bytes is fed to a network with `N` inputs.
+//! The outputs of each evaluation are written to stdout one after another.
+//!
+//! Exit codes: 0 on success, 1 when reading input or writing output fails, 2 for bad
+//! arguments, 3 when the network file cannot be read, 4 when it is not a valid network,
+//! and 5 when the network's input width does not fit the chosen mode.
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+use snn::neural::network::Network;
+use snn::neural::network_binary::MAGIC;
+
+const USAGE: &str =
+    "usage: snn-run --network FILE [--input FILE] [--window N [--stride N] [--pad]] [--trace]
+
+  --network FILE  network to run, in the JSON or binary format
+  --input FILE    read bytes from FILE instead of stdin
+  --window N      feed windows of N bytes instead of single bytes
+  --stride N      move windows N bytes at a time (default 1)
+  --pad           also evaluate the last windows, padded with zeros
+  --trace         write every layer's outputs to stderr";
+
+/// A failure with the exit code it maps to.
+struct Failure {
+    code: u8,
+    message: String,
+}
+
+impl Failure {
+    fn new(code: u8, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+struct Options {
+    network: String,
+    input: Option<String>,
+    window: Option<usize>,
+    stride: usize,
+    pad: bool,
+    trace: bool,
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            eprintln!("snn-run: {}", failure.message);
+            ExitCode::from(failure.code)
+        }
+    }
+}
+
+fn run() -> Result<(), Failure> {
+    let Some(options) = parse_args(std::env::args().skip(1))? else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+    let network = load_network(&options.network)?;
+    let data = read_input(options.input.as_deref())?;
+    let width = network.input_width();
+
+    let mut outputs = Vec::new();
+    let mut evaluate = |input: &[u8]| -> Result<(), Failure> {
+        if options.trace {
+            let trace = network
+                .forward_trace(input)
+                .map_err(|e| Failure::new(5, e.to_string()))?;
+            let layers: Vec<String> = trace.iter().map(|layer| format!("{layer:?}")).collect();
+            eprintln!("{input:?} -> {}", layers.join(" -> "));
+        }
+        let output = network
+            .forward(input)
+            .map_err(|e| Failure::new(5, e.to_string()))?;
+        outputs.extend(output);
+        Ok(())
+    };
+    match options.window {
+        None => {
+            if width.is_some_and(|w| w != 1) {
+                return Err(Failure::new(
+                    5,
+                    format!(
+                        "network takes {} inputs; use --window {0} to feed it windows",
+                        width.unwrap()
+                    ),
+                ));
+            }
+            for &byte in &data {
+                evaluate(&[byte])?;
+            }
+        }
+        Some(window) => {
+            if width.is_some_and(|w| w != window) {
+                return Err(Failure::new(
+                    5,
+                    format!(
+                        "network takes {} inputs, but the window is {window} bytes",
+                        width.unwrap()
+                    ),
+                ));
+            }
+            let mut start = 0;
+            while start < data.len() {
+                match data.get(start..start + window) {
+                    Some(slice) => evaluate(slice)?,
+                    None if options.pad => {
+                        let mut padded = data[start..].to_vec();
+                        padded.resize(window, 0);
+                        evaluate(&padded)?;
+                    }
+                    None => break,
+                }
+                start += options.stride;
+            }
+        }
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    stdout
+        .write_all(&outputs)
+        .and_then(|()| stdout.flush())
+        .map_err(|e| Failure::new(1, format!("failed to write output: {e}")))
+}
+
+/// Returns `None` when help was requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, Failure> {
+    let mut network = None;
+    let mut options = Options {
+        network: String::new(),
+        input: None,
+        window: None,
+        stride: 1,
+        pad: false,
+        trace: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| Failure::new(2, format!("{name} needs a value\n{USAGE}")))
+        };
+        match arg.as_str() {
+            "--network" => network = Some(value("--network")?),
+            "--input" => options.input = Some(value("--input")?),
+            "--window" => options.window = Some(parse_positive("--window", &value("--window")?)?),
+            "--stride" => options.stride = parse_positive("--stride", &value("--stride")?)?,
+            "--pad" => options.pad = true,
+            "--trace" => options.trace = true,
+            "-h" | "--help" => return Ok(None),
+            other => {
+                return Err(Failure::new(
+                    2,
+                    format!("unknown argument {other:?}\n{USAGE}"),
+                ));
+            }
+        }
+    }
+    options.network =
+        network.ok_or_else(|| Failure::new(2, format!("--network is required\n{USAGE}")))?;
+    Ok(Some(options))
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<usize, Failure> {
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(Failure::new(
+            2,
+            format!("{name} needs a positive number, got {value:?}"),
+        )),
+    }
+}
+
+/// Loads a network, telling the binary format from JSON by its magic bytes.
+fn load_network(path: &str) -> Result<Network, Failure> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| Failure::new(3, format!("cannot read network {path}: {e}")))?;
+    let network = if bytes.starts_with(&MAGIC) {
+        Network::read_binary(bytes.as_slice())
+    } else {
+        let text = String::from_utf8(bytes).map_err(|_| {
+            Failure::new(4, format!("{path} is neither a binary nor a JSON network"))
+        })?;
+        Network::from_json(&text)
+    };
+    network.map_err(|e| Failure::new(4, format!("invalid network {path}: {e}")))
+}
+
+fn read_input(path: Option<&str>) -> Result<Vec<u8>, Failure> {
+    let mut data = Vec::new();
+    match path {
+        Some(path) => {
+            data = std::fs::read(path)
+                .map_err(|e| Failure::new(1, format!("cannot read input {path}: {e}")))?
+        }
+        None => {
+            std::io::stdin()
+                .lock()
+                .read_to_end(&mut data)
+                .map_err(|e| Failure::new(1, format!("cannot read stdin: {e}")))?;
+        }
+    }
+    Ok(data)
+}
@@ -1 +1,5 @@
-pub mod neural;
\ No newline at end of file
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod neural;
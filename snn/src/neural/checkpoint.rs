@@ -0,0 +1,582 @@
+/// Checkpoints: a network saved together with the progress of the run training it, and the
+/// runtime state of a `StatefulNetwork`.
+///
+/// Both files start with magic bytes, the checkpoint version as a little-endian `u16` and the
+/// version of this crate as a string (`u16` length and UTF-8 bytes).
+///
+/// A network checkpoint starts with `SNNK`. After the header comes a `u8` tag: 0 for no
+/// trainer, 1 for a trainer state and 2 for an evolution state. A trainer state is two
+/// little-endian `u64`s, the random generator state and the generation. An evolution state is
+/// a trainer state followed by the number of individuals and the number of tables in each as
+/// little-endian `u32`s, and then every table as 256 bytes. The rest is the network in the
+/// binary format.
+///
+/// A state checkpoint starts with `SNNS`. After the header comes the number of elements as a
+/// little-endian `u32` and, for every element, a `u8` tag (0 for a node, 1 for a delay and 2
+/// for a stateful combinator) followed by its state as a `u32` length and that many bytes.
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::neural::network::Network;
+use crate::neural::network_binary::{
+    read_array, read_len, read_str, read_table, write_len, write_str,
+};
+use crate::neural::network_format::NetworkFormatError;
+use crate::neural::stateful_network::{ElementState, StatefulNetwork};
+use crate::neural::training::TrainerState;
+use crate::neural::training_genetic::EvolutionState;
+
+/// The bytes every network checkpoint starts with.
+pub const CHECKPOINT_MAGIC: [u8; 4] = *b"SNNK";
+
+/// The bytes every `StatefulNetwork` state checkpoint starts with.
+pub const STATE_CHECKPOINT_MAGIC: [u8; 4] = *b"SNNS";
+
+/// The version of the checkpoint layout.
+pub const CHECKPOINT_VERSION: u16 = 1;
+
+/// The version of this crate, which a checkpoint must match to be loaded.
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A network and, optionally, where the run training it left off.
+pub struct Checkpoint {
+    pub network: Network,
+    /// The random generator and generation of the run, also set for an evolution state.
+    pub trainer: Option<TrainerState>,
+    /// The population of a `GeneticTrainer` run.
+    pub evolution: Option<EvolutionState>,
+}
+
+/// What a network checkpoint holds besides the network.
+#[derive(Clone, Copy)]
+enum Progress<'a> {
+    None,
+    Trainer(&'a TrainerState),
+    Evolution(&'a EvolutionState),
+}
+
+impl<'a> From<Option<&'a TrainerState>> for Progress<'a> {
+    fn from(trainer: Option<&'a TrainerState>) -> Self {
+        trainer.map_or(Progress::None, Progress::Trainer)
+    }
+}
+
+impl Checkpoint {
+    /// Reads a checkpoint, rejecting one written by a different version of this crate.
+    pub fn read(r: impl Read) -> Result<Checkpoint, CheckpointError> {
+        read_checkpoint(r, CRATE_VERSION)
+    }
+}
+
+impl Network {
+    /// Saves the network to a checkpoint file at `path`.
+    pub fn save_checkpoint(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        self.write_checkpoint(std::fs::File::create(path)?, None)
+    }
+
+    /// Saves the network and the state of the run training it to a checkpoint file at `path`.
+    pub fn save_checkpoint_with(
+        &self,
+        path: impl AsRef<Path>,
+        trainer: &TrainerState,
+    ) -> std::io::Result<()> {
+        self.write_checkpoint(std::fs::File::create(path)?, Some(trainer))
+    }
+
+    /// Saves the network and the state of the `GeneticTrainer` run evolving it to a
+    /// checkpoint file at `path`.
+    pub fn save_checkpoint_evolution(
+        &self,
+        path: impl AsRef<Path>,
+        evolution: &EvolutionState,
+    ) -> std::io::Result<()> {
+        self.write_checkpoint_evolution(std::fs::File::create(path)?, evolution)
+    }
+
+    /// Writes a checkpoint of the network and, if given, the trainer state.
+    pub fn write_checkpoint(
+        &self,
+        w: impl Write,
+        trainer: Option<&TrainerState>,
+    ) -> std::io::Result<()> {
+        write_checkpoint(self, w, trainer.into(), CRATE_VERSION)
+    }
+
+    /// Writes a checkpoint of the network and the state of the `GeneticTrainer` run.
+    pub fn write_checkpoint_evolution(
+        &self,
+        w: impl Write,
+        evolution: &EvolutionState,
+    ) -> std::io::Result<()> {
+        write_checkpoint(self, w, Progress::Evolution(evolution), CRATE_VERSION)
+    }
+
+    /// Loads a checkpoint file written by `save_checkpoint`, `save_checkpoint_with` or
+    /// `save_checkpoint_evolution`.
+    pub fn load_checkpoint(path: impl AsRef<Path>) -> Result<Checkpoint, CheckpointError> {
+        let file = std::fs::File::open(path).map_err(NetworkFormatError::from)?;
+        Checkpoint::read(BufReader::new(file))
+    }
+}
+
+impl StatefulNetwork {
+    /// Saves the state of every delay and stateful combinator to a checkpoint file at `path`.
+    /// The structure of the network is not saved, so it has to be built again before the
+    /// state can be loaded into it.
+    pub fn save_checkpoint(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        self.write_checkpoint(std::fs::File::create(path)?)
+    }
+
+    /// Writes a checkpoint of the state of every delay and stateful combinator.
+    pub fn write_checkpoint(&self, w: impl Write) -> std::io::Result<()> {
+        write_state_checkpoint(self, w, CRATE_VERSION)
+    }
+
+    /// Loads a checkpoint file written by `save_checkpoint` into this network, which must be
+    /// built the same way as the network that saved it. On error the state is left unchanged.
+    pub fn load_checkpoint(&mut self, path: impl AsRef<Path>) -> Result<(), CheckpointError> {
+        let file = std::fs::File::open(path).map_err(NetworkFormatError::from)?;
+        self.read_checkpoint(BufReader::new(file))
+    }
+
+    /// Reads a checkpoint written by `write_checkpoint` into this network.
+    pub fn read_checkpoint(&mut self, r: impl Read) -> Result<(), CheckpointError> {
+        let states = read_state_checkpoint(r, CRATE_VERSION)?;
+        self.restore_element_states(&states)
+            .map_err(CheckpointError::Mismatch)
+    }
+}
+
+fn write_header(w: &mut impl Write, magic: [u8; 4], crate_version: &str) -> std::io::Result<()> {
+    w.write_all(&magic)?;
+    w.write_all(&CHECKPOINT_VERSION.to_le_bytes())?;
+    write_str(w, crate_version)
+}
+
+fn read_header(
+    r: &mut impl Read,
+    magic: [u8; 4],
+    crate_version: &str,
+) -> Result<(), CheckpointError> {
+    if read_array(r)? != magic {
+        return Err(NetworkFormatError::InvalidFormat(
+            "missing checkpoint magic bytes".to_string(),
+        )
+        .into());
+    }
+    let version = u16::from_le_bytes(read_array(r)?);
+    if version != CHECKPOINT_VERSION {
+        return Err(NetworkFormatError::UnsupportedVersion(version as u64).into());
+    }
+    let found = read_str(r)?;
+    if found != crate_version {
+        return Err(CheckpointError::VersionMismatch {
+            expected: crate_version.to_string(),
+            found,
+        });
+    }
+    Ok(())
+}
+
+fn write_trainer(w: &mut impl Write, state: &TrainerState) -> std::io::Result<()> {
+    w.write_all(&state.rng_state().to_le_bytes())?;
+    w.write_all(&state.generation.to_le_bytes())
+}
+
+fn read_trainer(r: &mut impl Read) -> Result<TrainerState, NetworkFormatError> {
+    let rng_state = u64::from_le_bytes(read_array(r)?);
+    let generation = u64::from_le_bytes(read_array(r)?);
+    Ok(TrainerState::from_parts(rng_state, generation))
+}
+
+fn write_checkpoint(
+    network: &Network,
+    w: impl Write,
+    progress: Progress,
+    crate_version: &str,
+) -> std::io::Result<()> {
+    let mut w = BufWriter::new(w);
+    write_header(&mut w, CHECKPOINT_MAGIC, crate_version)?;
+    match progress {
+        Progress::None => w.write_all(&[0])?,
+        Progress::Trainer(state) => {
+            w.write_all(&[1])?;
+            write_trainer(&mut w, state)?;
+        }
+        Progress::Evolution(state) => {
+            w.write_all(&[2])?;
+            write_trainer(&mut w, &state.trainer)?;
+            write_len(&mut w, state.population.len())?;
+            write_len(&mut w, state.population.first().map_or(0, Vec::len))?;
+            for table in state.population.iter().flatten() {
+                w.write_all(table.table())?;
+            }
+        }
+    }
+    network.write_binary(&mut w)?;
+    w.flush()
+}
+
+fn read_checkpoint(mut r: impl Read, crate_version: &str) -> Result<Checkpoint, CheckpointError> {
+    read_header(&mut r, CHECKPOINT_MAGIC, crate_version)?;
+    let (trainer, evolution) = match read_array::<1>(&mut r)? {
+        [0] => (None, None),
+        [1] => (Some(read_trainer(&mut r)?), None),
+        [2] => {
+            let trainer = read_trainer(&mut r)?;
+            let individuals = read_len(&mut r)?;
+            let tables = read_len(&mut r)?;
+            // The counts are untrusted, so the population only grows as tables are read.
+            let mut population = Vec::new();
+            for _ in 0..individuals {
+                let mut individual = Vec::new();
+                for _ in 0..tables {
+                    individual.push(read_table(&mut r)?);
+                }
+                population.push(individual);
+            }
+            let evolution = EvolutionState {
+                trainer,
+                population,
+            };
+            (Some(trainer), Some(evolution))
+        }
+        [other] => {
+            return Err(
+                NetworkFormatError::InvalidFormat(format!("invalid trainer tag {other}")).into(),
+            );
+        }
+    };
+    let network = Network::read_binary(r)?;
+    Ok(Checkpoint {
+        network,
+        trainer,
+        evolution,
+    })
+}
+
+fn write_state_checkpoint(
+    network: &StatefulNetwork,
+    w: impl Write,
+    crate_version: &str,
+) -> std::io::Result<()> {
+    let mut w = BufWriter::new(w);
+    write_header(&mut w, STATE_CHECKPOINT_MAGIC, crate_version)?;
+    let states = network.element_states();
+    write_len(&mut w, states.len())?;
+    for state in &states {
+        let (tag, bytes): (u8, &[u8]) = match state {
+            ElementState::Node => (0, &[]),
+            ElementState::Delay(values) => (1, values),
+            ElementState::Stateful(bytes) => (2, bytes),
+        };
+        w.write_all(&[tag])?;
+        write_len(&mut w, bytes.len())?;
+        w.write_all(bytes)?;
+    }
+    w.flush()
+}
+
+fn read_state_checkpoint(
+    mut r: impl Read,
+    crate_version: &str,
+) -> Result<Vec<ElementState>, CheckpointError> {
+    read_header(&mut r, STATE_CHECKPOINT_MAGIC, crate_version)?;
+    let count = read_len(&mut r)?;
+    let mut states = Vec::new();
+    for _ in 0..count {
+        let [tag] = read_array(&mut r)?;
+        let len = read_len(&mut r)?;
+        // The length is untrusted, so only allocate for bytes that are actually there.
+        let mut bytes = Vec::new();
+        (&mut r)
+            .take(len as u64)
+            .read_to_end(&mut bytes)
+            .map_err(NetworkFormatError::from)?;
+        if bytes.len() != len {
+            return Err(NetworkFormatError::Truncated.into());
+        }
+        states.push(match tag {
+            0 if bytes.is_empty() => ElementState::Node,
+            1 => ElementState::Delay(bytes),
+            2 => ElementState::Stateful(bytes),
+            _ => {
+                return Err(NetworkFormatError::InvalidFormat(format!(
+                    "invalid element {tag} with {len} bytes of state"
+                ))
+                .into());
+            }
+        });
+    }
+    Ok(states)
+}
+
+/// Error returned when a checkpoint cannot be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// The checkpoint was written by a different version of this crate.
+    VersionMismatch { expected: String, found: String },
+    /// The checkpoint could not be read or is malformed.
+    Format(NetworkFormatError),
+    /// The state checkpoint was saved from a network built differently.
+    Mismatch(String),
+}
+
+impl From<NetworkFormatError> for CheckpointError {
+    fn from(e: NetworkFormatError) -> Self {
+        CheckpointError::Format(e)
+    }
+}
+
+impl std::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckpointError::VersionMismatch { expected, found } => write!(
+                f,
+                "checkpoint was written by version {found}, this is version {expected}"
+            ),
+            CheckpointError::Format(e) => write!(f, "invalid checkpoint: {e}"),
+            CheckpointError::Mismatch(reason) => {
+                write!(f, "checkpoint does not fit the network: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CheckpointError::Format(e) => Some(e),
+            CheckpointError::VersionMismatch { .. } | CheckpointError::Mismatch(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode};
+    use crate::neural::combinator_stateful::{EmaCombinatorNode, Windowed};
+    use crate::neural::mapper::MapperNode;
+    use crate::neural::node::SignalNode;
+    use crate::neural::stateful_network::{DelayNode, Source};
+    use crate::neural::training::{AbsoluteLoss, Dataset, HillClimber};
+    use crate::neural::training_genetic::GeneticTrainer;
+
+    fn blank_network() -> Network {
+        Network::builder()
+            .input_width(1)
+            .layer_dense(1, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+                    .activation(MapperNode::new())
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn dataset() -> Dataset {
+        (0..=250u8)
+            .step_by(5)
+            .map(|x| (vec![x], vec![255 - x]))
+            .collect()
+    }
+
+    /// A running sum over the last three ticks' sums, an average of the input and the
+    /// maximum of that average over four ticks.
+    fn stateful_network(delay: usize) -> StatefulNetwork {
+        let mut builder = StatefulNetwork::builder(1);
+        let delay = builder.add_delay(DelayNode::new(delay), Source::Element(1));
+        let sum = builder.add_node(
+            SignalNode::builder()
+                .combinator(Box::new(AdditionCombinatorNode::new()))
+                .build()
+                .unwrap(),
+            vec![Source::External(0), Source::Element(delay)],
+        );
+        let ema = builder.add_stateful(EmaCombinatorNode::new(1, 4), vec![Source::External(0)]);
+        let max = builder.add_stateful(
+            Windowed::new(MaxCombinatorNode::new(), 4),
+            vec![Source::Element(ema)],
+        );
+        for id in [sum, ema, max] {
+            builder.add_output(Source::Element(id));
+        }
+        builder.build().unwrap()
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("snn-{}-{name}.ckpt", std::process::id()))
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted_run() {
+        let dataset = dataset();
+        let climber = HillClimber::new(AbsoluteLoss);
+
+        let mut uninterrupted = blank_network();
+        climber.train(&mut uninterrupted, &dataset, 3000, 21);
+
+        let mut network = blank_network();
+        let mut state = TrainerState::new(21);
+        let first = climber.train_resumable(&mut network, &dataset, 1500, &mut state);
+        assert_eq!(state.generation, 1500);
+        assert!(first.final_loss > 0);
+        let path = temp_path("resume");
+        network.save_checkpoint_with(&path, &state).unwrap();
+        drop(network);
+
+        let checkpoint = Network::load_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(checkpoint.trainer, Some(state));
+        let mut network = checkpoint.network;
+        let mut state = checkpoint.trainer.unwrap();
+        let second = climber.train_resumable(&mut network, &dataset, 1500, &mut state);
+        assert_eq!(second.initial_loss, first.final_loss);
+
+        #[cfg(feature = "json")]
+        assert_eq!(network.to_json(), uninterrupted.to_json());
+        assert_eq!(
+            dataset.loss(&network, &AbsoluteLoss),
+            dataset.loss(&uninterrupted, &AbsoluteLoss)
+        );
+    }
+
+    #[test]
+    fn test_evolution_resume_matches_uninterrupted_run() {
+        let dataset = dataset();
+        let trainer = GeneticTrainer::new(AbsoluteLoss).with_population_size(8);
+
+        let mut uninterrupted = blank_network();
+        let whole = trainer.train(&mut uninterrupted, &dataset, 20, 5);
+        assert_eq!(whole.history.len(), 21);
+
+        let mut network = blank_network();
+        let mut state = EvolutionState::new(5);
+        trainer.train_resumable(&mut network, &dataset, 8, &mut state);
+        let path = temp_path("evolution");
+        network.save_checkpoint_evolution(&path, &state).unwrap();
+        drop(network);
+
+        let checkpoint = Network::load_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(checkpoint.trainer, Some(state.trainer));
+        assert!(checkpoint.evolution == Some(state));
+        let mut network = checkpoint.network;
+        let mut state = checkpoint.evolution.unwrap();
+        let second = trainer.train_resumable(&mut network, &dataset, 12, &mut state);
+        assert_eq!(second.final_loss, whole.final_loss);
+        assert_eq!(second.history[..], whole.history[8..]);
+
+        #[cfg(feature = "json")]
+        assert_eq!(network.to_json(), uninterrupted.to_json());
+        assert_eq!(
+            dataset.loss(&network, &AbsoluteLoss),
+            dataset.loss(&uninterrupted, &AbsoluteLoss)
+        );
+    }
+
+    #[test]
+    fn test_stateful_resume_matches_uninterrupted_run() {
+        let inputs: Vec<u8> = (0..40u8).map(|x| x.wrapping_mul(37) % 50).collect();
+        let mut uninterrupted = stateful_network(3);
+        let expected: Vec<Vec<u8>> = inputs.iter().map(|&x| uninterrupted.tick(&[x])).collect();
+
+        let mut network = stateful_network(3);
+        let mut outputs: Vec<Vec<u8>> = inputs[..17].iter().map(|&x| network.tick(&[x])).collect();
+        let path = temp_path("stateful");
+        network.save_checkpoint(&path).unwrap();
+        drop(network);
+
+        let mut network = stateful_network(3);
+        network.load_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        outputs.extend(inputs[17..].iter().map(|&x| network.tick(&[x])));
+        assert_eq!(outputs, expected);
+    }
+
+    #[test]
+    fn test_stateful_mismatch() {
+        let mut network = stateful_network(3);
+        for x in 1..10 {
+            network.tick(&[x]);
+        }
+        let mut bytes = Vec::new();
+        network.write_checkpoint(&mut bytes).unwrap();
+
+        let mut other = stateful_network(2);
+        let error = other.read_checkpoint(bytes.as_slice()).err().unwrap();
+        assert_eq!(
+            error,
+            CheckpointError::Mismatch("element 0 does not match the saved state".to_string())
+        );
+        assert_eq!(
+            error.to_string(),
+            "checkpoint does not fit the network: element 0 does not match the saved state"
+        );
+        // The failed load left the fresh state alone.
+        assert_eq!(other.tick(&[5]), stateful_network(2).tick(&[5]));
+
+        assert_eq!(
+            other.read_checkpoint(&bytes[..bytes.len() - 1]).err(),
+            Some(CheckpointError::Format(NetworkFormatError::Truncated))
+        );
+        let mut plain = Vec::new();
+        blank_network().write_checkpoint(&mut plain, None).unwrap();
+        assert!(matches!(
+            other.read_checkpoint(plain.as_slice()).err(),
+            Some(CheckpointError::Format(NetworkFormatError::InvalidFormat(
+                _
+            )))
+        ));
+    }
+
+    #[test]
+    fn test_without_trainer() {
+        let path = temp_path("plain");
+        blank_network().save_checkpoint(&path).unwrap();
+        let checkpoint = Network::load_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(checkpoint.trainer, None);
+        assert!(checkpoint.evolution.is_none());
+        #[cfg(feature = "json")]
+        assert_eq!(checkpoint.network.to_json(), blank_network().to_json());
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let mut bytes = Vec::new();
+        write_checkpoint(&blank_network(), &mut bytes, Progress::None, "0.0.0-other").unwrap();
+        let error = Checkpoint::read(bytes.as_slice()).err().unwrap();
+        assert_eq!(
+            error,
+            CheckpointError::VersionMismatch {
+                expected: CRATE_VERSION.to_string(),
+                found: "0.0.0-other".to_string()
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "checkpoint was written by version 0.0.0-other, this is version {CRATE_VERSION}"
+            )
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        let mut bytes = Vec::new();
+        blank_network()
+            .write_checkpoint(&mut bytes, Some(&TrainerState::new(1)))
+            .unwrap();
+        assert_eq!(
+            Checkpoint::read(&bytes[..bytes.len() - 1]).err(),
+            Some(CheckpointError::Format(NetworkFormatError::Truncated))
+        );
+        assert!(matches!(
+            Checkpoint::read(&b"SNNB"[..]).err(),
+            Some(CheckpointError::Format(NetworkFormatError::InvalidFormat(
+                _
+            )))
+        ));
+    }
+}
@@ -1,28 +1,612 @@
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+use crate::neural::combinator_bits::{
+    AnyBitCombinatorNode, BitPackCombinatorNode, ParityCombinatorNode, PopcountCombinatorNode,
+};
+use crate::neural::combinator_compare::{
+    AbsDiffCombinatorNode, EqualCombinatorNode, GreaterThanCombinatorNode,
+};
+use crate::neural::combinator_fold::SaturatingSubCombinatorNode;
+use crate::neural::combinator_routing::{
+    ConstantCombinatorNode, FirstCombinatorNode, LastCombinatorNode, MuxCombinatorNode,
+    SelectCombinatorNode,
+};
+use crate::neural::combinator_stats::{
+    ArgMaxCombinatorNode, ArgMinCombinatorNode, GeometricMeanCombinatorNode,
+    HarmonicMeanCombinatorNode, ModeCombinatorNode, NthCombinatorNode, PercentileCombinatorNode,
+    RangeCombinatorNode, StdDevCombinatorNode, TopKAverageCombinatorNode,
+    TrimmedMeanCombinatorNode, most_frequent,
+};
+use crate::neural::combinator_wide::{
+    CountAboveCombinatorNode, CountEqualCombinatorNode, CountNonZeroCombinatorNode,
+};
 use crate::neural::traits::Combinator;
 
-pub fn combinator_from_str(name: &str) -> Option<Box<dyn Combinator>> {
-    match name.to_lowercase().as_str() {
-        AdditionCombinatorNode::IDENTIFIER | "add" | "+" => {
-            Some(Box::new(AdditionCombinatorNode {}))
+/// Creates a combinator from its identifier or one of its aliases, case-insensitively.
+/// Configurable combinators accept their parameters after a colon, e.g. `"weighted:3,1,1"`.
+pub fn combinator_from_str(name: &str) -> Result<Box<dyn Combinator>, CombinatorParseError> {
+    let lower = name.trim().to_lowercase();
+    let (name, args) = match lower.split_once(':') {
+        Some((name, args)) => (name.trim(), Some(args)),
+        None => (lower.as_str(), None),
+    };
+    let builtin = BUILTINS
+        .iter()
+        .find(|b| b.identifier == name || b.aliases.contains(&name))
+        .ok_or_else(|| CombinatorParseError::UnknownName(name.to_string()))?;
+    (builtin.factory)(args)
+}
+
+/// Like `combinator_from_str`, but only accepts a canonical identifier, exactly as
+/// `Combinator::identifier` returns it, for linting configurations. An alias, a different case
+/// or surrounding whitespace is rejected with the closest canonical identifier as a suggestion.
+pub fn combinator_from_str_strict(name: &str) -> Result<Box<dyn Combinator>, CombinatorParseError> {
+    let (name, args) = match name.split_once(':') {
+        Some((name, args)) => (name, Some(args)),
+        None => (name, None),
+    };
+    match BUILTINS.iter().find(|b| b.identifier == name) {
+        Some(builtin) => (builtin.factory)(args),
+        None => Err(CombinatorParseError::NotCanonical {
+            name: name.to_string(),
+            suggestion: closest_identifier(name).map(str::to_string),
+        }),
+    }
+}
+
+/// Returns the identifier of the built-in that `name` is an alias of, or else the identifier
+/// or alias closest to `name` by edit distance, if it is close enough to be a likely typo.
+fn closest_identifier(name: &str) -> Option<&'static str> {
+    let lower = name.trim().to_lowercase();
+    if let Some(builtin) = BUILTINS
+        .iter()
+        .find(|b| b.identifier == lower || b.aliases.contains(&lower.as_str()))
+    {
+        return Some(builtin.identifier);
+    }
+    BUILTINS
+        .iter()
+        .flat_map(|b| {
+            core::iter::once(b.identifier)
+                .chain(b.aliases.iter().copied())
+                .map(|candidate| (edit_distance(&lower, candidate), b.identifier))
+        })
+        .filter(|&(distance, _)| distance <= 1 + lower.chars().count() / 3)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, identifier)| identifier)
+}
+
+/// Returns the number of single-character insertions, deletions, substitutions and adjacent
+/// swaps needed to turn `a` into `b`, without editing any character twice.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    // Rows i - 2, i - 1 and i of the distance matrix between prefixes of `a` and `b`.
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = Vec::with_capacity(b.len() + 1);
+        current.push(i);
+        for j in 1..=b.len() {
+            let mut distance = (previous[j - 1] + usize::from(a[i - 1] != b[j - 1]))
+                .min(previous[j] + 1)
+                .min(current[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(before[j - 2] + 1);
+            }
+            current.push(distance);
+        }
+        before = core::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+/// Describes a built-in combinator that `combinator_from_str` understands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombinatorInfo {
+    /// The canonical identifier.
+    pub identifier: &'static str,
+    /// Alternative names for the same combinator.
+    pub aliases: &'static [&'static str],
+    /// A one-line description.
+    pub description: &'static str,
+    /// A string that `combinator_from_str` accepts, showing any arguments.
+    pub example: &'static str,
+}
+
+/// Lists every built-in combinator with its aliases and a short description.
+pub fn available_combinators() -> Vec<CombinatorInfo> {
+    BUILTINS
+        .iter()
+        .map(|b| CombinatorInfo {
+            identifier: b.identifier,
+            aliases: b.aliases,
+            description: b.description,
+            example: b.example,
+        })
+        .collect()
+}
+
+/// Like `combinator_from_str`, but discards the error.
+pub fn combinator_from_str_opt(name: &str) -> Option<Box<dyn Combinator>> {
+    combinator_from_str(name).ok()
+}
+
+/// Error returned when a combinator description cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CombinatorParseError {
+    /// No combinator is known by this name.
+    UnknownName(String),
+    /// The combinator exists, but its arguments are missing or malformed.
+    InvalidArguments {
+        name: String,
+        args: String,
+        reason: String,
+    },
+    /// Strict parsing found no combinator with exactly this identifier.
+    NotCanonical {
+        name: String,
+        /// The canonical identifier that was probably meant.
+        suggestion: Option<String>,
+    },
+}
+
+impl CombinatorParseError {
+    pub(crate) fn invalid_arguments(name: &str, args: &str, reason: impl Into<String>) -> Self {
+        CombinatorParseError::InvalidArguments {
+            name: name.to_string(),
+            args: args.to_string(),
+            reason: reason.into(),
         }
-        MultiplicationCombinatorNode::IDENTIFIER | "multiplication" | "*" => {
-            Some(Box::new(MultiplicationCombinatorNode {}))
+    }
+}
+
+impl core::fmt::Display for CombinatorParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CombinatorParseError::UnknownName(name) => write!(f, "unknown combinator {name:?}"),
+            CombinatorParseError::InvalidArguments { name, args, reason } => {
+                write!(
+                    f,
+                    "invalid arguments {args:?} for combinator {name:?}: {reason}"
+                )
+            }
+            CombinatorParseError::NotCanonical { name, suggestion } => {
+                write!(f, "{name:?} is not a canonical combinator identifier")?;
+                match suggestion {
+                    Some(suggestion) => write!(f, "; did you mean {suggestion:?}?"),
+                    None => Ok(()),
+                }
+            }
         }
-        MaxCombinatorNode::IDENTIFIER => Some(Box::new(MaxCombinatorNode {})),
-        MinCombinatorNode::IDENTIFIER => Some(Box::new(MinCombinatorNode {})),
-        AverageCombinatorNode::IDENTIFIER | "avg" => Some(Box::new(AverageCombinatorNode {})),
-        MedianCombinatorNode::IDENTIFIER => Some(Box::new(MedianCombinatorNode {})),
-        ORCombinatorNode::IDENTIFIER => Some(Box::new(ORCombinatorNode {})),
-        ANDCombinatorNode::IDENTIFIER => Some(Box::new(ANDCombinatorNode {})),
-        XORCombinatorNode::IDENTIFIER => Some(Box::new(XORCombinatorNode {})),
-        NANDCombinatorNode::IDENTIFIER => Some(Box::new(NANDCombinatorNode {})),
-        NORCombinatorNode::IDENTIFIER => Some(Box::new(NORCombinatorNode {})),
-        XNORCombinatorNode::IDENTIFIER => Some(Box::new(XNORCombinatorNode {})),
-        _ => None,
+    }
+}
+
+impl core::error::Error for CombinatorParseError {}
+
+/// Constructs a built-in combinator from its optional argument string.
+pub(crate) type BuiltinFactory =
+    fn(Option<&str>) -> Result<Box<dyn Combinator>, CombinatorParseError>;
+
+/// An entry in the table of built-in combinators.
+pub(crate) struct Builtin {
+    /// The canonical identifier.
+    pub(crate) identifier: &'static str,
+    /// Alternative names accepted by `combinator_from_str`.
+    pub(crate) aliases: &'static [&'static str],
+    /// A one-line description.
+    pub(crate) description: &'static str,
+    /// A string that `combinator_from_str` accepts, showing any arguments.
+    pub(crate) example: &'static str,
+    /// Constructs the combinator.
+    pub(crate) factory: BuiltinFactory,
+}
+
+/// Every built-in combinator. This is the single source of truth for name lookups.
+pub(crate) const BUILTINS: &[Builtin] = &[
+    Builtin {
+        identifier: AdditionCombinatorNode::IDENTIFIER,
+        description: "Sums the inputs, saturating at 255.",
+        example: AdditionCombinatorNode::IDENTIFIER,
+        aliases: &["add", "+", "sum", "plus"],
+        factory: without_args::<AdditionCombinatorNode>,
+    },
+    Builtin {
+        identifier: MultiplicationCombinatorNode::IDENTIFIER,
+        description: "Multiplies the inputs, saturating at 255.",
+        example: MultiplicationCombinatorNode::IDENTIFIER,
+        aliases: &["multiplication", "mul", "*", "×", "product"],
+        factory: without_args::<MultiplicationCombinatorNode>,
+    },
+    Builtin {
+        identifier: SaturatingAdditionCombinatorNode::IDENTIFIER,
+        description: "Sums the inputs, saturating at 255.",
+        example: SaturatingAdditionCombinatorNode::IDENTIFIER,
+        aliases: &["saturating_add"],
+        factory: without_args::<SaturatingAdditionCombinatorNode>,
+    },
+    Builtin {
+        identifier: WrappingAdditionCombinatorNode::IDENTIFIER,
+        description: "Sums the inputs modulo 256.",
+        example: WrappingAdditionCombinatorNode::IDENTIFIER,
+        aliases: &["wrapping_add"],
+        factory: without_args::<WrappingAdditionCombinatorNode>,
+    },
+    Builtin {
+        identifier: SaturatingSubCombinatorNode::IDENTIFIER,
+        description: "Subtracts the other inputs from the first, left to right, stopping at 0.",
+        example: SaturatingSubCombinatorNode::IDENTIFIER,
+        aliases: &["saturating_sub"],
+        factory: without_args::<SaturatingSubCombinatorNode>,
+    },
+    Builtin {
+        identifier: SaturatingMultiplicationCombinatorNode::IDENTIFIER,
+        description: "Multiplies the inputs, saturating at 255.",
+        example: SaturatingMultiplicationCombinatorNode::IDENTIFIER,
+        aliases: &["saturating_mul"],
+        factory: without_args::<SaturatingMultiplicationCombinatorNode>,
+    },
+    Builtin {
+        identifier: WrappingMultiplicationCombinatorNode::IDENTIFIER,
+        description: "Multiplies the inputs modulo 256.",
+        example: WrappingMultiplicationCombinatorNode::IDENTIFIER,
+        aliases: &["wrapping_mul"],
+        factory: without_args::<WrappingMultiplicationCombinatorNode>,
+    },
+    Builtin {
+        identifier: MaxCombinatorNode::IDENTIFIER,
+        description: "Returns the largest input.",
+        example: MaxCombinatorNode::IDENTIFIER,
+        aliases: &["maximum"],
+        factory: without_args::<MaxCombinatorNode>,
+    },
+    Builtin {
+        identifier: MinCombinatorNode::IDENTIFIER,
+        description: "Returns the smallest input.",
+        example: MinCombinatorNode::IDENTIFIER,
+        aliases: &["minimum"],
+        factory: without_args::<MinCombinatorNode>,
+    },
+    Builtin {
+        identifier: AverageCombinatorNode::IDENTIFIER,
+        description: "Returns the mean of the inputs, rounded down.",
+        example: AverageCombinatorNode::IDENTIFIER,
+        aliases: &["avg", "mean"],
+        factory: without_args::<AverageCombinatorNode>,
+    },
+    Builtin {
+        identifier: WeightedAverageCombinatorNode::IDENTIFIER,
+        description: "Returns the weighted mean of the inputs; weights are optional, e.g. weighted:3,1,1.",
+        example: "weighted:3,1,1",
+        aliases: &["weighted_average"],
+        factory: |args| match args {
+            Some(args) => Ok(Box::new(WeightedAverageCombinatorNode::from_args(args)?)),
+            None => Ok(Box::new(WeightedAverageCombinatorNode::new(Vec::new()))),
+        },
+    },
+    Builtin {
+        identifier: ThresholdCombinatorNode::IDENTIFIER,
+        description: "Returns high when the sum reaches the threshold, e.g. threshold:300 or threshold:300:0:255.",
+        example: "threshold:300",
+        aliases: &[],
+        factory: |args| {
+            Ok(Box::new(ThresholdCombinatorNode::from_args(
+                args.unwrap_or(""),
+            )?))
+        },
+    },
+    Builtin {
+        identifier: MedianCombinatorNode::IDENTIFIER,
+        description: "Returns the median of the inputs.",
+        example: MedianCombinatorNode::IDENTIFIER,
+        aliases: &[],
+        factory: without_args::<MedianCombinatorNode>,
+    },
+    Builtin {
+        identifier: MajorityVoteCombinatorNode::IDENTIFIER,
+        description: "Returns the most common input, preferring the smaller value on ties.",
+        example: MajorityVoteCombinatorNode::IDENTIFIER,
+        aliases: &["vote"],
+        factory: without_args::<MajorityVoteCombinatorNode>,
+    },
+    Builtin {
+        identifier: BitMajorityCombinatorNode::IDENTIFIER,
+        description: "Sets each bit that is set in more than half of the inputs.",
+        example: BitMajorityCombinatorNode::IDENTIFIER,
+        aliases: &["bitwise_majority"],
+        factory: without_args::<BitMajorityCombinatorNode>,
+    },
+    Builtin {
+        identifier: ORCombinatorNode::IDENTIFIER,
+        description: "Bitwise OR of the inputs.",
+        example: ORCombinatorNode::IDENTIFIER,
+        aliases: &["|"],
+        factory: without_args::<ORCombinatorNode>,
+    },
+    Builtin {
+        identifier: ANDCombinatorNode::IDENTIFIER,
+        description: "Bitwise AND of the inputs.",
+        example: ANDCombinatorNode::IDENTIFIER,
+        aliases: &["&"],
+        factory: without_args::<ANDCombinatorNode>,
+    },
+    Builtin {
+        identifier: XORCombinatorNode::IDENTIFIER,
+        description: "Bitwise XOR of the inputs.",
+        example: XORCombinatorNode::IDENTIFIER,
+        aliases: &["^"],
+        factory: without_args::<XORCombinatorNode>,
+    },
+    Builtin {
+        identifier: NANDCombinatorNode::IDENTIFIER,
+        description: "Bitwise NAND of the inputs.",
+        example: NANDCombinatorNode::IDENTIFIER,
+        aliases: &[],
+        factory: without_args::<NANDCombinatorNode>,
+    },
+    Builtin {
+        identifier: NORCombinatorNode::IDENTIFIER,
+        description: "Bitwise NOR of the inputs.",
+        example: NORCombinatorNode::IDENTIFIER,
+        aliases: &[],
+        factory: without_args::<NORCombinatorNode>,
+    },
+    Builtin {
+        identifier: XNORCombinatorNode::IDENTIFIER,
+        description: "Bitwise XNOR of the inputs.",
+        example: XNORCombinatorNode::IDENTIFIER,
+        aliases: &[],
+        factory: without_args::<XNORCombinatorNode>,
+    },
+    Builtin {
+        identifier: ModeCombinatorNode::IDENTIFIER,
+        description: "Returns the most frequent input, preferring the smaller value on ties.",
+        example: ModeCombinatorNode::IDENTIFIER,
+        aliases: &["most_frequent"],
+        factory: without_args::<ModeCombinatorNode>,
+    },
+    Builtin {
+        identifier: RangeCombinatorNode::IDENTIFIER,
+        description: "Returns the largest input minus the smallest input.",
+        example: RangeCombinatorNode::IDENTIFIER,
+        aliases: &["spread"],
+        factory: without_args::<RangeCombinatorNode>,
+    },
+    Builtin {
+        identifier: StdDevCombinatorNode::IDENTIFIER,
+        description: "Returns the population standard deviation of the inputs.",
+        example: StdDevCombinatorNode::IDENTIFIER,
+        aliases: &["standard_deviation"],
+        factory: without_args::<StdDevCombinatorNode>,
+    },
+    Builtin {
+        identifier: GeometricMeanCombinatorNode::IDENTIFIER,
+        description: "Returns the geometric mean of the inputs; any zero input yields 0.",
+        example: GeometricMeanCombinatorNode::IDENTIFIER,
+        aliases: &["gmean"],
+        factory: without_args::<GeometricMeanCombinatorNode>,
+    },
+    Builtin {
+        identifier: HarmonicMeanCombinatorNode::IDENTIFIER,
+        description: "Returns the harmonic mean of the inputs; any zero input yields 0.",
+        example: HarmonicMeanCombinatorNode::IDENTIFIER,
+        aliases: &["hmean"],
+        factory: without_args::<HarmonicMeanCombinatorNode>,
+    },
+    Builtin {
+        identifier: ArgMaxCombinatorNode::IDENTIFIER,
+        description: "Returns the index of the largest input; the first occurrence wins ties.",
+        example: ArgMaxCombinatorNode::IDENTIFIER,
+        aliases: &["arg_max"],
+        factory: without_args::<ArgMaxCombinatorNode>,
+    },
+    Builtin {
+        identifier: ArgMinCombinatorNode::IDENTIFIER,
+        description: "Returns the index of the smallest input; the first occurrence wins ties.",
+        example: ArgMinCombinatorNode::IDENTIFIER,
+        aliases: &["arg_min"],
+        factory: without_args::<ArgMinCombinatorNode>,
+    },
+    Builtin {
+        identifier: TopKAverageCombinatorNode::IDENTIFIER,
+        description: "Averages the k largest inputs, e.g. topk:3.",
+        example: "topk:3",
+        aliases: &["top_k"],
+        factory: |args| {
+            Ok(Box::new(TopKAverageCombinatorNode::from_args(
+                args.unwrap_or(""),
+            )?))
+        },
+    },
+    Builtin {
+        identifier: TrimmedMeanCombinatorNode::IDENTIFIER,
+        description: "Averages the inputs after dropping the n smallest and largest, e.g. trimmed:1.",
+        example: "trimmed:1",
+        aliases: &["trimmed_mean"],
+        factory: |args| {
+            Ok(Box::new(TrimmedMeanCombinatorNode::from_args(
+                args.unwrap_or(""),
+            )?))
+        },
+    },
+    Builtin {
+        identifier: PopcountCombinatorNode::IDENTIFIER,
+        description: "Counts the set bits across all inputs, saturating at 255.",
+        example: PopcountCombinatorNode::IDENTIFIER,
+        aliases: &["count_ones"],
+        factory: without_args::<PopcountCombinatorNode>,
+    },
+    Builtin {
+        identifier: ParityCombinatorNode::IDENTIFIER,
+        description: "Returns 255 when the total number of set bits is odd, 0 when even.",
+        example: ParityCombinatorNode::IDENTIFIER,
+        aliases: &[],
+        factory: without_args::<ParityCombinatorNode>,
+    },
+    Builtin {
+        identifier: AnyBitCombinatorNode::IDENTIFIER,
+        description: "Returns 255 when any bit of any input is set, 0 otherwise.",
+        example: AnyBitCombinatorNode::IDENTIFIER,
+        aliases: &["any"],
+        factory: without_args::<AnyBitCombinatorNode>,
+    },
+    Builtin {
+        identifier: BitPackCombinatorNode::IDENTIFIER,
+        description: "Packs up to 8 inputs into one byte, setting bit i when input i is 128 or more.",
+        example: BitPackCombinatorNode::IDENTIFIER,
+        aliases: &["pack_bits"],
+        factory: without_args::<BitPackCombinatorNode>,
+    },
+    Builtin {
+        identifier: CountAboveCombinatorNode::IDENTIFIER,
+        description: "Counts the inputs strictly above a threshold, saturating at 255, e.g. count_above:127.",
+        example: "count_above:127",
+        aliases: &[],
+        factory: |args| {
+            Ok(Box::new(CountAboveCombinatorNode::from_args(
+                args.unwrap_or(""),
+            )?))
+        },
+    },
+    Builtin {
+        identifier: CountEqualCombinatorNode::IDENTIFIER,
+        description: "Counts the inputs equal to a value, saturating at 255, e.g. count_eq:0.",
+        example: "count_eq:0",
+        aliases: &[],
+        factory: |args| {
+            Ok(Box::new(CountEqualCombinatorNode::from_args(
+                args.unwrap_or(""),
+            )?))
+        },
+    },
+    Builtin {
+        identifier: CountNonZeroCombinatorNode::IDENTIFIER,
+        description: "Counts the inputs other than 0, saturating at 255.",
+        example: CountNonZeroCombinatorNode::IDENTIFIER,
+        aliases: &[],
+        factory: without_args::<CountNonZeroCombinatorNode>,
+    },
+    Builtin {
+        identifier: PercentileCombinatorNode::IDENTIFIER,
+        description: "Returns the input at a percentile from 0 to 100 using nearest rank, e.g. percentile:90.",
+        example: "percentile:90",
+        aliases: &[],
+        factory: |args| {
+            Ok(Box::new(PercentileCombinatorNode::from_args(
+                args.unwrap_or(""),
+            )?))
+        },
+    },
+    Builtin {
+        identifier: NthCombinatorNode::IDENTIFIER,
+        description: "Returns the n-th smallest input counting from 0, e.g. nth:2.",
+        example: "nth:2",
+        aliases: &[],
+        factory: |args| Ok(Box::new(NthCombinatorNode::from_args(args.unwrap_or(""))?)),
+    },
+    Builtin {
+        identifier: AbsDiffCombinatorNode::IDENTIFIER,
+        description: "Returns the absolute difference of two inputs, folding left to right for more.",
+        example: AbsDiffCombinatorNode::IDENTIFIER,
+        aliases: &["diff", "abs_diff_fold"],
+        factory: without_args::<AbsDiffCombinatorNode>,
+    },
+    Builtin {
+        identifier: GreaterThanCombinatorNode::IDENTIFIER,
+        description: "Returns 255 when the first input is greater than the second, 0 otherwise.",
+        example: GreaterThanCombinatorNode::IDENTIFIER,
+        aliases: &["gt", ">"],
+        factory: without_args::<GreaterThanCombinatorNode>,
+    },
+    Builtin {
+        identifier: EqualCombinatorNode::IDENTIFIER,
+        description: "Returns 255 when all inputs are equal, 0 otherwise.",
+        example: EqualCombinatorNode::IDENTIFIER,
+        aliases: &["eq", "=="],
+        factory: without_args::<EqualCombinatorNode>,
+    },
+    Builtin {
+        identifier: LogSumCombinatorNode::IDENTIFIER,
+        description: "Multiplies 1 + x in the log domain, scaled so any number of inputs stays in range.",
+        example: LogSumCombinatorNode::IDENTIFIER,
+        aliases: &["soft_product"],
+        factory: without_args::<LogSumCombinatorNode>,
+    },
+    Builtin {
+        identifier: ConstantCombinatorNode::IDENTIFIER,
+        description: "Ignores its inputs and returns a fixed value, e.g. constant:128.",
+        example: "constant:128",
+        aliases: &["const"],
+        factory: |args| {
+            Ok(Box::new(ConstantCombinatorNode::from_args(
+                args.unwrap_or(""),
+            )?))
+        },
+    },
+    Builtin {
+        identifier: MuxCombinatorNode::IDENTIFIER,
+        description: "Uses the first input to select one of the others: by index, or with a threshold such as mux:128 picking the first when above it and the second otherwise.",
+        example: "mux:128",
+        aliases: &[],
+        factory: |args| Ok(Box::new(MuxCombinatorNode::from_args(args.unwrap_or(""))?)),
+    },
+    Builtin {
+        identifier: FirstCombinatorNode::IDENTIFIER,
+        description: "Passes the first input through.",
+        example: FirstCombinatorNode::IDENTIFIER,
+        aliases: &[],
+        factory: without_args::<FirstCombinatorNode>,
+    },
+    Builtin {
+        identifier: LastCombinatorNode::IDENTIFIER,
+        description: "Passes the last input through.",
+        example: LastCombinatorNode::IDENTIFIER,
+        aliases: &[],
+        factory: without_args::<LastCombinatorNode>,
+    },
+    Builtin {
+        identifier: SelectCombinatorNode::IDENTIFIER,
+        description: "Passes the input at a position counting from 0 through, e.g. select:2.",
+        example: "select:2",
+        aliases: &[],
+        factory: |args| {
+            Ok(Box::new(SelectCombinatorNode::from_args(
+                args.unwrap_or(""),
+            )?))
+        },
+    },
+];
+
+/// Returns true if `identifier` names a built-in combinator.
+/// Built-in combinators are pure: their output depends only on their inputs.
+#[cfg(feature = "std")]
+pub(crate) fn is_builtin(identifier: &str) -> bool {
+    BUILTINS.iter().any(|b| b.identifier == identifier)
+}
+
+/// Factory for combinators that take no arguments.
+fn without_args<C: Combinator + Default + 'static>(
+    args: Option<&str>,
+) -> Result<Box<dyn Combinator>, CombinatorParseError> {
+    let combinator = C::default();
+    match args {
+        Some(args) => Err(CombinatorParseError::invalid_arguments(
+            &combinator.identifier(),
+            args,
+            "takes no arguments",
+        )),
+        None => Ok(Box::new(combinator)),
     }
 }
 
 /// A combinator node that sums all input values.
+/// The sum saturates at 255 instead of overflowing.
+#[derive(Default)]
 pub struct AdditionCombinatorNode {}
 
 impl AdditionCombinatorNode {
@@ -34,7 +618,7 @@ impl AdditionCombinatorNode {
 
 impl Combinator for AdditionCombinatorNode {
     fn combine(&self, inputs: &[u8]) -> u8 {
-        inputs.iter().sum()
+        saturating_sum(inputs)
     }
     fn identifier(&self) -> String {
         Self::IDENTIFIER.to_string()
@@ -42,6 +626,8 @@ impl Combinator for AdditionCombinatorNode {
 }
 
 /// A combinator node that multiplies all input values.
+/// The product saturates at 255 instead of overflowing.
+#[derive(Default)]
 pub struct MultiplicationCombinatorNode {}
 
 impl MultiplicationCombinatorNode {
@@ -53,14 +639,173 @@ impl MultiplicationCombinatorNode {
 
 impl Combinator for MultiplicationCombinatorNode {
     fn combine(&self, inputs: &[u8]) -> u8 {
-        inputs.iter().product()
+        saturating_product(inputs)
     }
     fn identifier(&self) -> String {
         Self::IDENTIFIER.to_string()
     }
 }
 
+/// A combinator node that sums all input values, saturating at 255.
+#[derive(Default)]
+pub struct SaturatingAdditionCombinatorNode {}
+
+impl SaturatingAdditionCombinatorNode {
+    pub const IDENTIFIER: &'static str = "add_sat";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for SaturatingAdditionCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        saturating_sum(inputs)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+/// A combinator node that sums all input values, wrapping around on overflow (modulo 256).
+#[derive(Default)]
+pub struct WrappingAdditionCombinatorNode {}
+
+impl WrappingAdditionCombinatorNode {
+    pub const IDENTIFIER: &'static str = "add_wrap";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for WrappingAdditionCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        inputs.iter().fold(0, |acc, &x| acc.wrapping_add(x))
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+/// A combinator node that multiplies all input values, saturating at 255.
+#[derive(Default)]
+pub struct SaturatingMultiplicationCombinatorNode {}
+
+impl SaturatingMultiplicationCombinatorNode {
+    pub const IDENTIFIER: &'static str = "multiply_sat";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for SaturatingMultiplicationCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        saturating_product(inputs)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+/// A combinator node that multiplies all input values, wrapping around on overflow (modulo 256).
+#[derive(Default)]
+pub struct WrappingMultiplicationCombinatorNode {}
+
+impl WrappingMultiplicationCombinatorNode {
+    pub const IDENTIFIER: &'static str = "multiply_wrap";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for WrappingMultiplicationCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        inputs.iter().fold(1, |acc, &x| acc.wrapping_mul(x))
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+/// `LOG2_TABLE[x]` is `log2(1 + x)` in 8.8 fixed point, rounded to the nearest 1/256.
+/// It ranges from 0 for `x = 0` to 2048 (`8.0`) for `x = 255`.
+pub const LOG2_TABLE: [u16; 256] = {
+    let mut table = [0u16; 256];
+    let mut x = 0;
+    while x < 256 {
+        table[x] = log2_q8(x as u32 + 1);
+        x += 1;
+    }
+    table
+};
+
+/// Computes `log2(v)` for `v >= 1` in 8.8 fixed point, using only integer arithmetic
+/// so that the result is the same on every platform.
+const fn log2_q8(v: u32) -> u16 {
+    let integer = 31 - v.leading_zeros();
+    // The mantissa v / 2^integer in [1, 2), with 30 fractional bits.
+    let mut mantissa = (v as u64) << (30 - integer);
+    // Each squaring of the mantissa yields the next fractional bit of the logarithm.
+    let mut fraction = 0u32;
+    let mut bit = 0;
+    while bit < 10 {
+        mantissa = (mantissa * mantissa) >> 30;
+        fraction <<= 1;
+        if mantissa >= 2 << 30 {
+            mantissa >>= 1;
+            fraction |= 1;
+        }
+        bit += 1;
+    }
+    ((integer << 8) + ((fraction + 2) >> 2)) as u16
+}
+
+/// A combinator node that multiplies in the log domain so that the result stays in range.
+/// Each input `x` contributes `LOG2_TABLE[x] = log2(1 + x)`, so the sum is the logarithm of
+/// the product of `1 + x`. The sum is scaled by `255 / (8 * n)` for `n` inputs, mapping
+/// the largest possible product `256^n` to 255, and rounded to the nearest integer:
+///
+/// `output = round(255 * log2((1 + x1) * ... * (1 + xn)) / (8 * n))`
+///
+/// Because the scale depends on `n`, this is the logarithm of the geometric mean of `1 + x`
+/// rather than of the product itself. Raising any input never lowers the output, and neither
+/// does appending an input at least as large as every existing one, but appending a smaller
+/// input can: `[255]` yields 255 while `[255, 0]` yields 128. An empty input yields 0.
+#[derive(Default)]
+pub struct LogSumCombinatorNode {}
+
+impl LogSumCombinatorNode {
+    pub const IDENTIFIER: &'static str = "log_sum";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for LogSumCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        if inputs.is_empty() {
+            return 0;
+        }
+        let sum: u64 = inputs.iter().map(|&x| LOG2_TABLE[x as usize] as u64).sum();
+        let full_scale = 2048 * inputs.len() as u64;
+        ((sum * 255 + full_scale / 2) / full_scale) as u8
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+/// Sums the inputs, saturating at 255.
+fn saturating_sum(inputs: &[u8]) -> u8 {
+    inputs.iter().fold(0, |acc, &x| acc.saturating_add(x))
+}
+
+/// Multiplies the inputs, saturating at 255. An empty input yields 1.
+fn saturating_product(inputs: &[u8]) -> u8 {
+    inputs.iter().fold(1, |acc, &x| acc.saturating_mul(x))
+}
+
 /// A combinator node that returns the maximum value from the inputs.
+#[derive(Default)]
 pub struct MaxCombinatorNode {}
 
 impl MaxCombinatorNode {
@@ -77,9 +822,13 @@ impl Combinator for MaxCombinatorNode {
     fn identifier(&self) -> String {
         Self::IDENTIFIER.to_string()
     }
+    fn min_inputs(&self) -> usize {
+        1
+    }
 }
 
 /// A combinator node that returns the minimum value from the inputs.
+#[derive(Default)]
 pub struct MinCombinatorNode {}
 
 impl MinCombinatorNode {
@@ -96,9 +845,13 @@ impl Combinator for MinCombinatorNode {
     fn identifier(&self) -> String {
         Self::IDENTIFIER.to_string()
     }
+    fn min_inputs(&self) -> usize {
+        1
+    }
 }
 
 /// A combinator node that returns the average of the input values.
+#[derive(Default)]
 pub struct AverageCombinatorNode {}
 
 impl AverageCombinatorNode {
@@ -119,9 +872,147 @@ impl Combinator for AverageCombinatorNode {
     fn identifier(&self) -> String {
         Self::IDENTIFIER.to_string()
     }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that returns the weight-normalised average of the input values.
+/// Inputs without a matching weight are weighted 1, extra weights are ignored,
+/// and a total weight of zero yields 0. The result is rounded down.
+#[derive(Default)]
+pub struct WeightedAverageCombinatorNode {
+    weights: Vec<u16>,
+}
+
+impl WeightedAverageCombinatorNode {
+    pub const IDENTIFIER: &'static str = "weighted";
+    pub fn new(weights: Vec<u16>) -> Self {
+        Self { weights }
+    }
+
+    /// Parses a comma separated list of weights, e.g. `"3,1,1"`.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        let weights = args
+            .split(',')
+            .map(|w| w.trim().parse::<u16>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                CombinatorParseError::invalid_arguments(Self::IDENTIFIER, args, e.to_string())
+            })?;
+        Ok(Self::new(weights))
+    }
+
+    /// Returns the configured weights.
+    pub fn weights(&self) -> &[u16] {
+        &self.weights
+    }
+}
+
+impl Combinator for WeightedAverageCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        let (sum, total) = inputs
+            .iter()
+            .enumerate()
+            .fold((0u64, 0u64), |(sum, total), (i, &x)| {
+                let weight = self.weights.get(i).copied().unwrap_or(1) as u64;
+                (sum + x as u64 * weight, total + weight)
+            });
+        if total == 0 {
+            return 0;
+        }
+        (sum / total) as u8
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn parameters(&self) -> Option<String> {
+        if self.weights.is_empty() {
+            return None;
+        }
+        let weights: Vec<String> = self.weights.iter().map(|w| w.to_string()).collect();
+        Some(weights.join(","))
+    }
+    fn describe(&self) -> String {
+        match self.parameters() {
+            Some(weights) => format!("{}(weights={weights})", Self::IDENTIFIER),
+            None => Self::IDENTIFIER.to_string(),
+        }
+    }
+}
+
+/// A combinator node that sums all inputs without saturating and fires `high`
+/// when the sum reaches the threshold, `low` otherwise.
+pub struct ThresholdCombinatorNode {
+    threshold: u16,
+    low: u8,
+    high: u8,
+}
+
+impl ThresholdCombinatorNode {
+    pub const IDENTIFIER: &'static str = "threshold";
+    pub fn new(threshold: u16, low: u8, high: u8) -> Self {
+        Self {
+            threshold,
+            low,
+            high,
+        }
+    }
+
+    /// Parses `"<threshold>"` or `"<threshold>:<low>:<high>"`; commas may be used instead of colons.
+    /// When only the threshold is given, `low` is 0 and `high` is 255.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        let invalid = |reason: String| {
+            CombinatorParseError::invalid_arguments(Self::IDENTIFIER, args, reason)
+        };
+        let parts: Vec<&str> = args.split([':', ',']).map(str::trim).collect();
+        match parts.as_slice() {
+            [threshold] => Ok(Self::new(
+                threshold.parse().map_err(|e| invalid(format!("{e}")))?,
+                0,
+                u8::MAX,
+            )),
+            [threshold, low, high] => Ok(Self::new(
+                threshold.parse().map_err(|e| invalid(format!("{e}")))?,
+                low.parse().map_err(|e| invalid(format!("{e}")))?,
+                high.parse().map_err(|e| invalid(format!("{e}")))?,
+            )),
+            _ => Err(invalid(
+                "expected <threshold> or <threshold>:<low>:<high>".to_string(),
+            )),
+        }
+    }
+}
+
+impl Combinator for ThresholdCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        let sum: u32 = inputs.iter().map(|&x| x as u32).sum();
+        if sum >= self.threshold as u32 {
+            self.high
+        } else {
+            self.low
+        }
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn parameters(&self) -> Option<String> {
+        Some(format!("{}:{}:{}", self.threshold, self.low, self.high))
+    }
+    fn describe(&self) -> String {
+        format!(
+            "{}(cutoff={}, low={}, high={})",
+            Self::IDENTIFIER,
+            self.threshold,
+            self.low,
+            self.high
+        )
+    }
 }
 
 /// A combinator node that returns the median value from the inputs.
+/// For an even number of inputs the two middle values are averaged, rounding down.
+#[derive(Default)]
 pub struct MedianCombinatorNode {}
 
 impl MedianCombinatorNode {
@@ -136,21 +1027,111 @@ impl Combinator for MedianCombinatorNode {
         if inputs.is_empty() {
             return 0;
         }
-        let mut sorted = inputs.to_vec();
-        sorted.sort_unstable();
-        let mid = sorted.len() / 2;
-        if sorted.len() % 2 == 0 {
-            (sorted[mid - 1] + sorted[mid]) / 2
+        let mut values = inputs.to_vec();
+        let mid = values.len() / 2;
+        let (lower, &mut upper, _) = values.select_nth_unstable(mid);
+        if inputs.len().is_multiple_of(2) {
+            // Everything in `lower` is <= `upper`, so its maximum is the other middle value.
+            let lower = *lower.iter().max().unwrap();
+            ((lower as u16 + upper as u16) / 2) as u8
         } else {
-            sorted[mid]
+            upper
+        }
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that returns the most common input value.
+/// Ties are broken in favour of the smaller value, and an empty input yields 0.
+#[derive(Default)]
+pub struct MajorityVoteCombinatorNode {}
+
+impl MajorityVoteCombinatorNode {
+    pub const IDENTIFIER: &'static str = "majority";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for MajorityVoteCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        most_frequent(inputs)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that sets each output bit when that bit is set in more than half of the inputs.
+/// A bit set in exactly half of the inputs is cleared, and an empty input yields 0.
+#[derive(Default)]
+pub struct BitMajorityCombinatorNode {}
+
+impl BitMajorityCombinatorNode {
+    pub const IDENTIFIER: &'static str = "bit_majority";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for BitMajorityCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        let mut counts = [0usize; 8];
+        for &x in inputs {
+            for (bit, count) in counts.iter_mut().enumerate() {
+                *count += ((x >> bit) & 1) as usize;
+            }
         }
+        counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count * 2 > inputs.len())
+            .fold(0, |acc, (bit, _)| acc | (1 << bit))
     }
     fn identifier(&self) -> String {
         Self::IDENTIFIER.to_string()
     }
 }
 
+/// A shareable closure reducing a slice of inputs to a single output.
+pub type CombineFn = Arc<dyn Fn(&[u8]) -> u8 + Send + Sync>;
+
+/// A combinator node backed by a closure, identified by a caller-chosen name.
+/// Cloning shares the closure.
+#[derive(Clone)]
+pub struct FnCombinatorNode {
+    name: String,
+    f: CombineFn,
+}
+
+impl FnCombinatorNode {
+    pub fn new(name: impl Into<String>, f: impl Fn(&[u8]) -> u8 + Send + Sync + 'static) -> Self {
+        Self {
+            name: name.into(),
+            f: Arc::new(f),
+        }
+    }
+}
+
+impl Combinator for FnCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        (self.f)(inputs)
+    }
+    fn identifier(&self) -> String {
+        self.name.clone()
+    }
+}
+
 /// A combinator node that performs a bitwise OR across all input values.
+#[derive(Default)]
 pub struct ORCombinatorNode {}
 
 impl ORCombinatorNode {
@@ -169,6 +1150,7 @@ impl Combinator for ORCombinatorNode {
     }
 }
 /// A combinator node that performs a bitwise AND across all input values.
+#[derive(Default)]
 pub struct ANDCombinatorNode {}
 
 impl ANDCombinatorNode {
@@ -188,6 +1170,7 @@ impl Combinator for ANDCombinatorNode {
 }
 
 /// A combinator node that performs a bitwise XOR across all input values.
+#[derive(Default)]
 pub struct XORCombinatorNode {}
 
 impl XORCombinatorNode {
@@ -206,6 +1189,7 @@ impl Combinator for XORCombinatorNode {
     }
 }
 /// A combinator node that performs a bitwise NAND across all input values.
+#[derive(Default)]
 pub struct NANDCombinatorNode {}
 
 impl NANDCombinatorNode {
@@ -225,6 +1209,7 @@ impl Combinator for NANDCombinatorNode {
     }
 }
 /// A combinator node that performs a bitwise NOR across all input values.
+#[derive(Default)]
 pub struct NORCombinatorNode {}
 
 impl NORCombinatorNode {
@@ -244,6 +1229,7 @@ impl Combinator for NORCombinatorNode {
     }
 }
 /// A combinator node that performs a bitwise XNOR across all input values.
+#[derive(Default)]
 pub struct XNORCombinatorNode {}
 
 impl XNORCombinatorNode {
@@ -262,3 +1248,544 @@ impl Combinator for XNORCombinatorNode {
         Self::IDENTIFIER.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str) -> Box<dyn Combinator> {
+        combinator_from_str(name).unwrap()
+    }
+
+    fn is_invalid_arguments(name: &str) -> bool {
+        matches!(
+            combinator_from_str(name),
+            Err(CombinatorParseError::InvalidArguments { .. })
+        )
+    }
+
+    #[test]
+    fn test_available_combinators_round_trip() {
+        let available = available_combinators();
+        assert_eq!(available.len(), BUILTINS.len());
+        for info in available {
+            assert!(!info.description.is_empty());
+            let name_of = |c: Box<dyn Combinator>| c.identifier();
+            assert_eq!(name_of(parse(info.example)), info.identifier);
+            for alias in info.aliases {
+                let args = info.example.strip_prefix(info.identifier).unwrap();
+                assert_eq!(name_of(parse(&format!("{alias}{args}"))), info.identifier);
+            }
+        }
+    }
+
+    #[test]
+    fn test_available_combinators_unique_names() {
+        let mut names: Vec<&str> = available_combinators()
+            .iter()
+            .flat_map(|info| core::iter::once(info.identifier).chain(info.aliases.iter().copied()))
+            .collect();
+        let count = names.len();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn test_from_str_case_insensitive() {
+        assert_eq!(parse("MAX").identifier(), MaxCombinatorNode::IDENTIFIER);
+        assert_eq!(
+            parse(" Avg ").identifier(),
+            AverageCombinatorNode::IDENTIFIER
+        );
+        assert_eq!(
+            parse("THRESHOLD:5").parameters(),
+            Some("5:0:255".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_str_unknown_name() {
+        assert_eq!(
+            combinator_from_str("nope").err(),
+            Some(CombinatorParseError::UnknownName("nope".to_string()))
+        );
+        assert_eq!(
+            combinator_from_str("nope:1,2").err(),
+            Some(CombinatorParseError::UnknownName("nope".to_string()))
+        );
+        assert!(combinator_from_str_opt("nope").is_none());
+        assert!(combinator_from_str_opt("max").is_some());
+    }
+
+    #[test]
+    fn test_from_str_invalid_arguments() {
+        assert!(is_invalid_arguments("weighted:3,x"));
+        assert!(is_invalid_arguments("weighted:"));
+        assert!(is_invalid_arguments("threshold"));
+        assert!(is_invalid_arguments("threshold:1:2"));
+        assert!(is_invalid_arguments("threshold:abc"));
+        assert!(is_invalid_arguments("max:3"));
+        let message = combinator_from_str("weighted:3,x")
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(message.contains("weighted"), "{message}");
+    }
+
+    #[test]
+    fn test_identifier_round_trips() {
+        for builtin in BUILTINS {
+            let combinator = parse(builtin.example);
+            let description = match combinator.parameters() {
+                Some(args) => format!("{}:{args}", combinator.identifier()),
+                None => combinator.identifier(),
+            };
+            for reparsed in [
+                parse(&description),
+                combinator_from_str_strict(&description).unwrap(),
+            ] {
+                assert_eq!(reparsed.identifier(), builtin.identifier);
+                assert_eq!(reparsed.parameters(), combinator.parameters());
+                assert_eq!(reparsed.min_inputs(), combinator.min_inputs());
+                assert_eq!(reparsed.max_inputs(), combinator.max_inputs());
+            }
+        }
+    }
+
+    #[test]
+    fn test_symbol_aliases() {
+        for (alias, identifier) in [
+            ("sum", AdditionCombinatorNode::IDENTIFIER),
+            ("mul", MultiplicationCombinatorNode::IDENTIFIER),
+            ("×", MultiplicationCombinatorNode::IDENTIFIER),
+            ("&", ANDCombinatorNode::IDENTIFIER),
+            ("|", ORCombinatorNode::IDENTIFIER),
+            ("^", XORCombinatorNode::IDENTIFIER),
+            ("mean", AverageCombinatorNode::IDENTIFIER),
+        ] {
+            assert_eq!(parse(alias).identifier(), identifier, "{alias}");
+        }
+    }
+
+    #[test]
+    fn test_from_str_strict() {
+        assert_eq!(
+            combinator_from_str_strict("max").unwrap().identifier(),
+            "max"
+        );
+        assert_eq!(
+            combinator_from_str_strict("threshold:5")
+                .unwrap()
+                .parameters(),
+            Some("5:0:255".to_string())
+        );
+        let suggestion = |name: &str| match combinator_from_str_strict(name).err() {
+            Some(CombinatorParseError::NotCanonical { suggestion, .. }) => suggestion,
+            other => panic!("{name}: {other:?}"),
+        };
+        assert_eq!(suggestion("multiplication").as_deref(), Some("multiply"));
+        assert_eq!(suggestion("MAX").as_deref(), Some("max"));
+        assert_eq!(suggestion(" max").as_deref(), Some("max"));
+        assert_eq!(suggestion("medain").as_deref(), Some("median"));
+        assert_eq!(
+            suggestion("harmonic_maen").as_deref(),
+            Some("harmonic_mean")
+        );
+        assert_eq!(suggestion("&").as_deref(), Some("and"));
+        assert_eq!(suggestion("completely_unknown"), None);
+        assert!(matches!(
+            combinator_from_str_strict("weighted:3,x"),
+            Err(CombinatorParseError::InvalidArguments { .. })
+        ));
+        assert_eq!(
+            combinator_from_str_strict("Avg").err().unwrap().to_string(),
+            "\"Avg\" is not a canonical combinator identifier; did you mean \"average\"?"
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("max", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("medain", "median"), 1);
+        assert_eq!(edit_distance("×", "x"), 1);
+    }
+
+    fn second_largest(inputs: &[u8]) -> u8 {
+        let mut sorted = inputs.to_vec();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        sorted.get(1).copied().unwrap_or(0)
+    }
+
+    #[test]
+    fn test_fn_combinator_node() {
+        let node = FnCombinatorNode::new("second_largest", second_largest);
+        assert_eq!(node.identifier(), "second_largest");
+        assert_eq!(node.parameters(), None);
+        assert_eq!(node.combine(&[5, 9, 7]), 7);
+
+        let combinators: Vec<Box<dyn Combinator>> =
+            vec![Box::new(node.clone()), combinator_from_str("max").unwrap()];
+        let outputs: Vec<u8> = combinators.iter().map(|c| c.combine(&[1, 4, 3])).collect();
+        assert_eq!(outputs, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_builtin_arity() {
+        for name in [
+            "median", "max", "min", "average", "majority", "mode", "argmax", "topk:2",
+        ] {
+            let combinator = parse(name);
+            assert_eq!(combinator.min_inputs(), 1, "{name}");
+            assert!(combinator.combine_checked(&[]).is_err(), "{name}");
+            assert!(combinator.combine_checked(&[3]).is_ok(), "{name}");
+        }
+        for name in ["addition", "multiply", "or", "and", "xor", "popcount"] {
+            let combinator = parse(name);
+            assert_eq!(combinator.min_inputs(), 0, "{name}");
+            assert_eq!(combinator.max_inputs(), None, "{name}");
+            assert!(combinator.combine_checked(&[]).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_median_even_does_not_overflow() {
+        let median = MedianCombinatorNode::new();
+        assert_eq!(median.combine(&[200, 210]), 205);
+        assert_eq!(median.combine(&[210, 200]), 205);
+        assert_eq!(median.combine(&[255, 255]), 255);
+        assert_eq!(median.combine(&[0, 255]), 127);
+        assert_eq!(median.combine(&[255, 0, 1, 254]), 127);
+    }
+
+    #[test]
+    fn test_median_odd() {
+        let median = MedianCombinatorNode::new();
+        assert_eq!(median.combine(&[7]), 7);
+        assert_eq!(median.combine(&[255, 0, 128]), 128);
+        assert_eq!(median.combine(&[9, 1, 5, 3, 7]), 5);
+        assert_eq!(median.combine(&[]), 0);
+    }
+
+    #[test]
+    fn test_median_matches_sorted_reference() {
+        let median = MedianCombinatorNode::new();
+        for len in 1..40usize {
+            let inputs: Vec<u8> = (0..len)
+                .map(|i| ((i * 97 + len * 31) % 256) as u8)
+                .collect();
+            let mut sorted = inputs.clone();
+            sorted.sort_unstable();
+            let mid = len / 2;
+            let expected = if len % 2 == 0 {
+                ((sorted[mid - 1] as u16 + sorted[mid] as u16) / 2) as u8
+            } else {
+                sorted[mid]
+            };
+            assert_eq!(median.combine(&inputs), expected, "inputs {inputs:?}");
+        }
+    }
+
+    #[test]
+    fn test_weighted_average() {
+        let weighted = WeightedAverageCombinatorNode::new(vec![3, 1, 1]);
+        // (3 * 100 + 1 * 200 + 1 * 50) / 5 = 110
+        assert_eq!(weighted.combine(&[100, 200, 50]), 110);
+        // (3 * 255 + 255 + 255) / 5 = 255, no overflow in wide arithmetic.
+        assert_eq!(weighted.combine(&[255, 255, 255]), 255);
+    }
+
+    #[test]
+    fn test_weighted_average_mismatched_lengths() {
+        let weighted = WeightedAverageCombinatorNode::new(vec![2]);
+        // Missing weights are 1: (2 * 10 + 40) / 3 = 20
+        assert_eq!(weighted.combine(&[10, 40]), 20);
+        let weighted = WeightedAverageCombinatorNode::new(vec![1, 3, 9, 9]);
+        // Extra weights are ignored: (10 + 3 * 30) / 4 = 25
+        assert_eq!(weighted.combine(&[10, 30]), 25);
+    }
+
+    #[test]
+    fn test_weighted_average_zero_weight() {
+        let weighted = WeightedAverageCombinatorNode::new(vec![0, 0]);
+        assert_eq!(weighted.combine(&[10, 40]), 0);
+        assert_eq!(weighted.combine(&[]), 0);
+        let weighted = WeightedAverageCombinatorNode::new(vec![0, 1]);
+        assert_eq!(weighted.combine(&[10, 40]), 40);
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            parse("threshold:300:0:255").describe(),
+            "threshold(cutoff=300, low=0, high=255)"
+        );
+        assert_eq!(
+            parse("weighted:3,1,1").describe(),
+            "weighted(weights=3,1,1)"
+        );
+        assert_eq!(parse("weighted").describe(), "weighted");
+        assert_eq!(
+            parse("count_above:127").describe(),
+            "count_above(threshold=127)"
+        );
+        assert_eq!(parse("constant:9").describe(), "constant(value=9)");
+        assert_eq!(parse("mux:128").describe(), "mux(threshold=128)");
+        assert_eq!(parse("mux").describe(), "mux");
+        assert_eq!(parse("select:2").describe(), "select(index=2)");
+        assert_eq!(parse("topk:3").describe(), "topk(k=3)");
+        assert_eq!(parse("percentile:90").describe(), "percentile(p=90)");
+        // Combinators without configuration describe themselves by their identifier.
+        assert_eq!(parse("max").describe(), "max");
+        assert_eq!(FnCombinatorNode::new("custom", |_| 0).describe(), "custom");
+        // A boxed combinator forwards to the one inside.
+        let boxed: Box<dyn Combinator> = Box::new(ThresholdCombinatorNode::new(5, 1, 2));
+        assert_eq!(boxed.describe(), "threshold(cutoff=5, low=1, high=2)");
+    }
+
+    #[test]
+    fn test_describe_default_includes_parameters() {
+        struct Scaled(u8);
+        impl Combinator for Scaled {
+            fn combine(&self, inputs: &[u8]) -> u8 {
+                inputs.first().map_or(0, |&x| x.saturating_mul(self.0))
+            }
+            fn identifier(&self) -> String {
+                "scaled".to_string()
+            }
+            fn parameters(&self) -> Option<String> {
+                Some(self.0.to_string())
+            }
+        }
+        assert_eq!(Scaled(3).describe(), "scaled(3)");
+    }
+
+    #[test]
+    fn test_weighted_average_from_str() {
+        let weighted = parse("weighted:3,1,1");
+        assert_eq!(
+            weighted.identifier(),
+            WeightedAverageCombinatorNode::IDENTIFIER
+        );
+        assert_eq!(weighted.parameters(), Some("3,1,1".to_string()));
+        assert_eq!(weighted.combine(&[100, 200, 50]), 110);
+        assert_eq!(
+            parse("Weighted: 2 , 1").parameters(),
+            Some("2,1".to_string())
+        );
+        assert_eq!(parse("weighted").parameters(), None);
+        assert_eq!(parse("weighted").combine(&[10, 20]), 15);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let threshold = ThresholdCombinatorNode::new(300, 1, 200);
+        assert_eq!(threshold.combine(&[]), 1);
+        assert_eq!(threshold.combine(&[100, 199]), 1);
+        assert_eq!(threshold.combine(&[100, 200]), 200);
+        assert_eq!(threshold.combine(&[255, 255]), 200);
+    }
+
+    #[test]
+    fn test_threshold_does_not_saturate() {
+        let threshold = ThresholdCombinatorNode::new(u16::MAX, 0, 255);
+        // 257 * 255 = 65535, which only reaches the threshold without saturation.
+        assert_eq!(threshold.combine(&[255; 256]), 0);
+        assert_eq!(threshold.combine(&[255; 257]), 255);
+        assert_eq!(threshold.combine(&[255; 1000]), 255);
+    }
+
+    #[test]
+    fn test_threshold_zero_fires_on_empty() {
+        let threshold = ThresholdCombinatorNode::new(0, 0, 255);
+        assert_eq!(threshold.combine(&[]), 255);
+    }
+
+    #[test]
+    fn test_threshold_from_str() {
+        let threshold = parse("threshold:300");
+        assert_eq!(threshold.identifier(), ThresholdCombinatorNode::IDENTIFIER);
+        assert_eq!(threshold.parameters(), Some("300:0:255".to_string()));
+        assert_eq!(threshold.combine(&[150, 150]), 255);
+        assert_eq!(threshold.combine(&[150, 149]), 0);
+        let threshold = parse("threshold:10:5:6");
+        assert_eq!(threshold.combine(&[9]), 5);
+        assert_eq!(threshold.combine(&[10]), 6);
+        assert!(is_invalid_arguments("threshold:70000"));
+    }
+
+    #[test]
+    fn test_majority_vote() {
+        let vote = parse("majority");
+        assert_eq!(vote.identifier(), MajorityVoteCombinatorNode::IDENTIFIER);
+        // One of three redundant copies is corrupted.
+        assert_eq!(vote.combine(&[42, 42, 17]), 42);
+        assert_eq!(vote.combine(&[17, 42, 42]), 42);
+        assert_eq!(vote.combine(&[]), 0);
+    }
+
+    #[test]
+    fn test_majority_vote_tie_prefers_smaller() {
+        let vote = MajorityVoteCombinatorNode::new();
+        assert_eq!(vote.combine(&[200, 3, 200, 3]), 3);
+        assert_eq!(vote.combine(&[9, 8, 7]), 7);
+    }
+
+    #[test]
+    fn test_bit_majority() {
+        let vote = parse("bit_majority");
+        assert_eq!(vote.identifier(), BitMajorityCombinatorNode::IDENTIFIER);
+        // No single input equals the per-bit majority.
+        let inputs = [0b1100_0000, 0b1010_0000, 0b0110_0000];
+        assert_eq!(vote.combine(&inputs), 0b1110_0000);
+        assert!(!inputs.contains(&0b1110_0000));
+        // One corrupted copy is out-voted bit by bit.
+        assert_eq!(
+            vote.combine(&[0b1010_1010, 0b1010_1010, 0b0101_0101]),
+            0b1010_1010
+        );
+    }
+
+    #[test]
+    fn test_bit_majority_ties_and_empty() {
+        let vote = BitMajorityCombinatorNode::new();
+        assert_eq!(vote.combine(&[]), 0);
+        assert_eq!(vote.combine(&[0b1111_0000, 0b0000_1111]), 0);
+        assert_eq!(vote.combine(&[0xFF]), 0xFF);
+    }
+
+    #[test]
+    fn test_addition_saturates() {
+        let add = AdditionCombinatorNode::new();
+        assert_eq!(add.combine(&[200, 100]), 255);
+        assert_eq!(add.combine(&[255, 255, 255]), 255);
+        assert_eq!(add.combine(&[1, 2, 3]), 6);
+        assert_eq!(add.combine(&[]), 0);
+    }
+
+    #[test]
+    fn test_saturating_addition() {
+        let add = parse("add_sat");
+        assert_eq!(
+            add.identifier(),
+            SaturatingAdditionCombinatorNode::IDENTIFIER
+        );
+        assert_eq!(add.combine(&[200, 100]), 255);
+        assert_eq!(add.combine(&[10, 20]), 30);
+    }
+
+    #[test]
+    fn test_wrapping_addition() {
+        let add = parse("add_wrap");
+        assert_eq!(add.identifier(), WrappingAdditionCombinatorNode::IDENTIFIER);
+        assert_eq!(add.combine(&[200, 100]), 44);
+        assert_eq!(add.combine(&[255, 255, 255]), 253);
+        assert_eq!(add.combine(&[10, 20]), 30);
+    }
+
+    #[test]
+    fn test_multiplication_saturates() {
+        let mul = MultiplicationCombinatorNode::new();
+        assert_eq!(mul.combine(&[16, 16]), 255);
+        assert_eq!(mul.combine(&[16, 16, 0]), 0);
+        assert_eq!(mul.combine(&[3, 5]), 15);
+    }
+
+    #[test]
+    fn test_saturating_multiplication() {
+        let mul = parse("multiply_sat");
+        assert_eq!(
+            mul.identifier(),
+            SaturatingMultiplicationCombinatorNode::IDENTIFIER
+        );
+        assert_eq!(mul.combine(&[16, 16]), 255);
+        assert_eq!(mul.combine(&[3, 5]), 15);
+    }
+
+    #[test]
+    fn test_wrapping_multiplication() {
+        let mul = parse("multiply_wrap");
+        assert_eq!(
+            mul.identifier(),
+            WrappingMultiplicationCombinatorNode::IDENTIFIER
+        );
+        assert_eq!(mul.combine(&[16, 16]), 0);
+        assert_eq!(mul.combine(&[16, 17]), 16);
+        assert_eq!(mul.combine(&[3, 5]), 15);
+    }
+
+    #[test]
+    fn test_log2_table() {
+        assert_eq!(LOG2_TABLE[0], 0);
+        assert_eq!(LOG2_TABLE[1], 256);
+        assert_eq!(LOG2_TABLE[3], 512);
+        assert_eq!(LOG2_TABLE[255], 2048);
+        for (x, &log) in LOG2_TABLE.iter().enumerate() {
+            let reference = ((x as f64 + 1.0).log2() * 256.0).round();
+            assert!(
+                (log as f64 - reference).abs() <= 1.0,
+                "{x}: {log} vs {reference}"
+            );
+        }
+        assert!(LOG2_TABLE.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_log_sum_two_inputs_matches_reference() {
+        let log_sum = parse("log_sum");
+        assert_eq!(log_sum.identifier(), LogSumCombinatorNode::IDENTIFIER);
+        for a in 0..=255u8 {
+            for b in (0..=255u8).step_by(5) {
+                let product = (a as f64 + 1.0) * (b as f64 + 1.0);
+                let reference = 255.0 * product.log2() / 16.0;
+                let out = log_sum.combine(&[a, b]) as f64;
+                assert!(
+                    (out - reference).abs() <= 2.0,
+                    "{a}, {b}: {out} vs {reference}"
+                );
+            }
+        }
+        assert_eq!(log_sum.combine(&[]), 0);
+        assert_eq!(log_sum.combine(&[0, 0]), 0);
+        assert_eq!(log_sum.combine(&[255, 255, 255]), 255);
+    }
+
+    #[test]
+    fn test_log_sum_is_monotonic() {
+        let log_sum = LogSumCombinatorNode::new();
+        for base in [[0u8, 0, 0], [10, 200, 37], [255, 1, 128]] {
+            for position in 0..base.len() {
+                let mut inputs = base;
+                let mut previous = 0;
+                for x in 0..=255 {
+                    inputs[position] = x;
+                    let out = log_sum.combine(&inputs);
+                    assert!(out >= previous, "{inputs:?}");
+                    previous = out;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_log_sum_appending_inputs() {
+        let log_sum = LogSumCombinatorNode::new();
+        for start in [0u8, 3, 100, 254] {
+            let mut inputs = vec![start];
+            let mut previous = log_sum.combine(&inputs);
+            for step in [0u8, 1, 7, 60, 255] {
+                let largest = *inputs.iter().max().unwrap();
+                inputs.push(largest.saturating_add(step));
+                let out = log_sum.combine(&inputs);
+                assert!(out >= previous, "{inputs:?}");
+                previous = out;
+            }
+        }
+        // Appending a smaller input lowers the geometric mean.
+        assert_eq!(log_sum.combine(&[255]), 255);
+        assert_eq!(log_sum.combine(&[255, 0]), 128);
+        assert!(log_sum.combine(&[200, 200, 10]) < log_sum.combine(&[200, 200]));
+    }
+}
@@ -0,0 +1,202 @@
+/// Combinators that treat their inputs as bitfields.
+use alloc::string::{String, ToString};
+
+use crate::neural::traits::Combinator;
+
+/// Returns the total number of set bits across all inputs.
+fn total_popcount(inputs: &[u8]) -> usize {
+    inputs.iter().map(|x| x.count_ones() as usize).sum()
+}
+
+/// A combinator node that returns the total number of set bits across all inputs, saturating at 255.
+#[derive(Default)]
+pub struct PopcountCombinatorNode {}
+
+impl PopcountCombinatorNode {
+    pub const IDENTIFIER: &'static str = "popcount";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for PopcountCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        total_popcount(inputs).min(u8::MAX as usize) as u8
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+/// A combinator node that returns 255 when the total number of set bits across all inputs is odd,
+/// and 0 when it is even.
+#[derive(Default)]
+pub struct ParityCombinatorNode {}
+
+impl ParityCombinatorNode {
+    pub const IDENTIFIER: &'static str = "parity";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for ParityCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        let folded = inputs.iter().fold(0u8, |acc, &x| acc ^ x);
+        if folded.count_ones() % 2 == 1 {
+            u8::MAX
+        } else {
+            0
+        }
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+/// A combinator node that returns 255 when any bit of any input is set, and 0 otherwise.
+#[derive(Default)]
+pub struct AnyBitCombinatorNode {}
+
+impl AnyBitCombinatorNode {
+    pub const IDENTIFIER: &'static str = "any_bit";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for AnyBitCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        if inputs.iter().any(|&x| x != 0) {
+            u8::MAX
+        } else {
+            0
+        }
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+/// A combinator node that packs up to 8 inputs into one byte: input `i` sets bit `i` when it is
+/// 128 or more. Missing inputs leave their bits clear. Unpack the result with
+/// `MapperNode::extract_bit` or `create_mapper_unpack_bit`.
+#[derive(Default)]
+pub struct BitPackCombinatorNode {}
+
+impl BitPackCombinatorNode {
+    pub const IDENTIFIER: &'static str = "bit_pack";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for BitPackCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        inputs
+            .iter()
+            .take(8)
+            .enumerate()
+            .fold(0, |packed, (bit, &x)| packed | (u8::from(x >= 128) << bit))
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        Some(8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::combinator_from_str;
+    use crate::neural::mapper_defaults::create_mapper_unpack_bit;
+
+    /// Every input slice of up to two bytes, with the second byte stepped to keep the test fast.
+    fn small_inputs() -> impl Iterator<Item = Vec<u8>> {
+        core::iter::once(vec![])
+            .chain((0u8..=u8::MAX).map(|a| vec![a]))
+            .chain(
+                (0u8..=u8::MAX).flat_map(|a| (0u8..=u8::MAX).step_by(7).map(move |b| vec![a, b])),
+            )
+    }
+
+    fn bits_reference(inputs: &[u8]) -> usize {
+        inputs
+            .iter()
+            .map(|&x| (0..8).filter(|bit| x & (1 << bit) != 0).count())
+            .sum()
+    }
+
+    #[test]
+    fn test_popcount() {
+        let popcount = combinator_from_str("popcount").unwrap();
+        assert_eq!(popcount.identifier(), PopcountCombinatorNode::IDENTIFIER);
+        for inputs in small_inputs() {
+            assert_eq!(
+                popcount.combine(&inputs) as usize,
+                bits_reference(&inputs),
+                "{inputs:?}"
+            );
+        }
+        assert_eq!(popcount.combine(&[0xFF; 31]), 248);
+        assert_eq!(popcount.combine(&[0xFF; 32]), 255);
+        assert_eq!(popcount.combine(&[0xFF; 100]), 255);
+    }
+
+    #[test]
+    fn test_parity() {
+        let parity = combinator_from_str("parity").unwrap();
+        assert_eq!(parity.identifier(), ParityCombinatorNode::IDENTIFIER);
+        for inputs in small_inputs() {
+            let expected = if bits_reference(&inputs) % 2 == 1 {
+                255
+            } else {
+                0
+            };
+            assert_eq!(parity.combine(&inputs), expected, "{inputs:?}");
+        }
+        assert_eq!(parity.combine(&[0xFF; 100]), 0);
+        assert_eq!(parity.combine(&[1; 101]), 255);
+    }
+
+    #[test]
+    fn test_any_bit() {
+        let any = combinator_from_str("any_bit").unwrap();
+        assert_eq!(any.identifier(), AnyBitCombinatorNode::IDENTIFIER);
+        for inputs in small_inputs() {
+            let expected = if bits_reference(&inputs) > 0 { 255 } else { 0 };
+            assert_eq!(any.combine(&inputs), expected, "{inputs:?}");
+        }
+        assert_eq!(any.combine(&[0; 100]), 0);
+    }
+
+    #[test]
+    fn test_bit_pack() {
+        let pack = combinator_from_str("bit_pack").unwrap();
+        assert_eq!(pack.identifier(), BitPackCombinatorNode::IDENTIFIER);
+        assert_eq!(pack.max_inputs(), Some(8));
+        assert_eq!(pack.combine(&[]), 0);
+        assert_eq!(pack.combine(&[255]), 0b0000_0001);
+        assert_eq!(pack.combine(&[127, 128]), 0b0000_0010);
+        assert_eq!(pack.combine(&[0, 0, 200]), 0b0000_0100);
+        assert_eq!(pack.combine(&[255; 8]), 255);
+    }
+
+    #[test]
+    fn test_bit_pack_then_unpack() {
+        let pack = BitPackCombinatorNode::new();
+        let unpack: Vec<_> = (0..8).map(create_mapper_unpack_bit).collect();
+        for inputs in small_inputs().chain([vec![3, 250, 128, 127, 0, 255, 64, 192]]) {
+            let packed = pack.combine(&inputs);
+            for (bit, mapper) in unpack.iter().enumerate() {
+                let expected = match inputs.get(bit) {
+                    Some(&x) if x >= 128 => 255,
+                    _ => 0,
+                };
+                assert_eq!(mapper.transform(packed), expected, "{inputs:?} bit {bit}");
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+/// Combinators that compare their inputs with each other.
+use alloc::string::{String, ToString};
+
+use crate::neural::combinator_fold::fold_left;
+use crate::neural::traits::Combinator;
+
+/// Returns 255 for `true` and 0 for `false`.
+fn flag(condition: bool) -> u8 {
+    if condition { u8::MAX } else { 0 }
+}
+
+/// A combinator node that returns the absolute difference `|a - b|` of two inputs.
+/// More inputs are folded left to right, `||a - b| - c|` and so on, a single input is returned
+/// unchanged, and an empty input yields 0.
+#[derive(Default)]
+pub struct AbsDiffCombinatorNode {}
+
+impl AbsDiffCombinatorNode {
+    pub const IDENTIFIER: &'static str = "abs_diff";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for AbsDiffCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        fold_left(inputs, 0, u8::abs_diff)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        2
+    }
+}
+
+/// A combinator node that returns 255 when the first input is greater than the second, and 0 otherwise.
+/// Fewer than two inputs yield 0 and inputs after the second are ignored.
+#[derive(Default)]
+pub struct GreaterThanCombinatorNode {}
+
+impl GreaterThanCombinatorNode {
+    pub const IDENTIFIER: &'static str = "greater_than";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for GreaterThanCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        match inputs {
+            [a, b, ..] => flag(a > b),
+            _ => 0,
+        }
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        2
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        Some(2)
+    }
+}
+
+/// A combinator node that returns 255 when all inputs are equal, and 0 otherwise.
+/// A single input and an empty input both yield 255.
+#[derive(Default)]
+pub struct EqualCombinatorNode {}
+
+impl EqualCombinatorNode {
+    pub const IDENTIFIER: &'static str = "equal";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for EqualCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        flag(inputs.windows(2).all(|w| w[0] == w[1]))
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::combinator_from_str;
+
+    #[test]
+    fn test_abs_diff() {
+        let diff = combinator_from_str("abs_diff").unwrap();
+        assert_eq!(diff.identifier(), AbsDiffCombinatorNode::IDENTIFIER);
+        assert_eq!(diff.combine(&[10, 3]), 7);
+        assert_eq!(diff.combine(&[3, 10]), 7);
+        assert_eq!(diff.combine(&[0, 255]), 255);
+        assert_eq!(diff.combine(&[42, 42]), 0);
+        // ||10 - 3| - 20| = 13
+        assert_eq!(diff.combine(&[10, 3, 20]), 13);
+        assert_eq!(diff.combine(&[9]), 9);
+        assert_eq!(diff.combine(&[]), 0);
+        assert!(diff.combine_checked(&[9]).is_err());
+        assert!(diff.combine_checked(&[1, 2, 3]).is_ok());
+    }
+
+    #[test]
+    fn test_greater_than() {
+        let gt = combinator_from_str("greater_than").unwrap();
+        assert_eq!(gt.identifier(), GreaterThanCombinatorNode::IDENTIFIER);
+        assert_eq!(gt.combine(&[5, 4]), 255);
+        assert_eq!(gt.combine(&[4, 5]), 0);
+        assert_eq!(gt.combine(&[5, 5]), 0);
+        assert_eq!(gt.combine(&[255, 0]), 255);
+        assert_eq!(gt.combine(&[5, 4, 200]), 255);
+        assert_eq!(gt.combine(&[5]), 0);
+        assert_eq!(gt.combine(&[]), 0);
+        assert!(gt.combine_checked(&[5]).is_err());
+        assert!(gt.combine_checked(&[5, 4, 200]).is_err());
+    }
+
+    #[test]
+    fn test_equal() {
+        let eq = combinator_from_str("equal").unwrap();
+        assert_eq!(eq.identifier(), EqualCombinatorNode::IDENTIFIER);
+        assert_eq!(eq.combine(&[7, 7]), 255);
+        assert_eq!(eq.combine(&[7, 7, 7, 7]), 255);
+        assert_eq!(eq.combine(&[7, 7, 8]), 0);
+        assert_eq!(eq.combine(&[0, 255]), 0);
+        assert_eq!(eq.combine(&[3]), 255);
+        assert_eq!(eq.combine(&[]), 255);
+        assert!(eq.combine_checked(&[]).is_err());
+    }
+}
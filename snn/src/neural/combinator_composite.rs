@@ -0,0 +1,125 @@
+/// Combinators that wrap another combinator with lookup tables on its inputs and output.
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+
+use crate::neural::mapper::MapperNode;
+use crate::neural::traits::Combinator;
+
+/// A combinator node that optionally transforms each input through its own mapper,
+/// combines the results with an inner combinator, and transforms the output through `post`.
+pub struct CompositeCombinatorNode {
+    combinator: Box<dyn Combinator>,
+    post: MapperNode,
+    input_mappers: Vec<MapperNode>,
+}
+
+impl CompositeCombinatorNode {
+    pub const IDENTIFIER: &'static str = "composite";
+    pub fn new(combinator: Box<dyn Combinator>, post: MapperNode) -> Self {
+        Self {
+            combinator,
+            post,
+            input_mappers: Vec::new(),
+        }
+    }
+
+    /// Returns the node with a mapper per input position.
+    /// Inputs past the end of `mappers` are passed to the inner combinator unchanged.
+    pub fn with_input_mappers(self, mappers: Vec<MapperNode>) -> Self {
+        Self {
+            input_mappers: mappers,
+            ..self
+        }
+    }
+
+    /// Returns the inner combinator.
+    pub fn combinator(&self) -> &dyn Combinator {
+        self.combinator.as_ref()
+    }
+
+    /// Returns the mapper applied to the combined output.
+    pub fn post(&self) -> &MapperNode {
+        &self.post
+    }
+
+    /// Returns the mappers applied to the inputs.
+    pub fn input_mappers(&self) -> &[MapperNode] {
+        &self.input_mappers
+    }
+}
+
+impl Combinator for CompositeCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        let combined = if self.input_mappers.is_empty() {
+            self.combinator.combine(inputs)
+        } else {
+            let mapped: Vec<u8> = inputs
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| match self.input_mappers.get(i) {
+                    Some(mapper) => mapper.transform(x),
+                    None => x,
+                })
+                .collect();
+            self.combinator.combine(&mapped)
+        };
+        self.post.transform(combined)
+    }
+    fn identifier(&self) -> String {
+        format!("{}({})", Self::IDENTIFIER, self.combinator.identifier())
+    }
+    fn describe(&self) -> String {
+        format!("{}({})", Self::IDENTIFIER, self.combinator.describe())
+    }
+    fn min_inputs(&self) -> usize {
+        self.combinator.min_inputs()
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        self.combinator.max_inputs()
+    }
+    fn is_deterministic(&self) -> bool {
+        self.combinator.is_deterministic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode};
+
+    fn sigmoid_table() -> MapperNode {
+        MapperNode::new_transformation(|x| {
+            let centered = (x as f64 - 128.0) / 32.0;
+            (255.0 / (1.0 + (-centered).exp())).round() as u8
+        })
+    }
+
+    #[test]
+    fn test_sum_then_sigmoid() {
+        let sigmoid = sigmoid_table();
+        let node =
+            CompositeCombinatorNode::new(Box::new(AdditionCombinatorNode::new()), sigmoid_table());
+        assert_eq!(node.identifier(), "composite(addition)");
+        for inputs in [&[][..], &[10, 20], &[100, 28], &[200, 100], &[64, 64, 64]] {
+            let sum = inputs.iter().fold(0u8, |acc, &x| acc.saturating_add(x));
+            assert_eq!(node.combine(inputs), sigmoid.transform(sum), "{inputs:?}");
+        }
+    }
+
+    #[test]
+    fn test_scale_inputs_then_max() {
+        let double = MapperNode::new_transformation(|x| (x as u8).saturating_mul(2));
+        let halve = MapperNode::new_transformation(|x| x as u8 / 2);
+        let identity = MapperNode::new_transformation(|x| x as u8);
+        let node = CompositeCombinatorNode::new(Box::new(MaxCombinatorNode::new()), identity)
+            .with_input_mappers(vec![double, halve]);
+        assert_eq!(node.identifier(), "composite(max)");
+        assert_eq!(node.input_mappers().len(), 2);
+        // max(2 * 50, 180 / 2) = 100
+        assert_eq!(node.combine(&[50, 180]), 100);
+        // max(2 * 40, 250 / 2) = 125
+        assert_eq!(node.combine(&[40, 250]), 125);
+        // The third input has no mapper and passes through unchanged.
+        assert_eq!(node.combine(&[40, 250, 130]), 130);
+        assert_eq!(node.combine(&[200]), 255);
+    }
+}
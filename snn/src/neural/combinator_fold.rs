@@ -0,0 +1,128 @@
+/// Combinators built from a binary operation folded over the inputs.
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+};
+
+use crate::neural::traits::Combinator;
+
+/// A shareable binary operation folded over the inputs by `FoldCombinatorNode`.
+pub type FoldFn = Arc<dyn Fn(u8, u8) -> u8 + Send + Sync>;
+
+/// Folds `inputs` from left to right: the first input seeds the accumulator, and `f` is then
+/// applied as `f(accumulator, input)` to each following input. No inputs yield `init`.
+pub(crate) fn fold_left(inputs: &[u8], init: u8, f: impl Fn(u8, u8) -> u8) -> u8 {
+    match inputs.split_first() {
+        Some((&first, rest)) => rest.iter().fold(first, |acc, &x| f(acc, x)),
+        None => init,
+    }
+}
+
+/// A combinator node that left-folds its inputs with a binary operation, identified by a
+/// caller-chosen name. Cloning shares the operation.
+///
+/// The fold runs from left to right as `f(f(f(x0, x1), x2), x3)`, which matters for
+/// operations that are not associative or commutative. No inputs yield `init`.
+#[derive(Clone)]
+pub struct FoldCombinatorNode {
+    name: String,
+    init: u8,
+    f: FoldFn,
+}
+
+impl FoldCombinatorNode {
+    pub fn new(
+        name: impl Into<String>,
+        init: u8,
+        f: impl Fn(u8, u8) -> u8 + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            init,
+            f: Arc::new(f),
+        }
+    }
+
+    /// Returns the output for no inputs.
+    pub fn init(&self) -> u8 {
+        self.init
+    }
+}
+
+impl Combinator for FoldCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        fold_left(inputs, self.init, |acc, x| (self.f)(acc, x))
+    }
+    fn identifier(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// A combinator node that subtracts every following input from the first, left to right,
+/// stopping at 0 instead of wrapping. No inputs yield 0.
+#[derive(Default)]
+pub struct SaturatingSubCombinatorNode {}
+
+impl SaturatingSubCombinatorNode {
+    pub const IDENTIFIER: &'static str = "sub_sat";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for SaturatingSubCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        fold_left(inputs, 0, u8::saturating_sub)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::combinator_from_str;
+
+    #[test]
+    fn test_fold_order() {
+        // `2 * acc + x` reads the inputs as the digits of a base-2 number, most significant
+        // first, so any other order gives a different result.
+        let digits = FoldCombinatorNode::new("digits", 0, |acc, x| 2 * acc + x);
+        assert_eq!(digits.combine(&[1, 0, 1, 1]), 0b1011);
+        assert_eq!(digits.combine(&[1, 1, 0, 1]), 0b1101);
+        assert_eq!(digits.combine(&[1]), 1);
+        assert_eq!(digits.identifier(), "digits");
+    }
+
+    #[test]
+    fn test_fold_empty_yields_init() {
+        let min = FoldCombinatorNode::new("min_of_pairs", 255, u8::min);
+        assert_eq!(min.combine(&[]), 255);
+        assert_eq!(min.init(), 255);
+        assert_eq!(min.combine(&[9, 4, 7]), 4);
+        let shared = min.clone();
+        assert_eq!(shared.combine(&[3]), 3);
+    }
+
+    #[test]
+    fn test_saturating_sub() {
+        let sub = combinator_from_str("sub_sat").unwrap();
+        assert_eq!(sub.identifier(), SaturatingSubCombinatorNode::IDENTIFIER);
+        assert_eq!(sub.combine(&[100, 30, 20]), 50);
+        assert_eq!(sub.combine(&[30, 100]), 0);
+        // Saturation happens at each step, so a later input cannot bring the total back.
+        assert_eq!(sub.combine(&[10, 20, 0]), 0);
+        assert_eq!(sub.combine(&[42]), 42);
+        assert_eq!(sub.combine(&[]), 0);
+        assert!(combinator_from_str("saturating_sub").is_ok());
+    }
+
+    #[test]
+    fn test_abs_diff_fold() {
+        let diff = combinator_from_str("abs_diff_fold").unwrap();
+        assert_eq!(diff.identifier(), "abs_diff");
+        // |(|10 - 3|) - 20| = 13, while folding from the right would give |10 - 17| = 7.
+        assert_eq!(diff.combine(&[10, 3, 20]), 13);
+    }
+}
@@ -0,0 +1,198 @@
+/// JSON representation of combinators, tagged by identifier: `{"type": "threshold", "args": "300:0:255"}`.
+///
+/// The `args` field holds the combinator's `parameters()` and is omitted for combinators without any.
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+};
+
+use crate::neural::combinator::{CombinatorParseError, combinator_from_str};
+use crate::neural::combinator_registry::CombinatorRegistry;
+use crate::neural::json::{self, Value};
+use crate::neural::traits::Combinator;
+
+pub use crate::neural::json::JsonError;
+
+/// Returns the tagged JSON value for a combinator.
+pub(crate) fn combinator_to_value(combinator: &dyn Combinator) -> Value {
+    let mut entries = vec![("type".to_string(), Value::from(combinator.identifier()))];
+    if let Some(args) = combinator.parameters() {
+        entries.push(("args".to_string(), Value::from(args)));
+    }
+    Value::Object(entries)
+}
+
+/// Recreates a combinator from its tagged JSON value using the built-in combinators.
+pub(crate) fn combinator_from_value(
+    value: &Value,
+) -> Result<Box<dyn Combinator>, CombinatorDeserializeError> {
+    let (kind, args) = read_tag(value)?;
+    let description = match args {
+        Some(args) => format!("{kind}:{args}"),
+        None => kind.to_string(),
+    };
+    Ok(combinator_from_str(&description)?)
+}
+
+/// Recreates a combinator from its tagged JSON value using the combinators in `registry`.
+fn combinator_from_value_with(
+    value: &Value,
+    registry: &CombinatorRegistry,
+) -> Result<Box<dyn Combinator>, CombinatorDeserializeError> {
+    let (kind, args) = read_tag(value)?;
+    Ok(registry.create(kind, args.unwrap_or(""))?)
+}
+
+/// Serializes a combinator to JSON text.
+pub fn serialize_combinator(combinator: &dyn Combinator) -> String {
+    combinator_to_value(combinator).to_json()
+}
+
+/// Deserializes a combinator from JSON text produced by `serialize_combinator`.
+pub fn deserialize_combinator(
+    text: &str,
+) -> Result<Box<dyn Combinator>, CombinatorDeserializeError> {
+    combinator_from_value(&json::parse(text)?)
+}
+
+/// Like `deserialize_combinator`, but looks the combinator up in `registry`, so combinators
+/// registered outside this crate can be loaded.
+pub fn deserialize_combinator_with(
+    text: &str,
+    registry: &CombinatorRegistry,
+) -> Result<Box<dyn Combinator>, CombinatorDeserializeError> {
+    combinator_from_value_with(&json::parse(text)?, registry)
+}
+
+fn read_tag(value: &Value) -> Result<(&str, Option<&str>), CombinatorDeserializeError> {
+    let kind = value.get("type").and_then(Value::as_str).ok_or_else(|| {
+        CombinatorDeserializeError::InvalidFormat("missing string field \"type\"".to_string())
+    })?;
+    let args = match value.get("args") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(args)) => Some(args.as_str()),
+        Some(_) => {
+            return Err(CombinatorDeserializeError::InvalidFormat(
+                "field \"args\" must be a string".to_string(),
+            ));
+        }
+    };
+    Ok((kind, args))
+}
+
+/// Error returned when a combinator cannot be deserialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CombinatorDeserializeError {
+    /// The text was not valid JSON.
+    Json(JsonError),
+    /// The JSON did not have the tagged combinator shape.
+    InvalidFormat(String),
+    /// The tag did not describe a known combinator.
+    Parse(CombinatorParseError),
+}
+
+impl core::fmt::Display for CombinatorDeserializeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CombinatorDeserializeError::Json(e) => write!(f, "{e}"),
+            CombinatorDeserializeError::InvalidFormat(message) => {
+                write!(f, "invalid combinator format: {message}")
+            }
+            CombinatorDeserializeError::Parse(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl core::error::Error for CombinatorDeserializeError {}
+
+impl From<JsonError> for CombinatorDeserializeError {
+    fn from(e: JsonError) -> Self {
+        CombinatorDeserializeError::Json(e)
+    }
+}
+
+impl From<CombinatorParseError> for CombinatorDeserializeError {
+    fn from(e: CombinatorParseError) -> Self {
+        CombinatorDeserializeError::Parse(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{ThresholdCombinatorNode, available_combinators};
+
+    fn sample_inputs() -> Vec<Vec<u8>> {
+        vec![
+            vec![],
+            vec![7],
+            vec![200, 100],
+            vec![1, 2, 3, 250],
+            vec![255; 5],
+        ]
+    }
+
+    #[test]
+    fn test_round_trip_builtins() {
+        for info in available_combinators() {
+            let original = combinator_from_str(info.example).unwrap();
+            let text = serialize_combinator(original.as_ref());
+            let restored = deserialize_combinator(&text).unwrap();
+            assert_eq!(restored.identifier(), original.identifier(), "{text}");
+            assert_eq!(restored.parameters(), original.parameters(), "{text}");
+            for inputs in sample_inputs() {
+                assert_eq!(
+                    restored.combine(&inputs),
+                    original.combine(&inputs),
+                    "{text}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parameterized_format() {
+        let threshold = ThresholdCombinatorNode::new(300, 1, 9);
+        let text = serialize_combinator(&threshold);
+        assert_eq!(text, r#"{"type":"threshold","args":"300:1:9"}"#);
+        let restored = deserialize_combinator(&text).unwrap();
+        assert_eq!(restored.combine(&[200, 100]), 9);
+        assert_eq!(restored.combine(&[200, 99]), 1);
+        assert_eq!(
+            serialize_combinator(combinator_from_str("max").unwrap().as_ref()),
+            r#"{"type":"max"}"#
+        );
+    }
+
+    #[test]
+    fn test_deserialize_with_registry() {
+        let registry = CombinatorRegistry::with_builtins();
+        let text = r#"{"type":"weighted","args":"2,1"}"#;
+        let weighted = deserialize_combinator_with(text, &registry).unwrap();
+        assert_eq!(weighted.combine(&[3, 0]), 2);
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        assert!(matches!(
+            deserialize_combinator("{"),
+            Err(CombinatorDeserializeError::Json(_))
+        ));
+        assert!(matches!(
+            deserialize_combinator(r#"{"args":"1"}"#),
+            Err(CombinatorDeserializeError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            deserialize_combinator(r#"{"type":"max","args":3}"#),
+            Err(CombinatorDeserializeError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            deserialize_combinator(r#"{"type":"nope"}"#),
+            Err(CombinatorDeserializeError::Parse(
+                CombinatorParseError::UnknownName(_)
+            ))
+        ));
+    }
+}
@@ -0,0 +1,258 @@
+/// Runtime registry for looking up combinators by name, including ones defined outside this crate.
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::neural::combinator::{BUILTINS, CombinatorParseError, FnCombinatorNode};
+use crate::neural::traits::Combinator;
+
+/// Constructs a combinator from its argument string (empty when no arguments were given).
+pub type CombinatorFactory =
+    Box<dyn Fn(&str) -> Result<Box<dyn Combinator>, CombinatorParseError> + Send + Sync>;
+
+/// A name to factory lookup table for combinators.
+/// Names are matched case-insensitively.
+#[derive(Default)]
+pub struct CombinatorRegistry {
+    factories: BTreeMap<String, CombinatorFactory>,
+}
+
+impl CombinatorRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a registry containing every built-in combinator under its identifier and aliases.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        for builtin in BUILTINS {
+            let factory = builtin.factory;
+            for name in core::iter::once(&builtin.identifier).chain(builtin.aliases) {
+                registry.factories.insert(
+                    name.to_string(),
+                    Box::new(move |args: &str| factory((!args.is_empty()).then_some(args))),
+                );
+            }
+        }
+        registry
+    }
+
+    /// Registers a factory under `name`.
+    ///
+    /// # Returns
+    /// `RegistrationError` if the name is already taken; use `replace` to overwrite it.
+    pub fn register(
+        &mut self,
+        name: &str,
+        factory: CombinatorFactory,
+    ) -> Result<(), RegistrationError> {
+        let name = name.trim().to_lowercase();
+        if self.factories.contains_key(&name) {
+            return Err(RegistrationError { name });
+        }
+        self.factories.insert(name, factory);
+        Ok(())
+    }
+
+    /// Registers a closure under `name`; the combinators it creates are `FnCombinatorNode`s
+    /// sharing the closure and take no arguments.
+    pub fn register_fn(
+        &mut self,
+        name: &str,
+        f: impl Fn(&[u8]) -> u8 + Send + Sync + 'static,
+    ) -> Result<(), RegistrationError> {
+        let identifier = name.trim().to_lowercase();
+        let node = FnCombinatorNode::new(identifier.clone(), f);
+        self.register(
+            name,
+            Box::new(move |args: &str| {
+                if !args.is_empty() {
+                    return Err(CombinatorParseError::invalid_arguments(
+                        &identifier,
+                        args,
+                        "takes no arguments",
+                    ));
+                }
+                Ok(Box::new(node.clone()))
+            }),
+        )
+    }
+
+    /// Registers a factory under `name`, returning the factory it replaced, if any.
+    pub fn replace(&mut self, name: &str, factory: CombinatorFactory) -> Option<CombinatorFactory> {
+        self.factories.insert(name.trim().to_lowercase(), factory)
+    }
+
+    /// Returns whether a factory is registered under `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(&name.trim().to_lowercase())
+    }
+
+    /// Creates the combinator registered under `name` with the given arguments.
+    pub fn create(
+        &self,
+        name: &str,
+        args: &str,
+    ) -> Result<Box<dyn Combinator>, CombinatorParseError> {
+        let name = name.trim().to_lowercase();
+        let factory = self
+            .factories
+            .get(&name)
+            .ok_or(CombinatorParseError::UnknownName(name))?;
+        factory(args)
+    }
+
+    /// Creates a combinator from a `name` or `name:args` description, like `combinator_from_str`.
+    pub fn create_from_str(
+        &self,
+        description: &str,
+    ) -> Result<Box<dyn Combinator>, CombinatorParseError> {
+        match description.split_once(':') {
+            Some((name, args)) => self.create(name, args),
+            None => self.create(description, ""),
+        }
+    }
+
+    /// Returns every registered name in sorted order.
+    pub fn names(&self) -> Vec<String> {
+        self.factories.keys().cloned().collect()
+    }
+}
+
+/// Error returned when registering a name that is already taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistrationError {
+    /// The name that was already registered.
+    pub name: String,
+}
+
+impl core::fmt::Display for RegistrationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "combinator {:?} is already registered", self.name)
+    }
+}
+
+impl core::error::Error for RegistrationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::MaxCombinatorNode;
+
+    /// Returns the constant it was configured with.
+    struct ConstantCombinator(u8);
+
+    impl Combinator for ConstantCombinator {
+        fn combine(&self, _inputs: &[u8]) -> u8 {
+            self.0
+        }
+        fn identifier(&self) -> String {
+            "fixed".to_string()
+        }
+        fn parameters(&self) -> Option<String> {
+            Some(self.0.to_string())
+        }
+    }
+
+    fn constant_factory() -> CombinatorFactory {
+        Box::new(|args: &str| {
+            let value = args
+                .parse()
+                .map_err(|_| CombinatorParseError::InvalidArguments {
+                    name: "fixed".to_string(),
+                    args: args.to_string(),
+                    reason: "expected a byte".to_string(),
+                })?;
+            Ok(Box::new(ConstantCombinator(value)))
+        })
+    }
+
+    #[test]
+    fn test_register_custom() {
+        let mut registry = CombinatorRegistry::with_builtins();
+        registry.register("Fixed", constant_factory()).unwrap();
+        assert!(registry.contains("fixed"));
+
+        let constant = registry.create("fixed", "7").unwrap();
+        assert_eq!(constant.combine(&[1, 2, 3]), 7);
+        assert_eq!(constant.identifier(), "fixed");
+        assert_eq!(constant.parameters(), Some("7".to_string()));
+        assert_eq!(registry.create_from_str("FIXED:9").unwrap().combine(&[]), 9);
+        assert!(matches!(
+            registry.create("fixed", "x"),
+            Err(CombinatorParseError::InvalidArguments { .. })
+        ));
+    }
+
+    #[test]
+    fn test_builtins_still_resolve() {
+        let mut registry = CombinatorRegistry::with_builtins();
+        registry.register("fixed", constant_factory()).unwrap();
+        let max = registry.create("max", "").unwrap();
+        assert_eq!(max.identifier(), MaxCombinatorNode::IDENTIFIER);
+        assert_eq!(registry.create("+", "").unwrap().combine(&[1, 2]), 3);
+        assert_eq!(
+            registry.create("weighted", "3,1").unwrap().combine(&[4, 0]),
+            3
+        );
+        assert_eq!(
+            registry
+                .create_from_str("threshold:2")
+                .unwrap()
+                .combine(&[2]),
+            255
+        );
+        assert!(registry.create("max", "1").is_err());
+    }
+
+    #[test]
+    fn test_register_fn() {
+        let mut registry = CombinatorRegistry::with_builtins();
+        registry
+            .register_fn("second_largest", |inputs: &[u8]| {
+                let mut sorted = inputs.to_vec();
+                sorted.sort_unstable_by(|a, b| b.cmp(a));
+                sorted.get(1).copied().unwrap_or(0)
+            })
+            .unwrap();
+        let node = registry.create_from_str("second_largest").unwrap();
+        assert_eq!(node.identifier(), "second_largest");
+        assert_eq!(node.combine(&[5, 9, 7]), 7);
+        assert!(registry.create("second_largest", "1").is_err());
+        assert!(registry.register_fn("max", |_: &[u8]| 0).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_registration() {
+        let mut registry = CombinatorRegistry::with_builtins();
+        assert_eq!(
+            registry.register("MAX", constant_factory()).err(),
+            Some(RegistrationError {
+                name: "max".to_string()
+            })
+        );
+        assert!(registry.replace("max", constant_factory()).is_some());
+        assert_eq!(registry.create("max", "4").unwrap().combine(&[200]), 4);
+    }
+
+    #[test]
+    fn test_unknown_and_names() {
+        let registry = CombinatorRegistry::new();
+        assert!(registry.names().is_empty());
+        assert_eq!(
+            registry.create("max", "").err(),
+            Some(CombinatorParseError::UnknownName("max".to_string()))
+        );
+
+        let names = CombinatorRegistry::with_builtins().names();
+        assert!(names.contains(&"addition".to_string()));
+        assert!(names.contains(&"avg".to_string()));
+        assert!(names.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -0,0 +1,377 @@
+/// Plumbing combinators: fixed values and input selection.
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use crate::neural::combinator::CombinatorParseError;
+use crate::neural::combinator_stats::parse_count;
+use crate::neural::traits::Combinator;
+
+/// A combinator node that ignores its inputs and always returns the same value.
+pub struct ConstantCombinatorNode {
+    value: u8,
+}
+
+impl ConstantCombinatorNode {
+    pub const IDENTIFIER: &'static str = "constant";
+    pub fn new(value: u8) -> Self {
+        Self { value }
+    }
+
+    /// Parses the value to return, e.g. `"128"`.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        let value = parse_count(Self::IDENTIFIER, args)?;
+        let value = u8::try_from(value).map_err(|_| {
+            CombinatorParseError::invalid_arguments(
+                Self::IDENTIFIER,
+                args,
+                "value must be at most 255",
+            )
+        })?;
+        Ok(Self::new(value))
+    }
+
+    /// Returns the value produced.
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+}
+
+impl Combinator for ConstantCombinatorNode {
+    fn combine(&self, _inputs: &[u8]) -> u8 {
+        self.value
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn parameters(&self) -> Option<String> {
+        Some(self.value.to_string())
+    }
+    fn describe(&self) -> String {
+        format!("{}(value={})", Self::IDENTIFIER, self.value)
+    }
+}
+
+/// How a `MuxCombinatorNode` turns its selector into a choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MuxSelect {
+    /// The selector is the index of the data input to pass, counting from 0.
+    /// A selector past the last data input yields 0.
+    Exact,
+    /// A selector above the threshold passes the first data input, otherwise the second.
+    Threshold(u8),
+}
+
+/// A combinator node that uses its first input as a selector to pick one of the remaining inputs.
+/// A missing data input yields 0.
+pub struct MuxCombinatorNode {
+    select: MuxSelect,
+}
+
+impl MuxCombinatorNode {
+    pub const IDENTIFIER: &'static str = "mux";
+
+    /// Creates a mux whose selector is the index of the data input to pass.
+    pub fn exact() -> Self {
+        Self {
+            select: MuxSelect::Exact,
+        }
+    }
+
+    /// Creates a two-way mux that passes the first data input when the selector is above
+    /// `threshold`, and the second otherwise.
+    pub fn threshold(threshold: u8) -> Self {
+        Self {
+            select: MuxSelect::Threshold(threshold),
+        }
+    }
+
+    /// Parses an optional threshold: no arguments give an exact mux, `"128"` a threshold mux.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        if args.trim().is_empty() {
+            return Ok(Self::exact());
+        }
+        let threshold = parse_count(Self::IDENTIFIER, args)?;
+        let threshold = u8::try_from(threshold).map_err(|_| {
+            CombinatorParseError::invalid_arguments(
+                Self::IDENTIFIER,
+                args,
+                "threshold must be at most 255",
+            )
+        })?;
+        Ok(Self::threshold(threshold))
+    }
+
+    /// Returns how the selector picks an input.
+    pub fn select(&self) -> MuxSelect {
+        self.select
+    }
+}
+
+impl Default for MuxCombinatorNode {
+    fn default() -> Self {
+        Self::exact()
+    }
+}
+
+impl Combinator for MuxCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        let Some((&selector, data)) = inputs.split_first() else {
+            return 0;
+        };
+        let index = match self.select {
+            MuxSelect::Exact => selector as usize,
+            MuxSelect::Threshold(threshold) => usize::from(selector <= threshold),
+        };
+        data.get(index).copied().unwrap_or(0)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        match self.select {
+            MuxSelect::Exact => 2,
+            MuxSelect::Threshold(_) => 3,
+        }
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        match self.select {
+            MuxSelect::Exact => None,
+            MuxSelect::Threshold(_) => Some(3),
+        }
+    }
+    fn parameters(&self) -> Option<String> {
+        match self.select {
+            MuxSelect::Exact => None,
+            MuxSelect::Threshold(threshold) => Some(threshold.to_string()),
+        }
+    }
+    fn describe(&self) -> String {
+        match self.select {
+            MuxSelect::Exact => Self::IDENTIFIER.to_string(),
+            MuxSelect::Threshold(threshold) => {
+                format!("{}(threshold={threshold})", Self::IDENTIFIER)
+            }
+        }
+    }
+}
+
+/// A combinator node that passes its first input through. No inputs yield 0.
+#[derive(Default)]
+pub struct FirstCombinatorNode {}
+
+impl FirstCombinatorNode {
+    pub const IDENTIFIER: &'static str = "first";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for FirstCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        inputs.first().copied().unwrap_or(0)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that passes its last input through. No inputs yield 0.
+#[derive(Default)]
+pub struct LastCombinatorNode {}
+
+impl LastCombinatorNode {
+    pub const IDENTIFIER: &'static str = "last";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for LastCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        inputs.last().copied().unwrap_or(0)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that passes the input at a fixed position through, counting from 0.
+/// It declares that it needs at least `index + 1` inputs, but when called with fewer anyway it
+/// yields 0 rather than clamping to the last input.
+pub struct SelectCombinatorNode {
+    index: usize,
+}
+
+impl SelectCombinatorNode {
+    pub const IDENTIFIER: &'static str = "select";
+    pub fn new(index: usize) -> Self {
+        Self { index }
+    }
+
+    /// Parses the position to pass, e.g. `"2"`.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        Ok(Self::new(parse_count(Self::IDENTIFIER, args)?))
+    }
+
+    /// Returns the position of the input passed through.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl Combinator for SelectCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        inputs.get(self.index).copied().unwrap_or(0)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        self.index.saturating_add(1)
+    }
+    fn parameters(&self) -> Option<String> {
+        Some(self.index.to_string())
+    }
+    fn describe(&self) -> String {
+        format!("{}(index={})", Self::IDENTIFIER, self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::neural::graph::Graph;
+    use crate::neural::node::SignalNode;
+
+    #[cfg(feature = "std")]
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_constant() {
+        let constant = ConstantCombinatorNode::new(42);
+        assert_eq!(constant.combine(&[]), 42);
+        assert_eq!(constant.combine(&[1, 2, 3]), 42);
+        assert_eq!(ConstantCombinatorNode::from_args("7").unwrap().value(), 7);
+        assert!(ConstantCombinatorNode::from_args("256").is_err());
+    }
+
+    #[test]
+    fn test_exact_mux() {
+        let mux = MuxCombinatorNode::exact();
+        assert_eq!(mux.combine(&[0, 10, 20, 30]), 10);
+        assert_eq!(mux.combine(&[2, 10, 20, 30]), 30);
+        assert_eq!(mux.combine(&[3, 10, 20, 30]), 0);
+        assert_eq!(mux.combine(&[]), 0);
+        assert_eq!(
+            MuxCombinatorNode::from_args("").unwrap().select(),
+            MuxSelect::Exact
+        );
+        assert_eq!(
+            MuxCombinatorNode::from_args("128").unwrap().select(),
+            MuxSelect::Threshold(128)
+        );
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        assert_eq!(FirstCombinatorNode::new().combine(&[4, 5, 6]), 4);
+        assert_eq!(LastCombinatorNode::new().combine(&[4, 5, 6]), 6);
+        assert_eq!(FirstCombinatorNode::new().combine(&[9]), 9);
+        assert_eq!(LastCombinatorNode::new().combine(&[9]), 9);
+        assert_eq!(FirstCombinatorNode::new().combine(&[]), 0);
+        assert_eq!(LastCombinatorNode::new().combine(&[]), 0);
+        assert!(FirstCombinatorNode::new().combine_checked(&[]).is_err());
+        assert!(LastCombinatorNode::new().combine_checked(&[]).is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let select = SelectCombinatorNode::new(2);
+        assert_eq!(select.combine(&[4, 5, 6, 7]), 6);
+        assert_eq!(select.min_inputs(), 3);
+        assert_eq!(select.parameters(), Some("2".to_string()));
+        // Out of range yields 0, not the last input.
+        assert_eq!(select.combine(&[4, 5]), 0);
+        assert_eq!(select.combine(&[]), 0);
+        assert!(select.combine_checked(&[4, 5]).is_err());
+        assert_eq!(select.combine_checked(&[4, 5, 6]), Ok(6));
+        assert_eq!(SelectCombinatorNode::new(usize::MAX).combine(&[1]), 0);
+        assert_eq!(SelectCombinatorNode::from_args("0").unwrap().index(), 0);
+        assert!(SelectCombinatorNode::from_args("").is_err());
+        assert!(SelectCombinatorNode::from_args("-1").is_err());
+    }
+
+    #[test]
+    fn test_passthrough_from_str() {
+        use crate::neural::combinator::combinator_from_str;
+        assert_eq!(combinator_from_str("first").unwrap().combine(&[1, 2, 3]), 1);
+        assert_eq!(combinator_from_str("last").unwrap().combine(&[1, 2, 3]), 3);
+        let select = combinator_from_str("select:2").unwrap();
+        assert_eq!(select.identifier(), SelectCombinatorNode::IDENTIFIER);
+        assert_eq!(select.combine(&[1, 2, 3]), 3);
+        assert!(combinator_from_str("first:1").is_err());
+        // `select` names this combinator, not `mux`.
+        assert!(combinator_from_str("select").is_err());
+    }
+
+    /// Routes `a` when the selector is above 128 and `b` otherwise, and reports the branch
+    /// taken by muxing two constants on the same selector.
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_conditional_routing() {
+        let mut graph = Graph::new();
+        for input in ["selector", "a", "b"] {
+            graph.add_input(input).unwrap();
+        }
+        graph
+            .add_node("route", node(MuxCombinatorNode::threshold(128)))
+            .unwrap();
+        graph
+            .add_node("branch", node(MuxCombinatorNode::threshold(128)))
+            .unwrap();
+        graph
+            .add_node("on", node(ConstantCombinatorNode::new(255)))
+            .unwrap();
+        graph
+            .add_node("off", node(ConstantCombinatorNode::new(0)))
+            .unwrap();
+        graph
+            .fan_out("selector", &[("route", 0), ("branch", 0)])
+            .unwrap();
+        graph.connect("a", "route", 1).unwrap();
+        graph.connect("b", "route", 2).unwrap();
+        graph.connect("on", "branch", 1).unwrap();
+        graph.connect("off", "branch", 2).unwrap();
+        graph.add_output("route").unwrap();
+        graph.add_output("branch").unwrap();
+        let circuit = graph.compile().unwrap();
+
+        assert_eq!(circuit.evaluate(&[200, 11, 22]), Ok(vec![11, 255]));
+        assert_eq!(circuit.evaluate(&[129, 11, 22]), Ok(vec![11, 255]));
+        assert_eq!(circuit.evaluate(&[128, 11, 22]), Ok(vec![22, 0]));
+        assert_eq!(circuit.evaluate(&[0, 11, 22]), Ok(vec![22, 0]));
+    }
+
+    #[test]
+    fn test_threshold_mux_arity() {
+        let two_inputs = SignalNode::builder()
+            .combinator(Box::new(MuxCombinatorNode::threshold(1)))
+            .input_count(2)
+            .build();
+        assert!(two_inputs.is_err());
+    }
+}
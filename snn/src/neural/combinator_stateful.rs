@@ -0,0 +1,246 @@
+use alloc::{collections::VecDeque, vec::Vec};
+
+use crate::neural::combinator::AverageCombinatorNode;
+use crate::neural::traits::{Combinator, StatefulCombinator};
+
+/// A stateful combinator that smooths the average of each step's inputs with an
+/// exponential moving average, `state += alpha * (average - state)`.
+/// The state is kept in 8.8 fixed point so small steps do not get lost to rounding,
+/// and the first step after a reset starts the average at that step's value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmaCombinatorNode {
+    alpha_num: u8,
+    alpha_den: u8,
+    state: Option<u16>,
+}
+
+impl EmaCombinatorNode {
+    pub const IDENTIFIER: &'static str = "ema";
+
+    /// Creates an average with smoothing factor `alpha_num / alpha_den`.
+    /// A larger factor follows the input more quickly; `1/1` follows it exactly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alpha_den` is zero or `alpha_num` is greater than `alpha_den`.
+    pub fn new(alpha_num: u8, alpha_den: u8) -> Self {
+        assert!(
+            alpha_den > 0 && alpha_num <= alpha_den,
+            "EMA factor {alpha_num}/{alpha_den} must be between 0 and 1"
+        );
+        Self {
+            alpha_num,
+            alpha_den,
+            state: None,
+        }
+    }
+
+    /// Returns the current smoothed value, or `None` before the first step.
+    pub fn value(&self) -> Option<u8> {
+        self.state.map(from_fixed)
+    }
+}
+
+impl StatefulCombinator for EmaCombinatorNode {
+    fn step(&mut self, inputs: &[u8]) -> u8 {
+        let target = (AverageCombinatorNode::new().combine(inputs) as u16) << 8;
+        let state = match self.state {
+            None => target,
+            Some(state) => {
+                let delta =
+                    (target as i32 - state as i32) * self.alpha_num as i32 / self.alpha_den as i32;
+                (state as i32 + delta) as u16
+            }
+        };
+        self.state = Some(state);
+        from_fixed(state)
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+
+    /// The state is empty before the first step and otherwise the 8.8 fixed point average as
+    /// a little-endian `u16`.
+    fn save_state(&self) -> Vec<u8> {
+        self.state
+            .map_or(Vec::new(), |state| state.to_le_bytes().to_vec())
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> bool {
+        match *state {
+            [] => self.state = None,
+            [low, high] => self.state = Some(u16::from_le_bytes([low, high])),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Rounds an 8.8 fixed point value to the nearest byte.
+fn from_fixed(value: u16) -> u8 {
+    ((value as u32 + 0x80) >> 8).min(255) as u8
+}
+
+/// A stateful adapter that combines each step's inputs with `C`, remembers the last
+/// `window_size` of those results, and returns `C` applied over that window.
+/// For example `Windowed::new(MedianCombinatorNode::new(), 5)` is a running median filter.
+pub struct Windowed<C> {
+    combinator: C,
+    window_size: usize,
+    window: VecDeque<u8>,
+}
+
+impl<C: Combinator> Windowed<C> {
+    /// # Panics
+    ///
+    /// Panics if `window_size` is zero.
+    pub fn new(combinator: C, window_size: usize) -> Self {
+        assert!(window_size > 0, "window size must be at least 1");
+        Self {
+            combinator,
+            window_size,
+            window: VecDeque::with_capacity(window_size),
+        }
+    }
+
+    /// Returns the wrapped combinator.
+    pub fn combinator(&self) -> &C {
+        &self.combinator
+    }
+
+    /// Returns the number of steps remembered.
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Returns the remembered step results, oldest first.
+    pub fn window(&self) -> impl Iterator<Item = u8> + '_ {
+        self.window.iter().copied()
+    }
+}
+
+impl<C: Combinator> StatefulCombinator for Windowed<C> {
+    fn step(&mut self, inputs: &[u8]) -> u8 {
+        if self.window.len() == self.window_size {
+            self.window.pop_front();
+        }
+        self.window.push_back(self.combinator.combine(inputs));
+        self.combinator.combine(self.window.make_contiguous())
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+
+    /// The state is the remembered step results, oldest first.
+    fn save_state(&self) -> Vec<u8> {
+        self.window.iter().copied().collect()
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> bool {
+        if state.len() > self.window_size {
+            return false;
+        }
+        self.window.clear();
+        self.window.extend(state);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::MedianCombinatorNode;
+
+    #[test]
+    fn test_ema_converges_to_step() {
+        let mut ema = EmaCombinatorNode::new(1, 4);
+        assert_eq!(ema.value(), None);
+        assert_eq!(ema.step(&[10, 30]), 20);
+        assert_eq!(ema.step(&[20]), 20);
+
+        let outputs: Vec<u8> = (0..40).map(|_| ema.step(&[200])).collect();
+        assert!(outputs.windows(2).all(|w| w[0] <= w[1]), "{outputs:?}");
+        assert_eq!(outputs[0], 65);
+        assert_eq!(*outputs.last().unwrap(), 200);
+
+        let outputs: Vec<u8> = (0..40).map(|_| ema.step(&[0])).collect();
+        assert!(outputs.windows(2).all(|w| w[0] >= w[1]), "{outputs:?}");
+        assert_eq!(*outputs.last().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_ema_small_alpha_does_not_stall() {
+        let mut ema = EmaCombinatorNode::new(1, 100);
+        ema.step(&[0]);
+        let last = (0..2000).map(|_| ema.step(&[255])).last().unwrap();
+        assert_eq!(last, 255);
+    }
+
+    #[test]
+    fn test_ema_reset() {
+        let mut ema = EmaCombinatorNode::new(1, 2);
+        ema.step(&[0]);
+        ema.step(&[100]);
+        ema.reset();
+        assert_eq!(ema.value(), None);
+        assert_eq!(ema.step(&[100]), 100);
+    }
+
+    #[test]
+    fn test_ema_full_alpha_follows_input() {
+        let mut ema = EmaCombinatorNode::new(3, 3);
+        for value in [5, 250, 0, 77] {
+            assert_eq!(ema.step(&[value]), value);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ema_rejects_alpha_above_one() {
+        EmaCombinatorNode::new(2, 1);
+    }
+
+    #[test]
+    fn test_windowed_median() {
+        let mut median = Windowed::new(MedianCombinatorNode::new(), 3);
+        assert_eq!(median.step(&[10]), 10);
+        assert_eq!(median.step(&[200]), 105);
+        assert_eq!(median.step(&[20]), 20);
+        // A single spike is filtered out once the window is full.
+        assert_eq!(median.step(&[255]), 200);
+        assert_eq!(median.step(&[30]), 30);
+        assert_eq!(median.window().collect::<Vec<_>>(), vec![20, 255, 30]);
+
+        median.reset();
+        assert_eq!(median.window().count(), 0);
+        assert_eq!(median.step(&[1, 2, 3]), 2);
+    }
+
+    #[test]
+    fn test_save_and_load_state() {
+        let mut ema = EmaCombinatorNode::new(1, 4);
+        assert_eq!(ema.save_state(), Vec::<u8>::new());
+        ema.step(&[10]);
+        ema.step(&[200]);
+        let mut restored = EmaCombinatorNode::new(1, 4);
+        assert!(restored.load_state(&ema.save_state()));
+        assert_eq!(restored, ema);
+        assert_eq!(restored.step(&[90]), ema.step(&[90]));
+        assert!(!restored.load_state(&[1, 2, 3]));
+        assert_eq!(restored, ema);
+        assert!(restored.load_state(&[]));
+        assert_eq!(restored.value(), None);
+
+        let mut median = Windowed::new(MedianCombinatorNode::new(), 3);
+        for x in [10, 200, 20, 255] {
+            median.step(&[x]);
+        }
+        let mut restored = Windowed::new(MedianCombinatorNode::new(), 3);
+        assert!(restored.load_state(&median.save_state()));
+        assert_eq!(restored.step(&[30]), median.step(&[30]));
+        assert!(!restored.load_state(&[1, 2, 3, 4]));
+        assert_eq!(restored.window().collect::<Vec<_>>(), vec![20, 255, 30]);
+    }
+}
@@ -0,0 +1,776 @@
+/// Combinators computing statistics over their inputs.
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use crate::neural::combinator::CombinatorParseError;
+use crate::neural::traits::Combinator;
+
+/// Returns the most frequent value, preferring the smaller value on ties. An empty input yields 0.
+pub(crate) fn most_frequent(inputs: &[u8]) -> u8 {
+    let mut counts = [0usize; 256];
+    for &x in inputs {
+        counts[x as usize] += 1;
+    }
+    let mut best = 0;
+    for (value, &count) in counts.iter().enumerate() {
+        if count > counts[best] {
+            best = value;
+        }
+    }
+    best as u8
+}
+
+/// Rounds a floating point result and clamps it into the u8 range.
+/// Halves round up. Only `core` float operations are used so this also works without `std`.
+pub(crate) fn round_to_u8(value: f64) -> u8 {
+    let clamped = value.clamp(0.0, u8::MAX as f64);
+    let whole = clamped as u8;
+    if clamped - whole as f64 >= 0.5 {
+        whole + 1
+    } else {
+        whole
+    }
+}
+
+/// Returns whether the product of `inputs` is at least `root` raised to the number of inputs.
+/// The running product is kept in `[1, 2)` with its power of two tracked separately,
+/// so long inputs cannot overflow.
+fn product_at_least(inputs: &[u8], root: f64) -> bool {
+    let mut product = 1.0f64;
+    let mut exponent = 0i64;
+    for &x in inputs {
+        product *= x as f64 / root;
+        while product >= 2.0 {
+            product /= 2.0;
+            exponent += 1;
+        }
+        while product < 1.0 {
+            product *= 2.0;
+            exponent -= 1;
+        }
+    }
+    exponent >= 0
+}
+
+/// A combinator node that returns the most frequent input value.
+/// Ties are broken in favour of the smaller value, and an empty input yields 0.
+#[derive(Default)]
+pub struct ModeCombinatorNode {}
+
+impl ModeCombinatorNode {
+    pub const IDENTIFIER: &'static str = "mode";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for ModeCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        most_frequent(inputs)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that returns the difference between the largest and smallest input.
+/// An empty input yields 0.
+#[derive(Default)]
+pub struct RangeCombinatorNode {}
+
+impl RangeCombinatorNode {
+    pub const IDENTIFIER: &'static str = "range";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for RangeCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        match (inputs.iter().max(), inputs.iter().min()) {
+            (Some(max), Some(min)) => max - min,
+            _ => 0,
+        }
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that returns the population standard deviation of the inputs,
+/// rounded to the nearest integer. An empty input yields 0.
+#[derive(Default)]
+pub struct StdDevCombinatorNode {}
+
+impl StdDevCombinatorNode {
+    pub const IDENTIFIER: &'static str = "stddev";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for StdDevCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        if inputs.is_empty() {
+            return 0;
+        }
+        let n = inputs.len() as u128;
+        let sum: u128 = inputs.iter().map(|&x| x as u128).sum();
+        let square_sum: u128 = inputs.iter().map(|&x| (x as u128).pow(2)).sum();
+        // n² times the variance, exact in integers.
+        let scaled = n * square_sum - sum * sum;
+        // The rounded deviation is the largest k with (k - 0.5)² n² <= scaled.
+        (1..=u8::MAX)
+            .take_while(|&k| (2 * k as u128 - 1).pow(2) * n * n <= 4 * scaled)
+            .last()
+            .unwrap_or(0)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that returns the geometric mean of the inputs, rounded to the nearest integer.
+/// Any zero input makes the result 0, and an empty input yields 0.
+#[derive(Default)]
+pub struct GeometricMeanCombinatorNode {}
+
+impl GeometricMeanCombinatorNode {
+    pub const IDENTIFIER: &'static str = "geometric_mean";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for GeometricMeanCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        if inputs.is_empty() || inputs.contains(&0) {
+            return 0;
+        }
+        // The rounded mean is the largest k with (k - 0.5)^n <= product, found by bisection
+        // between 1 and the largest input.
+        let mut low = 1;
+        let mut high = inputs.iter().copied().max().unwrap_or(1);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if product_at_least(inputs, mid as f64 - 0.5) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        low
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that returns the harmonic mean of the inputs, rounded to the nearest integer.
+/// The harmonic mean is undefined for zero inputs, so any zero input makes the result 0.
+/// An empty input yields 0.
+#[derive(Default)]
+pub struct HarmonicMeanCombinatorNode {}
+
+impl HarmonicMeanCombinatorNode {
+    pub const IDENTIFIER: &'static str = "harmonic_mean";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for HarmonicMeanCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        if inputs.is_empty() || inputs.contains(&0) {
+            return 0;
+        }
+        let reciprocal_sum: f64 = inputs.iter().map(|&x| 1.0 / x as f64).sum();
+        round_to_u8(inputs.len() as f64 / reciprocal_sum)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// Converts an input position to an output byte, saturating at 255.
+fn index_to_u8(index: usize) -> u8 {
+    index.min(u8::MAX as usize) as u8
+}
+
+/// A combinator node that returns the index of the largest input.
+/// Ties go to the first occurrence, an empty input yields 0,
+/// and indices past 255 saturate to 255.
+#[derive(Default)]
+pub struct ArgMaxCombinatorNode {}
+
+impl ArgMaxCombinatorNode {
+    pub const IDENTIFIER: &'static str = "argmax";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for ArgMaxCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        let mut best = 0;
+        for (i, &x) in inputs.iter().enumerate() {
+            if x > inputs[best] {
+                best = i;
+            }
+        }
+        index_to_u8(best)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// A combinator node that returns the index of the smallest input.
+/// Ties go to the first occurrence, an empty input yields 0,
+/// and indices past 255 saturate to 255.
+#[derive(Default)]
+pub struct ArgMinCombinatorNode {}
+
+impl ArgMinCombinatorNode {
+    pub const IDENTIFIER: &'static str = "argmin";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Combinator for ArgMinCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        let mut best = 0;
+        for (i, &x) in inputs.iter().enumerate() {
+            if x < inputs[best] {
+                best = i;
+            }
+        }
+        index_to_u8(best)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+}
+
+/// Returns the mean of the values rounded down, or 0 when there are none.
+fn floor_mean(values: &[u8]) -> u8 {
+    if values.is_empty() {
+        return 0;
+    }
+    let sum: usize = values.iter().map(|&x| x as usize).sum();
+    (sum / values.len()) as u8
+}
+
+/// Parses a single count argument for the combinator `name`.
+pub(crate) fn parse_count(name: &str, args: &str) -> Result<usize, CombinatorParseError> {
+    args.trim()
+        .parse()
+        .map_err(|e| CombinatorParseError::invalid_arguments(name, args, format!("{e}")))
+}
+
+/// A combinator node that averages the `k` largest inputs, rounding down.
+/// A `k` larger than the number of inputs averages all of them, and `k = 0` or an empty input yields 0.
+pub struct TopKAverageCombinatorNode {
+    k: usize,
+}
+
+impl TopKAverageCombinatorNode {
+    pub const IDENTIFIER: &'static str = "topk";
+    pub fn new(k: usize) -> Self {
+        Self { k }
+    }
+
+    /// Parses the value of `k`, e.g. `"3"`.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        Ok(Self::new(parse_count(Self::IDENTIFIER, args)?))
+    }
+}
+
+impl Combinator for TopKAverageCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        let k = self.k.min(inputs.len());
+        if k == 0 {
+            return 0;
+        }
+        let mut values = inputs.to_vec();
+        values.select_nth_unstable_by(k - 1, |a, b| b.cmp(a));
+        floor_mean(&values[..k])
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+    fn parameters(&self) -> Option<String> {
+        Some(self.k.to_string())
+    }
+    fn describe(&self) -> String {
+        format!("{}(k={})", Self::IDENTIFIER, self.k)
+    }
+}
+
+/// A combinator node that drops the `trim` smallest and `trim` largest inputs and averages the rest,
+/// rounding down. The trim is reduced when needed so at least one input remains,
+/// and an empty input yields 0.
+pub struct TrimmedMeanCombinatorNode {
+    trim: usize,
+}
+
+impl TrimmedMeanCombinatorNode {
+    pub const IDENTIFIER: &'static str = "trimmed";
+    pub fn new(trim: usize) -> Self {
+        Self { trim }
+    }
+
+    /// Parses the number of inputs to drop from each end, e.g. `"1"`.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        Ok(Self::new(parse_count(Self::IDENTIFIER, args)?))
+    }
+}
+
+impl Combinator for TrimmedMeanCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        if inputs.is_empty() {
+            return 0;
+        }
+        let trim = self.trim.min((inputs.len() - 1) / 2);
+        let mut values = inputs.to_vec();
+        values.sort_unstable();
+        floor_mean(&values[trim..values.len() - trim])
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+    fn parameters(&self) -> Option<String> {
+        Some(self.trim.to_string())
+    }
+    fn describe(&self) -> String {
+        format!("{}(trim={})", Self::IDENTIFIER, self.trim)
+    }
+}
+
+/// A combinator node that returns the input at a percentile using the nearest-rank method:
+/// the result is the `ceil(percentile / 100 * n)`-th smallest of the `n` inputs (counting from 1),
+/// and percentile 0 returns the smallest input. An empty input yields 0.
+pub struct PercentileCombinatorNode {
+    percentile: u8,
+}
+
+impl PercentileCombinatorNode {
+    pub const IDENTIFIER: &'static str = "percentile";
+
+    /// # Panics
+    ///
+    /// Panics if `percentile` is greater than 100.
+    pub fn new(percentile: u8) -> Self {
+        assert!(percentile <= 100, "percentile {percentile} is above 100");
+        Self { percentile }
+    }
+
+    /// Parses a percentile from 0 to 100, e.g. `"90"`.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        let percentile = parse_count(Self::IDENTIFIER, args)?;
+        if percentile > 100 {
+            return Err(CombinatorParseError::invalid_arguments(
+                Self::IDENTIFIER,
+                args,
+                "percentile must be at most 100",
+            ));
+        }
+        Ok(Self::new(percentile as u8))
+    }
+
+    /// Returns the percentile selected, from 0 to 100.
+    pub fn percentile(&self) -> u8 {
+        self.percentile
+    }
+}
+
+impl Combinator for PercentileCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        if inputs.is_empty() {
+            return 0;
+        }
+        let rank = (self.percentile as usize * inputs.len())
+            .div_ceil(100)
+            .max(1);
+        let mut values = inputs.to_vec();
+        *values.select_nth_unstable(rank - 1).1
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+    fn parameters(&self) -> Option<String> {
+        Some(self.percentile.to_string())
+    }
+    fn describe(&self) -> String {
+        format!("{}(p={})", Self::IDENTIFIER, self.percentile)
+    }
+}
+
+/// A combinator node that returns the `n`-th smallest input, counting from 0,
+/// so `n = 0` is the minimum. An `n` past the end returns the largest input,
+/// and an empty input yields 0.
+pub struct NthCombinatorNode {
+    n: usize,
+}
+
+impl NthCombinatorNode {
+    pub const IDENTIFIER: &'static str = "nth";
+    pub fn new(n: usize) -> Self {
+        Self { n }
+    }
+
+    /// Parses the value of `n`, e.g. `"2"`.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        Ok(Self::new(parse_count(Self::IDENTIFIER, args)?))
+    }
+}
+
+impl Combinator for NthCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        if inputs.is_empty() {
+            return 0;
+        }
+        let mut values = inputs.to_vec();
+        *values.select_nth_unstable(self.n.min(inputs.len() - 1)).1
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn min_inputs(&self) -> usize {
+        1
+    }
+    fn parameters(&self) -> Option<String> {
+        Some(self.n.to_string())
+    }
+    fn describe(&self) -> String {
+        format!("{}(n={})", Self::IDENTIFIER, self.n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::combinator_from_str;
+
+    fn geometric_reference(inputs: &[u8]) -> u8 {
+        let product: f64 = inputs.iter().map(|&x| x as f64).product();
+        product.powf(1.0 / inputs.len() as f64).round() as u8
+    }
+
+    fn harmonic_reference(inputs: &[u8]) -> u8 {
+        let sum: f64 = inputs.iter().map(|&x| 1.0 / x as f64).sum();
+        (inputs.len() as f64 / sum).round() as u8
+    }
+
+    #[test]
+    fn test_geometric_mean() {
+        let mean = combinator_from_str("gmean").unwrap();
+        assert_eq!(mean.identifier(), GeometricMeanCombinatorNode::IDENTIFIER);
+        assert_eq!(mean.combine(&[]), 0);
+        assert_eq!(mean.combine(&[0, 255]), 0);
+        assert_eq!(mean.combine(&[255; 8]), 255);
+        assert_eq!(mean.combine(&[4, 16]), 8);
+        assert_eq!(mean.combine(&[1, 255]), 16);
+        for inputs in [&[2u8, 8, 32][..], &[10, 200, 30, 7], &[255, 255, 1], &[3]] {
+            assert_eq!(
+                mean.combine(inputs),
+                geometric_reference(inputs),
+                "{inputs:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_harmonic_mean() {
+        let mean = combinator_from_str("hmean").unwrap();
+        assert_eq!(mean.identifier(), HarmonicMeanCombinatorNode::IDENTIFIER);
+        assert_eq!(mean.combine(&[]), 0);
+        assert_eq!(mean.combine(&[0, 255]), 0);
+        assert_eq!(mean.combine(&[255; 8]), 255);
+        // 2 / (1/1 + 1/255) = 1.99...
+        assert_eq!(mean.combine(&[1, 255]), 2);
+        assert_eq!(mean.combine(&[40, 60]), 48);
+        for inputs in [&[2u8, 8, 32][..], &[10, 200, 30, 7], &[255, 255, 1], &[3]] {
+            assert_eq!(
+                mean.combine(inputs),
+                harmonic_reference(inputs),
+                "{inputs:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_argmax() {
+        let argmax = combinator_from_str("argmax").unwrap();
+        assert_eq!(argmax.identifier(), ArgMaxCombinatorNode::IDENTIFIER);
+        assert_eq!(argmax.combine(&[]), 0);
+        assert_eq!(argmax.combine(&[42]), 0);
+        assert_eq!(argmax.combine(&[3, 9, 1, 9]), 1);
+        assert_eq!(argmax.combine(&[0, 0, 0]), 0);
+        assert_eq!(argmax.combine(&[1, 2, 3, 4]), 3);
+    }
+
+    #[test]
+    fn test_argmin() {
+        let argmin = combinator_from_str("argmin").unwrap();
+        assert_eq!(argmin.identifier(), ArgMinCombinatorNode::IDENTIFIER);
+        assert_eq!(argmin.combine(&[]), 0);
+        assert_eq!(argmin.combine(&[42]), 0);
+        assert_eq!(argmin.combine(&[3, 1, 9, 1]), 1);
+        assert_eq!(argmin.combine(&[4, 3, 2, 1]), 3);
+    }
+
+    #[test]
+    fn test_arg_index_saturates() {
+        let mut inputs = vec![100u8; 300];
+        inputs[10] = 200;
+        inputs[20] = 1;
+        assert_eq!(ArgMaxCombinatorNode::new().combine(&inputs), 10);
+        assert_eq!(ArgMinCombinatorNode::new().combine(&inputs), 20);
+        inputs[10] = 100;
+        inputs[20] = 100;
+        inputs[280] = 200;
+        inputs[299] = 1;
+        assert_eq!(ArgMaxCombinatorNode::new().combine(&inputs), 255);
+        assert_eq!(ArgMinCombinatorNode::new().combine(&inputs), 255);
+    }
+
+    fn sample_inputs() -> Vec<Vec<u8>> {
+        vec![
+            vec![],
+            vec![77],
+            vec![5, 1],
+            vec![10, 250, 30, 30, 0, 99],
+            vec![255; 7],
+            (0..50).map(|i| (i * 37 % 256) as u8).collect(),
+        ]
+    }
+
+    fn mean_reference(values: &[u8]) -> u8 {
+        if values.is_empty() {
+            return 0;
+        }
+        (values.iter().map(|&x| x as usize).sum::<usize>() / values.len()) as u8
+    }
+
+    #[test]
+    fn test_topk_matches_sort() {
+        for k in 0..10 {
+            let topk = TopKAverageCombinatorNode::new(k);
+            for inputs in sample_inputs() {
+                let mut sorted = inputs.clone();
+                sorted.sort_unstable_by(|a, b| b.cmp(a));
+                let expected = mean_reference(&sorted[..k.min(sorted.len())]);
+                assert_eq!(topk.combine(&inputs), expected, "k {k}, inputs {inputs:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_topk() {
+        let topk = combinator_from_str("topk:2").unwrap();
+        assert_eq!(topk.identifier(), TopKAverageCombinatorNode::IDENTIFIER);
+        assert_eq!(topk.parameters(), Some("2".to_string()));
+        assert_eq!(topk.combine(&[1, 200, 3, 100]), 150);
+        assert_eq!(topk.combine(&[9]), 9);
+        assert_eq!(topk.combine(&[]), 0);
+        assert!(combinator_from_str("topk").is_err());
+        assert!(combinator_from_str("topk:-1").is_err());
+    }
+
+    #[test]
+    fn test_trimmed_matches_sort() {
+        for trim in 0..10 {
+            let trimmed = TrimmedMeanCombinatorNode::new(trim);
+            for inputs in sample_inputs() {
+                let mut sorted = inputs.clone();
+                sorted.sort_unstable();
+                let trim = trim.min(sorted.len().saturating_sub(1) / 2);
+                let expected = mean_reference(&sorted[trim..sorted.len() - trim]);
+                assert_eq!(
+                    trimmed.combine(&inputs),
+                    expected,
+                    "trim {trim}, inputs {inputs:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_trimmed() {
+        let trimmed = combinator_from_str("trimmed:1").unwrap();
+        assert_eq!(trimmed.identifier(), TrimmedMeanCombinatorNode::IDENTIFIER);
+        assert_eq!(trimmed.parameters(), Some("1".to_string()));
+        // The outliers 0 and 255 are dropped.
+        assert_eq!(trimmed.combine(&[0, 10, 20, 255]), 15);
+        assert_eq!(trimmed.combine(&[9]), 9);
+        assert_eq!(trimmed.combine(&[]), 0);
+        // Trimming more than available keeps the middle element.
+        assert_eq!(TrimmedMeanCombinatorNode::new(5).combine(&[1, 50, 200]), 50);
+        assert_eq!(TrimmedMeanCombinatorNode::new(5).combine(&[10, 20]), 15);
+    }
+
+    #[test]
+    fn test_mode() {
+        let mode = combinator_from_str("mode").unwrap();
+        assert_eq!(mode.identifier(), ModeCombinatorNode::IDENTIFIER);
+        assert_eq!(mode.combine(&[]), 0);
+        assert_eq!(mode.combine(&[9]), 9);
+        assert_eq!(mode.combine(&[4, 4, 4]), 4);
+        assert_eq!(mode.combine(&[1, 7, 7, 3, 1, 7]), 7);
+        assert_eq!(mode.combine(&[200, 5, 200, 5]), 5);
+    }
+
+    #[test]
+    fn test_range() {
+        let range = combinator_from_str("range").unwrap();
+        assert_eq!(range.identifier(), RangeCombinatorNode::IDENTIFIER);
+        assert_eq!(range.combine(&[]), 0);
+        assert_eq!(range.combine(&[9]), 0);
+        assert_eq!(range.combine(&[4, 4, 4]), 0);
+        assert_eq!(range.combine(&[10, 250, 30]), 240);
+        assert_eq!(range.combine(&[0, 255]), 255);
+    }
+
+    #[test]
+    fn test_stddev() {
+        let stddev = combinator_from_str("stddev").unwrap();
+        assert_eq!(stddev.identifier(), StdDevCombinatorNode::IDENTIFIER);
+        assert_eq!(stddev.combine(&[]), 0);
+        assert_eq!(stddev.combine(&[9]), 0);
+        assert_eq!(stddev.combine(&[4, 4, 4]), 0);
+        // mean 5, squared deviations 9 + 1 + 1 + 1 + 0 + 0 + 4 + 16 = 32, 32 / 8 = 4, sqrt = 2
+        assert_eq!(stddev.combine(&[2, 4, 4, 4, 5, 5, 7, 9]), 2);
+        // mean 127.5, every deviation is 127.5
+        assert_eq!(stddev.combine(&[0, 255]), 128);
+        // sqrt(2 / 3) = 0.816...
+        assert_eq!(stddev.combine(&[1, 2, 3]), 1);
+    }
+
+    #[test]
+    fn test_core_only_arithmetic_matches_float_reference() {
+        let stddev = StdDevCombinatorNode::new();
+        let mean = GeometricMeanCombinatorNode::new();
+        let mut state = 0x2545_f491_u32;
+        for len in 1..40 {
+            let inputs: Vec<u8> = (0..len)
+                .map(|_| {
+                    state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    (state >> 16) as u8 | 1
+                })
+                .collect();
+            let n = len as f64;
+            let average = inputs.iter().map(|&x| x as f64).sum::<f64>() / n;
+            let variance = inputs
+                .iter()
+                .map(|&x| (x as f64 - average).powi(2))
+                .sum::<f64>()
+                / n;
+            assert_eq!(
+                stddev.combine(&inputs),
+                variance.sqrt().round() as u8,
+                "{inputs:?}"
+            );
+            assert_eq!(
+                mean.combine(&inputs),
+                geometric_reference(&inputs),
+                "{inputs:?}"
+            );
+        }
+        for tenths in 0..=2600 {
+            let value = tenths as f64 / 10.0;
+            assert_eq!(round_to_u8(value), value.round().min(255.0) as u8);
+        }
+        assert_eq!(round_to_u8(-3.0), 0);
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let p90 = combinator_from_str("percentile:90").unwrap();
+        assert_eq!(p90.identifier(), PercentileCombinatorNode::IDENTIFIER);
+        assert_eq!(p90.parameters().as_deref(), Some("90"));
+        let ten: Vec<u8> = (1..=10).map(|x| x * 10).collect();
+        // rank = ceil(0.9 * 10) = 9
+        assert_eq!(p90.combine(&ten), 90);
+        // rank = ceil(0.9 * 5) = 5, the largest
+        assert_eq!(p90.combine(&[5, 1, 4, 2, 3]), 5);
+        assert_eq!(p90.combine(&[]), 0);
+
+        let at = |p: u8, inputs: &[u8]| PercentileCombinatorNode::new(p).combine(inputs);
+        // Percentile 0 is the smallest input and 100 the largest.
+        assert_eq!(at(0, &[30, 10, 20]), 10);
+        assert_eq!(at(100, &[30, 10, 20]), 30);
+        // rank = ceil(0.5 * 4) = 2, the lower middle value rather than an average.
+        assert_eq!(at(50, &[40, 10, 30, 20]), 20);
+        // rank = ceil(0.25 * 4) = 1 and ceil(0.26 * 4) = 2.
+        assert_eq!(at(25, &[40, 10, 30, 20]), 10);
+        assert_eq!(at(26, &[40, 10, 30, 20]), 20);
+        // rank = ceil(0.01 * 3) = 1
+        assert_eq!(at(1, &[9, 8, 7]), 7);
+    }
+
+    #[test]
+    fn test_percentile_rejects_above_100() {
+        assert!(combinator_from_str("percentile:100").is_ok());
+        assert!(matches!(
+            combinator_from_str("percentile:101"),
+            Err(CombinatorParseError::InvalidArguments { .. })
+        ));
+        assert!(combinator_from_str("percentile").is_err());
+        assert!(combinator_from_str("percentile:-1").is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_percentile_new_panics_above_100() {
+        PercentileCombinatorNode::new(101);
+    }
+
+    #[test]
+    fn test_nth_smallest() {
+        let nth = combinator_from_str("nth:2").unwrap();
+        assert_eq!(nth.identifier(), NthCombinatorNode::IDENTIFIER);
+        assert_eq!(nth.parameters().as_deref(), Some("2"));
+        // Counting from 0: 10 is the 0th, 20 the 1st and 30 the 2nd smallest.
+        assert_eq!(nth.combine(&[50, 30, 10, 40, 20]), 30);
+        // Duplicates each take a position.
+        assert_eq!(nth.combine(&[7, 7, 7, 9]), 7);
+        // Out of range clamps to the largest input.
+        assert_eq!(nth.combine(&[3, 1]), 3);
+        assert_eq!(nth.combine(&[]), 0);
+        assert_eq!(NthCombinatorNode::new(0).combine(&[5, 2, 8]), 2);
+    }
+}
@@ -0,0 +1,165 @@
+use alloc::vec::Vec;
+
+use crate::neural::combinator::{
+    ANDCombinatorNode, AdditionCombinatorNode, AverageCombinatorNode, MajorityVoteCombinatorNode,
+    MaxCombinatorNode, MedianCombinatorNode, MinCombinatorNode, MultiplicationCombinatorNode,
+    ORCombinatorNode, SaturatingAdditionCombinatorNode, SaturatingMultiplicationCombinatorNode,
+    WrappingAdditionCombinatorNode, WrappingMultiplicationCombinatorNode, XORCombinatorNode,
+};
+use crate::neural::combinator_stats::ModeCombinatorNode;
+use crate::neural::traits::{Combinator, StreamingCombinator};
+
+/// Implements `StreamingCombinator` for combinators that fold their inputs into a single byte.
+macro_rules! fold_streaming {
+    ($($node:ty => $init:expr, |$acc:ident, $value:ident| $step:expr;)*) => {
+        $(
+            impl StreamingCombinator for $node {
+                type Accumulator = u8;
+
+                fn begin(&self) -> u8 {
+                    $init
+                }
+                fn push(&self, acc: &mut u8, value: u8) {
+                    let ($acc, $value) = (*acc, value);
+                    *acc = $step;
+                }
+                fn finish(&self, acc: u8) -> u8 {
+                    acc
+                }
+            }
+        )*
+    };
+}
+
+fold_streaming! {
+    AdditionCombinatorNode => 0, |acc, value| acc.saturating_add(value);
+    SaturatingAdditionCombinatorNode => 0, |acc, value| acc.saturating_add(value);
+    WrappingAdditionCombinatorNode => 0, |acc, value| acc.wrapping_add(value);
+    MultiplicationCombinatorNode => 1, |acc, value| acc.saturating_mul(value);
+    SaturatingMultiplicationCombinatorNode => 1, |acc, value| acc.saturating_mul(value);
+    WrappingMultiplicationCombinatorNode => 1, |acc, value| acc.wrapping_mul(value);
+    MaxCombinatorNode => 0, |acc, value| acc.max(value);
+    ORCombinatorNode => 0, |acc, value| acc | value;
+    ANDCombinatorNode => 0xFF, |acc, value| acc & value;
+    XORCombinatorNode => 0, |acc, value| acc ^ value;
+}
+
+impl StreamingCombinator for MinCombinatorNode {
+    /// The smallest value so far, or `None` before the first input.
+    type Accumulator = Option<u8>;
+
+    fn begin(&self) -> Option<u8> {
+        None
+    }
+    fn push(&self, acc: &mut Option<u8>, value: u8) {
+        *acc = Some(acc.map_or(value, |min| min.min(value)));
+    }
+    fn finish(&self, acc: Option<u8>) -> u8 {
+        acc.unwrap_or(0)
+    }
+}
+
+/// The running sum and count of the inputs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AverageAccumulator {
+    pub sum: usize,
+    pub count: usize,
+}
+
+impl StreamingCombinator for AverageCombinatorNode {
+    type Accumulator = AverageAccumulator;
+
+    fn begin(&self) -> AverageAccumulator {
+        AverageAccumulator::default()
+    }
+    fn push(&self, acc: &mut AverageAccumulator, value: u8) {
+        acc.sum += value as usize;
+        acc.count += 1;
+    }
+    fn finish(&self, acc: AverageAccumulator) -> u8 {
+        acc.sum.checked_div(acc.count).unwrap_or(0) as u8
+    }
+}
+
+/// Implements `StreamingCombinator` by buffering the inputs and calling `combine` at the end,
+/// for combinators that need to see every input at once.
+macro_rules! buffered_streaming {
+    ($($node:ty),* $(,)?) => {
+        $(
+            impl StreamingCombinator for $node {
+                type Accumulator = Vec<u8>;
+
+                fn begin(&self) -> Vec<u8> {
+                    Vec::new()
+                }
+                fn push(&self, acc: &mut Vec<u8>, value: u8) {
+                    acc.push(value);
+                }
+                fn finish(&self, acc: Vec<u8>) -> u8 {
+                    self.combine(&acc)
+                }
+            }
+        )*
+    };
+}
+
+buffered_streaming!(
+    MedianCombinatorNode,
+    MajorityVoteCombinatorNode,
+    ModeCombinatorNode
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::rng::Rng;
+
+    /// Checks that streaming matches `combine` on many random vectors, including small values
+    /// so that the saturating and wrapping combinators do not always hit their limits.
+    fn assert_matches_batch<C: StreamingCombinator>(combinator: C) {
+        for seed in 1..200u64 {
+            let len = (seed % 23) as usize;
+            let mut inputs = Rng::new(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15)).bytes(len);
+            if seed % 3 == 0 {
+                inputs.iter_mut().for_each(|x| *x %= 4);
+            }
+            assert_eq!(
+                combinator.combine_iter(inputs.iter().copied()),
+                combinator.combine(&inputs),
+                "{} on {inputs:?}",
+                combinator.identifier()
+            );
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_batch() {
+        assert_matches_batch(AdditionCombinatorNode::new());
+        assert_matches_batch(SaturatingAdditionCombinatorNode::new());
+        assert_matches_batch(WrappingAdditionCombinatorNode::new());
+        assert_matches_batch(MultiplicationCombinatorNode::new());
+        assert_matches_batch(SaturatingMultiplicationCombinatorNode::new());
+        assert_matches_batch(WrappingMultiplicationCombinatorNode::new());
+        assert_matches_batch(MaxCombinatorNode::new());
+        assert_matches_batch(MinCombinatorNode::new());
+        assert_matches_batch(AverageCombinatorNode::new());
+        assert_matches_batch(ORCombinatorNode::new());
+        assert_matches_batch(ANDCombinatorNode::new());
+        assert_matches_batch(XORCombinatorNode::new());
+        assert_matches_batch(MedianCombinatorNode::new());
+        assert_matches_batch(MajorityVoteCombinatorNode::new());
+        assert_matches_batch(ModeCombinatorNode::new());
+    }
+
+    #[test]
+    fn test_push_one_at_a_time() {
+        let average = AverageCombinatorNode::new();
+        let mut acc = average.begin();
+        for value in [10, 20, 31] {
+            average.push(&mut acc, value);
+        }
+        assert_eq!(acc, AverageAccumulator { sum: 61, count: 3 });
+        assert_eq!(average.finish(acc), 20);
+        assert_eq!(average.finish(average.begin()), 0);
+    }
+}
@@ -0,0 +1,338 @@
+/// Reductions that keep their full width, and the adapter that narrows them back to a byte.
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use crate::neural::combinator::{AdditionCombinatorNode, CombinatorParseError};
+use crate::neural::combinator_bits::PopcountCombinatorNode;
+use crate::neural::combinator_stats::parse_count;
+use crate::neural::traits::{Combinator, WideCombinator};
+
+/// Converts a count to `u32`, saturating at `u32::MAX`.
+fn saturating_u32(value: u64) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+impl WideCombinator for AdditionCombinatorNode {
+    /// Returns the exact sum of the inputs.
+    fn combine_wide(&self, inputs: &[u8]) -> u32 {
+        saturating_u32(inputs.iter().map(|&x| x as u64).sum())
+    }
+}
+
+impl WideCombinator for PopcountCombinatorNode {
+    /// Returns the exact number of set bits across all inputs.
+    fn combine_wide(&self, inputs: &[u8]) -> u32 {
+        saturating_u32(inputs.iter().map(|x| x.count_ones() as u64).sum())
+    }
+}
+
+/// Parses a single byte argument, e.g. the `127` of `"count_above:127"`.
+fn parse_byte(identifier: &str, args: &str, name: &str) -> Result<u8, CombinatorParseError> {
+    let value = parse_count(identifier, args)?;
+    u8::try_from(value).map_err(|_| {
+        CombinatorParseError::invalid_arguments(
+            identifier,
+            args,
+            format!("{name} must be at most 255"),
+        )
+    })
+}
+
+/// A combinator node that counts the inputs strictly above a threshold, saturating at 255.
+/// An input equal to the threshold is not counted.
+/// Its `WideCombinator` implementation returns the exact count.
+pub struct CountAboveCombinatorNode {
+    threshold: u8,
+}
+
+impl CountAboveCombinatorNode {
+    pub const IDENTIFIER: &'static str = "count_above";
+    pub fn new(threshold: u8) -> Self {
+        Self { threshold }
+    }
+
+    /// Parses the threshold, e.g. `"127"`.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        Ok(Self::new(parse_byte(Self::IDENTIFIER, args, "threshold")?))
+    }
+
+    /// Returns the value an input must exceed to be counted.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+}
+
+impl WideCombinator for CountAboveCombinatorNode {
+    fn combine_wide(&self, inputs: &[u8]) -> u32 {
+        saturating_u32(inputs.iter().filter(|&&x| x > self.threshold).count() as u64)
+    }
+}
+
+impl Combinator for CountAboveCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        self.combine_wide(inputs).min(u8::MAX as u32) as u8
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn parameters(&self) -> Option<String> {
+        Some(self.threshold.to_string())
+    }
+    fn describe(&self) -> String {
+        format!("{}(threshold={})", Self::IDENTIFIER, self.threshold)
+    }
+}
+
+/// A combinator node that counts the inputs equal to a value, saturating at 255.
+/// Its `WideCombinator` implementation returns the exact count.
+pub struct CountEqualCombinatorNode {
+    value: u8,
+}
+
+impl CountEqualCombinatorNode {
+    pub const IDENTIFIER: &'static str = "count_eq";
+    pub fn new(value: u8) -> Self {
+        Self { value }
+    }
+
+    /// Parses the value, e.g. `"0"`.
+    pub fn from_args(args: &str) -> Result<Self, CombinatorParseError> {
+        Ok(Self::new(parse_byte(Self::IDENTIFIER, args, "value")?))
+    }
+
+    /// Returns the value an input must equal to be counted.
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+}
+
+impl WideCombinator for CountEqualCombinatorNode {
+    fn combine_wide(&self, inputs: &[u8]) -> u32 {
+        saturating_u32(inputs.iter().filter(|&&x| x == self.value).count() as u64)
+    }
+}
+
+impl Combinator for CountEqualCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        self.combine_wide(inputs).min(u8::MAX as u32) as u8
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+    fn parameters(&self) -> Option<String> {
+        Some(self.value.to_string())
+    }
+    fn describe(&self) -> String {
+        format!("{}(value={})", Self::IDENTIFIER, self.value)
+    }
+}
+
+/// A combinator node that counts the inputs other than 0, saturating at 255.
+/// Its `WideCombinator` implementation returns the exact count.
+#[derive(Default)]
+pub struct CountNonZeroCombinatorNode {}
+
+impl CountNonZeroCombinatorNode {
+    pub const IDENTIFIER: &'static str = "count_nonzero";
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl WideCombinator for CountNonZeroCombinatorNode {
+    fn combine_wide(&self, inputs: &[u8]) -> u32 {
+        saturating_u32(inputs.iter().filter(|&&x| x != 0).count() as u64)
+    }
+}
+
+impl Combinator for CountNonZeroCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        self.combine_wide(inputs).min(u8::MAX as u32) as u8
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+/// How `Narrowed` turns a wide result into a byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Narrowing {
+    /// Results above 255 become 255.
+    Saturate,
+    /// Results from 0 to the given maximum are scaled linearly to 0 to 255 and rounded to the
+    /// nearest value. Results above the maximum become 255, as does any result above 0 when the
+    /// maximum is 0.
+    Scale(u32),
+}
+
+impl Narrowing {
+    /// Narrows `wide` to a byte.
+    pub fn apply(self, wide: u32) -> u8 {
+        let scaled = match self {
+            Narrowing::Saturate => wide as u64,
+            Narrowing::Scale(0) => u64::from(wide > 0) * 255,
+            Narrowing::Scale(max) => (wide as u64 * 255 + max as u64 / 2) / max as u64,
+        };
+        scaled.min(u8::MAX as u64) as u8
+    }
+}
+
+/// Adapts a `WideCombinator` into a `Combinator`, so it can sit inside a `SignalNode`.
+///
+/// The identifier is the inner identifier followed by `_narrowed`. Like `FnCombinatorNode`,
+/// it is not a built-in, so a network that contains one cannot be reloaded by name.
+pub struct Narrowed<W> {
+    inner: W,
+    narrowing: Narrowing,
+}
+
+impl<W: WideCombinator + Combinator> Narrowed<W> {
+    pub fn new(inner: W, narrowing: Narrowing) -> Self {
+        Self { inner, narrowing }
+    }
+
+    /// Narrows by saturating at 255.
+    pub fn saturating(inner: W) -> Self {
+        Self::new(inner, Narrowing::Saturate)
+    }
+
+    /// Narrows by scaling 0 to `max` onto 0 to 255.
+    pub fn scaled(inner: W, max: u32) -> Self {
+        Self::new(inner, Narrowing::Scale(max))
+    }
+
+    /// Returns the wrapped combinator.
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns how results are narrowed.
+    pub fn narrowing(&self) -> Narrowing {
+        self.narrowing
+    }
+}
+
+impl<W: WideCombinator + Combinator> Combinator for Narrowed<W> {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        self.narrowing.apply(self.inner.combine_wide(inputs))
+    }
+    fn identifier(&self) -> String {
+        format!("{}_narrowed", self.inner.identifier())
+    }
+    fn describe(&self) -> String {
+        match self.narrowing {
+            Narrowing::Saturate => format!("narrowed({}, saturate)", self.inner.describe()),
+            Narrowing::Scale(max) => format!("narrowed({}, scale={max})", self.inner.describe()),
+        }
+    }
+    fn min_inputs(&self) -> usize {
+        self.inner.min_inputs()
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        self.inner.max_inputs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::combinator_from_str;
+
+    #[test]
+    fn test_wide_sums_are_exact() {
+        let inputs = [255u8; 100];
+        assert_eq!(AdditionCombinatorNode::new().combine_wide(&inputs), 25500);
+        assert_eq!(PopcountCombinatorNode::new().combine_wide(&inputs), 800);
+        assert_eq!(
+            CountAboveCombinatorNode::new(254).combine_wide(&inputs),
+            100
+        );
+        assert_eq!(AdditionCombinatorNode::new().combine_wide(&[]), 0);
+        let many = alloc::vec![1u8; 1000];
+        assert_eq!(CountAboveCombinatorNode::new(0).combine_wide(&many), 1000);
+        assert_eq!(CountAboveCombinatorNode::new(0).combine(&many), 255);
+    }
+
+    #[test]
+    fn test_count_above() {
+        let count = combinator_from_str("count_above:127").unwrap();
+        assert_eq!(count.identifier(), CountAboveCombinatorNode::IDENTIFIER);
+        assert_eq!(count.parameters(), Some("127".to_string()));
+        assert_eq!(count.combine(&[127, 128, 255, 0]), 2);
+        assert_eq!(count.combine(&[]), 0);
+        assert!(CountAboveCombinatorNode::from_args("256").is_err());
+        assert!(CountAboveCombinatorNode::from_args("x").is_err());
+    }
+
+    #[test]
+    fn test_count_above_is_strict() {
+        let count = CountAboveCombinatorNode::new(128);
+        assert_eq!(count.combine(&[127, 128, 129]), 1);
+        assert_eq!(CountAboveCombinatorNode::new(255).combine(&[255; 10]), 0);
+        assert_eq!(CountAboveCombinatorNode::new(0).combine(&[0, 1]), 1);
+    }
+
+    #[test]
+    fn test_count_equal() {
+        let count = combinator_from_str("count_eq:0").unwrap();
+        assert_eq!(count.identifier(), CountEqualCombinatorNode::IDENTIFIER);
+        assert_eq!(count.parameters(), Some("0".to_string()));
+        assert_eq!(count.combine(&[0, 1, 0, 255]), 2);
+        assert_eq!(count.combine(&[]), 0);
+        assert_eq!(CountEqualCombinatorNode::new(7).combine(&[6, 7, 8]), 1);
+        let many = alloc::vec![0u8; 300];
+        assert_eq!(count.combine(&many), 255);
+        assert_eq!(CountEqualCombinatorNode::new(0).combine_wide(&many), 300);
+        assert!(CountEqualCombinatorNode::from_args("256").is_err());
+        assert!(combinator_from_str("count_eq").is_err());
+    }
+
+    #[test]
+    fn test_count_nonzero() {
+        let count = combinator_from_str("count_nonzero").unwrap();
+        assert_eq!(count.identifier(), CountNonZeroCombinatorNode::IDENTIFIER);
+        assert_eq!(count.combine(&[0, 1, 0, 255]), 2);
+        assert_eq!(count.combine(&[]), 0);
+        let many = alloc::vec![1u8; 300];
+        assert_eq!(count.combine(&many), 255);
+        assert_eq!(CountNonZeroCombinatorNode::new().combine_wide(&many), 300);
+        assert!(combinator_from_str("count_nonzero:1").is_err());
+    }
+
+    #[test]
+    fn test_narrowing() {
+        assert_eq!(Narrowing::Saturate.apply(254), 254);
+        assert_eq!(Narrowing::Saturate.apply(25500), 255);
+        assert_eq!(Narrowing::Scale(25500).apply(25500), 255);
+        assert_eq!(Narrowing::Scale(25500).apply(12750), 128);
+        assert_eq!(Narrowing::Scale(25500).apply(49), 0);
+        assert_eq!(Narrowing::Scale(25500).apply(50), 1);
+        assert_eq!(Narrowing::Scale(25500).apply(u32::MAX), 255);
+        assert_eq!(Narrowing::Scale(0).apply(0), 0);
+        assert_eq!(Narrowing::Scale(0).apply(1), 255);
+    }
+
+    #[test]
+    fn test_narrowed_adapter() {
+        let inputs = [255u8; 100];
+        let saturating = Narrowed::saturating(AdditionCombinatorNode::new());
+        assert_eq!(saturating.combine(&inputs), 255);
+        assert_eq!(saturating.combine(&[100, 50]), 150);
+        assert_eq!(saturating.identifier(), "addition_narrowed");
+        assert_eq!(saturating.describe(), "narrowed(addition, saturate)");
+
+        let mean_like = Narrowed::scaled(AdditionCombinatorNode::new(), 255 * 100);
+        assert_eq!(mean_like.combine(&inputs), 255);
+        assert_eq!(mean_like.combine(&[255; 50]), 128);
+        assert_eq!(mean_like.narrowing(), Narrowing::Scale(25500));
+        assert_eq!(mean_like.describe(), "narrowed(addition, scale=25500)");
+
+        let bits = Narrowed::scaled(PopcountCombinatorNode::new(), 16);
+        assert_eq!(bits.combine(&[0xFF, 0xFF]), 255);
+        assert_eq!(bits.combine(&[0x0F]), 64);
+        let boxed: alloc::boxed::Box<dyn Combinator> = alloc::boxed::Box::new(bits);
+        assert_eq!(boxed.combine(&[0xFF, 0x00]), 128);
+    }
+}
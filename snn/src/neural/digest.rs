@@ -0,0 +1,101 @@
+/// Stable content digests for mapper tables, used to identify and cache them.
+///
+/// Digests use 64-bit FNV-1a, implemented here so that they never change with a dependency or
+/// compiler update. A digest identifies content; it is not a cryptographic hash.
+use alloc::{format, string::String};
+
+use crate::neural::mapper::MapperNode;
+
+/// The 64-bit FNV offset basis.
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+/// The 64-bit FNV prime.
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Incremental 64-bit FNV-1a hasher. Feeding the same bytes, in any split, gives the same digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fnv1a(u64);
+
+impl Fnv1a {
+    /// Creates a hasher that has seen no bytes.
+    pub const fn new() -> Fnv1a {
+        Fnv1a(OFFSET_BASIS)
+    }
+
+    /// Feeds `bytes` to the hasher.
+    pub const fn write(&mut self, bytes: &[u8]) {
+        let mut i = 0;
+        while i < bytes.len() {
+            self.0 = (self.0 ^ bytes[i] as u64).wrapping_mul(PRIME);
+            i += 1;
+        }
+    }
+
+    /// Feeds `value` as 8 little-endian bytes.
+    pub const fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Returns the digest of everything fed so far.
+    pub const fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a::new()
+    }
+}
+
+/// Formats the high 32 bits of `digest` as 8 lowercase hex characters, for log lines.
+pub fn short_id(digest: u64) -> String {
+    format!("{:08x}", digest >> 32)
+}
+
+impl MapperNode {
+    /// Returns the FNV-1a digest of the 256 table bytes. Equal tables have equal digests.
+    pub const fn digest(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write(self.table());
+        hasher.finish()
+    }
+
+    /// Returns `short_id` of the digest.
+    pub fn short_id(&self) -> String {
+        short_id(self.digest())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_fnv1a_vectors() {
+        assert_eq!(Fnv1a::new().finish(), 0xcbf2_9ce4_8422_2325);
+        let mut hasher = Fnv1a::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+        let mut hasher = Fnv1a::new();
+        hasher.write(b"foo");
+        hasher.write(b"bar");
+        assert_eq!(hasher.finish(), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_pinned_mapper_digests() {
+        let identity = MapperNode::new_transformation(|x| x as u8);
+        assert_eq!(MapperNode::new().digest(), 0xd80a_c658_736b_b725);
+        assert_eq!(identity.digest(), 0x4242_dc52_49c3_3625);
+        assert_eq!(identity.short_id(), "4242dc52");
+    }
+
+    #[test]
+    fn test_digest_follows_content() {
+        let a = MapperNode::new().with_fill(3);
+        let b = MapperNode::new().with_range(0..=255, 3);
+        assert_eq!(a.digest(), b.digest());
+        assert_ne!(a.digest(), a.with_mapdata([(255, 4)]).digest());
+        assert_eq!(a.short_id().len(), 8);
+    }
+}
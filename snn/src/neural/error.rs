@@ -0,0 +1,234 @@
+/// One error type for everything the crate can fail at.
+///
+/// Every module keeps its own precise error type; `SnnError` wraps them all so that code
+/// mixing several parts of the crate can use `?` throughout. The wrapped errors carry the
+/// indices, names and expected and actual values; `SnnError` adds the area that failed.
+use crate::neural::checkpoint::CheckpointError;
+use crate::neural::combinator::CombinatorParseError;
+#[cfg(feature = "json")]
+use crate::neural::combinator_json::{CombinatorDeserializeError, JsonError};
+use crate::neural::combinator_registry::RegistrationError;
+use crate::neural::graph::GraphError;
+use crate::neural::layer::LayerError;
+use crate::neural::mapper::{ConflictError, MapperError, NonAsciiError};
+use crate::neural::network::{BuildError, EvalError, NetworkError, ReplaceError};
+use crate::neural::network_format::NetworkFormatError;
+use crate::neural::node::NodeError;
+#[cfg(feature = "parallel")]
+use crate::neural::pipeline::PipelineError;
+use crate::neural::stateful_network::StatefulBuildError;
+use crate::neural::training_dataset::DatasetError;
+use crate::neural::traits::ArityError;
+
+/// Any error returned by this crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnnError {
+    // Building mapper tables.
+    Mapper(MapperError),
+    MapperConflict(ConflictError),
+    NonAscii(NonAsciiError),
+    // Parsing text into combinators, JSON and datasets.
+    CombinatorParse(CombinatorParseError),
+    #[cfg(feature = "json")]
+    CombinatorDeserialize(CombinatorDeserializeError),
+    Registration(RegistrationError),
+    #[cfg(feature = "json")]
+    Json(JsonError),
+    Dataset(DatasetError),
+    // Wiring nodes, layers and networks together.
+    Arity(ArityError),
+    Node(NodeError),
+    Layer(LayerError),
+    Network(NetworkError),
+    Build(BuildError),
+    Replace(ReplaceError),
+    Graph(GraphError),
+    StatefulBuild(StatefulBuildError),
+    // Reading and writing saved networks.
+    Format(NetworkFormatError),
+    Checkpoint(CheckpointError),
+    // Running networks.
+    Eval(EvalError),
+    #[cfg(feature = "parallel")]
+    Pipeline(PipelineError),
+}
+
+macro_rules! from_errors {
+    ($($variant:ident($error:ty)),* $(,)?) => {
+        $(
+            impl From<$error> for SnnError {
+                fn from(e: $error) -> Self {
+                    SnnError::$variant(e)
+                }
+            }
+        )*
+    };
+}
+
+from_errors!(
+    Mapper(MapperError),
+    MapperConflict(ConflictError),
+    NonAscii(NonAsciiError),
+    CombinatorParse(CombinatorParseError),
+    Registration(RegistrationError),
+    Dataset(DatasetError),
+    Arity(ArityError),
+    Node(NodeError),
+    Layer(LayerError),
+    Network(NetworkError),
+    Build(BuildError),
+    Replace(ReplaceError),
+    Graph(GraphError),
+    StatefulBuild(StatefulBuildError),
+    Format(NetworkFormatError),
+    Checkpoint(CheckpointError),
+    Eval(EvalError),
+);
+
+#[cfg(feature = "json")]
+from_errors!(
+    CombinatorDeserialize(CombinatorDeserializeError),
+    Json(JsonError),
+);
+
+#[cfg(feature = "parallel")]
+from_errors!(Pipeline(PipelineError));
+
+impl SnnError {
+    /// Returns the wrapped error.
+    fn inner(&self) -> &(dyn std::error::Error + 'static) {
+        match self {
+            SnnError::Mapper(e) => e,
+            SnnError::MapperConflict(e) => e,
+            SnnError::NonAscii(e) => e,
+            SnnError::CombinatorParse(e) => e,
+            #[cfg(feature = "json")]
+            SnnError::CombinatorDeserialize(e) => e,
+            SnnError::Registration(e) => e,
+            #[cfg(feature = "json")]
+            SnnError::Json(e) => e,
+            SnnError::Dataset(e) => e,
+            SnnError::Arity(e) => e,
+            SnnError::Node(e) => e,
+            SnnError::Layer(e) => e,
+            SnnError::Network(e) => e,
+            SnnError::Build(e) => e,
+            SnnError::Replace(e) => e,
+            SnnError::Graph(e) => e,
+            SnnError::StatefulBuild(e) => e,
+            SnnError::Format(e) => e,
+            SnnError::Checkpoint(e) => e,
+            SnnError::Eval(e) => e,
+            #[cfg(feature = "parallel")]
+            SnnError::Pipeline(e) => e,
+        }
+    }
+
+    /// Returns the area that failed: mapper, parse, wiring, format or evaluation.
+    pub fn category(&self) -> &'static str {
+        match self {
+            SnnError::Mapper(_) | SnnError::MapperConflict(_) | SnnError::NonAscii(_) => "mapper",
+            SnnError::CombinatorParse(_) | SnnError::Registration(_) | SnnError::Dataset(_) => {
+                "parse"
+            }
+            #[cfg(feature = "json")]
+            SnnError::CombinatorDeserialize(_) | SnnError::Json(_) => "parse",
+            SnnError::Arity(_)
+            | SnnError::Node(_)
+            | SnnError::Layer(_)
+            | SnnError::Network(_)
+            | SnnError::Build(_)
+            | SnnError::Replace(_)
+            | SnnError::Graph(_)
+            | SnnError::StatefulBuild(_) => "wiring",
+            SnnError::Format(_) | SnnError::Checkpoint(_) => "format",
+            SnnError::Eval(_) => "evaluation",
+            #[cfg(feature = "parallel")]
+            SnnError::Pipeline(_) => "evaluation",
+        }
+    }
+}
+
+impl std::fmt::Display for SnnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} error: {}", self.category(), self.inner())
+    }
+}
+
+impl std::error::Error for SnnError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::combinator_from_str;
+    use crate::neural::layer::Layer;
+    use crate::neural::mapper::MapperNode;
+    use crate::neural::network::Network;
+    use crate::neural::node::SignalNode;
+    use std::error::Error;
+
+    fn max_node() -> SignalNode {
+        SignalNode::builder()
+            .combinator(combinator_from_str("max").unwrap())
+            .build()
+            .unwrap()
+    }
+
+    /// Mixes several parts of the crate behind `?`.
+    fn build(combinator: &str, wiring: Vec<Vec<usize>>) -> Result<Network, SnnError> {
+        let node = SignalNode::builder()
+            .combinator(combinator_from_str(combinator)?)
+            .build()?;
+        let layer = Layer::sparse(vec![node], wiring, 2)?;
+        Ok(Network::try_new(vec![layer])?)
+    }
+
+    #[test]
+    fn test_question_mark_conversions() {
+        assert!(build("max", vec![vec![0, 1]]).is_ok());
+        assert_eq!(
+            build("maxx", vec![vec![0]]).err().unwrap().to_string(),
+            "parse error: unknown combinator \"maxx\""
+        );
+        assert_eq!(
+            build("max", vec![vec![0, 5]]).err().unwrap().to_string(),
+            "wiring error: node 0 is wired to input 5, but the layer has only 2 inputs"
+        );
+    }
+
+    #[test]
+    fn test_messages() {
+        let mapper = SnnError::from(MapperNode::new().clamp(9, 3).err().unwrap());
+        assert_eq!(
+            mapper.to_string(),
+            "mapper error: invalid bounds: min (9) is greater than max (3)"
+        );
+        assert_eq!(mapper.category(), "mapper");
+
+        let network = Network::try_new(vec![Layer::dense(vec![max_node()], 2).unwrap()]).unwrap();
+        let eval = SnnError::from(network.forward(&[1]).unwrap_err());
+        assert_eq!(
+            eval.to_string(),
+            "evaluation error: network expects 2 inputs, got 1"
+        );
+
+        let format = SnnError::from(Network::read_binary(&b"SN"[..]).err().unwrap());
+        assert_eq!(format.category(), "format");
+        assert!(format.to_string().starts_with("format error: "));
+
+        let width = SnnError::from(NetworkError::WidthMismatch {
+            layer: 1,
+            expected: 3,
+            actual: 2,
+        });
+        assert_eq!(width.category(), "wiring");
+        assert_eq!(
+            width.source().unwrap().to_string(),
+            width.to_string().trim_start_matches("wiring error: ")
+        );
+    }
+}
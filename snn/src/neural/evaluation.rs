@@ -0,0 +1,278 @@
+/// Measuring how well a network does on a dataset.
+use std::collections::BTreeSet;
+
+use crate::neural::network::{EvalError, Network};
+use crate::neural::training::{AbsoluteLoss, Dataset, Loss};
+
+/// The number of worst examples `evaluate` keeps.
+pub const DEFAULT_WORST_EXAMPLES: usize = 5;
+
+/// The result of running a network over a dataset with `evaluate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluationReport {
+    /// The number of examples evaluated.
+    pub examples: usize,
+    /// The number of examples whose output matched the expected bytes exactly.
+    pub exact_matches: usize,
+    /// The mean absolute difference between output and expected bytes at each position.
+    /// A byte missing from the output counts as a difference of 255.
+    pub mean_absolute_error: Vec<f64>,
+    /// Expected against actual first output byte, treating both as class labels.
+    pub confusion: ConfusionMatrix,
+    /// The examples with the highest absolute loss, worst first.
+    pub worst: Vec<ExampleResult>,
+}
+
+impl EvaluationReport {
+    /// Returns the fraction of examples that matched exactly, or 0 for an empty dataset.
+    pub fn accuracy(&self) -> f64 {
+        if self.examples == 0 {
+            0.0
+        } else {
+            self.exact_matches as f64 / self.examples as f64
+        }
+    }
+}
+
+/// One example and what the network made of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExampleResult {
+    pub input: Vec<u8>,
+    pub expected: Vec<u8>,
+    pub actual: Result<Vec<u8>, EvalError>,
+    /// The absolute loss of the example; `u64::MAX` if the network could not evaluate it.
+    pub loss: u64,
+}
+
+/// Evaluates `network` on every example of `dataset`, keeping the
+/// `DEFAULT_WORST_EXAMPLES` worst examples.
+pub fn evaluate(network: &Network, dataset: &Dataset) -> EvaluationReport {
+    evaluate_keeping(network, dataset, DEFAULT_WORST_EXAMPLES)
+}
+
+/// Evaluates `network` on every example of `dataset`, keeping the `worst` worst examples.
+/// Examples with equal loss are kept in dataset order.
+pub fn evaluate_keeping(network: &Network, dataset: &Dataset, worst: usize) -> EvaluationReport {
+    let mut exact_matches = 0;
+    let mut error_sums: Vec<u64> = Vec::new();
+    let mut error_counts: Vec<usize> = Vec::new();
+    let mut labels = Vec::new();
+    let mut results = Vec::with_capacity(dataset.len());
+    for (input, expected) in dataset.iter() {
+        let actual = network.forward(input);
+        let output = actual.as_deref().unwrap_or(&[]);
+        if actual.is_ok() && output == expected {
+            exact_matches += 1;
+        }
+        if expected.len() > error_sums.len() {
+            error_sums.resize(expected.len(), 0);
+            error_counts.resize(expected.len(), 0);
+        }
+        for (position, &want) in expected.iter().enumerate() {
+            let got = output.get(position).map_or(255, |&got| got.abs_diff(want));
+            error_sums[position] += got as u64;
+            error_counts[position] += 1;
+        }
+        if let (Some(&want), Some(&got)) = (expected.first(), output.first()) {
+            labels.push((want, got));
+        }
+        let loss = match &actual {
+            Ok(output) => AbsoluteLoss.loss(output, expected),
+            Err(_) => u64::MAX,
+        };
+        results.push(ExampleResult {
+            input: input.to_vec(),
+            expected: expected.to_vec(),
+            actual,
+            loss,
+        });
+    }
+    // A stable sort keeps equal losses in dataset order.
+    results.sort_by_key(|result| std::cmp::Reverse(result.loss));
+    results.truncate(worst);
+    EvaluationReport {
+        examples: dataset.len(),
+        exact_matches,
+        mean_absolute_error: error_sums
+            .iter()
+            .zip(&error_counts)
+            .map(|(&sum, &count)| sum as f64 / count as f64)
+            .collect(),
+        confusion: ConfusionMatrix::from_pairs(labels),
+        worst: results,
+    }
+}
+
+/// Counts how often each expected label was given each actual label.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfusionMatrix {
+    /// Every label seen as expected or actual, in ascending order.
+    labels: Vec<u8>,
+    /// `counts[i][j]` counts examples expecting `labels[i]` that got `labels[j]`.
+    counts: Vec<Vec<usize>>,
+}
+
+impl ConfusionMatrix {
+    /// Builds the matrix from `(expected, actual)` label pairs.
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (u8, u8)>) -> Self {
+        let pairs: Vec<(u8, u8)> = pairs.into_iter().collect();
+        let labels: Vec<u8> = pairs
+            .iter()
+            .flat_map(|&(a, b)| [a, b])
+            .collect::<BTreeSet<u8>>()
+            .into_iter()
+            .collect();
+        let mut counts = vec![vec![0; labels.len()]; labels.len()];
+        let index = |label| labels.binary_search(&label).unwrap();
+        for &(expected, actual) in &pairs {
+            counts[index(expected)][index(actual)] += 1;
+        }
+        Self { labels, counts }
+    }
+
+    /// Returns the labels that appear in the matrix, in ascending order.
+    pub fn labels(&self) -> &[u8] {
+        &self.labels
+    }
+
+    /// Returns how many examples expecting `expected` got `actual`.
+    pub fn count(&self, expected: u8, actual: u8) -> usize {
+        match (
+            self.labels.binary_search(&expected),
+            self.labels.binary_search(&actual),
+        ) {
+            (Ok(row), Ok(column)) => self.counts[row][column],
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of examples counted.
+    pub fn total(&self) -> usize {
+        self.counts.iter().flatten().sum()
+    }
+
+    /// Returns the number of examples whose actual label was the expected one.
+    pub fn correct(&self) -> usize {
+        (0..self.labels.len()).map(|i| self.counts[i][i]).sum()
+    }
+}
+
+/// Shows expected labels down the side and actual labels across the top.
+impl std::fmt::Display for ConfusionMatrix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let largest = self.counts.iter().flatten().max().copied().unwrap_or(0);
+        let width = largest.to_string().len().max(3);
+        write!(f, "exp\\act |")?;
+        for label in &self.labels {
+            write!(f, " {label:>width$}")?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "--------+{}",
+            "-".repeat((width + 1) * self.labels.len())
+        )?;
+        for (label, row) in self.labels.iter().zip(&self.counts) {
+            write!(f, "{label:>7} |")?;
+            for count in row {
+                write!(f, " {count:>width$}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::MaxCombinatorNode;
+    use crate::neural::mapper::MapperNode;
+
+    /// Maps 0 to 0 and everything else to 255.
+    fn classifier() -> Network {
+        Network::builder()
+            .input_width(1)
+            .layer_dense(1, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+                    .activation(MapperNode::new().with_range(1..=255, 255))
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn dataset() -> Dataset {
+        [
+            (vec![0], vec![0]),
+            (vec![5], vec![255]),
+            (vec![9], vec![0]),
+            (vec![0], vec![255]),
+            (vec![7], vec![250]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_metrics() {
+        let report = evaluate(&classifier(), &dataset());
+        assert_eq!(report.examples, 5);
+        assert_eq!(report.exact_matches, 2);
+        assert_eq!(report.accuracy(), 0.4);
+        // Errors 0, 0, 255, 255 and 5.
+        assert_eq!(report.mean_absolute_error, vec![103.0]);
+
+        let confusion = &report.confusion;
+        assert_eq!(confusion.labels(), &[0, 250, 255]);
+        assert_eq!(confusion.count(0, 0), 1);
+        assert_eq!(confusion.count(0, 255), 1);
+        assert_eq!(confusion.count(255, 0), 1);
+        assert_eq!(confusion.count(255, 255), 1);
+        assert_eq!(confusion.count(250, 255), 1);
+        assert_eq!(confusion.count(1, 1), 0);
+        assert_eq!((confusion.correct(), confusion.total()), (2, 5));
+        assert_eq!(
+            confusion.to_string(),
+            "exp\\act |   0 250 255\n\
+             --------+------------\n      \
+             0 |   1   0   1\n    \
+             250 |   0   0   1\n    \
+             255 |   1   0   1\n"
+        );
+    }
+
+    #[test]
+    fn test_worst_examples() {
+        let report = evaluate_keeping(&classifier(), &dataset(), 3);
+        let worst: Vec<(&[u8], u64)> = report
+            .worst
+            .iter()
+            .map(|r| (r.input.as_slice(), r.loss))
+            .collect();
+        assert_eq!(worst, vec![(&[9][..], 255), (&[0][..], 255), (&[7][..], 5)]);
+        assert_eq!(report.worst[0].actual, Ok(vec![255]));
+        assert_eq!(report.worst[0].expected, vec![0]);
+    }
+
+    #[test]
+    fn test_wrong_input_width() {
+        let dataset: Dataset = [(vec![1, 2], vec![3])].into_iter().collect();
+        let report = evaluate(&classifier(), &dataset);
+        assert_eq!(report.exact_matches, 0);
+        assert_eq!(report.mean_absolute_error, vec![255.0]);
+        assert_eq!(report.worst[0].loss, u64::MAX);
+        assert!(report.worst[0].actual.is_err());
+        assert_eq!(report.confusion.total(), 0);
+    }
+
+    #[test]
+    fn test_empty_dataset() {
+        let report = evaluate(&classifier(), &Dataset::new());
+        assert_eq!(report.examples, 0);
+        assert_eq!(report.accuracy(), 0.0);
+        assert!(report.mean_absolute_error.is_empty());
+        assert!(report.worst.is_empty());
+        assert_eq!(report.confusion, ConfusionMatrix::default());
+        assert_eq!(report.confusion.to_string(), "exp\\act |\n--------+\n");
+    }
+}
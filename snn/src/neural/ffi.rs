@@ -0,0 +1,337 @@
+/// C interface for embedding mappers and networks, enabled by the `ffi` feature.
+///
+/// Every function catches panics, so none unwinds into C. Functions that return a status
+/// return `SNN_OK` or one of the negative `SNN_ERR_*` codes; functions that return a pointer
+/// return null on failure. Either way, `snn_last_error_message` then describes the failure.
+/// The matching declarations are in `include/snn.h`. The crate itself only builds an `rlib`;
+/// `make ffi` builds the C shared and static libraries with
+/// `cargo rustc -p snn --lib --release --features ffi --crate-type cdylib,staticlib`.
+use std::cell::RefCell;
+use std::ffi::{CString, c_char, c_int};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::Network;
+
+pub const SNN_OK: c_int = 0;
+/// A required pointer was null.
+pub const SNN_ERR_NULL_POINTER: c_int = -1;
+/// A buffer length does not match what the mapper or network needs.
+pub const SNN_ERR_INVALID_LENGTH: c_int = -2;
+/// Serialized data could not be read as a network.
+pub const SNN_ERR_FORMAT: c_int = -3;
+/// The network could not evaluate the input.
+pub const SNN_ERR_EVAL: c_int = -4;
+/// The library panicked; this is a bug.
+pub const SNN_ERR_PANIC: c_int = -5;
+
+/// The mapper table length every C caller passes.
+const TABLE_LENGTH: usize = 256;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// A failure reported to C: a code and its message.
+struct Failure(c_int, String);
+
+fn set_last_error(message: String) {
+    // Messages never contain NUL bytes, but drop any rather than fail here.
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, turning failures and panics into a status code.
+fn status(f: impl FnOnce() -> Result<(), Failure>) -> c_int {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => SNN_OK,
+        Ok(Err(Failure(code, message))) => {
+            set_last_error(message);
+            code
+        }
+        Err(_) => {
+            set_last_error("panic inside snn".to_string());
+            SNN_ERR_PANIC
+        }
+    }
+}
+
+/// Runs `f`, turning failures and panics into a null pointer.
+fn boxed<T>(f: impl FnOnce() -> Result<T, Failure>) -> *mut T {
+    let mut result = None;
+    let code = status(|| {
+        result = Some(f()?);
+        Ok(())
+    });
+    match result {
+        Some(value) if code == SNN_OK => Box::into_raw(Box::new(value)),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+fn non_null<T>(pointer: *const T, name: &str) -> Result<(), Failure> {
+    if pointer.is_null() {
+        Err(Failure(SNN_ERR_NULL_POINTER, format!("{name} is null")))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the message of the last failure on this thread, or null if nothing failed yet.
+/// The string stays valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn snn_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Creates a mapper from a table of 256 bytes. Free it with `snn_mapper_free`.
+///
+/// # Safety
+/// `table` must be null or point to 256 readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn snn_mapper_new_from(table: *const u8) -> *mut MapperNode {
+    boxed(|| {
+        non_null(table, "table")?;
+        // SAFETY: the caller guarantees 256 readable bytes.
+        let table = unsafe { std::slice::from_raw_parts(table, TABLE_LENGTH) };
+        MapperNode::try_from(table).map_err(|e| Failure(SNN_ERR_INVALID_LENGTH, e.to_string()))
+    })
+}
+
+/// Transforms `len` bytes of `input` into `output`. The buffers may be the same.
+///
+/// # Safety
+/// `mapper` must come from `snn_mapper_new_from`, and `input` and `output` must each be
+/// null or point to `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn snn_mapper_transform_slice(
+    mapper: *const MapperNode,
+    input: *const u8,
+    output: *mut u8,
+    len: usize,
+) -> c_int {
+    status(|| {
+        non_null(mapper, "mapper")?;
+        non_null(input, "input")?;
+        non_null(output, "output")?;
+        // SAFETY: the caller guarantees valid pointers; indexing by position keeps this sound
+        // when input and output overlap.
+        let mapper = unsafe { &*mapper };
+        for i in 0..len {
+            unsafe { *output.add(i) = mapper.transform(*input.add(i)) };
+        }
+        Ok(())
+    })
+}
+
+/// Frees a mapper. Null is ignored.
+///
+/// # Safety
+/// `mapper` must be null or come from `snn_mapper_new_from` and not have been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn snn_mapper_free(mapper: *mut MapperNode) {
+    if !mapper.is_null() {
+        // SAFETY: the caller passes ownership of a pointer from `Box::into_raw`.
+        drop(unsafe { Box::from_raw(mapper) });
+    }
+}
+
+/// Loads a network from `len` bytes in the binary format. Free it with `snn_network_free`.
+///
+/// # Safety
+/// `data` must be null or point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn snn_network_load_binary(data: *const u8, len: usize) -> *mut Network {
+    boxed(|| {
+        non_null(data, "data")?;
+        // SAFETY: the caller guarantees `len` readable bytes.
+        let data = unsafe { std::slice::from_raw_parts(data, len) };
+        Network::read_binary(data).map_err(|e| Failure(SNN_ERR_FORMAT, e.to_string()))
+    })
+}
+
+/// Returns the number of inputs the network reads, or -1 for a null network.
+/// A network without layers reads any number of inputs and returns 0 here.
+///
+/// # Safety
+/// `network` must be null or come from `snn_network_load_binary`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn snn_network_input_width(network: *const Network) -> isize {
+    // SAFETY: the caller guarantees a valid pointer.
+    unsafe { network.as_ref() }.map_or(-1, |n| n.input_width().unwrap_or(0) as isize)
+}
+
+/// Returns the number of outputs the network writes, or -1 for a null network.
+/// A network without layers writes as many outputs as it is given inputs and returns 0 here.
+///
+/// # Safety
+/// `network` must be null or come from `snn_network_load_binary`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn snn_network_output_width(network: *const Network) -> isize {
+    // SAFETY: the caller guarantees a valid pointer.
+    unsafe { network.as_ref() }.map_or(-1, |n| n.output_width().unwrap_or(0) as isize)
+}
+
+/// Runs `input_len` bytes through the network and writes its outputs to `output`,
+/// which must have room for exactly as many bytes as the network outputs.
+///
+/// # Safety
+/// `network` must come from `snn_network_load_binary`, `input` must be null or point to
+/// `input_len` readable bytes, and `output` null or point to `output_len` writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn snn_network_forward(
+    network: *const Network,
+    input: *const u8,
+    input_len: usize,
+    output: *mut u8,
+    output_len: usize,
+) -> c_int {
+    status(|| {
+        non_null(network, "network")?;
+        non_null(input, "input")?;
+        non_null(output, "output")?;
+        // SAFETY: the caller guarantees valid pointers and lengths.
+        let (network, input) = unsafe { (&*network, std::slice::from_raw_parts(input, input_len)) };
+        let values = network
+            .forward(input)
+            .map_err(|e| Failure(SNN_ERR_EVAL, e.to_string()))?;
+        if values.len() != output_len {
+            return Err(Failure(
+                SNN_ERR_INVALID_LENGTH,
+                format!(
+                    "output buffer holds {output_len} bytes, network writes {}",
+                    values.len()
+                ),
+            ));
+        }
+        // SAFETY: `output` has room for `output_len` bytes, checked above to match.
+        unsafe { std::ptr::copy_nonoverlapping(values.as_ptr(), output, output_len) };
+        Ok(())
+    })
+}
+
+/// Frees a network. Null is ignored.
+///
+/// # Safety
+/// `network` must be null or come from `snn_network_load_binary` and not have been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn snn_network_free(network: *mut Network) {
+    if !network.is_null() {
+        // SAFETY: the caller passes ownership of a pointer from `Box::into_raw`.
+        drop(unsafe { Box::from_raw(network) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::MaxCombinatorNode;
+    use std::ffi::CStr;
+
+    fn last_error() -> String {
+        let message = snn_last_error_message();
+        assert!(!message.is_null());
+        unsafe { CStr::from_ptr(message) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn max3_binary() -> Vec<u8> {
+        let network = Network::builder()
+            .input_width(3)
+            .layer_dense(1, |n| n.combinator(Box::new(MaxCombinatorNode::new())))
+            .build()
+            .unwrap();
+        let mut bytes = Vec::new();
+        network.write_binary(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_mapper_round_trip() {
+        let table: Vec<u8> = (0..=255u8).map(|x| x.wrapping_add(1)).collect();
+        unsafe {
+            let mapper = snn_mapper_new_from(table.as_ptr());
+            assert!(!mapper.is_null());
+            let input = [0u8, 1, 255];
+            let mut output = [0u8; 3];
+            let code = snn_mapper_transform_slice(mapper, input.as_ptr(), output.as_mut_ptr(), 3);
+            assert_eq!(code, SNN_OK);
+            assert_eq!(output, [1, 2, 0]);
+            // In place.
+            let code = snn_mapper_transform_slice(mapper, output.as_ptr(), output.as_mut_ptr(), 3);
+            assert_eq!(code, SNN_OK);
+            assert_eq!(output, [2, 3, 1]);
+            snn_mapper_free(mapper);
+            snn_mapper_free(std::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_null_pointers() {
+        unsafe {
+            assert!(snn_mapper_new_from(std::ptr::null()).is_null());
+            assert_eq!(last_error(), "table is null");
+            let mut output = [0u8; 1];
+            let code = snn_mapper_transform_slice(
+                std::ptr::null(),
+                [0u8].as_ptr(),
+                output.as_mut_ptr(),
+                1,
+            );
+            assert_eq!(code, SNN_ERR_NULL_POINTER);
+            assert_eq!(last_error(), "mapper is null");
+            assert!(snn_network_load_binary(std::ptr::null(), 4).is_null());
+            assert_eq!(last_error(), "data is null");
+            let code =
+                snn_network_forward(std::ptr::null(), [0u8].as_ptr(), 1, output.as_mut_ptr(), 1);
+            assert_eq!(code, SNN_ERR_NULL_POINTER);
+            assert_eq!(snn_network_input_width(std::ptr::null()), -1);
+            snn_network_free(std::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_network_forward() {
+        let bytes = max3_binary();
+        unsafe {
+            let network = snn_network_load_binary(bytes.as_ptr(), bytes.len());
+            assert!(!network.is_null());
+            assert_eq!(snn_network_input_width(network), 3);
+            assert_eq!(snn_network_output_width(network), 1);
+            let input = [4u8, 9, 2];
+            let mut output = [0u8; 1];
+            let code = snn_network_forward(network, input.as_ptr(), 3, output.as_mut_ptr(), 1);
+            assert_eq!(code, SNN_OK);
+            assert_eq!(output, [9]);
+
+            let code = snn_network_forward(network, input.as_ptr(), 2, output.as_mut_ptr(), 1);
+            assert_eq!(code, SNN_ERR_EVAL);
+            assert_eq!(last_error(), "network expects 3 inputs, got 2");
+            let mut wide = [0u8; 2];
+            let code = snn_network_forward(network, input.as_ptr(), 3, wide.as_mut_ptr(), 2);
+            assert_eq!(code, SNN_ERR_INVALID_LENGTH);
+            assert_eq!(
+                last_error(),
+                "output buffer holds 2 bytes, network writes 1"
+            );
+            snn_network_free(network);
+        }
+    }
+
+    #[test]
+    fn test_bad_binary() {
+        let bytes = max3_binary();
+        unsafe {
+            let network = snn_network_load_binary(bytes.as_ptr(), bytes.len() - 1);
+            assert!(network.is_null());
+            assert_eq!(last_error(), "network data ends unexpectedly");
+            assert!(snn_network_load_binary(b"nope".as_ptr(), 4).is_null());
+        }
+    }
+}
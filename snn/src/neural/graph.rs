@@ -0,0 +1,441 @@
+/// Networks shaped as an arbitrary directed acyclic graph of named nodes.
+use crate::neural::network::EvalError;
+use crate::neural::node::{NodeError, SignalNode};
+use crate::neural::topology::topological_order;
+
+/// A graph of named inputs and nodes joined by explicit edges.
+/// Build it up with `add_input`, `add_node`, `connect` and `add_output`, then `compile` it
+/// to evaluate it.
+#[derive(Default)]
+pub struct Graph {
+    vertices: Vec<Vertex>,
+    outputs: Vec<usize>,
+}
+
+struct Vertex {
+    name: String,
+    kind: VertexKind,
+}
+
+enum VertexKind {
+    Input,
+    /// A node and the vertex wired to each of its input slots.
+    Node(Box<SignalNode>, Vec<Option<usize>>),
+}
+
+impl Graph {
+    /// Creates an empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an external input. Inputs are read in the order they were added.
+    pub fn add_input(&mut self, name: &str) -> Result<(), GraphError> {
+        self.add_vertex(name, VertexKind::Input)
+    }
+
+    /// Adds a node with no inputs connected yet.
+    pub fn add_node(&mut self, name: &str, node: SignalNode) -> Result<(), GraphError> {
+        self.add_vertex(name, VertexKind::Node(Box::new(node), Vec::new()))
+    }
+
+    fn add_vertex(&mut self, name: &str, kind: VertexKind) -> Result<(), GraphError> {
+        if self.index(name).is_some() {
+            return Err(GraphError::DuplicateName(name.to_string()));
+        }
+        self.vertices.push(Vertex {
+            name: name.to_string(),
+            kind,
+        });
+        Ok(())
+    }
+
+    /// Feeds the output of the input or node `from` into slot `input_slot` of node `to`.
+    pub fn connect(&mut self, from: &str, to: &str, input_slot: usize) -> Result<(), GraphError> {
+        let source = self.lookup(from)?;
+        let target = self.lookup(to)?;
+        let VertexKind::Node(_, slots) = &mut self.vertices[target].kind else {
+            return Err(GraphError::NotANode(to.to_string()));
+        };
+        if slots.len() <= input_slot {
+            slots.resize(input_slot + 1, None);
+        }
+        if slots[input_slot].is_some() {
+            return Err(GraphError::SlotTaken {
+                node: to.to_string(),
+                slot: input_slot,
+            });
+        }
+        slots[input_slot] = Some(source);
+        Ok(())
+    }
+
+    /// Feeds the output of `from` into every `(node, input_slot)` in `targets`.
+    /// Stops at the first connection that fails; the ones before it stay connected.
+    pub fn fan_out(&mut self, from: &str, targets: &[(&str, usize)]) -> Result<(), GraphError> {
+        targets
+            .iter()
+            .try_for_each(|&(to, slot)| self.connect(from, to, slot))
+    }
+
+    /// Adds the input or node `name` as the next output of the graph.
+    pub fn add_output(&mut self, name: &str) -> Result<(), GraphError> {
+        let index = self.lookup(name)?;
+        self.outputs.push(index);
+        Ok(())
+    }
+
+    /// Returns the number of inputs and nodes.
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Returns true if the graph has no inputs or nodes.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Checks that every input and node contributes to an output.
+    /// A graph with such dead vertices still compiles; this reports them all by name.
+    pub fn validate(&self) -> Result<(), GraphError> {
+        let mut used = vec![false; self.vertices.len()];
+        let mut pending = self.outputs.clone();
+        while let Some(index) = pending.pop() {
+            if std::mem::replace(&mut used[index], true) {
+                continue;
+            }
+            if let VertexKind::Node(_, slots) = &self.vertices[index].kind {
+                pending.extend(slots.iter().flatten());
+            }
+        }
+        let disconnected: Vec<String> = self
+            .vertices
+            .iter()
+            .zip(&used)
+            .filter(|(_, used)| !**used)
+            .map(|(vertex, _)| vertex.name.clone())
+            .collect();
+        if disconnected.is_empty() {
+            Ok(())
+        } else {
+            Err(GraphError::Disconnected(disconnected))
+        }
+    }
+
+    /// Checks every node's inputs and sorts the nodes so each is evaluated after its inputs.
+    pub fn compile(self) -> Result<CompiledGraph, GraphError> {
+        let mut sources = Vec::with_capacity(self.vertices.len());
+        for vertex in &self.vertices {
+            let slots = match &vertex.kind {
+                VertexKind::Input => Vec::new(),
+                VertexKind::Node(node, slots) => {
+                    let slots = slots
+                        .iter()
+                        .enumerate()
+                        .map(|(slot, source)| {
+                            source.ok_or_else(|| GraphError::MissingInput {
+                                node: vertex.name.clone(),
+                                slot,
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    node.check_input_count(slots.len())
+                        .map_err(|error| GraphError::Node {
+                            name: vertex.name.clone(),
+                            error,
+                        })?;
+                    slots
+                }
+            };
+            sources.push(slots);
+        }
+        let order =
+            topological_order(self.vertices.len(), |i| sources[i].clone()).map_err(|cycle| {
+                GraphError::Cycle(
+                    cycle
+                        .into_iter()
+                        .map(|i| self.vertices[i].name.clone())
+                        .collect(),
+                )
+            })?;
+
+        let mut inputs = Vec::new();
+        let mut nodes = Vec::with_capacity(self.vertices.len());
+        let mut names = Vec::with_capacity(self.vertices.len());
+        for (index, vertex) in self.vertices.into_iter().enumerate() {
+            names.push(vertex.name);
+            match vertex.kind {
+                VertexKind::Input => {
+                    inputs.push(index);
+                    nodes.push(None);
+                }
+                VertexKind::Node(node, _) => nodes.push(Some(node)),
+            }
+        }
+        let steps = order
+            .into_iter()
+            .filter_map(|index| {
+                Some(Step {
+                    vertex: index,
+                    node: nodes[index].take()?,
+                    sources: std::mem::take(&mut sources[index]),
+                })
+            })
+            .collect();
+        Ok(CompiledGraph {
+            names,
+            inputs,
+            outputs: self.outputs,
+            steps,
+        })
+    }
+
+    fn index(&self, name: &str) -> Option<usize> {
+        self.vertices.iter().position(|v| v.name == name)
+    }
+
+    fn lookup(&self, name: &str) -> Result<usize, GraphError> {
+        self.index(name)
+            .ok_or_else(|| GraphError::UnknownName(name.to_string()))
+    }
+}
+
+/// A graph whose evaluation order has been worked out once by `Graph::compile`.
+pub struct CompiledGraph {
+    names: Vec<String>,
+    /// The vertex of each external input.
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+    steps: Vec<Step>,
+}
+
+/// One node evaluation: the node, the vertex it writes, and the vertices it reads.
+struct Step {
+    vertex: usize,
+    node: Box<SignalNode>,
+    sources: Vec<usize>,
+}
+
+impl CompiledGraph {
+    /// Evaluates every node once and returns the outputs.
+    pub fn evaluate(&self, input: &[u8]) -> Result<Vec<u8>, EvalError> {
+        if input.len() != self.inputs.len() {
+            return Err(EvalError::InputWidth {
+                expected: self.inputs.len(),
+                actual: input.len(),
+            });
+        }
+        let mut values = vec![0; self.names.len()];
+        for (&vertex, &value) in self.inputs.iter().zip(input) {
+            values[vertex] = value;
+        }
+        let mut gathered = Vec::new();
+        for step in &self.steps {
+            gathered.clear();
+            gathered.extend(step.sources.iter().map(|&s| values[s]));
+            values[step.vertex] = step.node.evaluate(&gathered);
+        }
+        Ok(self.outputs.iter().map(|&o| values[o]).collect())
+    }
+
+    /// Returns the names of the nodes in the order they are evaluated.
+    pub fn order(&self) -> impl Iterator<Item = &str> {
+        self.steps
+            .iter()
+            .map(|step| self.names[step.vertex].as_str())
+    }
+
+    /// Returns the number of external inputs.
+    pub fn input_width(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Returns the number of outputs.
+    pub fn output_width(&self) -> usize {
+        self.outputs.len()
+    }
+}
+
+/// Error returned while building, validating or compiling a `Graph`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// An input or node with this name already exists.
+    DuplicateName(String),
+    /// No input or node has this name.
+    UnknownName(String),
+    /// Edges can only lead into nodes, not inputs.
+    NotANode(String),
+    /// The input slot of the node is already connected.
+    SlotTaken { node: String, slot: usize },
+    /// The node has a connected slot after this unconnected one.
+    MissingInput { node: String, slot: usize },
+    /// The node does not accept the number of inputs connected to it.
+    Node { name: String, error: NodeError },
+    /// These nodes feed each other in a loop; each reads the next and the last reads the first.
+    Cycle(Vec<String>),
+    /// These inputs and nodes do not contribute to any output.
+    Disconnected(Vec<String>),
+}
+
+impl std::fmt::Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::DuplicateName(name) => write!(f, "name '{name}' is used twice"),
+            GraphError::UnknownName(name) => write!(f, "no input or node named '{name}'"),
+            GraphError::NotANode(name) => write!(f, "'{name}' is an input and cannot be fed"),
+            GraphError::SlotTaken { node, slot } => {
+                write!(f, "input {slot} of '{node}' is already connected")
+            }
+            GraphError::MissingInput { node, slot } => {
+                write!(f, "input {slot} of '{node}' is not connected")
+            }
+            GraphError::Node { name, error } => write!(f, "node '{name}': {error}"),
+            GraphError::Cycle(names) => write!(f, "cycle through {}", names.join(" -> ")),
+            GraphError::Disconnected(names) => {
+                write!(f, "not connected to any output: {}", names.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GraphError::Node { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode, MinCombinatorNode};
+    use crate::neural::traits::Combinator;
+
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    /// `out = max(a, b) + min(a, b)`, with the nodes added out of order.
+    fn diamond() -> Graph {
+        let mut graph = Graph::new();
+        graph
+            .add_node("sum", node(AdditionCombinatorNode::new()))
+            .unwrap();
+        graph.add_input("a").unwrap();
+        graph.add_input("b").unwrap();
+        graph
+            .add_node("max", node(MaxCombinatorNode::new()))
+            .unwrap();
+        graph
+            .add_node("min", node(MinCombinatorNode::new()))
+            .unwrap();
+        for (from, to, slot) in [
+            ("a", "max", 0),
+            ("b", "max", 1),
+            ("a", "min", 0),
+            ("b", "min", 1),
+            ("max", "sum", 0),
+            ("min", "sum", 1),
+        ] {
+            graph.connect(from, to, slot).unwrap();
+        }
+        graph.add_output("sum").unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_diamond() {
+        let graph = diamond();
+        assert_eq!(graph.validate(), Ok(()));
+        let compiled = graph.compile().unwrap();
+        assert_eq!(compiled.evaluate(&[10, 30]), Ok(vec![40]));
+        assert_eq!(compiled.evaluate(&[200, 100]), Ok(vec![255]));
+        assert_eq!(compiled.order().last(), Some("sum"));
+        assert_eq!(compiled.order().count(), 3);
+        assert_eq!(
+            compiled.evaluate(&[1]),
+            Err(EvalError::InputWidth {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_cycle_rejected() {
+        let mut graph = Graph::new();
+        graph.add_input("in").unwrap();
+        for name in ["x", "y", "z"] {
+            graph
+                .add_node(name, node(MaxCombinatorNode::new()))
+                .unwrap();
+        }
+        graph.connect("in", "x", 0).unwrap();
+        graph.connect("z", "x", 1).unwrap();
+        graph.connect("x", "y", 0).unwrap();
+        graph.connect("y", "z", 0).unwrap();
+        graph.add_output("z").unwrap();
+        let error = graph.compile().err().unwrap();
+        assert_eq!(
+            error,
+            GraphError::Cycle(vec!["x".to_string(), "z".to_string(), "y".to_string()])
+        );
+        assert_eq!(error.to_string(), "cycle through x -> z -> y");
+    }
+
+    #[test]
+    fn test_validate_disconnected() {
+        let mut graph = diamond();
+        graph.add_input("unused").unwrap();
+        graph
+            .add_node("dead", node(MaxCombinatorNode::new()))
+            .unwrap();
+        graph.connect("a", "dead", 0).unwrap();
+        assert_eq!(
+            graph.validate(),
+            Err(GraphError::Disconnected(vec![
+                "unused".to_string(),
+                "dead".to_string()
+            ]))
+        );
+        // Dead vertices do not stop the graph from compiling.
+        assert_eq!(graph.compile().unwrap().evaluate(&[1, 2, 3]), Ok(vec![3]));
+    }
+
+    #[test]
+    fn test_connect_errors() {
+        let mut graph = diamond();
+        assert_eq!(
+            graph.add_input("a"),
+            Err(GraphError::DuplicateName("a".to_string()))
+        );
+        assert_eq!(
+            graph.connect("nope", "sum", 2),
+            Err(GraphError::UnknownName("nope".to_string()))
+        );
+        assert_eq!(
+            graph.connect("a", "b", 0),
+            Err(GraphError::NotANode("b".to_string()))
+        );
+        assert_eq!(
+            graph.connect("a", "max", 1),
+            Err(GraphError::SlotTaken {
+                node: "max".to_string(),
+                slot: 1
+            })
+        );
+        graph.connect("a", "sum", 3).unwrap();
+        assert_eq!(
+            graph.compile().err(),
+            Some(GraphError::MissingInput {
+                node: "sum".to_string(),
+                slot: 2
+            })
+        );
+    }
+}
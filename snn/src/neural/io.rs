@@ -0,0 +1,177 @@
+/// `std::io` adapters that transform bytes through a `Mapper` as they pass through a stream.
+use std::io::{self, Read, Write};
+
+use crate::neural::traits::Mapper;
+
+/// The size of the scratch buffer used by `MappedWriter` for each write call.
+const WRITE_BUFFER_SIZE: usize = 4096;
+
+/// A reader that transforms every byte read from the inner reader through a `Mapper`.
+pub struct MappedReader<R, M> {
+    inner: R,
+    mapper: M,
+}
+
+impl<R: Read, M: Mapper> MappedReader<R, M> {
+    /// Creates a new `MappedReader` reading from `inner` and transforming with `mapper`.
+    pub fn new(inner: R, mapper: M) -> Self {
+        MappedReader { inner, mapper }
+    }
+
+    /// Returns a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read, M: Mapper> Read for MappedReader<R, M> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.mapper.transform_in_place(&mut buf[..n]);
+        Ok(n)
+    }
+}
+
+/// A writer that transforms every byte through a `Mapper` before passing it to the inner writer.
+pub struct MappedWriter<W, M> {
+    inner: W,
+    mapper: M,
+}
+
+impl<W: Write, M: Mapper> MappedWriter<W, M> {
+    /// Creates a new `MappedWriter` writing to `inner` and transforming with `mapper`.
+    pub fn new(inner: W, mapper: M) -> Self {
+        MappedWriter { inner, mapper }
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, M: Mapper> Write for MappedWriter<W, M> {
+    /// Transforms and writes up to `WRITE_BUFFER_SIZE` bytes. The returned count is the number of
+    /// input bytes consumed, which matches the number of transformed bytes the inner writer accepted.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut scratch = [0u8; WRITE_BUFFER_SIZE];
+        let len = buf.len().min(WRITE_BUFFER_SIZE);
+        self.mapper.transform_slice(&buf[..len], &mut scratch[..len]);
+        self.inner.write(&scratch[..len])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::mapper::MapperNode;
+    use std::io::Cursor;
+
+    fn mapper() -> MapperNode {
+        MapperNode::new_transformation(|x| (x as u8).to_ascii_uppercase())
+    }
+
+    fn content() -> Vec<u8> {
+        (0..10_000).map(|i| b"hello, world! "[i % 14]).collect()
+    }
+
+    fn expected() -> Vec<u8> {
+        content().iter().map(|&b| mapper().transform(b)).collect()
+    }
+
+    /// A reader that returns at most `limit` bytes per call.
+    struct Throttled<R> {
+        inner: R,
+        limit: usize,
+    }
+
+    impl<R: Read> Read for Throttled<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.limit);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    /// A writer that accepts at most `limit` bytes per call and counts flushes.
+    struct Partial {
+        data: Vec<u8>,
+        limit: usize,
+        flushes: usize,
+    }
+
+    impl Write for Partial {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.limit);
+            self.data.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_mapped_reader() {
+        let mut reader = MappedReader::new(Cursor::new(content()), mapper());
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, expected());
+    }
+
+    #[test]
+    fn test_mapped_reader_short_reads() {
+        let throttled = Throttled {
+            inner: Cursor::new(content()),
+            limit: 7,
+        };
+        let mut reader = MappedReader::new(throttled, mapper());
+        let mut buf = [0u8; 64];
+        assert_eq!(reader.read(&mut buf).unwrap(), 7);
+        assert_eq!(&buf[..7], b"HELLO, ");
+        // Bytes beyond the short read are left untouched.
+        assert!(buf[7..].iter().all(|&b| b == 0));
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, expected()[7..]);
+    }
+
+    #[test]
+    fn test_mapped_writer() {
+        let mut writer = MappedWriter::new(Cursor::new(Vec::new()), mapper());
+        writer.write_all(&content()).unwrap();
+        assert_eq!(writer.into_inner().into_inner(), expected());
+    }
+
+    #[test]
+    fn test_mapped_writer_partial_writes_and_flush() {
+        let partial = Partial {
+            data: Vec::new(),
+            limit: 5,
+            flushes: 0,
+        };
+        let mut writer = MappedWriter::new(partial, mapper());
+        assert_eq!(writer.write(b"hello, world").unwrap(), 5);
+        writer.write_all(b", world").unwrap();
+        writer.flush().unwrap();
+        let inner = writer.into_inner();
+        assert_eq!(inner.data, b"HELLO, WORLD");
+        assert_eq!(inner.flushes, 1);
+    }
+}
@@ -0,0 +1,123 @@
+/// Iterator adapters for applying a `Mapper` lazily to a stream of bytes.
+use core::iter::FusedIterator;
+
+use crate::neural::traits::Mapper;
+
+/// An iterator that transforms every byte of the inner iterator through a `Mapper`.
+///
+/// Created by `MapperIterExt::mapped`.
+#[derive(Clone)]
+pub struct MappedIter<'a, I, M: ?Sized> {
+    inner: I,
+    mapper: &'a M,
+}
+
+impl<'a, I, M: ?Sized> MappedIter<'a, I, M> {
+    /// Creates a new `MappedIter` over `inner` using `mapper`.
+    pub fn new(inner: I, mapper: &'a M) -> Self {
+        MappedIter { inner, mapper }
+    }
+
+    /// Returns the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I, M> Iterator for MappedIter<'_, I, M>
+where
+    I: Iterator<Item = u8>,
+    M: Mapper + ?Sized,
+{
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.inner.next().map(|b| self.mapper.transform(b))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I, M> DoubleEndedIterator for MappedIter<'_, I, M>
+where
+    I: DoubleEndedIterator<Item = u8>,
+    M: Mapper + ?Sized,
+{
+    fn next_back(&mut self) -> Option<u8> {
+        self.inner.next_back().map(|b| self.mapper.transform(b))
+    }
+}
+
+impl<I, M> ExactSizeIterator for MappedIter<'_, I, M>
+where
+    I: ExactSizeIterator<Item = u8>,
+    M: Mapper + ?Sized,
+{
+}
+
+impl<I, M> FusedIterator for MappedIter<'_, I, M>
+where
+    I: FusedIterator<Item = u8>,
+    M: Mapper + ?Sized,
+{
+}
+
+/// Extension trait adding `mapped` to every iterator over bytes.
+pub trait MapperIterExt: Iterator<Item = u8> + Sized {
+    /// Lazily transforms every byte of this iterator through `mapper`.
+    fn mapped<M: Mapper + ?Sized>(self, mapper: &M) -> MappedIter<'_, Self, M> {
+        MappedIter::new(self, mapper)
+    }
+}
+
+impl<I: Iterator<Item = u8>> MapperIterExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::mapper::MapperNode;
+
+    /// A `Mapper` that is not backed by a lookup table.
+    struct AddOne;
+
+    impl Mapper for AddOne {
+        fn transform(&self, input: u8) -> u8 {
+            input.wrapping_add(1)
+        }
+    }
+
+    #[test]
+    fn test_mapped_chain_equals_composition() {
+        let first = MapperNode::new_transformation(|x| (x as u8).wrapping_mul(3));
+        let second = MapperNode::new_transformation(|x| (x as u8) ^ 0b1010_0101);
+        let composed =
+            MapperNode::new_transformation(|x| second.transform(first.transform(x as u8)));
+
+        let chained: Vec<u8> = (0u8..=u8::MAX).mapped(&first).mapped(&second).collect();
+        let expected: Vec<u8> = (0u8..=u8::MAX).mapped(&composed).collect();
+        assert_eq!(chained, expected);
+    }
+
+    #[test]
+    fn test_mapped_with_custom_mapper() {
+        let bytes = [1u8, 2, 255];
+        let result: Vec<u8> = bytes.iter().copied().mapped(&AddOne).collect();
+        assert_eq!(result, vec![2, 3, 0]);
+
+        let dynamic: &dyn Mapper = &AddOne;
+        let result: Vec<u8> = bytes.iter().copied().mapped(dynamic).collect();
+        assert_eq!(result, vec![2, 3, 0]);
+    }
+
+    #[test]
+    fn test_mapped_size_hint_and_reverse() {
+        let mapper = MapperNode::new_transformation(|x| (x as u8).wrapping_mul(2));
+        let iter = [1u8, 2, 3].into_iter().mapped(&mapper);
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.len(), 3);
+        let reversed: Vec<u8> = iter.rev().collect();
+        assert_eq!(reversed, vec![6, 4, 2]);
+    }
+}
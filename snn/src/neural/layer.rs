@@ -0,0 +1,423 @@
+/// A row of signal nodes evaluated over a shared input vector.
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use crate::neural::combinator::MaxCombinatorNode;
+use crate::neural::node::{NodeError, SignalNode};
+use crate::neural::normalizer::Normalizer;
+
+/// Describes which inputs of a layer feed each node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Wiring {
+    /// Every node receives every input, in order.
+    Dense,
+    /// Node `i` receives the inputs at the indices in entry `i`, in that order.
+    /// An index may appear more than once.
+    Sparse(Vec<Vec<usize>>),
+}
+
+/// A row of nodes that all read from the same input vector and each produce one output.
+pub struct Layer {
+    nodes: Vec<SignalNode>,
+    wiring: Wiring,
+    input_width: usize,
+    normalizer: Option<Normalizer>,
+}
+
+impl Layer {
+    /// Creates a layer in which every node receives all `input_width` inputs.
+    pub fn dense(nodes: Vec<SignalNode>, input_width: usize) -> Result<Self, LayerError> {
+        Self::new(nodes, Wiring::Dense, input_width)
+    }
+
+    /// Creates a layer in which node `i` receives the inputs listed in `wiring[i]`.
+    pub fn sparse(
+        nodes: Vec<SignalNode>,
+        wiring: Vec<Vec<usize>>,
+        input_width: usize,
+    ) -> Result<Self, LayerError> {
+        Self::new(nodes, Wiring::Sparse(wiring), input_width)
+    }
+
+    /// Creates a layer that repeats each of its `input_width` inputs `copies` times in a row,
+    /// so that one value can feed several slots of the next layer.
+    pub fn splitter(input_width: usize, copies: usize) -> Self {
+        let nodes = (0..input_width * copies)
+            .map(|_| {
+                SignalNode::builder()
+                    .combinator(Box::new(MaxCombinatorNode::new()))
+                    .build()
+                    .expect("a node with a combinator always builds")
+            })
+            .collect();
+        let wiring = (0..input_width)
+            .flat_map(|input| core::iter::repeat_n(vec![input], copies))
+            .collect();
+        Self::sparse(nodes, wiring, input_width).expect("splitter wiring is always valid")
+    }
+
+    /// Creates a layer, checking that the wiring has one entry per node, that every index is
+    /// below `input_width`, and that every node accepts the number of inputs wired to it.
+    pub fn new(
+        nodes: Vec<SignalNode>,
+        wiring: Wiring,
+        input_width: usize,
+    ) -> Result<Self, LayerError> {
+        if let Wiring::Sparse(indices) = &wiring {
+            if indices.len() != nodes.len() {
+                return Err(LayerError::WiringCount {
+                    nodes: nodes.len(),
+                    wiring: indices.len(),
+                });
+            }
+            for (node, indices) in indices.iter().enumerate() {
+                if let Some(&index) = indices.iter().find(|&&index| index >= input_width) {
+                    return Err(LayerError::IndexOutOfRange {
+                        node,
+                        index,
+                        input_width,
+                    });
+                }
+            }
+        }
+        let layer = Self {
+            nodes,
+            wiring,
+            input_width,
+            normalizer: None,
+        };
+        for (index, node) in layer.nodes.iter().enumerate() {
+            node.check_input_count(layer.fan_in(index))
+                .map_err(|error| LayerError::Node { node: index, error })?;
+        }
+        Ok(layer)
+    }
+
+    /// Returns the layer with `normalizer` applied to its outputs after every evaluation.
+    pub fn with_normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.normalizer = Some(normalizer);
+        self
+    }
+
+    /// Returns the normalizer applied to the outputs, if any.
+    pub fn normalizer(&self) -> Option<&Normalizer> {
+        self.normalizer.as_ref()
+    }
+
+    /// Evaluates every node and returns their outputs in node order, normalized if the layer
+    /// has a normalizer.
+    ///
+    /// # Panics
+    /// Panics if `inputs` is not `input_width` long.
+    pub fn evaluate(&self, inputs: &[u8]) -> Vec<u8> {
+        assert_eq!(inputs.len(), self.input_width, "layer input width differs");
+        let mut outputs = self.evaluate_nodes(inputs);
+        if let Some(normalizer) = &self.normalizer {
+            normalizer.normalize_in_place(&mut outputs);
+        }
+        outputs
+    }
+
+    /// Evaluates every node without normalizing.
+    fn evaluate_nodes(&self, inputs: &[u8]) -> Vec<u8> {
+        match &self.wiring {
+            Wiring::Dense => self
+                .nodes
+                .iter()
+                .map(|node| node.evaluate(inputs))
+                .collect(),
+            Wiring::Sparse(wiring) => {
+                let mut gathered = Vec::new();
+                self.nodes
+                    .iter()
+                    .zip(wiring)
+                    .map(|(node, indices)| {
+                        gathered.clear();
+                        gathered.extend(indices.iter().map(|&i| inputs[i]));
+                        node.evaluate(&gathered)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Evaluates every node, returning an error instead of panicking on a wrong input width.
+    pub fn try_evaluate(&self, inputs: &[u8]) -> Result<Vec<u8>, LayerError> {
+        if inputs.len() != self.input_width {
+            return Err(LayerError::InputWidth {
+                expected: self.input_width,
+                actual: inputs.len(),
+            });
+        }
+        Ok(self.evaluate(inputs))
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the layer has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the number of inputs the layer reads.
+    pub fn input_width(&self) -> usize {
+        self.input_width
+    }
+
+    /// Returns the number of outputs the layer produces, one per node.
+    pub fn output_width(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the nodes.
+    pub fn nodes(&self) -> &[SignalNode] {
+        &self.nodes
+    }
+
+    /// Returns node `index` for modification, or `None` if there is no such node.
+    /// Use `replace_node` to swap in a different node, so that its input count is checked.
+    pub fn node_mut(&mut self, index: usize) -> Option<&mut SignalNode> {
+        self.nodes.get_mut(index)
+    }
+
+    /// Returns the nodes for modification.
+    #[cfg(feature = "std")]
+    pub(crate) fn nodes_mut(&mut self) -> &mut [SignalNode] {
+        &mut self.nodes
+    }
+
+    /// Replaces node `index` and returns the old node, after checking that the replacement
+    /// accepts the number of inputs wired to it.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of range.
+    pub fn replace_node(
+        &mut self,
+        index: usize,
+        node: SignalNode,
+    ) -> Result<SignalNode, LayerError> {
+        node.check_input_count(self.fan_in(index))
+            .map_err(|error| LayerError::Node { node: index, error })?;
+        Ok(core::mem::replace(&mut self.nodes[index], node))
+    }
+
+    /// Returns the wiring.
+    pub fn wiring(&self) -> &Wiring {
+        &self.wiring
+    }
+
+    /// Returns the input indices wired to node `index`.
+    #[cfg(feature = "std")]
+    pub(crate) fn sources(&self, index: usize) -> Vec<usize> {
+        match &self.wiring {
+            Wiring::Dense => (0..self.input_width).collect(),
+            Wiring::Sparse(wiring) => wiring[index].clone(),
+        }
+    }
+
+    /// Splits the layer into its nodes, wiring and input width.
+    #[cfg(feature = "std")]
+    pub(crate) fn into_parts(self) -> (Vec<SignalNode>, Wiring, usize) {
+        (self.nodes, self.wiring, self.input_width)
+    }
+
+    /// Returns the number of inputs wired to node `index`.
+    fn fan_in(&self, index: usize) -> usize {
+        match &self.wiring {
+            Wiring::Dense => self.input_width,
+            Wiring::Sparse(wiring) => wiring[index].len(),
+        }
+    }
+}
+
+/// Error returned when a `Layer` is misconfigured or given the wrong number of inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayerError {
+    /// Sparse wiring must have exactly one entry per node.
+    WiringCount { nodes: usize, wiring: usize },
+    /// A node is wired to an input that does not exist.
+    IndexOutOfRange {
+        node: usize,
+        index: usize,
+        input_width: usize,
+    },
+    /// A node does not accept the number of inputs wired to it.
+    Node { node: usize, error: NodeError },
+    /// The input vector does not match the layer's input width.
+    InputWidth { expected: usize, actual: usize },
+}
+
+impl core::fmt::Display for LayerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LayerError::WiringCount { nodes, wiring } => {
+                write!(f, "layer has {nodes} nodes but {wiring} wiring entries")
+            }
+            LayerError::IndexOutOfRange {
+                node,
+                index,
+                input_width,
+            } => write!(
+                f,
+                "node {node} is wired to input {index}, but the layer has only {input_width} inputs"
+            ),
+            LayerError::Node { node, error } => write!(f, "node {node}: {error}"),
+            LayerError::InputWidth { expected, actual } => {
+                write!(f, "layer expects {expected} inputs, got {actual}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for LayerError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            LayerError::Node { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MinCombinatorNode};
+    use crate::neural::combinator_compare::GreaterThanCombinatorNode;
+    use crate::neural::traits::Combinator;
+
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dense() {
+        let layer = Layer::dense(
+            vec![
+                node(MaxCombinatorNode::new()),
+                node(MinCombinatorNode::new()),
+                node(AdditionCombinatorNode::new()),
+            ],
+            4,
+        )
+        .unwrap();
+        assert_eq!(layer.len(), 3);
+        assert_eq!(layer.input_width(), 4);
+        assert_eq!(layer.output_width(), 3);
+        assert_eq!(layer.evaluate(&[5, 1, 9, 3]), vec![9, 1, 18]);
+        assert_eq!(layer.evaluate(&[200, 100, 0, 0]), vec![200, 0, 255]);
+        assert_eq!(
+            layer.try_evaluate(&[1, 2]),
+            Err(LayerError::InputWidth {
+                expected: 4,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_sparse() {
+        let layer = Layer::sparse(
+            vec![
+                node(GreaterThanCombinatorNode::new()),
+                node(GreaterThanCombinatorNode::new()),
+                node(AdditionCombinatorNode::new()),
+            ],
+            vec![vec![0, 1], vec![1, 0], vec![2, 2, 2]],
+            3,
+        )
+        .unwrap();
+        assert_eq!(layer.output_width(), 3);
+        assert_eq!(layer.evaluate(&[7, 3, 10]), vec![255, 0, 30]);
+        assert_eq!(layer.evaluate(&[3, 7, 1]), vec![0, 255, 3]);
+        assert_eq!(layer.try_evaluate(&[1, 1, 1]), Ok(vec![0, 0, 3]));
+    }
+
+    #[test]
+    fn test_replace_node() {
+        let mut layer = Layer::sparse(
+            vec![
+                node(MaxCombinatorNode::new()),
+                node(MaxCombinatorNode::new()),
+            ],
+            vec![vec![0, 1], vec![0, 1, 2]],
+            3,
+        )
+        .unwrap();
+        let old = layer
+            .replace_node(0, node(GreaterThanCombinatorNode::new()))
+            .unwrap();
+        assert_eq!(old.combinator().identifier(), "max");
+        assert_eq!(layer.evaluate(&[9, 3, 4]), vec![255, 9]);
+        // The second node reads three inputs, too many for a comparator.
+        assert!(matches!(
+            layer.replace_node(1, node(GreaterThanCombinatorNode::new())),
+            Err(LayerError::Node { node: 1, .. })
+        ));
+        assert_eq!(layer.nodes()[1].combinator().identifier(), "max");
+    }
+
+    #[test]
+    fn test_splitter() {
+        let layer = Layer::splitter(3, 2);
+        assert_eq!(layer.output_width(), 6);
+        assert_eq!(layer.evaluate(&[1, 2, 3]), vec![1, 1, 2, 2, 3, 3]);
+        assert!(Layer::splitter(3, 0).is_empty());
+    }
+
+    #[test]
+    fn test_normalizer() {
+        let layer = Layer::splitter(3, 1).with_normalizer(Normalizer::linear());
+        assert_eq!(layer.normalizer(), Some(&Normalizer::linear()));
+        assert_eq!(layer.evaluate(&[0, 50, 100]), vec![0, 128, 255]);
+        assert_eq!(layer.try_evaluate(&[0, 0, 0]), Ok(vec![0, 0, 0]));
+        assert_eq!(Layer::splitter(3, 1).normalizer(), None);
+    }
+
+    #[test]
+    fn test_empty_layer() {
+        let layer = Layer::dense(vec![], 5).unwrap();
+        assert!(layer.is_empty());
+        assert_eq!(layer.output_width(), 0);
+        assert_eq!(layer.evaluate(&[1, 2, 3, 4, 5]), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_construction_errors() {
+        let out_of_range = Layer::sparse(
+            vec![
+                node(MaxCombinatorNode::new()),
+                node(MaxCombinatorNode::new()),
+            ],
+            vec![vec![0], vec![1, 3]],
+            3,
+        );
+        assert_eq!(
+            out_of_range.err(),
+            Some(LayerError::IndexOutOfRange {
+                node: 1,
+                index: 3,
+                input_width: 3
+            })
+        );
+
+        let wiring_count = Layer::sparse(vec![node(MaxCombinatorNode::new())], vec![], 3);
+        assert_eq!(
+            wiring_count.err(),
+            Some(LayerError::WiringCount {
+                nodes: 1,
+                wiring: 0
+            })
+        );
+
+        // A two-input comparator cannot take all three inputs of a dense layer.
+        let arity = Layer::dense(vec![node(GreaterThanCombinatorNode::new())], 3);
+        let error = arity.err().unwrap();
+        assert!(matches!(error, LayerError::Node { node: 0, .. }));
+        assert!(error.to_string().starts_with("node 0: "));
+    }
+}
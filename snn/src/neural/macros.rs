@@ -0,0 +1,227 @@
+/// Declarative macros for building mappers and networks with less builder noise.
+#[cfg(feature = "std")]
+use crate::neural::error::SnnError;
+
+/// Builds a `MapperNode` from `key => value` entries, where a key is a single byte literal or
+/// an inclusive range of byte literals. Bytes not covered map to the optional trailing
+/// `default` entry, or to 0.
+///
+/// ```
+/// use snn::mapper;
+///
+/// static CLASSES: snn::neural::mapper::MapperNode = mapper! {
+///     b'a'..=b'z' => 0xFF,
+///     b'0'..=b'9' => 0x0F,
+///     b'_' => 0x01,
+///     default => 0x00,
+/// };
+/// assert_eq!(CLASSES.transform(b'q'), 0xFF);
+/// assert_eq!(CLASSES.transform(b'!'), 0x00);
+/// ```
+///
+/// Overlapping keys and ranges whose start is after their end fail to compile:
+///
+/// ```compile_fail
+/// let overlapping = snn::mapper! { b'a'..=b'z' => 1, b'q' => 2 };
+/// ```
+///
+/// The expansion only uses const functions, so the macro can initialize a `static` or `const`.
+#[macro_export]
+macro_rules! mapper {
+    (@end $start:literal) => {
+        $start
+    };
+    (@end $start:literal $end:literal) => {
+        $end
+    };
+    ($($start:literal $(..= $end:literal)? => $value:expr,)* default => $default:expr $(,)?) => {{
+        const _: () = $crate::neural::macros::assert_disjoint(&[
+            $(($start, $crate::mapper!(@end $start $($end)?))),*
+        ]);
+        $crate::neural::mapper::MapperNode::new()
+            .with_fill($default)
+            $(.const_with_range($start, $crate::mapper!(@end $start $($end)?), $value))*
+    }};
+    ($($start:literal $(..= $end:literal)? => $value:expr),* $(,)?) => {
+        $crate::mapper!($($start $(..= $end)? => $value,)* default => 0)
+    };
+}
+
+/// Builds a `Network` from an input width and a list of dense layers, returning
+/// `Result<Network, SnnError>`.
+///
+/// Each layer is a bracketed list of nodes. A node is a combinator name, resolved with
+/// `combinator_from_str` when the macro runs, optionally followed by a block of
+/// `SignalNodeBuilder` method calls written as `method: argument`.
+///
+/// ```
+/// use snn::network;
+/// use snn::neural::mapper_defaults::create_mapper_to_uppercase;
+///
+/// let network = network! {
+///     input: 3;
+///     ["max", "min", "xor" { name: "mix" }];
+///     ["average" { activation: create_mapper_to_uppercase() }];
+/// }
+/// .unwrap();
+/// assert_eq!(network.forward(b"abc").unwrap(), b"A");
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! network {
+    (
+        input: $width:expr;
+        $([$($combinator:literal $({$($method:ident: $argument:expr),* $(,)?})?),* $(,)?];)*
+    ) => {
+        $crate::neural::macros::try_build(|| {
+            Ok($crate::neural::network::Network::builder()
+                .input_width($width)
+                $(.layer_nodes(vec![$(
+                    $crate::neural::node::SignalNode::builder()
+                        .combinator($crate::neural::combinator::combinator_from_str($combinator)?)
+                        $($(.$method($argument))*)?
+                        .build()?
+                ),*]))*
+                .build()?)
+        })
+    };
+}
+
+/// Panics, failing compilation inside `mapper!`, if any two inclusive ranges overlap or a
+/// range is reversed.
+#[doc(hidden)]
+pub const fn assert_disjoint(ranges: &[(u8, u8)]) {
+    let mut i = 0;
+    while i < ranges.len() {
+        let (start, end) = ranges[i];
+        assert!(start <= end, "mapper! range start is after its end");
+        let mut j = 0;
+        while j < i {
+            let (other_start, other_end) = ranges[j];
+            assert!(
+                end < other_start || other_end < start,
+                "mapper! keys overlap"
+            );
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+/// Runs the body `network!` expands to, giving `?` a single error type.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub fn try_build<T>(build: impl FnOnce() -> Result<T, SnnError>) -> Result<T, SnnError> {
+    build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::mapper::MapperNode;
+    #[cfg(feature = "std")]
+    use crate::neural::{
+        combinator::CombinatorParseError,
+        combinator::{MaxCombinatorNode, MinCombinatorNode, XORCombinatorNode},
+        mapper_defaults::create_mapper_sigmoid,
+        network::Network,
+    };
+
+    static CLASSES: MapperNode = mapper! {
+        b'a'..=b'z' => 0xFF,
+        b'0'..=b'9' => 0x0F,
+        b'_' => 0x01,
+        default => 0x80,
+    };
+
+    #[test]
+    fn test_mapper_matches_builder() {
+        let built = MapperNode::new()
+            .with_fill(0x80)
+            .with_range(b'a'..=b'z', 0xFF)
+            .with_range(b'0'..=b'9', 0x0F)
+            .with_mapdata([(b'_', 0x01)]);
+        assert_eq!(CLASSES.table(), built.table());
+
+        let runtime_value = 7;
+        let no_default = mapper! { 200..=255 => runtime_value, 3 => 4 };
+        let built = MapperNode::new()
+            .with_range(200..=255, 7)
+            .with_mapdata([(3, 4)]);
+        assert_eq!(no_default.table(), built.table());
+        assert_eq!(mapper! {}.table(), MapperNode::new().table());
+        assert_eq!(
+            mapper! { default => 9 }.table(),
+            MapperNode::new().with_fill(9).table()
+        );
+    }
+
+    #[test]
+    fn test_assert_disjoint() {
+        assert_disjoint(&[(0, 9), (10, 10), (11, 255)]);
+        assert_disjoint(&[]);
+        let overlap = std::panic::catch_unwind(|| assert_disjoint(&[(0, 10), (10, 20)]));
+        assert!(overlap.is_err());
+        let reversed = std::panic::catch_unwind(|| assert_disjoint(&[(9, 3)]));
+        assert!(reversed.is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_network_matches_builder() {
+        let activation = create_mapper_sigmoid(128, 16);
+        let network = network! {
+            input: 2;
+            ["max", "min" { name: "low" }, "xor"];
+            ["addition" { activation: activation.clone() }];
+        }
+        .unwrap();
+        let built = Network::builder()
+            .input_width(2)
+            .layer_nodes(vec![
+                crate::neural::node::SignalNode::builder()
+                    .combinator(Box::new(MaxCombinatorNode::new()))
+                    .build()
+                    .unwrap(),
+                crate::neural::node::SignalNode::builder()
+                    .combinator(Box::new(MinCombinatorNode::new()))
+                    .name("low")
+                    .build()
+                    .unwrap(),
+                crate::neural::node::SignalNode::builder()
+                    .combinator(Box::new(XORCombinatorNode::new()))
+                    .build()
+                    .unwrap(),
+            ])
+            .layer_dense(1, |n| {
+                n.combinator(crate::neural::combinator::combinator_from_str("addition").unwrap())
+                    .activation(activation.clone())
+            })
+            .build()
+            .unwrap();
+        #[cfg(feature = "json")]
+        assert_eq!(network.to_json(), built.to_json());
+        for input in [[0, 0], [3, 200], [255, 1]] {
+            assert_eq!(
+                network.forward(&input).unwrap(),
+                built.forward(&input).unwrap()
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_network_reports_errors() {
+        let unknown = network! { input: 2; ["max", "nonsense"]; };
+        assert!(matches!(
+            unknown,
+            Err(SnnError::CombinatorParse(
+                CombinatorParseError::UnknownName(_)
+            ))
+        ));
+        let empty: Result<Network, SnnError> = network! { input: 2; };
+        assert!(empty.unwrap().is_empty());
+        let duplicate = network! { input: 2; ["max" { name: "a" }, "min" { name: "a" }]; };
+        assert!(matches!(duplicate, Err(SnnError::Build(_))));
+    }
+}
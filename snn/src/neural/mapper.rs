@@ -1,14 +1,19 @@
 /// Mapper module for efficient byte-to-byte mapping and transformation.
-use std::u8;
+use alloc::{string::String, vec::Vec};
 
-use crate::neural::traits::Mapper;
+use crate::neural::traits::{FnMapper, Mapper};
 
 /// The maximum value for a single byte (u8).
-const MAX: usize = std::u8::MAX as usize;
+const MAX: usize = u8::MAX as usize;
 /// The length of the mapping array (256 for all possible u8 values).
 const MAX_LENGTH: usize = MAX + 1;
 
 /// A struct that provides fast mapping from u8 to u8 using a lookup table.
+///
+/// The layout is exactly that of the table, so a borrowed table can be viewed as a mapper
+/// without copying (see `mapper_shared::MapperRef`).
+#[derive(Clone, PartialEq, Eq)]
+#[repr(transparent)]
 pub struct MapperNode {
     /// The transformation table: maps each u8 value to another u8 value.
     tf: [u8; MAX_LENGTH],
@@ -18,7 +23,7 @@ impl MapperNode {
     /// Creates a new `Mapper` with all values initialized to 0.
     pub const fn new() -> MapperNode {
         MapperNode {
-            tf: [0 as u8; MAX_LENGTH],
+            tf: [0u8; MAX_LENGTH],
         }
     }
 
@@ -27,7 +32,7 @@ impl MapperNode {
     /// # Arguments
     /// * `data` - An array of 256 u8 values representing the mapping.
     pub const fn new_from(data: [u8; MAX_LENGTH]) -> MapperNode {
-        return MapperNode { tf: data };
+        MapperNode { tf: data }
     }
 
     /// Creates a new `Mapper` using a transformation function.
@@ -35,13 +40,44 @@ impl MapperNode {
     /// # Arguments
     /// * `transfn` - A function that takes an index (usize) and returns a u8 value for that index.
     pub fn new_transformation(transfn: impl Fn(usize) -> u8) -> MapperNode {
-        let mut data = [0 as u8; MAX_LENGTH];
-        for i in 0..MAX_LENGTH {
-            data[i] = transfn(i);
+        let mut data = [0u8; MAX_LENGTH];
+        for (i, d) in data.iter_mut().enumerate() {
+            *d = transfn(i);
         }
         MapperNode::new_from(data)
     }
 
+    /// Creates a new `Mapper` that interpolates linearly between breakpoints `(x, y)`, given
+    /// in any order. Inputs before the first or after the last breakpoint keep its output.
+    /// Interpolated outputs are rounded half up, so 0.5 rounds to 1 on rising and falling
+    /// segments alike.
+    ///
+    /// # Returns
+    /// A new `MapperNode`, or a `SegmentError` if `points` is empty or gives one x twice.
+    pub fn with_segments(points: &[(u8, u8)]) -> Result<MapperNode, SegmentError> {
+        let mut points = points.to_vec();
+        points.sort_unstable_by_key(|&(x, _)| x);
+        if let Some(pair) = points.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(SegmentError::DuplicateX(pair[0].0));
+        }
+        let (&(first_x, first_y), &(last_x, last_y)) = match (points.first(), points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(SegmentError::Empty),
+        };
+        let mut data = [0u8; MAX_LENGTH];
+        data[..first_x as usize].fill(first_y);
+        data[last_x as usize..].fill(last_y);
+        for pair in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            let (dx, dy) = (x1 as i32 - x0 as i32, y1 as i32 - y0 as i32);
+            for x in x0..x1 {
+                let n = dy * (x - x0) as i32;
+                data[x as usize] = (y0 as i32 + (2 * n + dx).div_euclid(2 * dx)) as u8;
+            }
+        }
+        Ok(MapperNode { tf: data })
+    }
+
     /// Transforms an input byte using the mapping table.
     ///
     /// # Arguments
@@ -49,16 +85,154 @@ impl MapperNode {
     ///
     /// # Returns
     /// The mapped output byte.
+    pub const fn transform(&self, input: u8) -> u8 {
+        self.tf[input as usize]
+    }
+
+    /// Misspelled former name of `transform`.
+    #[deprecated(note = "renamed to `transform`")]
     pub const fn tranform(&self, input: u8) -> u8 {
-        return self.tf[input as usize];
+        self.transform(input)
+    }
+
+    /// Returns the mapping table, where entry `i` is the output for input `i`.
+    pub const fn table(&self) -> &[u8; MAX_LENGTH] {
+        &self.tf
+    }
+
+    /// Views a borrowed table as a mapper without copying it.
+    pub(crate) const fn from_table_ref(table: &[u8; MAX_LENGTH]) -> &MapperNode {
+        // SAFETY: `MapperNode` is `repr(transparent)` over `[u8; MAX_LENGTH]`, so both types
+        // have the same layout and any table is a valid mapper.
+        unsafe { &*(table as *const [u8; MAX_LENGTH] as *const MapperNode) }
+    }
+
+    /// Returns the table entry for `index`. The same as `transform`, for code that reads the
+    /// table rather than transforming data.
+    pub const fn get(&self, index: u8) -> u8 {
+        self.tf[index as usize]
+    }
+
+    /// Returns the mapping table. The same as `table`.
+    pub const fn as_array(&self) -> &[u8; MAX_LENGTH] {
+        self.table()
+    }
+
+    /// Returns every `(input, output)` pair in input order.
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = (u8, u8)> + DoubleEndedIterator + '_ {
+        self.tf.iter().enumerate().map(|(i, &d)| (i as u8, d))
+    }
+
+    /// Returns the `(input, output)` pairs whose output differs from `baseline`, in input order.
+    /// Pass `MapperNode::new()` to list the entries that are not 0, or an identity mapper to
+    /// list the inputs that are changed.
+    pub fn non_default_entries<'a>(
+        &'a self,
+        baseline: &'a MapperNode,
+    ) -> impl Iterator<Item = (u8, u8)> + 'a {
+        self.entries()
+            .filter(|&(i, d)| baseline.tf[i as usize] != d)
+    }
+
+    /// Returns the mapping table for modification.
+    #[cfg(feature = "std")]
+    pub(crate) fn table_mut(&mut self) -> &mut [u8; MAX_LENGTH] {
+        &mut self.tf
+    }
+
+    /// Returns true if every input maps to itself.
+    pub fn is_identity(&self) -> bool {
+        self.tf.iter().enumerate().all(|(i, &d)| i == d as usize)
     }
 
     /// Returns the length of the mapping table (always 256).
     pub const fn len(&self) -> usize {
-        return self.tf.len();
+        self.tf.len()
+    }
+
+    /// Returns whether the mapping table is empty (never, as it always holds 256 entries).
+    pub const fn is_empty(&self) -> bool {
+        self.tf.is_empty()
+    }
+
+    /// Transforms every byte of `input` into the matching position of `output`.
+    ///
+    /// With the `simd` feature enabled and a CPU that supports it, the table lookups are vectorized;
+    /// otherwise this falls back to a scalar loop.
+    ///
+    /// # Panics
+    /// Panics if `input` and `output` have different lengths.
+    pub fn transform_slice(&self, input: &[u8], output: &mut [u8]) {
+        assert_eq!(input.len(), output.len(), "input and output lengths differ");
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        if crate::neural::mapper_simd::is_supported() {
+            // SAFETY: SSSE3 support was checked above and the lengths are equal.
+            let done =
+                unsafe { crate::neural::mapper_simd::transform_slice(&self.tf, input, output) };
+            self.transform_slice_scalar(&input[done..], &mut output[done..]);
+            return;
+        }
+        self.transform_slice_scalar(input, output);
+    }
+
+    /// Transforms every byte of `data` in place.
+    pub fn transform_in_place(&self, data: &mut [u8]) {
+        for d in data.iter_mut() {
+            *d = self.tf[*d as usize];
+        }
     }
 
-    pub fn with_fill(&self, value: u8) -> MapperNode {
+    /// Scalar implementation of `transform_slice`.
+    fn transform_slice_scalar(&self, input: &[u8], output: &mut [u8]) {
+        for (o, &i) in output.iter_mut().zip(input) {
+            *o = self.tf[i as usize];
+        }
+    }
+
+    /// Transforms the ASCII characters of a string using the mapping table.
+    /// Multi-byte UTF-8 characters are copied to the output unchanged rather than split into bytes.
+    ///
+    /// # Arguments
+    /// * `input` - The string to transform.
+    ///
+    /// # Returns
+    /// The transformed bytes.
+    pub fn transform_str(&self, input: &str) -> Vec<u8> {
+        let mut output = Vec::with_capacity(input.len());
+        for (i, c) in input.char_indices() {
+            if c.is_ascii() {
+                output.push(self.transform(c as u8));
+            } else {
+                output.extend_from_slice(&input.as_bytes()[i..i + c.len_utf8()]);
+            }
+        }
+        output
+    }
+
+    /// Transforms a string that must stay ASCII on both sides of the mapping.
+    ///
+    /// # Arguments
+    /// * `input` - The string to transform.
+    ///
+    /// # Returns
+    /// The transformed string, or a `NonAsciiError` with the byte position of the first offending character.
+    pub fn transform_str_ascii(&self, input: &str) -> Result<String, NonAsciiError> {
+        let mut output = String::with_capacity(input.len());
+        for (position, c) in input.char_indices() {
+            if !c.is_ascii() {
+                return Err(NonAsciiError::Input { position, char: c });
+            }
+            let byte = self.transform(c as u8);
+            if !byte.is_ascii() {
+                return Err(NonAsciiError::Output { position, byte });
+            }
+            output.push(byte as char);
+        }
+        Ok(output)
+    }
+
+    /// Returns a new `Mapper` with every entry set to the given value.
+    pub const fn with_fill(&self, value: u8) -> MapperNode {
         let data = [value; MAX_LENGTH];
         MapperNode::new_from(data)
     }
@@ -78,10 +252,41 @@ impl MapperNode {
         MapperNode::new_from(data)
     }
 
+    /// Returns a new `Mapper` with the given key-value pairs updated in the mapping table,
+    /// rejecting keys that are assigned two different values.
+    /// Assigning the same value to a key more than once is allowed.
+    ///
+    /// # Arguments
+    /// * `mapdata` - An iterator of (u8, u8) pairs to update in the mapping.
+    ///
+    /// # Returns
+    /// The new `MapperNode`, or a `ConflictError` describing the first conflicting key.
+    pub fn try_with_mapdata<I>(&self, mapdata: I) -> Result<MapperNode, ConflictError>
+    where
+        I: IntoIterator<Item = (u8, u8)>,
+    {
+        let mut data = self.tf;
+        let mut assigned = [false; MAX_LENGTH];
+        for (k, v) in mapdata {
+            let index = k as usize;
+            if assigned[index] && data[index] != v {
+                return Err(ConflictError {
+                    key: k,
+                    first: data[index],
+                    second: v,
+                });
+            }
+            assigned[index] = true;
+            data[index] = v;
+        }
+        Ok(MapperNode::new_from(data))
+    }
+
     /// Returns a new `Mapper` with the given key-value pairs from a HashMap updated in the mapping table.
     ///
     /// # Arguments
     /// * `mapdata` - A reference to a HashMap of (u8, u8) pairs to update in the mapping.
+    #[cfg(feature = "std")]
     pub fn with_mapdata_hashmap(&self, mapdata: &std::collections::HashMap<u8, u8>) -> MapperNode {
         let mut data = self.tf;
         for (&k, &v) in mapdata.iter() {
@@ -106,11 +311,67 @@ impl MapperNode {
         MapperNode::new_from(data)
     }
 
+    /// Returns a new `Mapper` with all values in the given range set to the specified value,
+    /// but only for keys that are currently unset (mapped to 0).
+    ///
+    /// # Arguments
+    /// * `range` - An iterator of u8 values to update in the mapping.
+    /// * `value` - The value to set for each unset key in the range.
+    pub fn with_range_if_unset<R>(&self, range: R, value: u8) -> MapperNode
+    where
+        R: IntoIterator<Item = u8>,
+    {
+        let mut data = self.tf;
+        for c in range {
+            if data[c as usize] == 0 {
+                data[c as usize] = value;
+            }
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Returns a new `Mapper` with the given key-value pairs updated in the mapping table,
+    /// but only for keys that are currently unset (mapped to 0).
+    ///
+    /// # Arguments
+    /// * `mapdata` - An iterator of (u8, u8) pairs to update in the mapping.
+    pub fn with_mapdata_if_unset<I>(&self, mapdata: I) -> MapperNode
+    where
+        I: IntoIterator<Item = (u8, u8)>,
+    {
+        let mut data = self.tf;
+        for (k, v) in mapdata {
+            if data[k as usize] == 0 {
+                data[k as usize] = v;
+            }
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Returns a new `Mapper` with `other` layered on top of this one.
+    /// Entries in `other` that are 0 are transparent and keep the value from `self`.
+    ///
+    /// # Arguments
+    /// * `other` - The `MapperNode` to place on top.
+    pub fn overlay(&self, other: &MapperNode) -> MapperNode {
+        self.overlay_with_sentinel(other, 0)
+    }
+
+    /// Returns a new `Mapper` with `other` layered on top of this one.
+    /// Entries in `other` equal to `sentinel` are transparent and keep the value from `self`.
+    ///
+    /// # Arguments
+    /// * `other` - The `MapperNode` to place on top.
+    /// * `sentinel` - The value in `other` that is treated as transparent.
+    pub fn overlay_with_sentinel(&self, other: &MapperNode, sentinel: u8) -> MapperNode {
+        self.with_modification(|i, d| if other.tf[i] == sentinel { d } else { other.tf[i] })
+    }
+
     /// Returns a new `Mapper` with a modification function applied to each value in the mapping table.
     pub fn with_modification(&self, modification: impl Fn(usize, u8) -> u8) -> MapperNode {
         let mut data = self.tf;
-        for i in 0..MAX_LENGTH {
-            data[i] = modification(i, data[i]);
+        for (i, d) in data.iter_mut().enumerate() {
+            *d = modification(i, *d);
         }
         MapperNode::new_from(data)
     }
@@ -121,7 +382,85 @@ impl MapperNode {
     /// # Returns
     /// A new `MapperNode` where each value is replaced by its inverse with respect to `MAX`.
     pub fn invert(&self) -> MapperNode {
-        self.with_modification(|_, d| (MAX as u8 - d))
+        self.with_modification(|_, d| MAX as u8 - d)
+    }
+
+    /// Returns the mapper that undoes this one, or `None` if two inputs share an output.
+    /// Not to be confused with `invert`, which flips the output values.
+    pub fn inverse(&self) -> Option<MapperNode> {
+        let mut data = [0u8; MAX_LENGTH];
+        let mut seen = [false; MAX_LENGTH];
+        for (input, &output) in self.tf.iter().enumerate() {
+            if core::mem::replace(&mut seen[output as usize], true) {
+                return None;
+            }
+            data[output as usize] = input as u8;
+        }
+        Some(MapperNode::new_from(data))
+    }
+
+    /// Returns true if every output is produced by exactly one input.
+    pub fn is_bijective(&self) -> bool {
+        let mut seen = [false; MAX_LENGTH];
+        self.tf
+            .iter()
+            .all(|&output| !core::mem::replace(&mut seen[output as usize], true))
+    }
+
+    /// Returns true if applying the mapper twice gives every input back, as for ROT13 or `not`.
+    pub fn is_involution(&self) -> bool {
+        self.entries().all(|(input, output)| self.transform(output) == input)
+    }
+
+    /// Returns the inputs that map to themselves, in ascending order.
+    pub fn fixed_points(&self) -> Vec<u8> {
+        self.entries()
+            .filter(|&(input, output)| input == output)
+            .map(|(input, _)| input)
+            .collect()
+    }
+
+    /// Returns true if no output is smaller than the one before it.
+    pub fn is_monotonic_increasing(&self) -> bool {
+        self.tf.windows(2).all(|pair| pair[0] <= pair[1])
+    }
+
+    /// Returns true if no output is larger than the one before it.
+    pub fn is_monotonic_decreasing(&self) -> bool {
+        self.tf.windows(2).all(|pair| pair[0] >= pair[1])
+    }
+
+    /// Splits a bijective mapper into its cycles, fixed points included as cycles of one.
+    /// Each cycle starts at its smallest input and follows the mapper from there, and cycles are
+    /// ordered by that input. Returns an empty list if the mapper is not bijective.
+    pub fn cycle_decomposition(&self) -> Vec<Vec<u8>> {
+        if !self.is_bijective() {
+            return Vec::new();
+        }
+        let mut visited = [false; MAX_LENGTH];
+        let mut cycles = Vec::new();
+        for start in 0..=u8::MAX {
+            let mut cycle = Vec::new();
+            let mut current = start;
+            while !core::mem::replace(&mut visited[current as usize], true) {
+                cycle.push(current);
+                current = self.transform(current);
+            }
+            if !cycle.is_empty() {
+                cycles.push(cycle);
+            }
+        }
+        cycles
+    }
+
+    /// Returns `(input, self output, other output)` for every input the two mappers map
+    /// differently, in ascending order of input.
+    pub fn diff(&self, other: &MapperNode) -> Vec<(u8, u8, u8)> {
+        self.entries()
+            .zip(other.tf.iter())
+            .filter(|((_, left), right)| left != *right)
+            .map(|((input, left), &right)| (input, left, right))
+            .collect()
     }
 
 
@@ -204,31 +543,681 @@ impl MapperNode {
     pub fn xor(&self, v: u8) -> MapperNode {
         self.with_modification(|_, d| d ^ v)
     }
+
+    /// Returns a new `Mapper` whose values are 255 where any bit of `mask` is set in this
+    /// mapper's value, and 0 elsewhere.
+    pub fn select_bits(&self, mask: u8) -> MapperNode {
+        self.with_modification(|_, d| if d & mask != 0 { u8::MAX } else { 0 })
+    }
+
+    /// Returns a new `Mapper` whose values are 255 where bit `bit` of this mapper's value is
+    /// set, and 0 elsewhere. Bit 0 is the least significant.
+    ///
+    /// # Panics
+    /// Panics if `bit` is 8 or more.
+    pub fn extract_bit(&self, bit: u8) -> MapperNode {
+        assert!(bit < 8, "bit index {bit} is out of range");
+        self.select_bits(1 << bit)
+    }
+
+    /// Const variant of `with_range` for the inclusive range `start..=end`.
+    /// If `start > end` the range is empty and the mapping is left unchanged.
+    ///
+    /// # Arguments
+    /// * `start` - The first key to update.
+    /// * `end` - The last key to update (inclusive).
+    /// * `value` - The value to set for each key in the range.
+    pub const fn const_with_range(&self, start: u8, end: u8, value: u8) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = start as usize;
+        while i <= end as usize {
+            data[i] = value;
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Const variant of `with_mapdata` for a fixed-size array of key-value pairs.
+    ///
+    /// # Arguments
+    /// * `mapdata` - An array of (u8, u8) pairs to update in the mapping.
+    pub const fn const_with_mapdata<const N: usize>(&self, mapdata: [(u8, u8); N]) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = 0;
+        while i < N {
+            let (k, v) = mapdata[i];
+            data[k as usize] = v;
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Const variant of `invert`.
+    pub const fn const_invert(&self) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = 0;
+        while i < MAX_LENGTH {
+            data[i] = MAX as u8 - data[i];
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Const variant of `not`.
+    pub const fn const_not(&self) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = 0;
+        while i < MAX_LENGTH {
+            data[i] = !data[i];
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Const variant of `and`.
+    pub const fn const_and(&self, v: u8) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = 0;
+        while i < MAX_LENGTH {
+            data[i] &= v;
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Const variant of `and_node`.
+    pub const fn const_and_node(&self, other: &MapperNode) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = 0;
+        while i < MAX_LENGTH {
+            data[i] &= other.tf[i];
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Const variant of `or`.
+    pub const fn const_or(&self, v: u8) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = 0;
+        while i < MAX_LENGTH {
+            data[i] |= v;
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Const variant of `or_node`.
+    pub const fn const_or_node(&self, other: &MapperNode) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = 0;
+        while i < MAX_LENGTH {
+            data[i] |= other.tf[i];
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Const variant of `xor`.
+    pub const fn const_xor(&self, v: u8) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = 0;
+        while i < MAX_LENGTH {
+            data[i] ^= v;
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Const variant of `xor_node`.
+    pub const fn const_xor_node(&self, other: &MapperNode) -> MapperNode {
+        let mut data = self.tf;
+        let mut i = 0;
+        while i < MAX_LENGTH {
+            data[i] ^= other.tf[i];
+            i += 1;
+        }
+        MapperNode::new_from(data)
+    }
+
+    /// Returns a new `Mapper` with each value clamped to the band `min..=max`.
+    ///
+    /// # Arguments
+    /// * `min` - The lowest value an entry may have.
+    /// * `max` - The highest value an entry may have.
+    ///
+    /// # Returns
+    /// A new `MapperNode` with clamped values, or `MapperError::InvalidBounds` if `min > max`.
+    pub fn clamp(&self, min: u8, max: u8) -> Result<MapperNode, MapperError> {
+        if min > max {
+            return Err(MapperError::InvalidBounds { min, max });
+        }
+        Ok(self.with_modification(|_, d| d.clamp(min, max)))
+    }
+
+    /// Returns a new `Mapper` where each value is binarized around a cutoff.
+    ///
+    /// # Arguments
+    /// * `cutoff` - Values below this become `low`, all others become `high`.
+    /// * `low` - The value for entries below the cutoff.
+    /// * `high` - The value for entries at or above the cutoff.
+    pub fn with_threshold(&self, cutoff: u8, low: u8, high: u8) -> MapperNode {
+        self.with_modification(|_, d| if d < cutoff { low } else { high })
+    }
+
+    /// Returns a new `Mapper` where each value is snapped to the nearest of `levels` evenly spaced steps
+    /// across `0..=255`. The first step is always 0 and the last step is always 255.
+    ///
+    /// A `levels` of 0 or 1 leaves no room for more than a single step, so every value becomes 0.
+    ///
+    /// # Arguments
+    /// * `levels` - The number of distinct output values.
+    pub fn quantize(&self, levels: u8) -> MapperNode {
+        if levels <= 1 {
+            return self.with_fill(0);
+        }
+        let steps = (levels - 1) as usize;
+        self.with_modification(|_, d| {
+            let k = (d as usize * steps + MAX / 2) / MAX;
+            ((k * MAX + steps / 2) / steps) as u8
+        })
+    }
+}
+
+/// Errors that can occur while building a `MapperNode`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapperError {
+    /// The lower bound was greater than the upper bound.
+    InvalidBounds { min: u8, max: u8 },
+    /// A table was given with a length other than 256.
+    TableLength { actual: usize },
+}
+
+impl core::fmt::Display for MapperError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MapperError::InvalidBounds { min, max } => {
+                write!(f, "invalid bounds: min ({min}) is greater than max ({max})")
+            }
+            MapperError::TableLength { actual } => {
+                write!(f, "mapper table needs {MAX_LENGTH} entries, got {actual}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for MapperError {}
+
+/// Error returned by `MapperNode::with_segments`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentError {
+    /// No breakpoints were given.
+    Empty,
+    /// Two breakpoints share this x.
+    DuplicateX(u8),
+}
+
+impl core::fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SegmentError::Empty => write!(f, "no breakpoints given"),
+            SegmentError::DuplicateX(x) => write!(f, "more than one breakpoint at x = {x}"),
+        }
+    }
+}
+
+impl core::error::Error for SegmentError {}
+
+/// Error returned when the same key is assigned two different values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictError {
+    /// The key that was assigned twice.
+    pub key: u8,
+    /// The value the key was assigned first.
+    pub first: u8,
+    /// The conflicting value assigned afterwards.
+    pub second: u8,
+}
+
+impl core::fmt::Display for ConflictError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "conflicting values for key {}: {} and {}",
+            self.key, self.first, self.second
+        )
+    }
+}
+
+impl core::error::Error for ConflictError {}
+
+/// Error returned when an ASCII-only string transformation encounters a non-ASCII character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NonAsciiError {
+    /// The input contained a non-ASCII character at the given byte position.
+    Input { position: usize, char: char },
+    /// The input character at the given byte position mapped to a non-ASCII byte.
+    Output { position: usize, byte: u8 },
+}
+
+impl core::fmt::Display for NonAsciiError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NonAsciiError::Input { position, char } => {
+                write!(f, "non-ASCII input character {char:?} at position {position}")
+            }
+            NonAsciiError::Output { position, byte } => {
+                write!(f, "non-ASCII output byte {byte:#04x} at position {position}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for NonAsciiError {}
+
+/// Builds a mapper from a slice holding exactly 256 entries, as handed over by bindings
+/// that cannot pass a fixed-size array.
+impl TryFrom<&[u8]> for MapperNode {
+    type Error = MapperError;
+
+    fn try_from(table: &[u8]) -> Result<Self, Self::Error> {
+        let tf = table.try_into().map_err(|_| MapperError::TableLength {
+            actual: table.len(),
+        })?;
+        Ok(MapperNode { tf })
+    }
+}
+
+impl From<[u8; MAX_LENGTH]> for MapperNode {
+    fn from(table: [u8; MAX_LENGTH]) -> Self {
+        MapperNode::new_from(table)
+    }
+}
+
+impl From<MapperNode> for [u8; MAX_LENGTH] {
+    fn from(mapper: MapperNode) -> Self {
+        mapper.tf
+    }
+}
+
+/// Maps the keys of the map to their values and every other input to 0.
+#[cfg(feature = "std")]
+impl From<&std::collections::HashMap<u8, u8>> for MapperNode {
+    fn from(mapdata: &std::collections::HashMap<u8, u8>) -> Self {
+        MapperNode::new().with_mapdata_hashmap(mapdata)
+    }
+}
+
+/// Samples the function at all 256 inputs. Closures can be converted through `FnMapper`.
+impl From<fn(u8) -> u8> for MapperNode {
+    fn from(f: fn(u8) -> u8) -> Self {
+        MapperNode::new_transformation(|x| f(x as u8))
+    }
+}
+
+/// Samples the closure at all 256 inputs.
+impl<F: Fn(u8) -> u8> From<FnMapper<F>> for MapperNode {
+    fn from(mapper: FnMapper<F>) -> Self {
+        MapperNode::new_transformation(|x| (mapper.0)(x as u8))
+    }
+}
+
+impl AsRef<[u8]> for MapperNode {
+    fn as_ref(&self) -> &[u8] {
+        &self.tf
+    }
+}
+
+impl Default for MapperNode {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Mapper for MapperNode {
     fn transform(&self, input: u8) -> u8 {
-        self.tranform(input)
+        MapperNode::transform(self, input)
+    }
+    fn transform_slice(&self, input: &[u8], out: &mut [u8]) {
+        MapperNode::transform_slice(self, input, out)
+    }
+    fn transform_in_place(&self, buf: &mut [u8]) {
+        MapperNode::transform_in_place(self, buf)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    static LOWERCASE: MapperNode = MapperNode::new().const_with_range(b'a', b'z', 0b1111_1111);
+
+    static CLASSES: MapperNode = MapperNode::new()
+        .with_fill(0b1000_0000)
+        .const_with_range(b'a', b'z', 0b0000_0001)
+        .const_with_mapdata([(b'_', 0b0000_0010), (b'-', 0b0000_0100)])
+        .const_or(0b0001_0000)
+        .const_xor(0b0011_0000)
+        .const_and(0b1110_1111)
+        .const_not()
+        .const_invert();
+
+    #[test]
+    fn test_static_lowercase() {
+        for i in 0u8..=u8::MAX {
+            let expected = if i.is_ascii_lowercase() { 0b1111_1111 } else { 0 };
+            assert_eq!(LOWERCASE.transform(i), expected);
+        }
+    }
+
+    #[test]
+    fn test_static_chain_matches_runtime() {
+        let runtime = MapperNode::new()
+            .with_fill(0b1000_0000)
+            .with_range(b'a'..=b'z', 0b0000_0001)
+            .with_mapdata([(b'_', 0b0000_0010), (b'-', 0b0000_0100)])
+            .or(0b0001_0000)
+            .xor(0b0011_0000)
+            .and(0b1110_1111)
+            .not()
+            .invert();
+        assert_eq!(CLASSES.tf, runtime.tf);
+    }
+
+    #[test]
+    fn test_const_node_modifiers_match_runtime() {
+        const A: MapperNode = MapperNode::new().const_with_range(0, 127, 0b1010_1010);
+        const B: MapperNode = MapperNode::new().const_with_range(64, 255, 0b0110_0110);
+        assert_eq!(A.const_and_node(&B).tf, A.and_node(&B).tf);
+        assert_eq!(A.const_or_node(&B).tf, A.or_node(&B).tf);
+        assert_eq!(A.const_xor_node(&B).tf, A.xor_node(&B).tf);
+    }
+
+    #[test]
+    fn test_const_with_range_empty() {
+        let mapper = MapperNode::new().const_with_range(10, 5, 1);
+        assert!(mapper.tf.iter().all(|&v| v == 0));
+        let full = MapperNode::new().const_with_range(0, 255, 1);
+        assert!(full.tf.iter().all(|&v| v == 1));
+    }
+
+    fn identity() -> MapperNode {
+        MapperNode::new_transformation(|x| x as u8)
+    }
+
+    #[test]
+    fn test_with_range_if_unset() {
+        let mapper = MapperNode::new()
+            .with_range(b'0'..=b'9', 1)
+            .with_range_if_unset(b'0'..=b'z', 2);
+        for i in 0u8..=u8::MAX {
+            let expected = match i {
+                b'0'..=b'9' => 1,
+                b':'..=b'z' => 2,
+                _ => 0,
+            };
+            assert_eq!(mapper.transform(i), expected);
+        }
+    }
+
+    #[test]
+    fn test_with_mapdata_if_unset() {
+        let mapper = MapperNode::new()
+            .with_mapdata([(1, 10)])
+            .with_mapdata_if_unset([(1, 20), (2, 30)]);
+        assert_eq!(mapper.transform(1), 10);
+        assert_eq!(mapper.transform(2), 30);
+        assert_eq!(mapper.transform(3), 0);
+    }
+
+    #[test]
+    fn test_overlay() {
+        let base = MapperNode::new().with_fill(5);
+        let top = MapperNode::new().with_range(100..=200, 9);
+        let layered = base.overlay(&top);
+        for i in 0u8..=u8::MAX {
+            let expected = if (100..=200).contains(&i) { 9 } else { 5 };
+            assert_eq!(layered.transform(i), expected);
+        }
+    }
+
+    #[test]
+    fn test_overlay_with_sentinel() {
+        let base = MapperNode::new().with_fill(5);
+        let top = MapperNode::new().with_fill(255).with_mapdata([(3, 0), (4, 7)]);
+        let layered = base.overlay_with_sentinel(&top, 255);
+        assert_eq!(layered.transform(3), 0);
+        assert_eq!(layered.transform(4), 7);
+        assert_eq!(layered.transform(5), 5);
+    }
+
+    #[test]
+    #[allow(clippy::manual_clamp)]
+    fn test_clamp() {
+        let clamped = identity().clamp(40, 200).unwrap();
+        for i in 0u8..=u8::MAX {
+            let expected = i.max(40).min(200);
+            assert_eq!(clamped.transform(i), expected);
+        }
+    }
+
+    #[test]
+    fn test_clamp_single_value() {
+        let clamped = identity().clamp(7, 7).unwrap();
+        for i in 0u8..=u8::MAX {
+            assert_eq!(clamped.transform(i), 7);
+        }
+    }
+
+    #[test]
+    fn test_clamp_invalid_bounds() {
+        let result = identity().clamp(10, 5);
+        assert_eq!(result.err(), Some(MapperError::InvalidBounds { min: 10, max: 5 }));
+    }
+
+    #[test]
+    fn test_with_threshold() {
+        let thresholded = identity().with_threshold(128, 3, 250);
+        for i in 0u8..=u8::MAX {
+            let expected = if i < 128 { 3 } else { 250 };
+            assert_eq!(thresholded.transform(i), expected);
+        }
+    }
+
+    #[test]
+    fn test_with_threshold_zero_cutoff() {
+        let thresholded = identity().with_threshold(0, 1, 2);
+        for i in 0u8..=u8::MAX {
+            assert_eq!(thresholded.transform(i), 2);
+        }
+    }
+
+    #[test]
+    fn test_quantize() {
+        for levels in 2u8..=u8::MAX {
+            let quantized = identity().quantize(levels);
+            let steps = (levels - 1) as i64;
+            for i in 0u8..=u8::MAX {
+                // Pick the step whose exact position k * 255 / steps is closest to i, then round it.
+                let k = (0..=steps)
+                    .min_by_key(|k| (i as i64 * steps - k * 255).abs())
+                    .unwrap();
+                let expected = ((2 * k * 255 + steps) / (2 * steps)) as u8;
+                assert_eq!(quantized.transform(i), expected, "levels {levels}, input {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_quantize_two_levels() {
+        let quantized = identity().quantize(2);
+        for i in 0u8..=u8::MAX {
+            assert_eq!(quantized.transform(i), if i < 128 { 0 } else { 255 });
+        }
+    }
+
+    #[test]
+    fn test_quantize_zero_and_one_levels() {
+        for levels in [0, 1] {
+            let quantized = identity().quantize(levels);
+            for i in 0u8..=u8::MAX {
+                assert_eq!(quantized.transform(i), 0);
+            }
+        }
+    }
+
     #[test]
     fn test_invert() {
         let mapper = MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, x)));
         let inverted = mapper.invert();
         for i in 0u8..=u8::MAX {
-            assert_eq!(inverted.tranform(i), u8::MAX - i);
+            assert_eq!(inverted.transform(i), u8::MAX - i);
         }
     }
 
+    #[test]
+    fn test_inverse() {
+        let shifted = MapperNode::new_transformation(|x| (x as u8).wrapping_add(3));
+        let inverse = shifted.inverse().unwrap();
+        assert_eq!(inverse.transform(3), 0);
+        assert_eq!(inverse.transform(1), 254);
+        assert_eq!(inverse.inverse().unwrap().tf, shifted.tf);
+        assert!(identity().inverse().unwrap().is_identity());
+        assert!(MapperNode::new().inverse().is_none());
+        assert!(shifted.with_mapdata([(0, 4)]).inverse().is_none());
+    }
+
+    #[test]
+    fn test_select_and_extract_bits() {
+        let flags = identity().select_bits(0b1010_0000);
+        assert_eq!(flags.transform(0b0010_0000), 255);
+        assert_eq!(flags.transform(0b1000_0001), 255);
+        assert_eq!(flags.transform(0b0101_1111), 0);
+        for bit in 0..8 {
+            let extracted = identity().extract_bit(bit);
+            for i in 0u8..=u8::MAX {
+                let expected = if (i >> bit) & 1 == 1 { 255 } else { 0 };
+                assert_eq!(extracted.transform(i), expected);
+            }
+        }
+        // Selection applies to the mapped value, not the input.
+        let classified = MapperNode::new().with_mapdata([(b'a', 0b0100)]);
+        assert_eq!(classified.extract_bit(2).transform(b'a'), 255);
+        assert_eq!(classified.extract_bit(2).transform(0b0100), 0);
+        assert!(std::panic::catch_unwind(|| identity().extract_bit(8)).is_err());
+    }
+
+    #[test]
+    fn test_structure() {
+        use crate::neural::mapper_defaults::{create_mapper_pyramid, create_mapper_rot13};
+
+        let identity = identity();
+        assert!(identity.is_bijective() && identity.is_involution());
+        assert_eq!(identity.fixed_points(), (0..=255).collect::<Vec<u8>>());
+        assert!(identity.is_monotonic_increasing() && !identity.is_monotonic_decreasing());
+        let cycles = identity.cycle_decomposition();
+        assert_eq!(cycles.len(), 256);
+        assert!(cycles.iter().enumerate().all(|(i, c)| c == &[i as u8]));
+
+        let not = identity.not();
+        assert!(not.is_bijective() && not.is_involution());
+        assert!(not.fixed_points().is_empty());
+        assert!(not.is_monotonic_decreasing() && !not.is_monotonic_increasing());
+        let cycles = not.cycle_decomposition();
+        assert_eq!(cycles.len(), 128);
+        assert_eq!(cycles[0], [0, 255]);
+        assert_eq!(cycles[127], [127, 128]);
+
+        let rot13 = create_mapper_rot13();
+        assert!(rot13.is_involution());
+        assert_eq!(rot13.fixed_points().len(), 256 - 52);
+        assert!(!rot13.fixed_points().contains(&b'q'));
+        let cycles = rot13.cycle_decomposition();
+        assert_eq!(cycles.iter().filter(|c| c.len() == 2).count(), 26);
+        assert!(cycles.iter().all(|c| c.len() <= 2));
+        assert!(cycles.contains(&vec![b'a', b'n']));
+
+        let pyramid = create_mapper_pyramid();
+        assert!(!pyramid.is_bijective() && !pyramid.is_involution());
+        assert_eq!(pyramid.fixed_points(), (0..=127).collect::<Vec<u8>>());
+        assert!(!pyramid.is_monotonic_increasing() && !pyramid.is_monotonic_decreasing());
+        assert!(pyramid.cycle_decomposition().is_empty());
+
+        let shifted = MapperNode::new_transformation(|x| (x as u8).wrapping_add(1));
+        assert!(!shifted.is_involution());
+        assert_eq!(
+            shifted.cycle_decomposition(),
+            [(0..=255).collect::<Vec<u8>>()]
+        );
+        let constant = MapperNode::new().with_fill(9);
+        assert!(constant.is_monotonic_increasing() && constant.is_monotonic_decreasing());
+        assert_eq!(constant.fixed_points(), [9]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let identity = identity();
+        assert!(identity.diff(&identity.clone()).is_empty());
+        let changed = identity.with_mapdata([(0x41, 0xFF), (0x00, 0x07)]);
+        assert_eq!(
+            identity.diff(&changed),
+            vec![(0x00, 0x00, 0x07), (0x41, 0x41, 0xFF)]
+        );
+        assert_eq!(
+            changed.diff(&identity),
+            vec![(0x00, 0x07, 0x00), (0x41, 0xFF, 0x41)]
+        );
+        assert_eq!(identity.diff(&identity.invert()).len(), 256);
+    }
+
+    #[test]
+    fn test_with_segments() {
+        let mapper = MapperNode::with_segments(&[(255, 255), (0, 0), (64, 200)]).unwrap();
+        assert_eq!(mapper.transform(0), 0);
+        assert_eq!(mapper.transform(64), 200);
+        assert_eq!(mapper.transform(255), 255);
+        assert_eq!(mapper.transform(32), 100);
+        assert_eq!(mapper.transform(1), 3);
+        // 200 + 55 * 96 / 191 = 227.6
+        assert_eq!(mapper.transform(160), 228);
+        assert!(mapper.is_monotonic_increasing());
+    }
+
+    #[test]
+    fn test_with_segments_rounds_half_up() {
+        let rising = MapperNode::with_segments(&[(10, 0), (12, 1)]).unwrap();
+        assert_eq!(rising.transform(11), 1);
+        let falling = MapperNode::with_segments(&[(10, 1), (12, 0)]).unwrap();
+        assert_eq!(falling.transform(11), 1);
+        // Flat before the first and after the last breakpoint.
+        assert_eq!(rising.transform(0), 0);
+        assert_eq!(rising.transform(255), 1);
+        assert_eq!(falling.transform(9), 1);
+        assert_eq!(falling.transform(13), 0);
+    }
+
+    #[test]
+    fn test_with_segments_single_point() {
+        let mapper = MapperNode::with_segments(&[(100, 42)]).unwrap();
+        assert!(mapper == MapperNode::new().with_fill(42));
+    }
+
+    #[test]
+    fn test_with_segments_errors() {
+        assert_eq!(
+            MapperNode::with_segments(&[]).err(),
+            Some(SegmentError::Empty)
+        );
+        assert_eq!(
+            MapperNode::with_segments(&[(5, 1), (0, 0), (5, 2)]).err(),
+            Some(SegmentError::DuplicateX(5))
+        );
+    }
+
     #[test]
     fn test_not() {
         let mapper = MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, x)));
         let not = mapper.not();
         for i in 0u8..=u8::MAX {
-            assert_eq!(not.tranform(i), !i);
+            assert_eq!(not.transform(i), !i);
         }
     }
 
@@ -238,7 +1227,7 @@ mod tests {
         let mapper2 = MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, 0b10101010)));
         let anded = mapper1.and_node(&mapper2);
         for i in 0u8..=u8::MAX {
-            assert_eq!(anded.tranform(i), i & 0b10101010);
+            assert_eq!(anded.transform(i), i & 0b10101010);
         }
     }
 
@@ -247,7 +1236,7 @@ mod tests {
         let mapper = MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, x)));
         let anded = mapper.and(0b11001100);
         for i in 0u8..=u8::MAX {
-            assert_eq!(anded.tranform(i), i & 0b11001100);
+            assert_eq!(anded.transform(i), i & 0b11001100);
         }
     }
 
@@ -257,7 +1246,7 @@ mod tests {
         let mapper2 = MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, 0b00001111)));
         let ored = mapper1.or_node(&mapper2);
         for i in 0u8..=u8::MAX {
-            assert_eq!(ored.tranform(i), i | 0b00001111);
+            assert_eq!(ored.transform(i), i | 0b00001111);
         }
     }
 
@@ -266,7 +1255,7 @@ mod tests {
         let mapper = MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, x)));
         let ored = mapper.or(0b11110000);
         for i in 0u8..=u8::MAX {
-            assert_eq!(ored.tranform(i), i | 0b11110000);
+            assert_eq!(ored.transform(i), i | 0b11110000);
         }
     }
 
@@ -276,7 +1265,7 @@ mod tests {
         let mapper2 = MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, 0b11111111)));
         let xored = mapper1.xor_node(&mapper2);
         for i in 0u8..=u8::MAX {
-            assert_eq!(xored.tranform(i), i ^ 0b11111111);
+            assert_eq!(xored.transform(i), i ^ 0b11111111);
         }
     }
 
@@ -285,11 +1274,13 @@ mod tests {
         let mapper = MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, x)));
         let xored = mapper.xor(0b01010101);
         for i in 0u8..=u8::MAX {
-            assert_eq!(xored.tranform(i), i ^ 0b01010101);
+            assert_eq!(xored.transform(i), i ^ 0b01010101);
         }
     }
     use super::*;
+    use crate::neural::rng::Rng;
 
+    #[cfg(feature = "std")]
     use std::collections::HashMap;
 
     #[test]
@@ -299,18 +1290,123 @@ mod tests {
         assert!(mapper.tf.iter().all(|&v| v == 0));
     }
 
+    #[test]
+    fn test_transform_max_input() {
+        let mapper = MapperNode::new_from([7; 256]);
+        assert_eq!(mapper.transform(u8::MAX), 7);
+        assert_eq!(Mapper::transform(&mapper, u8::MAX), 7);
+    }
+
+    #[test]
+    fn test_inherent_and_trait_transform_agree() {
+        const SHIFTED: u8 = MapperNode::new().with_fill(1).transform(9);
+        assert_eq!(SHIFTED, 1);
+        let mapper = MapperNode::new_transformation(|x| (x as u8).rotate_left(1));
+        let dynamic: &dyn Mapper = &mapper;
+        for i in 0u8..=u8::MAX {
+            assert_eq!(mapper.transform(i), i.rotate_left(1));
+            assert_eq!(dynamic.transform(i), mapper.transform(i));
+            #[allow(deprecated)]
+            let old = mapper.tranform(i);
+            assert_eq!(old, mapper.transform(i));
+        }
+    }
+
     #[test]
     fn test_new_from() {
         let mapper = MapperNode::new().with_mapdata([(65, 42)]);
-        assert_eq!(mapper.tranform(65), 42);
-        assert_eq!(mapper.tranform(0), 0);
+        assert_eq!(mapper.transform(65), 42);
+        assert_eq!(mapper.transform(0), 0);
+    }
+
+    #[test]
+    fn test_try_from_slice() {
+        let table: Vec<u8> = (0..=255u8).rev().collect();
+        let mapper = MapperNode::try_from(table.as_slice()).unwrap();
+        assert_eq!(mapper.transform(0), 255);
+        assert_eq!(mapper.table().as_slice(), table.as_slice());
+        let error = MapperNode::try_from(&table[..10]).err().unwrap();
+        assert_eq!(error, MapperError::TableLength { actual: 10 });
+        assert_eq!(error.to_string(), "mapper table needs 256 entries, got 10");
+    }
+
+    #[test]
+    fn test_read_access() {
+        let mapper = identity().invert();
+        assert_eq!(mapper.get(3), 252);
+        assert_eq!(mapper.as_array(), mapper.table());
+        let entries: Vec<(u8, u8)> = mapper.entries().collect();
+        assert_eq!(entries.len(), 256);
+        assert!(
+            entries
+                .iter()
+                .enumerate()
+                .all(|(i, &(input, _))| input as usize == i)
+        );
+        assert!(entries.iter().all(|&(input, output)| output == 255 - input));
+        assert_eq!(mapper.entries().next_back(), Some((255, 0)));
+    }
+
+    #[test]
+    fn test_non_default_entries() {
+        let sparse = MapperNode::new().with_mapdata([(200, 1), (7, 9), (30, 0)]);
+        let set: Vec<_> = sparse.non_default_entries(&MapperNode::new()).collect();
+        assert_eq!(set, [(7, 9), (200, 1)]);
+
+        let changed = identity().with_mapdata([(4, 5), (9, 9)]);
+        let set: Vec<_> = changed.non_default_entries(&identity()).collect();
+        assert_eq!(set, [(4, 5)]);
+        assert_eq!(identity().non_default_entries(&identity()).count(), 0);
+    }
+
+    #[test]
+    fn test_try_from_short_slice() {
+        let table = [7u8; 255];
+        let error = MapperNode::try_from(table.as_slice()).err().unwrap();
+        assert_eq!(error, MapperError::TableLength { actual: 255 });
+    }
+
+    #[test]
+    fn test_array_conversions() {
+        let table: [u8; MAX_LENGTH] = core::array::from_fn(|i| i as u8 ^ 0x55);
+        let mapper = MapperNode::from(table);
+        assert_eq!(mapper.transform(0), 0x55);
+        assert_eq!(<[u8; MAX_LENGTH]>::from(mapper.clone()), table);
+        assert_eq!(mapper.as_ref(), table.as_slice());
+        let bytes: &[u8] = mapper.as_ref();
+        assert_eq!(bytes.len(), 256);
+    }
+
+    #[test]
+    fn test_function_conversions() {
+        fn double(x: u8) -> u8 {
+            x.wrapping_mul(2)
+        }
+        let mapper = MapperNode::from(double as fn(u8) -> u8);
+        assert_eq!(mapper.transform(3), 6);
+        assert_eq!(mapper.transform(200), 144);
+
+        let offset = 10;
+        let mapper = MapperNode::from(FnMapper(|x: u8| x.saturating_add(offset)));
+        assert_eq!(mapper.transform(0), 10);
+        assert_eq!(mapper.transform(250), 255);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_hashmap() {
+        let map = HashMap::from([(1, 10), (255, 20)]);
+        let mapper = MapperNode::from(&map);
+        assert_eq!(mapper.transform(1), 10);
+        assert_eq!(mapper.transform(255), 20);
+        assert_eq!(mapper.transform(2), 0);
     }
 
     #[test]
     fn test_new_transformation_identity() {
         let mapper = MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, x)));
         for i in 0u8..=u8::MAX {
-            assert_eq!(mapper.tranform(i), i);
+            assert_eq!(mapper.transform(i), i);
         }
     }
 
@@ -319,27 +1415,157 @@ mod tests {
         let mapper =
             MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, x.wrapping_add(1))));
         for i in 0u8..=u8::MAX {
-            assert_eq!(mapper.tranform(i), i.wrapping_add(1));
+            assert_eq!(mapper.transform(i), i.wrapping_add(1));
         }
     }
 
     #[test]
     fn test_with_mapdata_array() {
         let mapper = MapperNode::new().with_mapdata([(1, 42), (255, 99)]);
-        assert_eq!(mapper.tranform(1), 42);
-        assert_eq!(mapper.tranform(255), 99);
-        assert_eq!(mapper.tranform(0), 0);
+        assert_eq!(mapper.transform(1), 42);
+        assert_eq!(mapper.transform(255), 99);
+        assert_eq!(mapper.transform(0), 0);
+    }
+
+    #[test]
+    fn test_try_with_mapdata() {
+        let mapper = MapperNode::new()
+            .try_with_mapdata([(1, 42), (255, 99)])
+            .unwrap();
+        assert_eq!(mapper.transform(1), 42);
+        assert_eq!(mapper.transform(255), 99);
+        assert_eq!(mapper.transform(0), 0);
+    }
+
+    #[test]
+    fn test_try_with_mapdata_benign_duplicate() {
+        let mapper = MapperNode::new()
+            .try_with_mapdata([(7, 3), (8, 4), (7, 3)])
+            .unwrap();
+        assert_eq!(mapper.transform(7), 3);
+        assert_eq!(mapper.transform(8), 4);
+    }
+
+    #[test]
+    fn test_try_with_mapdata_conflict() {
+        let result = MapperNode::new().try_with_mapdata([(7, 3), (8, 4), (7, 5)]);
+        assert_eq!(
+            result.err(),
+            Some(ConflictError {
+                key: 7,
+                first: 3,
+                second: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_with_mapdata_ignores_existing_table() {
+        // Only duplicates within the supplied pairs conflict, not entries already in the table.
+        let mapper = MapperNode::new()
+            .with_fill(9)
+            .try_with_mapdata([(7, 3)])
+            .unwrap();
+        assert_eq!(mapper.transform(7), 3);
+    }
+
+    #[test]
+    fn test_transform_slice_matches_scalar() {
+        let mapper = MapperNode::new_transformation(|x| (x as u8).wrapping_mul(31).rotate_left(3));
+        for (n, len) in [0, 1, 15, 16, 17, 31, 32, 33, 4096 + 3].into_iter().enumerate() {
+            let input = Rng::new(0x9E37_79B9_7F4A_7C15 + n as u64).bytes(len);
+            let mut fast = vec![0; len];
+            let mut scalar = vec![0; len];
+            mapper.transform_slice(&input, &mut fast);
+            mapper.transform_slice_scalar(&input, &mut scalar);
+            assert_eq!(fast, scalar, "length {len}");
+            assert!(input.iter().zip(&fast).all(|(&i, &o)| mapper.transform(i) == o));
+        }
+    }
+
+    #[test]
+    fn test_transform_slice_all_values() {
+        let mapper = MapperNode::new_transformation(|x| 255 - x as u8);
+        let input: Vec<u8> = (0u8..=u8::MAX).collect();
+        let mut output = vec![0; input.len()];
+        mapper.transform_slice(&input, &mut output);
+        for (&i, &o) in input.iter().zip(&output) {
+            assert_eq!(o, 255 - i);
+        }
+    }
+
+    #[test]
+    fn test_transform_in_place() {
+        let mapper = MapperNode::new_transformation(|x| (x as u8).wrapping_add(3));
+        let mut data = Rng::new(1).bytes(100);
+        let expected: Vec<u8> = data.iter().map(|&b| b.wrapping_add(3)).collect();
+        mapper.transform_in_place(&mut data);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transform_slice_length_mismatch() {
+        let mut output = [0u8; 3];
+        MapperNode::new().transform_slice(&[1, 2], &mut output);
+    }
+
+    fn to_uppercase() -> MapperNode {
+        MapperNode::new_transformation(|x| (x as u8).to_ascii_uppercase())
+    }
+
+    #[test]
+    fn test_transform_str() {
+        let upper = to_uppercase();
+        assert_eq!(upper.transform_str("Hello, World!"), b"HELLO, WORLD!".to_vec());
+        // Multi-byte characters are passed through whole.
+        assert_eq!(upper.transform_str("héllo"), "HéLLO".as_bytes().to_vec());
+    }
+
+    #[test]
+    fn test_transform_str_ascii() {
+        let upper = to_uppercase();
+        assert_eq!(
+            upper.transform_str_ascii("Hello, World!"),
+            Ok("HELLO, WORLD!".to_string())
+        );
+        assert_eq!(upper.transform_str_ascii(""), Ok(String::new()));
+    }
+
+    #[test]
+    fn test_transform_str_ascii_non_ascii_input() {
+        let upper = to_uppercase();
+        assert_eq!(
+            upper.transform_str_ascii("héllo"),
+            Err(NonAsciiError::Input {
+                position: 1,
+                char: 'é'
+            })
+        );
+    }
+
+    #[test]
+    fn test_transform_str_ascii_non_ascii_output() {
+        let mapper = to_uppercase().with_mapdata([(b'l', 0xE9)]);
+        assert_eq!(
+            mapper.transform_str_ascii("hello"),
+            Err(NonAsciiError::Output {
+                position: 2,
+                byte: 0xE9
+            })
+        );
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn test_with_mapdata_hashmap() {
         let mut map = HashMap::new();
         map.insert(1, 42);
         map.insert(255, 99);
         let mapper = MapperNode::new().with_mapdata_hashmap(&map);
-        assert_eq!(mapper.tranform(1), 42);
-        assert_eq!(mapper.tranform(255), 99);
-        assert_eq!(mapper.tranform(0), 0);
+        assert_eq!(mapper.transform(1), 42);
+        assert_eq!(mapper.transform(255), 99);
+        assert_eq!(mapper.transform(0), 0);
     }
 
     #[test]
@@ -347,18 +1573,18 @@ mod tests {
         // Use with_range for lowercase
         let lower = MapperNode::new().with_range(b'a'..=b'z', 1);
         for c in b'a'..=b'z' {
-            assert_eq!(lower.tranform(c), 1);
+            assert_eq!(lower.transform(c), 1);
         }
-        assert_eq!(lower.tranform(b'A'), 0);
-        assert_eq!(lower.tranform(b'0'), 0);
+        assert_eq!(lower.transform(b'A'), 0);
+        assert_eq!(lower.transform(b'0'), 0);
 
         // Use with_range for uppercase
         let upper = MapperNode::new().with_range(b'A'..=b'Z', 2);
         for c in b'A'..=b'Z' {
-            assert_eq!(upper.tranform(c), 2);
+            assert_eq!(upper.transform(c), 2);
         }
-        assert_eq!(upper.tranform(b'a'), 0);
-        assert_eq!(upper.tranform(b'0'), 0);
+        assert_eq!(upper.transform(b'a'), 0);
+        assert_eq!(upper.transform(b'0'), 0);
 
         // Combine both
         let both = MapperNode::new().with_mapdata(
@@ -366,10 +1592,10 @@ mod tests {
                 .map(|c| (c, 1))
                 .chain((b'a'..=b'z').map(|c| (c, 1))),
         );
-        assert_eq!(both.tranform(b'A'), 1);
-        assert_eq!(both.tranform(b'z'), 1);
-        assert_eq!(both.tranform(b'0'), 0);
-        assert_eq!(both.tranform(b'!'), 0);
+        assert_eq!(both.transform(b'A'), 1);
+        assert_eq!(both.transform(b'z'), 1);
+        assert_eq!(both.transform(b'0'), 0);
+        assert_eq!(both.transform(b'!'), 0);
     }
 
     #[test]
@@ -391,14 +1617,14 @@ mod tests {
             pairs.push((c, 0b00010000));
         }
         let mapper = MapperNode::new().with_mapdata(pairs);
-        assert_eq!(mapper.tranform(b'a'), 0b00000001);
-        assert_eq!(mapper.tranform(b'B'), 0b00000010);
-        assert_eq!(mapper.tranform(b'3'), 0b00000100);
-        assert_eq!(mapper.tranform(b'!'), 0b00001000);
-        assert_eq!(mapper.tranform(b' '), 0b00010000);
-        assert_eq!(mapper.tranform(b'\t'), 0b00010000);
-        assert_eq!(mapper.tranform(b'\n'), 0b00010000);
-        assert_eq!(mapper.tranform(b'$'), 0);
+        assert_eq!(mapper.transform(b'a'), 0b00000001);
+        assert_eq!(mapper.transform(b'B'), 0b00000010);
+        assert_eq!(mapper.transform(b'3'), 0b00000100);
+        assert_eq!(mapper.transform(b'!'), 0b00001000);
+        assert_eq!(mapper.transform(b' '), 0b00010000);
+        assert_eq!(mapper.transform(b'\t'), 0b00010000);
+        assert_eq!(mapper.transform(b'\n'), 0b00010000);
+        assert_eq!(mapper.transform(b'$'), 0);
     }
 
     #[test]
@@ -406,10 +1632,10 @@ mod tests {
         let mapper =
             MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, if x > 5 { 1 } else { 0 })));
         for i in 0u8..=5 {
-            assert_eq!(mapper.tranform(i), 0);
+            assert_eq!(mapper.transform(i), 0);
         }
         for i in 6u8..=u8::MAX {
-            assert_eq!(mapper.tranform(i), 1);
+            assert_eq!(mapper.transform(i), 1);
         }
     }
 
@@ -418,10 +1644,10 @@ mod tests {
         let mapper =
             MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, if x < 5 { 1 } else { 0 })));
         for i in 0u8..=4 {
-            assert_eq!(mapper.tranform(i), 1);
+            assert_eq!(mapper.transform(i), 1);
         }
         for i in 5u8..=u8::MAX {
-            assert_eq!(mapper.tranform(i), 0);
+            assert_eq!(mapper.transform(i), 0);
         }
     }
 
@@ -430,7 +1656,7 @@ mod tests {
         let mapper =
             MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, x.wrapping_add(1))));
         for i in 0u8..=u8::MAX {
-            assert_eq!(mapper.tranform(i), i.wrapping_add(1));
+            assert_eq!(mapper.transform(i), i.wrapping_add(1));
         }
     }
 
@@ -439,7 +1665,7 @@ mod tests {
         let mapper =
             MapperNode::new().with_mapdata((0u8..=u8::MAX).map(|x| (x, x.wrapping_mul(2))));
         for i in 0u8..=u8::MAX {
-            assert_eq!(mapper.tranform(i), i.wrapping_mul(2));
+            assert_eq!(mapper.transform(i), i.wrapping_mul(2));
         }
     }
 
@@ -451,7 +1677,7 @@ mod tests {
         }));
         for i in 0u8..=u8::MAX {
             let expected = ((i as u16 * i as u16) % 256) as u8;
-            assert_eq!(mapper.tranform(i), expected);
+            assert_eq!(mapper.transform(i), expected);
         }
     }
 }
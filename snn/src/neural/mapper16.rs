@@ -0,0 +1,306 @@
+/// 16-bit mapper for signals that need more resolution than a byte.
+use alloc::{boxed::Box, vec};
+
+use crate::neural::mapper::MapperNode;
+use crate::neural::traits::WideMapper;
+
+/// The length of the mapping table (65536 for all possible u16 values).
+const LENGTH: usize = u16::MAX as usize + 1;
+
+/// A mapper from u16 to u16 using a lookup table, with the builder methods of `MapperNode`.
+/// The 128 KiB table lives on the heap, so cloning a mapper allocates.
+#[derive(PartialEq, Eq)]
+pub struct MapperNode16 {
+    /// The transformation table: maps each u16 value to another u16 value.
+    tf: Box<[u16; LENGTH]>,
+}
+
+/// Allocates a table filled with `value` directly on the heap.
+fn filled_table(value: u16) -> Box<[u16; LENGTH]> {
+    vec![value; LENGTH]
+        .into_boxed_slice()
+        .try_into()
+        .expect("table has LENGTH entries")
+}
+
+/// Copies a table into a new heap allocation without passing it through the stack.
+fn copied_table(table: &[u16; LENGTH]) -> Box<[u16; LENGTH]> {
+    Box::<[u16]>::from(&table[..])
+        .try_into()
+        .expect("table has LENGTH entries")
+}
+
+impl MapperNode16 {
+    /// Creates a new `MapperNode16` with all values initialized to 0.
+    pub fn new() -> MapperNode16 {
+        MapperNode16 {
+            tf: filled_table(0),
+        }
+    }
+
+    /// Creates a new `MapperNode16` from a heap-allocated mapping table.
+    pub fn new_from(data: Box<[u16; LENGTH]>) -> MapperNode16 {
+        MapperNode16 { tf: data }
+    }
+
+    /// Creates a new `MapperNode16` using a transformation function of the input index.
+    pub fn new_transformation(transfn: impl Fn(usize) -> u16) -> MapperNode16 {
+        MapperNode16::new().with_modification(|i, _| transfn(i))
+    }
+
+    /// Creates a mapper that applies `mapper` to the low byte of the input and keeps the high
+    /// byte, for signals that pack a byte-sized value below a tag.
+    pub fn from_low_byte(mapper: &MapperNode) -> MapperNode16 {
+        MapperNode16::new_transformation(|x| (x as u16 & 0xFF00) | mapper.transform(x as u8) as u16)
+    }
+
+    /// Creates a mapper that applies `mapper` to the high byte of the input and scales its
+    /// output to the full range, so 0 maps to 0 and 255 to 65535. The low byte of the input is
+    /// ignored, making the result a step function.
+    pub fn from_scaled(mapper: &MapperNode) -> MapperNode16 {
+        MapperNode16::new_transformation(|x| mapper.transform((x >> 8) as u8) as u16 * 257)
+    }
+
+    /// Transforms an input value using the mapping table.
+    pub fn transform(&self, input: u16) -> u16 {
+        self.tf[input as usize]
+    }
+
+    /// Returns the mapping table, where entry `i` is the output for input `i`.
+    pub fn table(&self) -> &[u16; LENGTH] {
+        &self.tf
+    }
+
+    /// Returns true if every input maps to itself.
+    pub fn is_identity(&self) -> bool {
+        self.tf.iter().enumerate().all(|(i, &d)| i == d as usize)
+    }
+
+    /// Returns the length of the mapping table (always 65536).
+    pub fn len(&self) -> usize {
+        self.tf.len()
+    }
+
+    /// Returns whether the mapping table is empty (never, as it always holds 65536 entries).
+    pub fn is_empty(&self) -> bool {
+        self.tf.is_empty()
+    }
+
+    /// Transforms every value of `data` in place.
+    pub fn transform_in_place(&self, data: &mut [u16]) {
+        for d in data.iter_mut() {
+            *d = self.tf[*d as usize];
+        }
+    }
+
+    /// Returns a new `MapperNode16` with every entry set to the given value.
+    pub fn with_fill(&self, value: u16) -> MapperNode16 {
+        MapperNode16::new_from(filled_table(value))
+    }
+
+    /// Returns a new `MapperNode16` with the given key-value pairs updated in the mapping table.
+    pub fn with_mapdata<I>(&self, mapdata: I) -> MapperNode16
+    where
+        I: IntoIterator<Item = (u16, u16)>,
+    {
+        let mut data = copied_table(&self.tf);
+        for (k, v) in mapdata {
+            data[k as usize] = v;
+        }
+        MapperNode16::new_from(data)
+    }
+
+    /// Returns a new `MapperNode16` with all values in the given range set to the specified value.
+    pub fn with_range<R>(&self, range: R, value: u16) -> MapperNode16
+    where
+        R: IntoIterator<Item = u16>,
+    {
+        self.with_mapdata(range.into_iter().map(|k| (k, value)))
+    }
+
+    /// Returns a new `MapperNode16` with a modification function applied to each value in the
+    /// mapping table.
+    pub fn with_modification(&self, modification: impl Fn(usize, u16) -> u16) -> MapperNode16 {
+        let mut data = copied_table(&self.tf);
+        for (i, d) in data.iter_mut().enumerate() {
+            *d = modification(i, *d);
+        }
+        MapperNode16::new_from(data)
+    }
+
+    /// Returns a new `MapperNode16` that applies this mapper and then `next`.
+    pub fn compose(&self, next: &MapperNode16) -> MapperNode16 {
+        self.with_modification(|_, d| next.tf[d as usize])
+    }
+
+    /// Returns a new `MapperNode16` with each value inverted (i.e., `65535 - value`).
+    pub fn invert(&self) -> MapperNode16 {
+        self.with_modification(|_, d| u16::MAX - d)
+    }
+
+    /// Returns a new `MapperNode16` with each value bitwise negated.
+    pub fn not(&self) -> MapperNode16 {
+        self.with_modification(|_, d| !d)
+    }
+
+    /// Returns a new `MapperNode16` with each value bitwise ANDed with `v`.
+    pub fn and(&self, v: u16) -> MapperNode16 {
+        self.with_modification(|_, d| d & v)
+    }
+
+    /// Returns a new `MapperNode16` with each value bitwise ORed with `v`.
+    pub fn or(&self, v: u16) -> MapperNode16 {
+        self.with_modification(|_, d| d | v)
+    }
+
+    /// Returns a new `MapperNode16` with each value bitwise XORed with `v`.
+    pub fn xor(&self, v: u16) -> MapperNode16 {
+        self.with_modification(|_, d| d ^ v)
+    }
+
+    /// Returns a new `MapperNode16` with each value bitwise ANDed with the value of `other`.
+    pub fn and_node(&self, other: &MapperNode16) -> MapperNode16 {
+        self.with_modification(|i, d| d & other.tf[i])
+    }
+
+    /// Returns a new `MapperNode16` with each value bitwise ORed with the value of `other`.
+    pub fn or_node(&self, other: &MapperNode16) -> MapperNode16 {
+        self.with_modification(|i, d| d | other.tf[i])
+    }
+
+    /// Returns a new `MapperNode16` with each value bitwise XORed with the value of `other`.
+    pub fn xor_node(&self, other: &MapperNode16) -> MapperNode16 {
+        self.with_modification(|i, d| d ^ other.tf[i])
+    }
+}
+
+impl Clone for MapperNode16 {
+    fn clone(&self) -> Self {
+        MapperNode16::new_from(copied_table(&self.tf))
+    }
+}
+
+impl Default for MapperNode16 {
+    fn default() -> Self {
+        MapperNode16::new()
+    }
+}
+
+impl WideMapper for MapperNode16 {
+    fn transform(&self, input: u16) -> u16 {
+        MapperNode16::transform(self, input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn identity() -> MapperNode16 {
+        MapperNode16::new_transformation(|x| x as u16)
+    }
+
+    #[test]
+    fn test_new_and_len() {
+        let mapper = MapperNode16::new();
+        assert_eq!(mapper.len(), 65536);
+        assert!(!mapper.is_empty());
+        assert!(mapper.table().iter().all(|&v| v == 0));
+        assert!(identity().is_identity());
+        assert!(!mapper.is_identity());
+    }
+
+    #[test]
+    fn test_with_mapdata_and_range() {
+        let mapper = MapperNode16::new()
+            .with_range(1000..=2000, 7)
+            .with_mapdata([(1500, 9), (u16::MAX, 1)]);
+        assert_eq!(mapper.transform(999), 0);
+        assert_eq!(mapper.transform(1000), 7);
+        assert_eq!(mapper.transform(1500), 9);
+        assert_eq!(mapper.transform(2000), 7);
+        assert_eq!(mapper.transform(2001), 0);
+        assert_eq!(mapper.transform(u16::MAX), 1);
+        assert!(
+            MapperNode16::new()
+                .with_fill(3)
+                .table()
+                .iter()
+                .all(|&v| v == 3)
+        );
+    }
+
+    #[test]
+    fn test_bitwise_and_invert() {
+        let id = identity();
+        for i in (0..=u16::MAX).step_by(97).chain([u16::MAX]) {
+            assert_eq!(id.invert().transform(i), u16::MAX - i);
+            assert_eq!(id.not().transform(i), !i);
+            assert_eq!(id.and(0xF0F0).transform(i), i & 0xF0F0);
+            assert_eq!(id.or(0x0101).transform(i), i | 0x0101);
+            assert_eq!(id.xor(0x5555).transform(i), i ^ 0x5555);
+        }
+        let other = MapperNode16::new().with_fill(0xAAAA);
+        assert_eq!(id.and_node(&other).transform(0xFFFF), 0xAAAA);
+        assert_eq!(id.or_node(&other).transform(0x5555), 0xFFFF);
+        assert_eq!(id.xor_node(&other).transform(0xAAAA), 0);
+    }
+
+    #[test]
+    fn test_compose() {
+        let double = MapperNode16::new_transformation(|x| (x as u16).wrapping_mul(2));
+        let plus_one = MapperNode16::new_transformation(|x| (x as u16).wrapping_add(1));
+        let composed = double.compose(&plus_one);
+        assert_eq!(composed.transform(10), 21);
+        assert_eq!(plus_one.compose(&double).transform(10), 22);
+        assert!(identity().compose(&identity()).is_identity());
+        let copy = composed.clone();
+        assert!(copy == composed);
+    }
+
+    #[test]
+    fn test_lift_from_mapper_node() {
+        let upper = MapperNode::new_transformation(|x| (x as u8).to_ascii_uppercase());
+        let low = MapperNode16::from_low_byte(&upper);
+        assert_eq!(low.transform(0x0161), 0x0141);
+        assert_eq!(low.transform(0xFF7A), 0xFF5A);
+        assert!(
+            MapperNode16::from_low_byte(&MapperNode::new_transformation(|x| x as u8)).is_identity()
+        );
+
+        let scaled = MapperNode16::from_scaled(&MapperNode::new_transformation(|x| x as u8));
+        assert_eq!(scaled.transform(0), 0);
+        assert_eq!(scaled.transform(0x12FF), 0x1212);
+        assert_eq!(scaled.transform(u16::MAX), u16::MAX);
+    }
+
+    #[test]
+    fn test_transform_in_place_and_trait() {
+        let mapper = identity().xor(1);
+        let mut data: Vec<u16> = vec![0, 1, 40000];
+        mapper.transform_in_place(&mut data);
+        assert_eq!(data, [1, 0, 40001]);
+        let wide: &dyn WideMapper = &mapper;
+        let mut out = [0u16; 3];
+        wide.transform_slice(&data, &mut out);
+        assert_eq!(out, [0, 1, 40000]);
+        assert_eq!(wide.transform(7), 6);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_table_stays_off_the_stack() {
+        // The table is twice this stack size, so copying it through the stack would overflow.
+        let built = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(|| {
+                let mapper = identity().xor(3).clone().compose(&identity());
+                mapper.transform(1)
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(built, 2);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::neural::combinator_stats::round_to_u8;
 use crate::neural::mapper::MapperNode;
 
 /// Creates a MapperNode that maps lowercase ASCII letters (a-z) to a fixed value (0b1111_1111).
@@ -32,22 +33,48 @@ pub fn create_mapper_for_letters_cased() -> MapperNode {
 /// - Digits (0-9) are mapped to 0b0000_0001.
 /// - Lowercase letters (a-z) are mapped to 0b0000_0010.
 /// - Uppercase letters (A-Z) are mapped to 0b0000_0100.
-/// - Printable ASCII characters (excluding control characters) are mapped to 0b0000_1000.
-/// - Control characters (space, tab, newline) are mapped to 0b0001_0000.
+/// - Other printable ASCII characters (excluding control characters) are mapped to 0b0000_1000.
+/// - Space, tab, newline and carriage return are mapped to 0b0001_0000.
 pub fn create_mapper_for_character() -> MapperNode {
     MapperNode::new()
         .with_range(b'0'..=b'9', 0b0000_0001)
         .with_range(b'a'..=b'z', 0b0000_0010)
         .with_range(b'A'..=b'Z', 0b0000_0100)
-        .with_range(b'!'..=b'~', 0b0000_1000)
-        .with_range(b' '..=b'\n', 0b0001_0000)
+        .with_range_if_unset(b'!'..=b'~', 0b0000_1000)
+        .with_mapdata([b' ', b'\t', b'\n', b'\r'].map(|c| (c, 0b0001_0000)))
+}
+
+/// Creates a MapperNode that maps ASCII whitespace (space, tab, newline, form feed and
+/// carriage return) to 0b1111_1111 and everything else to 0.
+pub fn create_mapper_for_whitespace() -> MapperNode {
+    MapperNode::new().with_mapdata([b' ', b'\t', b'\n', 0x0C, b'\r'].map(|c| (c, 0b1111_1111)))
 }
 
 /// Creates a MapperNode that maps the outside numbers as low as possible. and middle numbers as high as possible.
+/// Inputs map to their distance from the nearer end, so the output only reaches 127, at
+/// inputs 127 and 128; use `create_mapper_pyramid_at(128)` for a triangle reaching 255.
 pub fn create_mapper_pyramid() -> MapperNode {
     MapperNode::new_transformation(|x| {
         let y = x as u8;
-        if y < 128 { return y } else { 255 - y }
+        if y < 128 { y } else { 255 - y }
+    })
+}
+
+/// Creates a triangle that rises linearly from 0 at input 0 to 255 at input `peak` and falls
+/// linearly back to 0 at input 255, each side rounded to the nearest value.
+/// A `peak` of 0 gives a falling ramp `255 - x` and a `peak` of 255 a rising ramp `x`.
+pub fn create_mapper_pyramid_at(peak: u8) -> MapperNode {
+    let peak = peak as usize;
+    MapperNode::new_transformation(|x| {
+        let (distance, span) = if x <= peak {
+            (x, peak)
+        } else {
+            (255 - x, 255 - peak)
+        };
+        match span {
+            0 => 255,
+            _ => ((255 * distance + span / 2) / span) as u8,
+        }
     })
 }
 
@@ -55,6 +82,962 @@ pub fn create_mapper_pyramid() -> MapperNode {
 pub fn create_mapper_pyramid_inverted() -> MapperNode {
     MapperNode::new_transformation(|x| {
         let y = x as u8;
-        if y < 128 { return 255 - y } else { y }
+        if y < 128 { 255 - y } else { y }
+    })
+}
+
+/// Creates a MapperNode that maps ASCII uppercase letters (A-Z) to lowercase and every other
+/// byte, including 128-255, to itself.
+pub fn create_mapper_to_lowercase() -> MapperNode {
+    MapperNode::new_transformation(|x| (x as u8).to_ascii_lowercase())
+}
+
+/// Creates a MapperNode that maps ASCII lowercase letters (a-z) to uppercase and every other
+/// byte, including 128-255, to itself.
+pub fn create_mapper_to_uppercase() -> MapperNode {
+    MapperNode::new_transformation(|x| (x as u8).to_ascii_uppercase())
+}
+
+/// Creates a MapperNode like `create_mapper_for_lowercase` that also maps the Latin-1
+/// lowercase letters (0xDF-0xFF except ÷ 0xF7) to 0b1111_1111.
+/// The ordinal indicators ª and º and the micro sign µ are not included.
+pub fn create_mapper_for_lowercase_latin1() -> MapperNode {
+    create_mapper_for_lowercase()
+        .with_range(0xDF..=0xFF, 0b1111_1111)
+        .with_mapdata([(0xF7, 0)])
+}
+
+/// Creates a MapperNode like `create_mapper_for_uppercase` that also maps the Latin-1
+/// uppercase letters (0xC0-0xDE except × 0xD7) to 0b1111_1111.
+pub fn create_mapper_for_uppercase_latin1() -> MapperNode {
+    create_mapper_for_uppercase()
+        .with_range(0xC0..=0xDE, 0b1111_1111)
+        .with_mapdata([(0xD7, 0)])
+}
+
+/// Creates a MapperNode like `create_mapper_for_letters` that also maps the Latin-1 letters
+/// (0xC0-0xFF except × 0xD7 and ÷ 0xF7) to 0b1111_1111.
+pub fn create_mapper_for_letters_latin1() -> MapperNode {
+    create_mapper_for_letters()
+        .with_range(0xC0..=0xFF, 0b1111_1111)
+        .with_mapdata([(0xD7, 0), (0xF7, 0)])
+}
+
+/// Creates a MapperNode that maps ASCII and Latin-1 uppercase letters to their lowercase
+/// forms (0xC0-0xDE except × 0xD7 map to 0xE0-0xFE) and every other byte to itself.
+/// ß and ÿ have no single-byte uppercase form and map to themselves.
+pub fn create_mapper_latin1_to_lower() -> MapperNode {
+    create_mapper_to_lowercase().with_modification(|x, y| match x {
+        0xC0..=0xDE if x != 0xD7 => x as u8 + 0x20,
+        _ => y,
+    })
+}
+
+/// Creates a MapperNode that applies ROT13 to ASCII letters. It is its own inverse.
+pub fn create_mapper_rot13() -> MapperNode {
+    create_mapper_caesar(13)
+}
+
+/// Creates a MapperNode that shifts ASCII letters `shift` places forward in the alphabet,
+/// wrapping from z back to a and keeping case. Every other byte maps to itself.
+/// Shifts of 26 and more wrap around, so `create_mapper_caesar(26 - k)` undoes a shift of `k`.
+pub fn create_mapper_caesar(shift: u8) -> MapperNode {
+    let shift = shift % 26;
+    MapperNode::new_transformation(|x| {
+        let x = x as u8;
+        let base = match x {
+            b'a'..=b'z' => b'a',
+            b'A'..=b'Z' => b'A',
+            _ => return x,
+        };
+        base + (x - base + shift) % 26
+    })
+}
+
+/// The value the decoder tables map bytes outside their alphabet to.
+pub const INVALID_VALUE: u8 = 0xFF;
+
+/// The value `create_mapper_base64_value` maps the padding character `=` to.
+pub const BASE64_PADDING: u8 = 0xFE;
+
+/// The standard base64 alphabet, indexed by value.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Creates a MapperNode that decodes hex digits: '0'-'9' map to 0-9, and 'a'-'f' and 'A'-'F'
+/// to 10-15. Every other byte maps to `INVALID_VALUE`.
+pub fn create_mapper_hex_value() -> MapperNode {
+    MapperNode::new_transformation(|x| match x as u8 {
+        c @ b'0'..=b'9' => c - b'0',
+        c @ b'a'..=b'f' => c - b'a' + 10,
+        c @ b'A'..=b'F' => c - b'A' + 10,
+        _ => INVALID_VALUE,
+    })
+}
+
+/// Creates a MapperNode that encodes the low nibble of a byte as a lowercase hex digit,
+/// so 0-15 map to '0'-'9' and 'a'-'f'. Undone by `create_mapper_hex_value`.
+pub fn create_mapper_hex_digit_lower() -> MapperNode {
+    MapperNode::new_transformation(|x| b"0123456789abcdef"[x & 0xF])
+}
+
+/// Creates a MapperNode that encodes the low nibble of a byte as an uppercase hex digit,
+/// so 0-15 map to '0'-'9' and 'A'-'F'. Undone by `create_mapper_hex_value`.
+pub fn create_mapper_hex_digit_upper() -> MapperNode {
+    MapperNode::new_transformation(|x| b"0123456789ABCDEF"[x & 0xF])
+}
+
+/// Creates a MapperNode that decodes decimal digits: '0'-'9' map to 0-9 and every other byte
+/// to `INVALID_VALUE`.
+pub fn create_mapper_digit_value() -> MapperNode {
+    MapperNode::new_transformation(|x| match x as u8 {
+        c @ b'0'..=b'9' => c - b'0',
+        _ => INVALID_VALUE,
+    })
+}
+
+/// Creates a MapperNode that encodes 0-9 as the digits '0'-'9' and every other byte as
+/// `INVALID_VALUE`. Undone by `create_mapper_digit_value`.
+pub fn create_mapper_digit_char() -> MapperNode {
+    MapperNode::new_transformation(|x| {
+        if x < 10 {
+            b'0' + x as u8
+        } else {
+            INVALID_VALUE
+        }
+    })
+}
+
+/// Creates a MapperNode that decodes the standard base64 alphabet: 'A'-'Z' map to 0-25,
+/// 'a'-'z' to 26-51, '0'-'9' to 52-61, '+' to 62 and '/' to 63. The padding character '='
+/// maps to `BASE64_PADDING` and every other byte to `INVALID_VALUE`.
+pub fn create_mapper_base64_value() -> MapperNode {
+    let mut mapper = MapperNode::new().with_fill(INVALID_VALUE);
+    for (value, &c) in BASE64_ALPHABET.iter().enumerate() {
+        mapper = mapper.with_mapdata([(c, value as u8)]);
+    }
+    mapper.with_mapdata([(b'=', BASE64_PADDING)])
+}
+
+/// Creates a MapperNode that encodes the low six bits of a byte as a character of the standard
+/// base64 alphabet, so 0-63 map to 'A'-'Z', 'a'-'z', '0'-'9', '+' and '/'.
+/// Undone by `create_mapper_base64_value`.
+pub fn create_mapper_base64_char() -> MapperNode {
+    MapperNode::new_transformation(|x| BASE64_ALPHABET[x & 0x3F])
+}
+
+/// Creates a MapperNode that converts binary to reflected Gray code, `x ^ (x >> 1)`.
+/// Consecutive inputs map to outputs that differ in exactly one bit.
+pub fn create_mapper_gray_encode() -> MapperNode {
+    MapperNode::new_transformation(|x| (x ^ (x >> 1)) as u8)
+}
+
+/// Creates a MapperNode that converts reflected Gray code back to binary, undoing
+/// `create_mapper_gray_encode`.
+pub fn create_mapper_gray_decode() -> MapperNode {
+    MapperNode::new_transformation(|x| {
+        let mut binary = x;
+        let mut shifted = x >> 1;
+        while shifted != 0 {
+            binary ^= shifted;
+            shifted >>= 1;
+        }
+        binary as u8
+    })
+}
+
+/// Creates a MapperNode that reverses the bit order of each byte, so bit 0 becomes bit 7.
+/// It is its own inverse.
+pub fn create_mapper_bit_reverse() -> MapperNode {
+    MapperNode::new_transformation(|x| (x as u8).reverse_bits())
+}
+
+/// Creates a MapperNode that outputs 255 when bit `bit` of the input is set and 0 otherwise,
+/// undoing one bit of a `BitPackCombinatorNode`.
+///
+/// # Panics
+/// Panics if `bit` is 8 or more.
+pub fn create_mapper_unpack_bit(bit: u8) -> MapperNode {
+    MapperNode::new_transformation(|x| x as u8).extract_bit(bit)
+}
+
+/// Creates the 8 `create_mapper_unpack_bit` mappers, for bits 0 to 7.
+pub fn create_mappers_unpack_bits() -> [MapperNode; 8] {
+    core::array::from_fn(|bit| create_mapper_unpack_bit(bit as u8))
+}
+
+/// Creates a compressive curve: `x` maps to `255 * log2(1 + x) / 8`, rounded, so 0 maps to 0,
+/// 1 to 32 and 255 to 255.
+///
+/// The log, exponential and gamma tables use only integer arithmetic, so they are the same on
+/// every platform: `log2` in 16.16 fixed point by repeated squaring, truncating, and `2^t` in
+/// 32.32 fixed point as a product of the constants `2^(2^-k)`, truncating after each product.
+/// Divisions by the scale round half up.
+pub fn create_mapper_log2() -> MapperNode {
+    MapperNode::new_transformation(|x| {
+        let log = log2_fixed(x as u64 + 1) as u64;
+        ((255 * log + (1 << 18)) >> 19) as u8
+    })
+}
+
+/// Creates an expanding curve, the approximate inverse of `create_mapper_log2`:
+/// `y` maps to `2^(8 * y / 255) - 1`, with the exponent rounded to 16.16 fixed point and the
+/// result rounded. 0 maps to 0 and 255 to 255.
+pub fn create_mapper_exp() -> MapperNode {
+    MapperNode::new_transformation(|y| {
+        let exponent = ((y as i64) << 19) + 127;
+        let power = exp2_fixed(exponent / 255);
+        (((power + (1 << 15)) >> 16) - 1).min(255) as u8
+    })
+}
+
+/// Creates a power-law curve: `x` maps to `255 * (x / 255)^(gamma_num / gamma_den)`, rounded.
+/// The exponent is applied in the log domain, `2^(log2(x / 255) * gamma_num / gamma_den)`,
+/// with the product rounded toward zero. Exponents above 1 darken the middle of the range
+/// and exponents below 1 brighten it; 0 and 255 always map to themselves, except that an
+/// exponent of 0 maps everything to 255. A `gamma_den` of 0 is treated as 1.
+pub fn create_mapper_gamma(gamma_num: u8, gamma_den: u8) -> MapperNode {
+    let (num, den) = (gamma_num as i64, gamma_den.max(1) as i64);
+    let full = log2_fixed(255);
+    MapperNode::new_transformation(|x| {
+        if x == 0 && num > 0 {
+            return 0;
+        }
+        let exponent = (log2_fixed(x.max(1) as u64) - full) * num / den;
+        ((255 * exp2_fixed(exponent) + (1 << 15)) >> 16).min(255) as u8
+    })
+}
+
+/// Returns `log2(n)` for `n >= 1` in 16.16 fixed point, truncated.
+fn log2_fixed(n: u64) -> i64 {
+    let whole = 63 - n.leading_zeros() as i64;
+    // The mantissa n / 2^whole in [1, 2), as 2.62 fixed point.
+    let mut mantissa = (n as u128) << (62 - whole);
+    let mut fraction = 0;
+    for bit in (0..16).rev() {
+        mantissa = (mantissa * mantissa) >> 62;
+        if mantissa >= 2 << 62 {
+            mantissa >>= 1;
+            fraction |= 1 << bit;
+        }
+    }
+    (whole << 16) | fraction
+}
+
+/// `2^(2^-k)` for k = 1 to 16 in 32.32 fixed point, rounded to nearest.
+const ROOTS_OF_TWO: [u64; 16] = [
+    0x1_6a09_e668,
+    0x1_306f_e0a3,
+    0x1_172b_83c8,
+    0x1_0b55_86d0,
+    0x1_059b_0d31,
+    0x1_02c9_a3e7,
+    0x1_0163_daa0,
+    0x1_00b1_afa6,
+    0x1_0058_c86e,
+    0x1_002c_605e,
+    0x1_0016_2f39,
+    0x1_000b_175f,
+    0x1_0005_8ba0,
+    0x1_0002_c5cc,
+    0x1_0001_62e5,
+    0x1_0000_b172,
+];
+
+/// Returns `2^t` for `t` in 16.16 fixed point as 16.16 fixed point, truncated.
+/// Results below 2^-16 are 0.
+pub(crate) fn exp2_fixed(t: i64) -> u64 {
+    let (whole, fraction) = (t >> 16, t & 0xFFFF);
+    let mut power: u64 = 1 << 32;
+    for (k, root) in ROOTS_OF_TWO.iter().enumerate() {
+        if fraction & (1 << (15 - k)) != 0 {
+            power = ((power as u128 * *root as u128) >> 32) as u64;
+        }
+    }
+    match whole - 16 {
+        shift if shift >= 0 => power << shift,
+        shift if shift > -64 => power >> -shift,
+        _ => 0,
+    }
+}
+
+/// Creates a logistic curve: `255 / (1 + e^(-(x - midpoint) * steepness / 32))`, rounded.
+/// Inputs below `midpoint` map toward 0 and inputs above it toward 255; `steepness` is the
+/// slope in 32nds, so 32 spans the whole output range within about 10 inputs of the midpoint
+/// and 0 maps every input to 128.
+pub fn create_mapper_sigmoid(midpoint: u8, steepness: u8) -> MapperNode {
+    MapperNode::new_transformation(|x| {
+        let z = (x as f64 - midpoint as f64) * steepness as f64 / 32.0;
+        round_to_u8(255.0 / (1.0 + exp(-z)))
+    })
+}
+
+/// Creates a rectifier: inputs below `threshold` map to 0, the rest map to themselves.
+pub fn create_mapper_relu(threshold: u8) -> MapperNode {
+    MapperNode::new_transformation(|x| if x >= threshold as usize { x as u8 } else { 0 })
+}
+
+/// Creates a leaky rectifier: inputs from `threshold` up map to themselves and inputs below
+/// it to `x * leak_num / leak_den`, rounded down and saturated at 255.
+/// A `leak_den` of 0 gives no leak, the same as `create_mapper_relu`.
+pub fn create_mapper_leaky_relu(threshold: u8, leak_num: u8, leak_den: u8) -> MapperNode {
+    MapperNode::new_transformation(|x| {
+        if x >= threshold as usize {
+            x as u8
+        } else {
+            (x * leak_num as usize)
+                .checked_div(leak_den as usize)
+                .map_or(0, |y| y.min(255) as u8)
+        }
+    })
+}
+
+/// Creates a tanh curve centered on the middle of the range:
+/// `127.5 + 127.5 * tanh((x - 127.5) / 32)`, rounded. 0 maps to 0, 255 to 255, and the
+/// steepest part is around 128.
+pub fn create_mapper_tanh_like() -> MapperNode {
+    MapperNode::new_transformation(|x| {
+        let z = (x as f64 - 127.5) / 32.0;
+        let tanh = 1.0 - 2.0 / (exp(2.0 * z) + 1.0);
+        round_to_u8(127.5 + 127.5 * tanh)
+    })
+}
+
+/// Creates a bell curve: `255 * e^(-((x - center) / width)^2 / 2)`, rounded.
+/// `center` maps to 255 and inputs further away fall toward 0, symmetrically on both sides;
+/// `width` is the standard deviation. A `width` of 0 maps only `center` to 255.
+pub fn create_mapper_gaussian(center: u8, width: u8) -> MapperNode {
+    MapperNode::new_transformation(|x| {
+        if width == 0 {
+            return if x == center as usize { 255 } else { 0 };
+        }
+        let z = (x as f64 - center as f64) / width as f64;
+        round_to_u8(255.0 * exp(-z * z / 2.0))
+    })
+}
+
+/// Creates one period of a sine wave: phase `x` maps to `255 * (1 + sin(2π * x / 256)) / 2`.
+/// Phase 0 and 128 map to 128, 64 to 255 and 192 to 0.
+///
+/// The waveform tables round to the nearest value, halves rounding up.
+pub fn create_mapper_sine() -> MapperNode {
+    create_mapper_sine_scaled(255, 0)
+}
+
+/// Creates one period of a triangle wave: 0 at phase 0, rising linearly to 255 at phase 128
+/// and falling back toward 0, as `255 * (1 - |x - 128| / 128)`, rounded.
+pub fn create_mapper_triangle() -> MapperNode {
+    create_mapper_triangle_scaled(255, 0)
+}
+
+/// Creates one period of a sawtooth wave: rising linearly from 0 at phase 0 to 255 at phase 255.
+pub fn create_mapper_sawtooth() -> MapperNode {
+    create_mapper_sawtooth_scaled(255, 0)
+}
+
+/// Creates one period of a square wave: 255 for the first `duty` phases, then 0.
+/// A `duty` of 128 gives a 50% duty cycle.
+pub fn create_mapper_square(duty: u8) -> MapperNode {
+    create_mapper_square_scaled(duty, 255, 0)
+}
+
+/// Like `create_mapper_sine`, but swinging `amplitude` from peak to peak above `offset`:
+/// `offset + amplitude * (1 + sin(2π * x / 256)) / 2`, rounded and saturated at 255.
+pub fn create_mapper_sine_scaled(amplitude: u8, offset: u8) -> MapperNode {
+    waveform(amplitude, offset, |x| (1.0 + sin_256ths(x)) / 2.0)
+}
+
+/// Like `create_mapper_triangle`, but as `offset + amplitude * (1 - |x - 128| / 128)`,
+/// rounded and saturated at 255.
+pub fn create_mapper_triangle_scaled(amplitude: u8, offset: u8) -> MapperNode {
+    waveform(amplitude, offset, |x| {
+        1.0 - (x as f64 - 128.0).abs() / 128.0
     })
 }
+
+/// Like `create_mapper_sawtooth`, but as `offset + amplitude * x / 255`, rounded and saturated
+/// at 255.
+pub fn create_mapper_sawtooth_scaled(amplitude: u8, offset: u8) -> MapperNode {
+    waveform(amplitude, offset, |x| x as f64 / 255.0)
+}
+
+/// Like `create_mapper_square`, but switching between `offset` and `offset + amplitude`,
+/// saturated at 255.
+pub fn create_mapper_square_scaled(duty: u8, amplitude: u8, offset: u8) -> MapperNode {
+    waveform(
+        amplitude,
+        offset,
+        |x| if x < duty as usize { 1.0 } else { 0.0 },
+    )
+}
+
+/// Builds `offset + amplitude * wave(x)` for a `wave` between 0 and 1, rounded and saturated.
+fn waveform(amplitude: u8, offset: u8, wave: impl Fn(usize) -> f64) -> MapperNode {
+    MapperNode::new_transformation(|x| round_to_u8(offset as f64 + amplitude as f64 * wave(x)))
+}
+
+/// Returns `sin(2π * x / 256)` using only `core` arithmetic. The phase is folded into the
+/// first quarter first, so the zeros and peaks at multiples of 64 are exact.
+fn sin_256ths(x: usize) -> f64 {
+    let (quarter, step) = (x / 64 % 4, x % 64);
+    let step = if quarter % 2 == 0 { step } else { 64 - step };
+    let t = step as f64 * core::f64::consts::FRAC_PI_2 / 64.0;
+    // Taylor series; with t <= π/2 ten terms are well past f64 precision.
+    let (mut sum, mut term) = (t, t);
+    for n in 1..10 {
+        term *= -t * t / ((2 * n) * (2 * n + 1)) as f64;
+        sum += term;
+    }
+    if quarter < 2 { sum } else { -sum }
+}
+
+/// Returns `e^x` using only `core` arithmetic, accurate to a few units in the last place,
+/// which is far finer than the `u8` tables built from it need.
+fn exp(x: f64) -> f64 {
+    if x < -700.0 {
+        return 0.0;
+    }
+    if x > 700.0 {
+        return f64::INFINITY;
+    }
+    // x = k * ln 2 + r with |r| <= ln 2 / 2, so e^x = 2^k * e^r.
+    let k = (x * core::f64::consts::LOG2_E + if x < 0.0 { -0.5 } else { 0.5 }) as i64;
+    // ln 2 split into a high part with trailing zero bits, so `k * LN2_HI` is exact.
+    const LN2_HI: f64 = f64::from_bits(0x3fe6_2e42_fee0_0000);
+    const LN2_LO: f64 = f64::from_bits(0x3dea_39ef_3579_3c76);
+    let r = (x - k as f64 * LN2_HI) - k as f64 * LN2_LO;
+    let (mut sum, mut term) = (1.0, 1.0);
+    for n in 1..20 {
+        term *= r / n as f64;
+        sum += term;
+    }
+    sum * f64::from_bits(((k + 1023) as u64) << 52)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::mapper_ext::MapperExt;
+
+    #[test]
+    fn test_character_classes() {
+        let mapper = create_mapper_for_character();
+        assert_eq!(mapper.transform(b'3'), 0b0000_0001);
+        assert_eq!(mapper.transform(b'a'), 0b0000_0010);
+        assert_eq!(mapper.transform(b'Q'), 0b0000_0100);
+        assert_eq!(mapper.transform(b'$'), 0b0000_1000);
+        assert_eq!(mapper.transform(b'~'), 0b0000_1000);
+    }
+
+    #[test]
+    fn test_character_whitespace() {
+        let mapper = create_mapper_for_character();
+        for c in [b' ', b'\t', b'\n', b'\r'] {
+            assert_eq!(mapper.transform(c), 0b0001_0000, "{c}");
+        }
+        assert_eq!(mapper.transform(b'a') & 0b0001_0000, 0);
+        assert_eq!(mapper.transform(0x0B), 0);
+    }
+
+    #[test]
+    fn test_whitespace() {
+        let mapper = create_mapper_for_whitespace();
+        for c in 0..=255u8 {
+            let expected = if c.is_ascii_whitespace() {
+                0b1111_1111
+            } else {
+                0
+            };
+            assert_eq!(mapper.transform(c), expected, "{c}");
+        }
+    }
+
+    #[test]
+    fn test_exp_matches_std() {
+        for i in -2000..=2000 {
+            let x = i as f64 / 7.0;
+            let (actual, expected) = (exp(x), x.exp());
+            assert!((actual - expected).abs() <= expected * 1e-14, "e^{x}");
+        }
+        assert_eq!(exp(0.0), 1.0);
+        assert_eq!(exp(-800.0), 0.0);
+    }
+
+    #[test]
+    fn test_sigmoid() {
+        let mapper = create_mapper_sigmoid(128, 8);
+        assert!(mapper.is_monotonic_increasing());
+        assert_eq!(mapper.transform(0), 0);
+        assert_eq!(mapper.transform(255), 255);
+        // 255 / (1 + e^0) = 127.5 rounds up; 255 / (1 + e^-2) = 224.62; 255 / (1 + e^2) = 30.38.
+        assert_eq!(mapper.transform(128), 128);
+        assert_eq!(mapper.transform(136), 225);
+        assert_eq!(mapper.transform(120), 30);
+        assert!(create_mapper_sigmoid(40, 255).is_monotonic_increasing());
+        assert!(
+            create_mapper_sigmoid(40, 0)
+                .table()
+                .iter()
+                .all(|&y| y == 128)
+        );
+    }
+
+    #[test]
+    fn test_relu() {
+        let relu = create_mapper_relu(100);
+        assert_eq!(relu.transform(99), 0);
+        assert_eq!(relu.transform(100), 100);
+        assert_eq!(relu.transform(255), 255);
+        assert!(create_mapper_relu(0).is_identity());
+
+        let leaky = create_mapper_leaky_relu(100, 1, 4);
+        assert!(leaky.is_monotonic_increasing());
+        assert_eq!(leaky.transform(99), 24);
+        assert_eq!(leaky.transform(100), 100);
+        assert_eq!(create_mapper_leaky_relu(100, 1, 0).table(), relu.table());
+        assert_eq!(create_mapper_leaky_relu(100, 255, 1).transform(50), 255);
+    }
+
+    #[test]
+    fn test_tanh_like() {
+        let mapper = create_mapper_tanh_like();
+        assert!(mapper.is_monotonic_increasing());
+        assert_eq!(mapper.transform(0), 0);
+        assert_eq!(mapper.transform(255), 255);
+        for x in 0..=255u8 {
+            assert_eq!(mapper.transform(x), 255 - mapper.transform(255 - x), "{x}");
+        }
+        // 127.5 + 127.5 * tanh(32.5 / 32) = 224.55.
+        assert_eq!(mapper.transform(160), 225);
+    }
+
+    #[test]
+    fn test_gaussian() {
+        let mapper = create_mapper_gaussian(100, 20);
+        assert_eq!(mapper.transform(100), 255);
+        for d in 0..=100u8 {
+            assert_eq!(mapper.transform(100 - d), mapper.transform(100 + d), "{d}");
+        }
+        assert!(
+            MapperNode::new_from(core::array::from_fn(|i| mapper.transform(i.min(100) as u8)))
+                .is_monotonic_increasing()
+        );
+        // 255 * e^-0.5 = 154.66.
+        assert_eq!(mapper.transform(120), 155);
+        assert_eq!(mapper.transform(255), 0);
+        let narrow = create_mapper_gaussian(7, 0);
+        assert_eq!(narrow.transform(7), 255);
+        assert_eq!(narrow.transform(8), 0);
+    }
+
+    #[test]
+    fn test_curves_match_float_reference() {
+        let reference = |f: &dyn Fn(f64) -> f64| {
+            MapperNode::new_transformation(|x| f(x as f64).round().clamp(0.0, 255.0) as u8)
+        };
+        assert_eq!(
+            create_mapper_sigmoid(90, 20).table(),
+            reference(&|x| 255.0 / (1.0 + (-(x - 90.0) * 20.0 / 32.0).exp())).table()
+        );
+        assert_eq!(
+            create_mapper_tanh_like().table(),
+            reference(&|x| 127.5 + 127.5 * ((x - 127.5) / 32.0).tanh()).table()
+        );
+        assert_eq!(
+            create_mapper_gaussian(30, 45).table(),
+            reference(&|x| 255.0 * (-((x - 30.0) / 45.0).powi(2) / 2.0).exp()).table()
+        );
+    }
+
+    #[test]
+    fn test_sine() {
+        let sine = create_mapper_sine();
+        assert_eq!(sine.transform(0), 128);
+        assert_eq!(sine.transform(64), 255);
+        assert_eq!(sine.transform(128), 128);
+        assert_eq!(sine.transform(192), 0);
+        for x in 1..64u8 {
+            assert_eq!(sine.transform(x), sine.transform(128 - x), "{x}");
+        }
+        let reference = MapperNode::new_transformation(|x| {
+            let angle = 2.0 * core::f64::consts::PI * x as f64 / 256.0;
+            (255.0 * (1.0 + angle.sin()) / 2.0 + 0.5).floor() as u8
+        });
+        assert_eq!(sine.table(), reference.table());
+    }
+
+    #[test]
+    fn test_triangle_sawtooth() {
+        let triangle = create_mapper_triangle();
+        assert_eq!(triangle.transform(0), 0);
+        assert_eq!(triangle.transform(128), 255);
+        assert_eq!(triangle.table().iter().position(|&y| y == 255), Some(128));
+        assert!(
+            MapperNode::new_from(core::array::from_fn(|i| {
+                triangle.transform(i.min(128) as u8)
+            }))
+            .is_monotonic_increasing()
+        );
+        // 255 * 32 / 128 = 63.75.
+        assert_eq!(triangle.transform(32), 64);
+        assert_eq!(triangle.transform(224), 64);
+
+        assert!(create_mapper_sawtooth().is_identity());
+        // 10 + 100 * 128 / 255 = 60.2.
+        assert_eq!(create_mapper_sawtooth_scaled(100, 10).transform(128), 60);
+    }
+
+    #[test]
+    fn test_square() {
+        let square = create_mapper_square(64);
+        assert_eq!(square.transform(0), 255);
+        assert_eq!(square.transform(63), 255);
+        assert_eq!(square.transform(64), 0);
+        assert_eq!(square.table().iter().filter(|&&y| y == 255).count(), 64);
+        assert!(create_mapper_square(0).table().iter().all(|&y| y == 0));
+        let scaled = create_mapper_square_scaled(128, 50, 20);
+        assert_eq!((scaled.transform(127), scaled.transform(128)), (70, 20));
+    }
+
+    #[test]
+    fn test_scaled_waveforms_stay_in_range() {
+        for mapper in [
+            create_mapper_sine_scaled(100, 200),
+            create_mapper_triangle_scaled(100, 200),
+            create_mapper_sawtooth_scaled(100, 200),
+            create_mapper_square_scaled(128, 100, 200),
+        ] {
+            assert!(mapper.table().iter().all(|&y| y >= 200));
+            assert_eq!(mapper.table().iter().max(), Some(&255));
+        }
+        let sine = create_mapper_sine_scaled(100, 20);
+        assert_eq!(sine.transform(64), 120);
+        assert_eq!(sine.transform(192), 20);
+        assert_eq!(sine.transform(0), 70);
+    }
+
+    #[test]
+    fn test_case_folding() {
+        let lower = create_mapper_to_lowercase();
+        let upper = create_mapper_to_uppercase();
+        for x in 0..=255u8 {
+            assert_eq!(lower.transform(x), x.to_ascii_lowercase());
+            assert_eq!(upper.transform(x), x.to_ascii_uppercase());
+        }
+        assert_eq!(lower.transform_str("Hello, World!"), b"hello, world!");
+        assert!(
+            lower.clone().then(upper.clone()).to_node().table()[b'a' as usize..=b'z' as usize]
+                .iter()
+                .eq(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ")
+        );
+    }
+
+    #[test]
+    fn test_rot13_is_its_own_inverse() {
+        let rot13 = create_mapper_rot13();
+        assert_eq!(rot13.transform_str("Hello, World!"), b"Uryyb, Jbeyq!");
+        assert!(rot13.clone().then(rot13).to_node().is_identity());
+    }
+
+    #[test]
+    fn test_caesar() {
+        let caesar = create_mapper_caesar(3);
+        assert_eq!(caesar.transform_str("xyz ABC"), b"abc DEF");
+        assert_eq!(create_mapper_caesar(29).table(), caesar.table());
+        assert!(create_mapper_caesar(0).is_identity());
+        assert!(create_mapper_caesar(26).is_identity());
+        for k in 0..=26 {
+            let round_trip = create_mapper_caesar(k).then(create_mapper_caesar(26 - k));
+            assert!(round_trip.to_node().is_identity(), "{k}");
+        }
+    }
+
+    #[test]
+    fn test_text_mappers_keep_non_ascii() {
+        for mapper in [
+            create_mapper_to_lowercase(),
+            create_mapper_to_uppercase(),
+            create_mapper_rot13(),
+            create_mapper_caesar(7),
+        ] {
+            assert!((128..=255u8).all(|x| mapper.transform(x) == x));
+            assert_eq!(mapper.transform(b'5'), b'5');
+        }
+    }
+
+    #[test]
+    fn test_hex() {
+        let value = create_mapper_hex_value();
+        for (i, &c) in b"0123456789abcdef".iter().enumerate() {
+            assert_eq!(value.transform(c), i as u8);
+            assert_eq!(value.transform(c.to_ascii_uppercase()), i as u8);
+        }
+        let valid = |c: u8| c.is_ascii_hexdigit();
+        assert!(
+            (0..=255u8)
+                .filter(|&c| !valid(c))
+                .all(|c| value.transform(c) == INVALID_VALUE)
+        );
+
+        let (lower, upper) = (
+            create_mapper_hex_digit_lower(),
+            create_mapper_hex_digit_upper(),
+        );
+        for v in 0..16u8 {
+            assert_eq!(value.transform(lower.transform(v)), v);
+            assert_eq!(value.transform(upper.transform(v)), v);
+        }
+        assert_eq!(lower.transform(0xAB), b'b');
+        assert_eq!(upper.transform(0xAB), b'B');
+        for c in (0..=255u8).filter(|c| c.is_ascii_digit() || (b'a'..=b'f').contains(c)) {
+            assert_eq!(lower.transform(value.transform(c)), c);
+        }
+    }
+
+    #[test]
+    fn test_digits() {
+        let (value, encode) = (create_mapper_digit_value(), create_mapper_digit_char());
+        for c in 0..=255u8 {
+            let expected = if c.is_ascii_digit() {
+                c - b'0'
+            } else {
+                INVALID_VALUE
+            };
+            assert_eq!(value.transform(c), expected);
+        }
+        for v in 0..10u8 {
+            assert_eq!(value.transform(encode.transform(v)), v);
+        }
+        assert_eq!(encode.transform(10), INVALID_VALUE);
+    }
+
+    #[test]
+    fn test_base64() {
+        let (value, encode) = (create_mapper_base64_value(), create_mapper_base64_char());
+        let alphabet = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        for (v, &c) in alphabet.iter().enumerate() {
+            assert_eq!(value.transform(c), v as u8);
+            assert_eq!(encode.transform(v as u8), c);
+            assert_eq!(encode.transform(value.transform(c)), c);
+        }
+        assert_eq!(value.transform(b'='), BASE64_PADDING);
+        let invalid = (0..=255u8).filter(|c| !alphabet.contains(c) && *c != b'=');
+        assert!(invalid.clone().all(|c| value.transform(c) == INVALID_VALUE));
+        assert_eq!(invalid.count(), 256 - 65);
+        assert_eq!(encode.transform(64 + 5), b'F');
+    }
+
+    #[test]
+    fn test_gray_code() {
+        let (encode, decode) = (create_mapper_gray_encode(), create_mapper_gray_decode());
+        assert_eq!(encode.inverse().unwrap().table(), decode.table());
+        assert!(encode.clone().then(decode.clone()).to_node().is_identity());
+        assert!(decode.then(encode.clone()).to_node().is_identity());
+        for x in 0..255u8 {
+            let changed = encode.transform(x) ^ encode.transform(x + 1);
+            assert_eq!(changed.count_ones(), 1, "{x}");
+        }
+        assert_eq!(encode.transform(255) ^ encode.transform(0), 0b1000_0000);
+        assert_eq!(encode.transform(0b0110), 0b0101);
+    }
+
+    #[test]
+    fn test_bit_reverse() {
+        let reverse = create_mapper_bit_reverse();
+        assert_eq!(reverse.transform(0b0000_0001), 0b1000_0000);
+        assert_eq!(reverse.transform(0b1100_1010), 0b0101_0011);
+        assert_eq!(reverse.inverse().unwrap().table(), reverse.table());
+        assert!(reverse.clone().then(reverse).to_node().is_identity());
+    }
+
+    #[test]
+    fn test_unpack_bits() {
+        let unpacked = create_mappers_unpack_bits();
+        for x in 0u8..=u8::MAX {
+            let repacked = unpacked
+                .iter()
+                .enumerate()
+                .fold(0u8, |acc, (bit, m)| acc | ((m.transform(x) & 1) << bit));
+            assert_eq!(repacked, x);
+        }
+        assert_eq!(create_mapper_unpack_bit(7).transform(0x80), 255);
+        assert_eq!(create_mapper_unpack_bit(7).transform(0x7F), 0);
+    }
+
+    #[test]
+    fn test_pyramid_at() {
+        for peak in [0, 1, 64, 128, 200, 254, 255] {
+            let mapper = create_mapper_pyramid_at(peak);
+            let table = mapper.table();
+            assert_eq!(mapper.transform(peak), 255, "{peak}");
+            assert_eq!(table.iter().max(), Some(&255));
+            assert!(
+                table[..=peak as usize].windows(2).all(|p| p[0] <= p[1]),
+                "{peak}"
+            );
+            assert!(
+                table[peak as usize..].windows(2).all(|p| p[0] >= p[1]),
+                "{peak}"
+            );
+            assert_eq!(table[0], if peak == 0 { 255 } else { 0 });
+            assert_eq!(table[255], if peak == 255 { 255 } else { 0 });
+        }
+        assert!(create_mapper_pyramid_at(255).is_identity());
+        assert_eq!(
+            create_mapper_pyramid_at(0).table(),
+            create_mapper_sawtooth().invert().table()
+        );
+        // 255 * 32 / 64 = 127.5 rounds up; 255 * 55 / 191 = 73.43.
+        let mapper = create_mapper_pyramid_at(64);
+        assert_eq!(mapper.transform(32), 128);
+        assert_eq!(mapper.transform(200), 73);
+    }
+
+    #[test]
+    fn test_pyramid_half_range() {
+        let mapper = create_mapper_pyramid();
+        assert_eq!(mapper.table().iter().max(), Some(&127));
+        assert_eq!((mapper.transform(127), mapper.transform(128)), (127, 127));
+        assert_eq!(create_mapper_pyramid_at(128).transform(128), 255);
+    }
+
+    #[test]
+    fn test_fixed_point_helpers() {
+        assert_eq!(log2_fixed(1), 0);
+        assert_eq!(log2_fixed(256), 8 << 16);
+        assert_eq!(exp2_fixed(0), 1 << 16);
+        assert_eq!(exp2_fixed(8 << 16), 256 << 16);
+        assert_eq!(exp2_fixed(-1 << 16), 1 << 15);
+        for n in 1..=256u64 {
+            let error = log2_fixed(n) as f64 / 65536.0 - (n as f64).log2();
+            assert!((-2e-5..=0.0).contains(&error), "{n}");
+        }
+        for t in (-8 << 16..8 << 16).step_by(977) {
+            let expected = (t as f64 / 65536.0).exp2() * 65536.0;
+            assert!(
+                (exp2_fixed(t) as f64 - expected).abs() <= expected * 1e-6 + 1.0,
+                "{t}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_log2_and_exp() {
+        let (log, exp) = (create_mapper_log2(), create_mapper_exp());
+        assert!(log.is_monotonic_increasing());
+        assert!(exp.is_monotonic_increasing());
+        assert_eq!(
+            (log.transform(0), log.transform(1), log.transform(255)),
+            (0, 32, 255)
+        );
+        assert_eq!(
+            (exp.transform(0), exp.transform(32), exp.transform(255)),
+            (0, 1, 255)
+        );
+        // 255 * log2(16) / 8 = 127.5 rounds up.
+        assert_eq!(log.transform(15), 128);
+        let round_trip = log.then(exp).to_node();
+        for x in 0..=255u8 {
+            assert!(round_trip.transform(x).abs_diff(x) <= 3, "{x}");
+        }
+    }
+
+    #[test]
+    fn test_gamma() {
+        assert!(create_mapper_gamma(1, 1).is_identity());
+        assert!(create_mapper_gamma(3, 3).is_identity());
+        assert!(create_mapper_gamma(2, 0).table() == create_mapper_gamma(2, 1).table());
+        assert!(create_mapper_gamma(0, 1).table().iter().all(|&y| y == 255));
+        for (num, den) in [(22, 10), (10, 22), (1, 2), (2, 1), (255, 1)] {
+            let gamma = create_mapper_gamma(num, den);
+            assert!(gamma.is_monotonic_increasing(), "{num}/{den}");
+            assert_eq!((gamma.transform(0), gamma.transform(255)), (0, 255));
+            let reference = MapperNode::new_transformation(|x| {
+                let y = 255.0 * (x as f64 / 255.0).powf(num as f64 / den as f64);
+                (y + 0.5).floor() as u8
+            });
+            let worst = (0..=255u8)
+                .map(|x| gamma.transform(x).abs_diff(reference.transform(x)))
+                .max();
+            assert!(worst <= Some(1), "{num}/{den}");
+        }
+        // 255 * (128 / 255)^2 = 64.25; 255 * (64 / 255)^0.5 = 127.75.
+        assert_eq!(create_mapper_gamma(2, 1).transform(128), 64);
+        assert_eq!(create_mapper_gamma(1, 2).transform(64), 128);
+    }
+
+    #[test]
+    fn test_latin1_classes() {
+        let (lower, upper) = (
+            create_mapper_for_lowercase_latin1(),
+            create_mapper_for_uppercase_latin1(),
+        );
+        let letters = create_mapper_for_letters_latin1();
+        let (e_acute, e_acute_upper) = (0xE9, 0xC9);
+        assert_eq!(
+            (lower.transform(e_acute), upper.transform(e_acute)),
+            (0xFF, 0)
+        );
+        assert_eq!(
+            (
+                lower.transform(e_acute_upper),
+                upper.transform(e_acute_upper)
+            ),
+            (0, 0xFF)
+        );
+        for exception in [0xD7, 0xF7] {
+            assert_eq!(letters.transform(exception), 0);
+            assert_eq!(lower.transform(exception) | upper.transform(exception), 0);
+        }
+        for c in 0xC0..=0xFFu8 {
+            let c_char = char::from(c);
+            assert_eq!(
+                lower.transform(c) == 0xFF,
+                c_char.is_lowercase(),
+                "{c_char}"
+            );
+            assert_eq!(
+                upper.transform(c) == 0xFF,
+                c_char.is_uppercase(),
+                "{c_char}"
+            );
+            assert_eq!(
+                letters.transform(c) == 0xFF,
+                c_char.is_alphabetic(),
+                "{c_char}"
+            );
+        }
+        assert_eq!(
+            lower.table()[..0xC0],
+            create_mapper_for_lowercase().table()[..0xC0]
+        );
+        assert_eq!(
+            upper.table()[..0xC0],
+            create_mapper_for_uppercase().table()[..0xC0]
+        );
+        assert_eq!(
+            letters.table()[..0xC0],
+            create_mapper_for_letters().table()[..0xC0]
+        );
+    }
+
+    #[test]
+    fn test_latin1_to_lower() {
+        let mapper = create_mapper_latin1_to_lower();
+        assert_eq!(mapper.transform(0xC9), 0xE9);
+        assert_eq!(mapper.transform(0xD7), 0xD7);
+        assert_eq!(mapper.transform(0xDF), 0xDF);
+        for c in 0..=255u8 {
+            let mut folded = char::from(c).to_lowercase();
+            let expected = match (folded.next(), folded.next()) {
+                (Some(single), None) if (single as u32) < 256 => single as u8,
+                _ => c,
+            };
+            assert_eq!(mapper.transform(c), expected, "{c}");
+        }
+        assert_eq!(
+            mapper.table()[..0x80],
+            create_mapper_to_lowercase().table()[..0x80]
+        );
+    }
+}
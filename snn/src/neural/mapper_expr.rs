@@ -0,0 +1,445 @@
+/// Mapper tables written as arithmetic expressions of the input `x`, such as `"255 - x"`.
+///
+/// Expressions use integers, `x`, parentheses, the operators `+ - * / % & | ^ << >>` and unary
+/// `-` with the precedence they have in Rust, and the functions `min(a, b, ...)`,
+/// `max(a, b, ...)`, `abs(a)` and `clamp(a, low, high)`. Numbers may be written in decimal,
+/// or in hexadecimal or binary with a `0x` or `0b` prefix. Arithmetic is done on 64-bit
+/// integers that saturate instead of overflowing, division rounds towards zero, and the
+/// result for each input is clamped to `0..=255`. Expressions nested more than 128 levels
+/// deep, counting parentheses, function calls, unary `-` and each chained binary operator, are
+/// rejected so that hostile input fails with an error instead of overflowing the stack.
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::neural::mapper::MapperNode;
+
+impl MapperNode {
+    /// Creates a new `Mapper` by evaluating `expression` for every input `x`. See the module
+    /// documentation for the syntax.
+    ///
+    /// # Returns
+    /// A new `MapperNode`, or an `ExprError` if the expression cannot be parsed or divides by
+    /// zero for some input.
+    pub fn from_expr(expression: &str) -> Result<MapperNode, ExprError> {
+        let mut parser = Parser {
+            bytes: expression.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        parser.whitespace();
+        if parser.pos != parser.bytes.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        let mut table = [0u8; 256];
+        for (x, out) in table.iter_mut().enumerate() {
+            *out = expr.eval(x as i64)?.clamp(0, 255) as u8;
+        }
+        Ok(MapperNode::new_from(table))
+    }
+}
+
+/// Error returned by `MapperNode::from_expr`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprError {
+    /// The byte offset in the expression where the problem is.
+    pub position: usize,
+    /// What was expected or went wrong.
+    pub message: String,
+}
+
+impl core::fmt::Display for ExprError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "invalid expression at byte {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl core::error::Error for ExprError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Min,
+    Max,
+    Abs,
+    Clamp,
+}
+
+enum Expr {
+    X,
+    Number(i64),
+    Neg(Box<Expr>),
+    /// An operator and the byte offset it was written at.
+    Binary(Op, usize, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, x: i64) -> Result<i64, ExprError> {
+        Ok(match self {
+            Expr::X => x,
+            Expr::Number(n) => *n,
+            Expr::Neg(a) => a.eval(x)?.saturating_neg(),
+            Expr::Binary(op, position, a, b) => {
+                let (a, b) = (a.eval(x)?, b.eval(x)?);
+                let shift = u32::try_from(b).ok().filter(|&b| b < 64);
+                match op {
+                    Op::Add => a.saturating_add(b),
+                    Op::Sub => a.saturating_sub(b),
+                    Op::Mul => a.saturating_mul(b),
+                    Op::Div | Op::Rem if b == 0 => {
+                        return Err(ExprError {
+                            position: *position,
+                            message: format!("division by zero when x = {x}"),
+                        });
+                    }
+                    Op::Div => a.saturating_div(b),
+                    Op::Rem => a.checked_rem(b).unwrap_or(0),
+                    Op::And => a & b,
+                    Op::Or => a | b,
+                    Op::Xor => a ^ b,
+                    // Shifting by a negative amount or by 64 or more shifts every bit out.
+                    Op::Shl => shift.map_or(0, |b| a << b),
+                    Op::Shr => shift.map_or(if a < 0 { -1 } else { 0 }, |b| a >> b),
+                }
+            }
+            Expr::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(x))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match function {
+                    Function::Min => args.into_iter().min().unwrap_or(0),
+                    Function::Max => args.into_iter().max().unwrap_or(0),
+                    Function::Abs => args[0].saturating_abs(),
+                    Function::Clamp => args[0].max(args[1]).min(args[2]),
+                }
+            }
+        })
+    }
+}
+
+/// The deepest expression tree `from_expr` builds.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// How deep the node being parsed sits in the expression tree.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> ExprError {
+        ExprError {
+            position: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Moves one level deeper into the expression tree, failing past `MAX_DEPTH`.
+    fn nest(&mut self) -> Result<(), ExprError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(&format!("nesting deeper than {MAX_DEPTH} levels")));
+        }
+        Ok(())
+    }
+
+    /// Consumes `token` if it comes next, skipping whitespace before it.
+    fn eat(&mut self, token: &str) -> bool {
+        self.whitespace();
+        if self.bytes[self.pos..].starts_with(token.as_bytes()) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Parses one level of left-associative binary operators, each level binding tighter
+    /// than the one before.
+    fn binary(
+        &mut self,
+        operators: &[(&str, Op)],
+        next: fn(&mut Self) -> Result<Expr, ExprError>,
+    ) -> Result<Expr, ExprError> {
+        let depth = self.depth;
+        let mut left = next(self)?;
+        'outer: loop {
+            self.whitespace();
+            let position = self.pos;
+            for &(token, op) in operators {
+                if self.eat(token) {
+                    // Each operator puts everything parsed so far one level further down.
+                    self.nest()?;
+                    let right = next(self)?;
+                    left = Expr::Binary(op, position, Box::new(left), Box::new(right));
+                    continue 'outer;
+                }
+            }
+            self.depth = depth;
+            return Ok(left);
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ExprError> {
+        self.binary(&[("|", Op::Or)], Self::xor)
+    }
+
+    fn xor(&mut self) -> Result<Expr, ExprError> {
+        self.binary(&[("^", Op::Xor)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, ExprError> {
+        self.binary(&[("&", Op::And)], Self::shift)
+    }
+
+    fn shift(&mut self) -> Result<Expr, ExprError> {
+        self.binary(&[("<<", Op::Shl), (">>", Op::Shr)], Self::sum)
+    }
+
+    fn sum(&mut self) -> Result<Expr, ExprError> {
+        self.binary(&[("+", Op::Add), ("-", Op::Sub)], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Expr, ExprError> {
+        self.binary(
+            &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr, ExprError> {
+        if self.eat("-") {
+            self.nest()?;
+            let operand = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expr::Neg(Box::new(operand)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, ExprError> {
+        self.whitespace();
+        match self.bytes.get(self.pos) {
+            None => Err(self.error("unexpected end of expression")),
+            Some(b'(') => {
+                self.pos += 1;
+                self.nest()?;
+                let expr = self.or()?;
+                if !self.eat(")") {
+                    return Err(self.error("expected ')'"));
+                }
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Some(b'0'..=b'9') => self.number(),
+            Some(c) if c.is_ascii_alphabetic() || *c == b'_' => self.name(),
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Expr, ExprError> {
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_')
+        {
+            self.pos += 1;
+        }
+        let text: String = self.bytes[start..self.pos]
+            .iter()
+            .filter(|&&c| c != b'_')
+            .map(|&c| c as char)
+            .collect();
+        let parsed = match text.get(..2) {
+            Some("0x" | "0X") => i64::from_str_radix(&text[2..], 16),
+            Some("0b" | "0B") => i64::from_str_radix(&text[2..], 2),
+            _ => text.parse(),
+        };
+        parsed.map(Expr::Number).map_err(|_| ExprError {
+            position: start,
+            message: format!("invalid number {text:?}"),
+        })
+    }
+
+    fn name(&mut self) -> Result<Expr, ExprError> {
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_')
+        {
+            self.pos += 1;
+        }
+        let name = core::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or_default();
+        let (function, arity) = match name {
+            "x" => return Ok(Expr::X),
+            "min" => (Function::Min, None),
+            "max" => (Function::Max, None),
+            "abs" => (Function::Abs, Some(1)),
+            "clamp" => (Function::Clamp, Some(3)),
+            _ => {
+                return Err(ExprError {
+                    position: start,
+                    message: format!("unknown name {name:?}"),
+                });
+            }
+        };
+        if !self.eat("(") {
+            return Err(self.error("expected '('"));
+        }
+        self.nest()?;
+        let mut args = Vec::new();
+        if !self.eat(")") {
+            loop {
+                args.push(self.or()?);
+                if self.eat(")") {
+                    break;
+                }
+                if !self.eat(",") {
+                    return Err(self.error("expected ',' or ')'"));
+                }
+            }
+        }
+        self.depth -= 1;
+        let valid = match arity {
+            Some(arity) => args.len() == arity,
+            None => !args.is_empty(),
+        };
+        if !valid {
+            return Err(ExprError {
+                position: start,
+                message: match arity {
+                    Some(arity) => format!("{name} takes {arity} arguments, got {}", args.len()),
+                    None => format!("{name} takes at least 1 argument"),
+                },
+            });
+        }
+        Ok(Expr::Call(function, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(expression: &str) -> MapperNode {
+        MapperNode::from_expr(expression).unwrap()
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert!(table("x").is_identity());
+        assert!(table("255 - x") == MapperNode::new_transformation(|x| 255 - x as u8));
+        assert_eq!(table("x / 2").transform(201), 100);
+        assert_eq!(table("x % 10").transform(123), 3);
+        // Multiplication binds tighter than addition, and parentheses override it.
+        assert_eq!(table("1 + x * 2").transform(10), 21);
+        assert_eq!(table("(1 + x) * 2").transform(10), 22);
+        // Subtraction is left-associative.
+        assert_eq!(table("100 - x - 10").transform(20), 70);
+        assert_eq!(table("-x + 50").transform(20), 30);
+    }
+
+    #[test]
+    fn test_results_are_clamped() {
+        let doubled = table("x * 2");
+        assert_eq!(doubled.transform(100), 200);
+        assert_eq!(doubled.transform(200), 255);
+        assert_eq!(table("x - 100").transform(50), 0);
+        assert_eq!(table("x * 9223372036854775807").transform(3), 255);
+    }
+
+    #[test]
+    fn test_bits_and_functions() {
+        assert_eq!(table("x & 0x0f").transform(0xAB), 0x0B);
+        assert_eq!(table("x | 0b1").transform(4), 5);
+        assert_eq!(table("x ^ 255").transform(1), 254);
+        assert_eq!(table("x << 1 | 1").transform(3), 7);
+        assert_eq!(table("x >> 4").transform(0xF0), 0x0F);
+        assert_eq!(table("x << 64").transform(1), 0);
+        assert_eq!(table("min(x, 100, 50 + 1)").transform(200), 51);
+        assert_eq!(table("max(x, 100)").transform(7), 100);
+        assert_eq!(table("abs(x - 128)").transform(100), 28);
+        assert_eq!(table("clamp(x, 64, 192)").transform(10), 64);
+        assert_eq!(table("clamp(x, 64, 192)").transform(250), 192);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = MapperNode::from_expr("x / (x - 3)").err().unwrap();
+        assert_eq!(error.position, 2);
+        assert_eq!(error.message, "division by zero when x = 3");
+        assert_eq!(MapperNode::from_expr("y + 1").err().unwrap().position, 0);
+        assert_eq!(MapperNode::from_expr("x +").err().unwrap().position, 3);
+        assert_eq!(MapperNode::from_expr("(x").err().unwrap().position, 2);
+        assert_eq!(MapperNode::from_expr("x x").err().unwrap().position, 2);
+        assert_eq!(MapperNode::from_expr("0xZZ").err().unwrap().position, 0);
+        assert!(MapperNode::from_expr("abs(x, 1)").is_err());
+        assert!(MapperNode::from_expr("min()").is_err());
+        assert!(MapperNode::from_expr("").is_err());
+        assert_eq!(
+            MapperNode::from_expr("x / 0").err().unwrap().to_string(),
+            "invalid expression at byte 2: division by zero when x = 0"
+        );
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let parens = |depth: usize| format!("{}x{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(table(&parens(MAX_DEPTH)).transform(7), 7);
+        let error = MapperNode::from_expr(&parens(MAX_DEPTH + 1)).err().unwrap();
+        assert_eq!(error.position, MAX_DEPTH + 1);
+        assert_eq!(error.message, "nesting deeper than 128 levels");
+
+        let negations = |depth: usize| format!("{}x", "-".repeat(depth));
+        assert_eq!(table(&negations(MAX_DEPTH)).transform(7), 7);
+        assert!(MapperNode::from_expr(&negations(MAX_DEPTH + 1)).is_err());
+
+        let calls = |depth: usize| format!("{}x{}", "abs(".repeat(depth), ")".repeat(depth));
+        assert_eq!(table(&calls(MAX_DEPTH)).transform(7), 7);
+        assert!(MapperNode::from_expr(&calls(MAX_DEPTH + 1)).is_err());
+
+        let chain = |length: usize| format!("x{}", " + 0".repeat(length));
+        assert_eq!(table(&chain(MAX_DEPTH)).transform(7), 7);
+        assert!(MapperNode::from_expr(&chain(MAX_DEPTH + 1)).is_err());
+        assert_eq!(
+            table(&format!("({}) * 1", chain(MAX_DEPTH - 2))).transform(7),
+            7
+        );
+
+        assert!(MapperNode::from_expr(&"(".repeat(200_000)).is_err());
+        assert!(MapperNode::from_expr(&"-".repeat(200_000)).is_err());
+        assert!(MapperNode::from_expr(&format!("{}x", "x+".repeat(200_000))).is_err());
+    }
+}
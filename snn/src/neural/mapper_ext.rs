@@ -0,0 +1,102 @@
+/// Extension methods available on every `Mapper`, including closures and trait objects.
+use crate::neural::mapper::MapperNode;
+use crate::neural::traits::Mapper;
+
+/// Extension trait with composition and inspection helpers, implemented for every `Mapper`.
+pub trait MapperExt: Mapper {
+    /// Materializes this mapper into a lookup table by sampling all 256 inputs.
+    fn to_node(&self) -> MapperNode {
+        MapperNode::new_transformation(|i| self.transform(i as u8))
+    }
+
+    /// Returns a mapper that applies `self` first and then `next`.
+    fn then<M: Mapper>(self, next: M) -> Then<Self, M>
+    where
+        Self: Sized,
+    {
+        Then { first: self, next }
+    }
+
+    /// Returns a mapper that calls `f(input, output)` for every transformed byte.
+    fn inspect<F: Fn(u8, u8)>(self, f: F) -> Inspect<Self, F>
+    where
+        Self: Sized,
+    {
+        Inspect { mapper: self, f }
+    }
+}
+
+impl<M: Mapper + ?Sized> MapperExt for M {}
+
+/// A mapper that applies two mappers in sequence. Created by `MapperExt::then`.
+#[derive(Clone)]
+pub struct Then<A, B> {
+    first: A,
+    next: B,
+}
+
+impl<A: Mapper, B: Mapper> Mapper for Then<A, B> {
+    fn transform(&self, input: u8) -> u8 {
+        self.next.transform(self.first.transform(input))
+    }
+}
+
+/// A mapper that reports every transformation to a callback. Created by `MapperExt::inspect`.
+#[derive(Clone)]
+pub struct Inspect<M, F> {
+    mapper: M,
+    f: F,
+}
+
+impl<M: Mapper, F: Fn(u8, u8)> Mapper for Inspect<M, F> {
+    fn transform(&self, input: u8) -> u8 {
+        let output = self.mapper.transform(input);
+        (self.f)(input, output);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::traits::FnMapper;
+    use core::cell::RefCell;
+
+    #[test]
+    fn test_to_node_matches_closure() {
+        let f = |x: u8| x.wrapping_mul(5) ^ 0x33;
+        let node = FnMapper(f).to_node();
+        for i in 0u8..=u8::MAX {
+            assert_eq!(node.transform(i), f(i));
+        }
+    }
+
+    #[test]
+    fn test_to_node_from_dyn() {
+        let original = MapperNode::new().with_range(10..=20, 3);
+        let dynamic: &dyn Mapper = &original;
+        let copy = dynamic.to_node();
+        for i in 0u8..=u8::MAX {
+            assert_eq!(copy.transform(i), original.transform(i));
+        }
+    }
+
+    #[test]
+    fn test_then() {
+        let a = MapperNode::new_transformation(|x| (x as u8).wrapping_add(17));
+        let b = FnMapper(|x: u8| x.rotate_left(3));
+        let composed = (&a).then(&b);
+        for i in 0u8..=u8::MAX {
+            assert_eq!(composed.transform(i), b.transform(a.transform(i)));
+        }
+    }
+
+    #[test]
+    fn test_inspect() {
+        let seen = RefCell::new(Vec::new());
+        let mapper = FnMapper(|x: u8| x + 1).inspect(|i, o| seen.borrow_mut().push((i, o)));
+        assert_eq!(mapper.transform(1), 2);
+        assert_eq!(mapper.transform(5), 6);
+        assert_eq!(*seen.borrow(), vec![(1, 2), (5, 6)]);
+    }
+}
@@ -0,0 +1,127 @@
+/// Multi-threaded batch transformation for `MapperNode`, enabled by the `parallel` feature.
+///
+/// Buffers are split into contiguous spans of whole chunks and each span is transformed on its own
+/// scoped thread, so the output is byte-identical to the serial methods.
+use std::thread;
+
+use crate::neural::mapper::MapperNode;
+
+/// The default number of bytes per chunk; buffers smaller than this are processed serially.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+impl MapperNode {
+    /// Transforms every byte of `input` into `output` using multiple threads.
+    ///
+    /// # Panics
+    /// Panics if `input` and `output` have different lengths.
+    pub fn par_transform_slice(&self, input: &[u8], output: &mut [u8]) {
+        self.par_transform_slice_with_chunk_size(input, output, DEFAULT_CHUNK_SIZE);
+    }
+
+    /// Transforms every byte of `input` into `output` using multiple threads,
+    /// never handing a thread less than `chunk_size` bytes.
+    ///
+    /// # Panics
+    /// Panics if `input` and `output` have different lengths.
+    pub fn par_transform_slice_with_chunk_size(
+        &self,
+        input: &[u8],
+        output: &mut [u8],
+        chunk_size: usize,
+    ) {
+        assert_eq!(input.len(), output.len(), "input and output lengths differ");
+        let span = span_size(input.len(), chunk_size);
+        if span >= input.len() {
+            self.transform_slice(input, output);
+            return;
+        }
+        thread::scope(|scope| {
+            for (i, o) in input.chunks(span).zip(output.chunks_mut(span)) {
+                scope.spawn(move || self.transform_slice(i, o));
+            }
+        });
+    }
+
+    /// Transforms every byte of `data` in place using multiple threads.
+    pub fn par_transform_in_place(&self, data: &mut [u8]) {
+        self.par_transform_in_place_with_chunk_size(data, DEFAULT_CHUNK_SIZE);
+    }
+
+    /// Transforms every byte of `data` in place using multiple threads,
+    /// never handing a thread less than `chunk_size` bytes.
+    pub fn par_transform_in_place_with_chunk_size(&self, data: &mut [u8], chunk_size: usize) {
+        let span = span_size(data.len(), chunk_size);
+        if span >= data.len() {
+            self.transform_in_place(data);
+            return;
+        }
+        thread::scope(|scope| {
+            for d in data.chunks_mut(span) {
+                scope.spawn(move || self.transform_in_place(d));
+            }
+        });
+    }
+}
+
+/// Returns the number of bytes each thread should process: a whole number of chunks,
+/// spread evenly over the available threads.
+fn span_size(len: usize, chunk_size: usize) -> usize {
+    let chunk_size = chunk_size.max(1);
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunks = len.div_ceil(chunk_size);
+    chunks.div_ceil(threads).max(1) * chunk_size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::rng::Rng;
+
+    fn mapper() -> MapperNode {
+        MapperNode::new_transformation(|x| (x as u8).wrapping_mul(7) ^ 0x5A)
+    }
+
+    #[test]
+    fn test_par_transform_slice_matches_serial() {
+        let mapper = mapper();
+        for (n, len) in [0, 1, 17, 4096 + 3, 3 * DEFAULT_CHUNK_SIZE + 11]
+            .into_iter()
+            .enumerate()
+        {
+            let input = Rng::new(0xDEAD_BEEF + n as u64).bytes(len);
+            let mut serial = vec![0; len];
+            let mut parallel = vec![0; len];
+            mapper.transform_slice(&input, &mut serial);
+            mapper.par_transform_slice(&input, &mut parallel);
+            assert_eq!(serial, parallel, "length {len}");
+        }
+    }
+
+    #[test]
+    fn test_par_transform_small_chunks() {
+        let mapper = mapper();
+        let input = Rng::new(42).bytes(10_000);
+        let mut serial = vec![0; input.len()];
+        mapper.transform_slice(&input, &mut serial);
+        for chunk_size in [0, 1, 7, 64, 10_000, 20_000] {
+            let mut parallel = vec![0; input.len()];
+            mapper.par_transform_slice_with_chunk_size(&input, &mut parallel, chunk_size);
+            assert_eq!(serial, parallel, "chunk size {chunk_size}");
+
+            let mut in_place = input.clone();
+            mapper.par_transform_in_place_with_chunk_size(&mut in_place, chunk_size);
+            assert_eq!(serial, in_place, "chunk size {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn test_par_transform_in_place_matches_serial() {
+        let mapper = mapper();
+        let input = Rng::new(7).bytes(2 * DEFAULT_CHUNK_SIZE + 5);
+        let mut serial = input.clone();
+        let mut parallel = input;
+        mapper.transform_in_place(&mut serial);
+        mapper.par_transform_in_place(&mut parallel);
+        assert_eq!(serial, parallel);
+    }
+}
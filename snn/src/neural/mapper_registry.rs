@@ -0,0 +1,615 @@
+/// Looking up mapper tables by name, mirroring `combinator_from_str` for combinators.
+///
+/// Every function in `mapper_defaults` is available under its name without the
+/// `create_mapper_` prefix, such as `"for_lowercase"` or `"rot13"`. Functions with parameters
+/// take them as bytes after colons, as in `"pyramid_at:200"` or `"gamma:22:10"`. The tables of
+/// `create_mappers_unpack_bits` are available one at a time as `"unpack_bit:N"`. There are
+/// also `"identity"`, `"fill:N"` for a constant table and `"expr:..."` for an expression
+/// accepted by `MapperNode::from_expr`.
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::neural::mapper::MapperNode;
+use crate::neural::mapper_defaults as defaults;
+
+/// Creates a mapper table from its name, case-insensitively, with any parameters after
+/// colons, e.g. `"pyramid_at:200"`. Returns `None` for unknown names or invalid parameters.
+pub fn mapper_from_str(description: &str) -> Option<MapperNode> {
+    try_mapper_from_str(description).ok()
+}
+
+/// Like `mapper_from_str`, but reports why the description was rejected.
+pub fn try_mapper_from_str(description: &str) -> Result<MapperNode, MapperParseError> {
+    let (name, args) = match description.split_once(':') {
+        Some((name, args)) => (name, Some(args)),
+        None => (description, None),
+    };
+    let name = name.trim().to_lowercase();
+    let builtin = MAPPER_BUILTINS
+        .iter()
+        .find(|b| b.name == name)
+        .ok_or(MapperParseError::UnknownName(name))?;
+    builtin.create(args.unwrap_or(""))
+}
+
+/// Describes a built-in mapper that `mapper_from_str` understands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapperInfo {
+    /// The name to look the mapper up by.
+    pub name: &'static str,
+    /// The names of the parameters that follow the name, separated by colons.
+    pub parameters: &'static [&'static str],
+}
+
+/// Lists every built-in mapper with the parameters it takes.
+pub fn available_mappers() -> Vec<MapperInfo> {
+    MAPPER_BUILTINS
+        .iter()
+        .map(|b| MapperInfo {
+            name: b.name,
+            parameters: b.parameters,
+        })
+        .collect()
+}
+
+/// Error returned when a mapper description cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapperParseError {
+    /// No mapper is known by this name.
+    UnknownName(String),
+    /// The mapper exists, but its parameters are missing or malformed.
+    InvalidArguments {
+        name: String,
+        args: String,
+        reason: String,
+    },
+}
+
+impl MapperParseError {
+    fn invalid_arguments(name: &str, args: &str, reason: impl Into<String>) -> Self {
+        MapperParseError::InvalidArguments {
+            name: name.to_string(),
+            args: args.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl core::fmt::Display for MapperParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MapperParseError::UnknownName(name) => write!(f, "unknown mapper {name:?}"),
+            MapperParseError::InvalidArguments { name, args, reason } => {
+                write!(
+                    f,
+                    "invalid arguments {args:?} for mapper {name:?}: {reason}"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for MapperParseError {}
+
+/// How a built-in mapper builds its table from its arguments.
+#[derive(Clone, Copy)]
+enum Build {
+    /// From one byte per parameter.
+    Bytes(fn(&[u8]) -> MapperNode),
+    /// From the argument text as an expression.
+    Expr,
+}
+
+/// An entry in the table of built-in mappers.
+struct MapperBuiltin {
+    name: &'static str,
+    parameters: &'static [&'static str],
+    build: Build,
+}
+
+impl MapperBuiltin {
+    /// Builds the table from the text after the name, empty when there is none.
+    fn create(&self, args: &str) -> Result<MapperNode, MapperParseError> {
+        let build = match self.build {
+            Build::Bytes(build) => build,
+            Build::Expr => {
+                return MapperNode::from_expr(args).map_err(|error| {
+                    MapperParseError::invalid_arguments(self.name, args, error.to_string())
+                });
+            }
+        };
+        let bytes = if args.trim().is_empty() {
+            Vec::new()
+        } else {
+            args.split(':')
+                .map(|arg| {
+                    arg.trim().parse::<u8>().map_err(|_| {
+                        MapperParseError::invalid_arguments(
+                            self.name,
+                            args,
+                            format!("{:?} is not a byte", arg.trim()),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<u8>, _>>()?
+        };
+        if bytes.len() != self.parameters.len() {
+            return Err(MapperParseError::invalid_arguments(
+                self.name,
+                args,
+                format!(
+                    "takes {} arguments, got {}",
+                    self.parameters.len(),
+                    bytes.len()
+                ),
+            ));
+        }
+        Ok(build(&bytes))
+    }
+}
+
+/// Shorthand for a built-in mapper taking bytes.
+const fn bytes(
+    name: &'static str,
+    parameters: &'static [&'static str],
+    build: fn(&[u8]) -> MapperNode,
+) -> MapperBuiltin {
+    MapperBuiltin {
+        name,
+        parameters,
+        build: Build::Bytes(build),
+    }
+}
+
+/// Every built-in mapper. This is the single source of truth for name lookups.
+const MAPPER_BUILTINS: &[MapperBuiltin] = &[
+    bytes("identity", &[], |_| {
+        MapperNode::new_transformation(|x| x as u8)
+    }),
+    bytes("fill", &["value"], |a| MapperNode::new().with_fill(a[0])),
+    MapperBuiltin {
+        name: "expr",
+        parameters: &["expression"],
+        build: Build::Expr,
+    },
+    bytes("for_lowercase", &[], |_| {
+        defaults::create_mapper_for_lowercase()
+    }),
+    bytes("for_uppercase", &[], |_| {
+        defaults::create_mapper_for_uppercase()
+    }),
+    bytes("for_letters", &[], |_| {
+        defaults::create_mapper_for_letters()
+    }),
+    bytes("for_letters_cased", &[], |_| {
+        defaults::create_mapper_for_letters_cased()
+    }),
+    bytes("for_character", &[], |_| {
+        defaults::create_mapper_for_character()
+    }),
+    bytes("for_whitespace", &[], |_| {
+        defaults::create_mapper_for_whitespace()
+    }),
+    bytes("pyramid", &[], |_| defaults::create_mapper_pyramid()),
+    bytes("pyramid_at", &["peak"], |a| {
+        defaults::create_mapper_pyramid_at(a[0])
+    }),
+    bytes("pyramid_inverted", &[], |_| {
+        defaults::create_mapper_pyramid_inverted()
+    }),
+    bytes("to_lowercase", &[], |_| {
+        defaults::create_mapper_to_lowercase()
+    }),
+    bytes("to_uppercase", &[], |_| {
+        defaults::create_mapper_to_uppercase()
+    }),
+    bytes("for_lowercase_latin1", &[], |_| {
+        defaults::create_mapper_for_lowercase_latin1()
+    }),
+    bytes("for_uppercase_latin1", &[], |_| {
+        defaults::create_mapper_for_uppercase_latin1()
+    }),
+    bytes("for_letters_latin1", &[], |_| {
+        defaults::create_mapper_for_letters_latin1()
+    }),
+    bytes("latin1_to_lower", &[], |_| {
+        defaults::create_mapper_latin1_to_lower()
+    }),
+    bytes("rot13", &[], |_| defaults::create_mapper_rot13()),
+    bytes("caesar", &["shift"], |a| {
+        defaults::create_mapper_caesar(a[0])
+    }),
+    bytes("hex_value", &[], |_| defaults::create_mapper_hex_value()),
+    bytes("hex_digit_lower", &[], |_| {
+        defaults::create_mapper_hex_digit_lower()
+    }),
+    bytes("hex_digit_upper", &[], |_| {
+        defaults::create_mapper_hex_digit_upper()
+    }),
+    bytes("digit_value", &[], |_| {
+        defaults::create_mapper_digit_value()
+    }),
+    bytes("digit_char", &[], |_| defaults::create_mapper_digit_char()),
+    bytes("base64_value", &[], |_| {
+        defaults::create_mapper_base64_value()
+    }),
+    bytes("base64_char", &[], |_| {
+        defaults::create_mapper_base64_char()
+    }),
+    bytes("gray_encode", &[], |_| {
+        defaults::create_mapper_gray_encode()
+    }),
+    bytes("gray_decode", &[], |_| {
+        defaults::create_mapper_gray_decode()
+    }),
+    bytes("bit_reverse", &[], |_| {
+        defaults::create_mapper_bit_reverse()
+    }),
+    bytes("unpack_bit", &["bit"], |a| {
+        defaults::create_mapper_unpack_bit(a[0])
+    }),
+    bytes("log2", &[], |_| defaults::create_mapper_log2()),
+    bytes("exp", &[], |_| defaults::create_mapper_exp()),
+    bytes("gamma", &["gamma_num", "gamma_den"], |a| {
+        defaults::create_mapper_gamma(a[0], a[1])
+    }),
+    bytes("sigmoid", &["midpoint", "steepness"], |a| {
+        defaults::create_mapper_sigmoid(a[0], a[1])
+    }),
+    bytes("relu", &["threshold"], |a| {
+        defaults::create_mapper_relu(a[0])
+    }),
+    bytes("leaky_relu", &["threshold", "leak_num", "leak_den"], |a| {
+        defaults::create_mapper_leaky_relu(a[0], a[1], a[2])
+    }),
+    bytes("tanh_like", &[], |_| defaults::create_mapper_tanh_like()),
+    bytes("gaussian", &["center", "width"], |a| {
+        defaults::create_mapper_gaussian(a[0], a[1])
+    }),
+    bytes("sine", &[], |_| defaults::create_mapper_sine()),
+    bytes("triangle", &[], |_| defaults::create_mapper_triangle()),
+    bytes("sawtooth", &[], |_| defaults::create_mapper_sawtooth()),
+    bytes("square", &["duty"], |a| {
+        defaults::create_mapper_square(a[0])
+    }),
+    bytes("sine_scaled", &["amplitude", "offset"], |a| {
+        defaults::create_mapper_sine_scaled(a[0], a[1])
+    }),
+    bytes("triangle_scaled", &["amplitude", "offset"], |a| {
+        defaults::create_mapper_triangle_scaled(a[0], a[1])
+    }),
+    bytes("sawtooth_scaled", &["amplitude", "offset"], |a| {
+        defaults::create_mapper_sawtooth_scaled(a[0], a[1])
+    }),
+    bytes("square_scaled", &["duty", "amplitude", "offset"], |a| {
+        defaults::create_mapper_square_scaled(a[0], a[1], a[2])
+    }),
+];
+
+/// Constructs a mapper table from its argument string (empty when no arguments were given).
+pub type MapperFactory = Box<dyn Fn(&str) -> Result<MapperNode, MapperParseError> + Send + Sync>;
+
+/// A name to factory lookup table for mappers, including ones defined outside this crate.
+/// Names are matched case-insensitively.
+#[derive(Default)]
+pub struct MapperRegistry {
+    factories: BTreeMap<String, MapperFactory>,
+}
+
+impl MapperRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a registry containing every built-in mapper.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        for builtin in MAPPER_BUILTINS {
+            registry.factories.insert(
+                builtin.name.to_string(),
+                Box::new(move |args: &str| builtin.create(args)),
+            );
+        }
+        registry
+    }
+
+    /// Registers a factory under `name`.
+    ///
+    /// # Returns
+    /// `MapperRegistrationError` if the name is already taken; use `replace` to overwrite it.
+    pub fn register(
+        &mut self,
+        name: &str,
+        factory: MapperFactory,
+    ) -> Result<(), MapperRegistrationError> {
+        let name = name.trim().to_lowercase();
+        if self.factories.contains_key(&name) {
+            return Err(MapperRegistrationError { name });
+        }
+        self.factories.insert(name, factory);
+        Ok(())
+    }
+
+    /// Registers a fixed table under `name`; it takes no arguments.
+    pub fn register_table(
+        &mut self,
+        name: &str,
+        table: MapperNode,
+    ) -> Result<(), MapperRegistrationError> {
+        let identifier = name.trim().to_lowercase();
+        self.register(
+            name,
+            Box::new(move |args: &str| {
+                if !args.is_empty() {
+                    return Err(MapperParseError::invalid_arguments(
+                        &identifier,
+                        args,
+                        "takes no arguments",
+                    ));
+                }
+                Ok(table.clone())
+            }),
+        )
+    }
+
+    /// Registers a factory under `name`, returning the factory it replaced, if any.
+    pub fn replace(&mut self, name: &str, factory: MapperFactory) -> Option<MapperFactory> {
+        self.factories.insert(name.trim().to_lowercase(), factory)
+    }
+
+    /// Returns whether a factory is registered under `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(&name.trim().to_lowercase())
+    }
+
+    /// Creates the table registered under `name` with the given arguments.
+    pub fn create(&self, name: &str, args: &str) -> Result<MapperNode, MapperParseError> {
+        let name = name.trim().to_lowercase();
+        let factory = self
+            .factories
+            .get(&name)
+            .ok_or(MapperParseError::UnknownName(name))?;
+        factory(args)
+    }
+
+    /// Creates a table from a `name` or `name:args` description, like `try_mapper_from_str`.
+    pub fn create_from_str(&self, description: &str) -> Result<MapperNode, MapperParseError> {
+        match description.split_once(':') {
+            Some((name, args)) => self.create(name, args),
+            None => self.create(description, ""),
+        }
+    }
+
+    /// Returns every registered name in sorted order.
+    pub fn names(&self) -> Vec<String> {
+        self.factories.keys().cloned().collect()
+    }
+}
+
+/// Error returned when registering a mapper name that is already taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapperRegistrationError {
+    /// The name that was already registered.
+    pub name: String,
+}
+
+impl core::fmt::Display for MapperRegistrationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "mapper {:?} is already registered", self.name)
+    }
+}
+
+impl core::error::Error for MapperRegistrationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{CombinatorParseError, combinator_from_str};
+
+    #[test]
+    fn test_every_default_by_name() {
+        let defaults: Vec<(&str, MapperNode)> = vec![
+            ("for_lowercase", defaults::create_mapper_for_lowercase()),
+            ("for_uppercase", defaults::create_mapper_for_uppercase()),
+            ("for_letters", defaults::create_mapper_for_letters()),
+            (
+                "for_letters_cased",
+                defaults::create_mapper_for_letters_cased(),
+            ),
+            ("for_character", defaults::create_mapper_for_character()),
+            ("for_whitespace", defaults::create_mapper_for_whitespace()),
+            ("pyramid", defaults::create_mapper_pyramid()),
+            ("pyramid_at:200", defaults::create_mapper_pyramid_at(200)),
+            (
+                "pyramid_inverted",
+                defaults::create_mapper_pyramid_inverted(),
+            ),
+            ("to_lowercase", defaults::create_mapper_to_lowercase()),
+            ("to_uppercase", defaults::create_mapper_to_uppercase()),
+            (
+                "for_lowercase_latin1",
+                defaults::create_mapper_for_lowercase_latin1(),
+            ),
+            (
+                "for_uppercase_latin1",
+                defaults::create_mapper_for_uppercase_latin1(),
+            ),
+            (
+                "for_letters_latin1",
+                defaults::create_mapper_for_letters_latin1(),
+            ),
+            ("latin1_to_lower", defaults::create_mapper_latin1_to_lower()),
+            ("rot13", defaults::create_mapper_rot13()),
+            ("caesar:3", defaults::create_mapper_caesar(3)),
+            ("hex_value", defaults::create_mapper_hex_value()),
+            ("hex_digit_lower", defaults::create_mapper_hex_digit_lower()),
+            ("hex_digit_upper", defaults::create_mapper_hex_digit_upper()),
+            ("digit_value", defaults::create_mapper_digit_value()),
+            ("digit_char", defaults::create_mapper_digit_char()),
+            ("base64_value", defaults::create_mapper_base64_value()),
+            ("base64_char", defaults::create_mapper_base64_char()),
+            ("gray_encode", defaults::create_mapper_gray_encode()),
+            ("gray_decode", defaults::create_mapper_gray_decode()),
+            ("bit_reverse", defaults::create_mapper_bit_reverse()),
+            (
+                "unpack_bit:5",
+                defaults::create_mappers_unpack_bits()[5].clone(),
+            ),
+            ("log2", defaults::create_mapper_log2()),
+            ("exp", defaults::create_mapper_exp()),
+            ("gamma:22:10", defaults::create_mapper_gamma(22, 10)),
+            ("sigmoid:128:4", defaults::create_mapper_sigmoid(128, 4)),
+            ("relu:64", defaults::create_mapper_relu(64)),
+            (
+                "leaky_relu:64:1:4",
+                defaults::create_mapper_leaky_relu(64, 1, 4),
+            ),
+            ("tanh_like", defaults::create_mapper_tanh_like()),
+            ("gaussian:100:20", defaults::create_mapper_gaussian(100, 20)),
+            ("sine", defaults::create_mapper_sine()),
+            ("triangle", defaults::create_mapper_triangle()),
+            ("sawtooth", defaults::create_mapper_sawtooth()),
+            ("square:64", defaults::create_mapper_square(64)),
+            (
+                "sine_scaled:100:20",
+                defaults::create_mapper_sine_scaled(100, 20),
+            ),
+            (
+                "triangle_scaled:100:20",
+                defaults::create_mapper_triangle_scaled(100, 20),
+            ),
+            (
+                "sawtooth_scaled:100:20",
+                defaults::create_mapper_sawtooth_scaled(100, 20),
+            ),
+            (
+                "square_scaled:64:100:20",
+                defaults::create_mapper_square_scaled(64, 100, 20),
+            ),
+        ];
+        for (description, expected) in &defaults {
+            assert!(
+                mapper_from_str(description).as_ref() == Some(expected),
+                "{description}"
+            );
+        }
+        // Besides the defaults, only identity, fill and expr are built in.
+        assert_eq!(available_mappers().len(), defaults.len() + 3);
+        for info in available_mappers() {
+            let args = vec!["1"; info.parameters.len()].join(":");
+            assert!(
+                try_mapper_from_str(&format!("{}:{args}", info.name)).is_ok(),
+                "{}",
+                info.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_parameterized_forms() {
+        let identity = MapperNode::new_transformation(|x| x as u8);
+        assert!(mapper_from_str("identity") == Some(identity.clone()));
+        assert!(mapper_from_str("fill:128") == Some(MapperNode::new().with_fill(128)));
+        let double = mapper_from_str("expr:x*2").unwrap();
+        assert_eq!(double.transform(100), 200);
+        assert_eq!(double.transform(200), 255);
+        // Only the name is case-insensitive; spaces around it are ignored.
+        assert!(mapper_from_str(" ROT13 ") == mapper_from_str("rot13"));
+        assert!(mapper_from_str("Pyramid_At: 200").is_some());
+        assert!(mapper_from_str("expr:x % 3 == 0").is_none());
+        assert_eq!(
+            available_mappers()
+                .iter()
+                .find(|info| info.name == "gamma")
+                .map(|info| info.parameters),
+            Some(&["gamma_num", "gamma_den"][..])
+        );
+    }
+
+    #[test]
+    fn test_errors_match_combinator_parser() {
+        assert!(mapper_from_str("no_such_mapper").is_none());
+        assert_eq!(
+            try_mapper_from_str("No_Such_Mapper:1").err(),
+            Some(MapperParseError::UnknownName("no_such_mapper".to_string()))
+        );
+        assert!(matches!(
+            combinator_from_str("no_such_combinator"),
+            Err(CombinatorParseError::UnknownName(_))
+        ));
+        assert_eq!(
+            try_mapper_from_str("gamma:22").err().unwrap().to_string(),
+            "invalid arguments \"22\" for mapper \"gamma\": takes 2 arguments, got 1"
+        );
+        assert!(matches!(
+            try_mapper_from_str("fill:256"),
+            Err(MapperParseError::InvalidArguments { .. })
+        ));
+        assert!(matches!(
+            try_mapper_from_str("rot13:1"),
+            Err(MapperParseError::InvalidArguments { .. })
+        ));
+        assert!(try_mapper_from_str("relu").is_err());
+        assert!(try_mapper_from_str("expr:").is_err());
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = MapperRegistry::with_builtins();
+        let vowels = MapperNode::new().with_mapdata(b"aeiou".iter().map(|&c| (c, 255)));
+        registry.register_table("Vowels", vowels.clone()).unwrap();
+        registry
+            .register(
+                "shift",
+                Box::new(|args: &str| {
+                    let by: u8 = args.parse().map_err(|_| {
+                        MapperParseError::invalid_arguments("shift", args, "expected a byte")
+                    })?;
+                    Ok(MapperNode::new_transformation(|x| {
+                        (x as u8).wrapping_add(by)
+                    }))
+                }),
+            )
+            .unwrap();
+        assert!(registry.contains("VOWELS"));
+        assert!(registry.create("vowels", "").unwrap() == vowels);
+        assert!(registry.create("vowels", "1").is_err());
+        assert_eq!(
+            registry.create_from_str("SHIFT:3").unwrap().transform(255),
+            2
+        );
+        assert!(
+            registry.create_from_str("gamma:22:10").unwrap()
+                == defaults::create_mapper_gamma(22, 10)
+        );
+        assert_eq!(
+            registry.register_table("rot13", vowels.clone()).err(),
+            Some(MapperRegistrationError {
+                name: "rot13".to_string()
+            })
+        );
+        assert!(
+            registry
+                .replace("rot13", Box::new(|_: &str| Ok(MapperNode::new())))
+                .is_some()
+        );
+        assert!(registry.create("rot13", "").unwrap() == MapperNode::new());
+
+        let names = registry.names();
+        assert!(names.contains(&"vowels".to_string()));
+        assert!(names.contains(&"expr".to_string()));
+        assert!(names.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            MapperRegistry::new().create("rot13", "").err(),
+            Some(MapperParseError::UnknownName("rot13".to_string()))
+        );
+    }
+}
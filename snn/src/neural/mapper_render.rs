@@ -0,0 +1,126 @@
+/// Text rendering of mapper tables for inspecting them in a terminal.
+use alloc::string::String;
+
+use crate::neural::mapper::MapperNode;
+
+/// Characters from empty to full, used by `render_ascii`.
+const RAMP: &[u8] = b" .:-=+*#%@";
+/// Block characters from lowest to highest, used by `render_sparkline`.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// The number of characters in a sparkline; each covers 4 inputs.
+const SPARKLINE_WIDTH: usize = 64;
+
+impl MapperNode {
+    /// Renders the table as a `width` by `height` heatmap, with inputs rising from left to
+    /// right and outputs from bottom to top. Each column covers an equal share of the inputs,
+    /// and each character shows how many of them map into its row, from `' '` for none to `'@'`
+    /// for all. Every row ends with a newline; a zero `width` or `height` gives an empty string.
+    pub fn render_ascii(&self, width: usize, height: usize) -> String {
+        if width == 0 || height == 0 {
+            return String::new();
+        }
+        let mut counts = alloc::vec![0usize; width * height];
+        let mut sizes = alloc::vec![0usize; width];
+        for column in 0..width {
+            // Columns wider than an input sample the input they start at.
+            let start = column * 256 / width;
+            let end = ((column + 1) * 256 / width).max(start + 1);
+            for &value in &self.table()[start..end] {
+                let row = (255 - value as usize) * height / 256;
+                counts[row * width + column] += 1;
+            }
+            sizes[column] = end - start;
+        }
+        let mut out = String::with_capacity((width + 1) * height);
+        for row in counts.chunks(width) {
+            for (&count, &size) in row.iter().zip(&sizes) {
+                // Any hit shows at least the first visible character.
+                let level = (count * (RAMP.len() - 1)).div_ceil(size);
+                out.push(RAMP[level] as char);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders the table as one line of 64 block characters followed by a newline. Each
+    /// character shows the average output of 4 consecutive inputs, from `'▁'` to `'█'`.
+    pub fn render_sparkline(&self) -> String {
+        let mut out: String = self
+            .table()
+            .chunks(256 / SPARKLINE_WIDTH)
+            .map(|chunk| {
+                let average = chunk.iter().map(|&x| x as usize).sum::<usize>() / chunk.len();
+                BLOCKS[average * BLOCKS.len() / 256]
+            })
+            .collect();
+        out.push('\n');
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pyramid() -> MapperNode {
+        MapperNode::new_transformation(|i| {
+            if i < 128 {
+                2 * i as u8
+            } else {
+                2 * (255 - i) as u8
+            }
+        })
+    }
+
+    #[test]
+    fn test_identity_is_a_diagonal() {
+        let identity = MapperNode::new_transformation(|i| i as u8);
+        assert_eq!(identity.render_ascii(4, 4), "   @\n  @ \n @  \n@   \n");
+        assert_eq!(
+            identity.render_sparkline(),
+            "▁▁▁▁▁▁▁▁▂▂▂▂▂▂▂▂▃▃▃▃▃▃▃▃▄▄▄▄▄▄▄▄▅▅▅▅▅▅▅▅▆▆▆▆▆▆▆▆▇▇▇▇▇▇▇▇████████\n"
+        );
+        // Two rows per column: each half of a column's inputs lands in its own row.
+        assert_eq!(identity.render_ascii(2, 4), " +\n +\n+ \n+ \n");
+    }
+
+    #[test]
+    fn test_constant_is_flat() {
+        let constant = MapperNode::new().with_fill(100);
+        assert_eq!(constant.render_ascii(6, 3), "      \n@@@@@@\n      \n");
+        assert_eq!(MapperNode::new().render_sparkline(), "▁".repeat(64) + "\n");
+        assert_eq!(
+            MapperNode::new().with_fill(255).render_sparkline(),
+            "█".repeat(64) + "\n"
+        );
+    }
+
+    #[test]
+    fn test_pyramid_peaks_in_the_middle() {
+        let rendered = pyramid().render_ascii(8, 4);
+        assert_eq!(rendered, "   @@   \n  @  @  \n @    @ \n@      @\n");
+        let sparkline: alloc::vec::Vec<char> = pyramid().render_sparkline().chars().collect();
+        assert_eq!(sparkline[31], '█');
+        assert_eq!(sparkline[0], '▁');
+        assert_eq!(sparkline[63], '▁');
+    }
+
+    #[test]
+    fn test_shapes() {
+        let identity = MapperNode::new_transformation(|i| i as u8);
+        assert_eq!(identity.render_ascii(0, 5), "");
+        assert_eq!(identity.render_ascii(5, 0), "");
+        let wide = identity.render_ascii(300, 10);
+        assert_eq!(wide.lines().count(), 10);
+        assert!(wide.lines().all(|line| line.chars().count() == 300));
+        assert_eq!(wide, identity.render_ascii(300, 10));
+        // Full resolution draws exactly one point per input.
+        let full = identity.render_ascii(256, 256);
+        assert_eq!(full.matches('@').count(), 256);
+        assert_eq!(
+            full.matches(|c| c != '@' && c != ' ' && c != '\n').count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,269 @@
+/// Mapper tables that many nodes can share without copying, either reference-counted or
+/// borrowed from static storage.
+use alloc::sync::Arc;
+use core::ops::Deref;
+
+use crate::neural::mapper::MapperNode;
+use crate::neural::traits::Mapper;
+
+/// A borrowed 256-byte table viewed as a mapper, e.g. a `static [u8; 256]` compiled into the
+/// binary. Creating one copies nothing.
+///
+/// Derefs to `MapperNode`, so every read-only method (including `transform`) is available
+/// directly. Use `to_owned` for a copy that can be modified.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct MapperRef<'a>(&'a MapperNode);
+
+impl<'a> MapperRef<'a> {
+    /// Borrows `table`, where entry `i` is the output for input `i`.
+    pub const fn new(table: &'a [u8; 256]) -> MapperRef<'a> {
+        MapperRef(MapperNode::from_table_ref(table))
+    }
+
+    /// Returns a copy of the table that can be modified.
+    pub fn to_owned(self) -> MapperNode {
+        self.0.clone()
+    }
+
+    /// Returns the borrowed table as a mapper.
+    pub const fn get(self) -> &'a MapperNode {
+        self.0
+    }
+}
+
+impl Deref for MapperRef<'_> {
+    type Target = MapperNode;
+
+    fn deref(&self) -> &MapperNode {
+        self.0
+    }
+}
+
+impl<'a> From<&'a [u8; 256]> for MapperRef<'a> {
+    fn from(table: &'a [u8; 256]) -> Self {
+        MapperRef::new(table)
+    }
+}
+
+impl Mapper for MapperRef<'_> {
+    fn transform(&self, input: u8) -> u8 {
+        self.0.transform(input)
+    }
+
+    fn transform_slice(&self, input: &[u8], out: &mut [u8]) {
+        self.0.transform_slice(input, out);
+    }
+
+    fn transform_in_place(&self, buf: &mut [u8]) {
+        self.0.transform_in_place(buf);
+    }
+}
+
+/// A `MapperNode` behind an `Arc`, or a table borrowed for `'static`. Cloning shares the
+/// 256-byte table instead of copying it; `make_mut` copies it only when a shared or borrowed
+/// table is about to be modified.
+///
+/// Derefs to `MapperNode`, so every read-only method (including `transform`) is available directly.
+#[derive(Clone)]
+pub struct SharedMapper(Storage);
+
+/// Where the table of a `SharedMapper` lives.
+#[derive(Clone)]
+enum Storage {
+    Owned(Arc<MapperNode>),
+    Borrowed(&'static MapperNode),
+}
+
+impl SharedMapper {
+    /// Wraps `mapper` so that it can be shared.
+    pub fn new(mapper: MapperNode) -> SharedMapper {
+        SharedMapper(Storage::Owned(Arc::new(mapper)))
+    }
+
+    /// Shares a static table without copying it.
+    pub const fn borrowed(mapper: MapperRef<'static>) -> SharedMapper {
+        SharedMapper(Storage::Borrowed(mapper.get()))
+    }
+
+    /// Returns true if the table is borrowed rather than owned.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Storage::Borrowed(_))
+    }
+
+    /// Returns the table for modification, first copying it if other clones still share it
+    /// or it is borrowed.
+    pub fn make_mut(&mut self) -> &mut MapperNode {
+        if let Storage::Borrowed(mapper) = self.0 {
+            self.0 = Storage::Owned(Arc::new(mapper.clone()));
+        }
+        match &mut self.0 {
+            Storage::Owned(mapper) => Arc::make_mut(mapper),
+            Storage::Borrowed(_) => unreachable!("borrowed tables were copied above"),
+        }
+    }
+
+    /// Returns a mapper with `f` applied to this table, reusing the storage when it is not shared.
+    ///
+    /// # Arguments
+    /// * `f` - One of the `MapperNode` builder methods, e.g. `|m| m.with_fill(7)`.
+    pub fn with(mut self, f: impl FnOnce(&MapperNode) -> MapperNode) -> SharedMapper {
+        let updated = f(&self);
+        *self.make_mut() = updated;
+        self
+    }
+
+    /// Returns the table, copying it only if other clones still share it or it is borrowed.
+    pub fn into_inner(self) -> MapperNode {
+        match self.0 {
+            Storage::Owned(mapper) => Arc::unwrap_or_clone(mapper),
+            Storage::Borrowed(mapper) => mapper.clone(),
+        }
+    }
+
+    /// Returns true if both mappers point at the same table storage.
+    pub fn ptr_eq(a: &SharedMapper, b: &SharedMapper) -> bool {
+        core::ptr::eq::<MapperNode>(&**a, &**b)
+    }
+}
+
+impl Deref for SharedMapper {
+    type Target = MapperNode;
+
+    fn deref(&self) -> &MapperNode {
+        match &self.0 {
+            Storage::Owned(mapper) => mapper,
+            Storage::Borrowed(mapper) => mapper,
+        }
+    }
+}
+
+/// Compares the tables, regardless of where they are stored.
+impl PartialEq for SharedMapper {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for SharedMapper {}
+
+impl From<MapperNode> for SharedMapper {
+    fn from(mapper: MapperNode) -> Self {
+        SharedMapper::new(mapper)
+    }
+}
+
+impl From<MapperRef<'static>> for SharedMapper {
+    fn from(mapper: MapperRef<'static>) -> Self {
+        SharedMapper::borrowed(mapper)
+    }
+}
+
+impl Default for SharedMapper {
+    fn default() -> Self {
+        SharedMapper::new(MapperNode::new())
+    }
+}
+
+impl Mapper for SharedMapper {
+    fn transform(&self, input: u8) -> u8 {
+        (**self).transform(input)
+    }
+
+    fn transform_slice(&self, input: &[u8], out: &mut [u8]) {
+        (**self).transform_slice(input, out);
+    }
+
+    fn transform_in_place(&self, buf: &mut [u8]) {
+        (**self).transform_in_place(buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_until_modified() {
+        let original = SharedMapper::new(MapperNode::new().with_fill(7));
+        let mut copy = original.clone();
+        assert!(SharedMapper::ptr_eq(&original, &copy));
+        assert_eq!(copy.table().as_ptr(), original.table().as_ptr());
+
+        let updated = copy.with_mapdata([(3, 9)]);
+        *copy.make_mut() = updated;
+        assert!(!SharedMapper::ptr_eq(&original, &copy));
+        assert_eq!(original.transform(3), 7);
+        assert_eq!(copy.transform(3), 9);
+        assert_eq!(copy.transform(4), 7);
+    }
+
+    #[test]
+    fn test_unshared_modification_reuses_storage() {
+        let mut mapper = SharedMapper::new(MapperNode::new());
+        let before = mapper.table().as_ptr();
+        mapper.make_mut();
+        assert_eq!(mapper.table().as_ptr(), before);
+
+        let shifted = mapper.with(|m| m.with_fill(1));
+        assert_eq!(shifted.table().as_ptr(), before);
+        assert_eq!(shifted.transform(200), 1);
+    }
+
+    #[test]
+    fn test_with_copies_shared_table() {
+        let base = SharedMapper::from(MapperNode::new_transformation(|x| x as u8));
+        let inverted = base.clone().with(MapperNode::invert);
+        assert!(!SharedMapper::ptr_eq(&base, &inverted));
+        assert!(base.is_identity());
+        assert_eq!(inverted.transform(0), 255);
+        assert!(base.clone().into_inner().is_identity());
+    }
+
+    static SQUARES: [u8; 256] = {
+        let mut table = [0u8; 256];
+        let mut i = 0;
+        while i < 256 {
+            table[i] = ((i * i) >> 8) as u8;
+            i += 1;
+        }
+        table
+    };
+
+    #[test]
+    fn test_mapper_ref_borrows_without_copying() {
+        let borrowed = MapperRef::new(&SQUARES);
+        assert_eq!(borrowed.table().as_ptr(), SQUARES.as_ptr());
+        let owned = MapperNode::new_transformation(|i| ((i * i) >> 8) as u8);
+        for i in 0..=255 {
+            assert_eq!(borrowed.transform(i), owned.transform(i));
+        }
+        assert!(borrowed.is_monotonic_increasing());
+        assert!(*borrowed == owned);
+
+        let mut copy = borrowed.to_owned();
+        assert_ne!(copy.table().as_ptr(), SQUARES.as_ptr());
+        copy = copy.with_fill(1);
+        assert_eq!(copy.transform(200), 1);
+        assert_eq!(borrowed.transform(200), 156);
+    }
+
+    #[test]
+    fn test_shared_mapper_borrows_static_tables() {
+        let shared = SharedMapper::from(MapperRef::new(&SQUARES));
+        assert!(shared.is_borrowed());
+        assert_eq!(shared.table().as_ptr(), SQUARES.as_ptr());
+        let clone = shared.clone();
+        assert!(SharedMapper::ptr_eq(&shared, &clone));
+        assert!(shared == SharedMapper::new(MapperRef::new(&SQUARES).to_owned()));
+
+        // Modifying copies the table and leaves the static one alone.
+        let mut modified = clone;
+        let updated = modified.with_mapdata([(0, 9)]);
+        *modified.make_mut() = updated;
+        assert!(!modified.is_borrowed());
+        assert_ne!(modified.table().as_ptr(), SQUARES.as_ptr());
+        assert_eq!(modified.transform(0), 9);
+        assert_eq!(SQUARES[0], 0);
+        assert_eq!(shared.into_inner().transform(16), 1);
+    }
+}
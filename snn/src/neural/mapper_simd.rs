@@ -0,0 +1,54 @@
+/// SSSE3 accelerated table lookups for `MapperNode`.
+///
+/// The 256-entry table is split into 16 sub-tables of 16 bytes, selected by the high nibble of each
+/// input byte. Every sub-table is looked up with a single `pshufb` on the low nibble, and the result
+/// is kept only for the lanes whose high nibble matches.
+use core::arch::x86_64::{
+    __m128i, _mm_and_si128, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_or_si128, _mm_set1_epi8,
+    _mm_setzero_si128, _mm_shuffle_epi8, _mm_srli_epi16, _mm_storeu_si128,
+};
+
+/// The number of bytes processed per vector.
+const LANES: usize = 16;
+
+/// Returns whether the running CPU supports the accelerated path.
+pub(crate) fn is_supported() -> bool {
+    is_x86_feature_detected!("ssse3")
+}
+
+/// Transforms `input` into `output` using the 256-entry `table`.
+/// Trailing bytes that do not fill a whole vector are left to the caller.
+///
+/// # Returns
+/// The number of bytes that were transformed.
+///
+/// # Safety
+/// The caller must ensure the CPU supports SSSE3 and that `output` is at least as long as `input`.
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn transform_slice(table: &[u8; 256], input: &[u8], output: &mut [u8]) -> usize {
+    let mut sub_tables = [_mm_setzero_si128(); 16];
+    for (i, sub_table) in sub_tables.iter_mut().enumerate() {
+        // SAFETY: each sub-table is 16 bytes inside the 256 byte table.
+        *sub_table = unsafe { _mm_loadu_si128(table[i * LANES..].as_ptr() as *const __m128i) };
+    }
+
+    let low_mask = _mm_set1_epi8(0x0F);
+    let chunks = input.len() / LANES;
+    for chunk in 0..chunks {
+        let offset = chunk * LANES;
+        // SAFETY: `offset + LANES <= input.len() <= output.len()`.
+        let v = unsafe { _mm_loadu_si128(input[offset..].as_ptr() as *const __m128i) };
+        let lo = _mm_and_si128(v, low_mask);
+        let hi = _mm_and_si128(_mm_srli_epi16(v, 4), low_mask);
+
+        let mut result = _mm_setzero_si128();
+        for (i, &sub_table) in sub_tables.iter().enumerate() {
+            let selected = _mm_cmpeq_epi8(hi, _mm_set1_epi8(i as i8));
+            let looked_up = _mm_shuffle_epi8(sub_table, lo);
+            result = _mm_or_si128(result, _mm_and_si128(looked_up, selected));
+        }
+        // SAFETY: see the load above.
+        unsafe { _mm_storeu_si128(output[offset..].as_mut_ptr() as *mut __m128i, result) };
+    }
+    chunks * LANES
+}
@@ -1,4 +1,99 @@
 pub mod mapper;
+pub mod mapper16;
 pub mod mapper_defaults;
+pub mod mapper_ext;
+pub mod mapper_expr;
+pub mod mapper_registry;
+pub mod mapper_shared;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod mapper_simd;
+#[cfg(feature = "parallel")]
+pub mod mapper_parallel;
+pub mod mapper_render;
+#[cfg(feature = "std")]
+pub mod training;
+#[cfg(feature = "std")]
+pub mod training_dataset;
+#[cfg(feature = "std")]
+pub mod training_genetic;
 pub mod traits;
-pub mod combinator;
\ No newline at end of file
+#[cfg(feature = "std")]
+pub mod checkpoint;
+pub mod combinator;
+pub mod combinator_bits;
+pub mod combinator_compare;
+pub mod combinator_fold;
+pub mod combinator_composite;
+#[cfg(feature = "json")]
+pub mod combinator_json;
+pub mod combinator_registry;
+pub mod combinator_routing;
+pub mod combinator_stateful;
+pub mod combinator_stats;
+pub mod combinator_streaming;
+pub mod combinator_wide;
+pub mod digest;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+pub mod evaluation;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod graph;
+#[cfg(feature = "std")]
+pub mod io;
+pub mod iter;
+#[cfg(feature = "json")]
+pub(crate) mod json;
+pub mod layer;
+pub mod macros;
+#[cfg(feature = "std")]
+pub mod network;
+#[cfg(feature = "std")]
+pub mod network_binary;
+#[cfg(feature = "std")]
+pub mod network_compile;
+#[cfg(feature = "std")]
+pub mod network_digest;
+#[cfg(feature = "std")]
+pub mod network_diff;
+#[cfg(feature = "std")]
+pub mod network_dot;
+#[cfg(feature = "std")]
+pub mod network_equivalence;
+#[cfg(feature = "std")]
+pub mod network_format;
+#[cfg(all(feature = "std", feature = "json"))]
+pub mod network_json;
+#[cfg(feature = "std")]
+pub mod network_observe;
+#[cfg(feature = "std")]
+pub mod network_optimize;
+#[cfg(feature = "std")]
+pub mod network_prune;
+#[cfg(feature = "std")]
+pub mod network_random;
+#[cfg(feature = "std")]
+pub mod network_sensitivity;
+#[cfg(feature = "std")]
+pub mod network_stats;
+#[cfg(feature = "toml")]
+pub mod network_toml;
+#[cfg(feature = "std")]
+pub mod network_windows;
+pub mod node;
+pub mod noise;
+pub mod normalizer;
+pub mod partial_mapper;
+#[cfg(feature = "parallel")]
+pub mod pipeline;
+mod rng;
+#[cfg(feature = "std")]
+pub mod stateful_network;
+#[cfg(feature = "test-util")]
+pub mod testing;
+#[cfg(feature = "toml")]
+pub mod toml;
+#[cfg(feature = "std")]
+mod topology;
@@ -0,0 +1,917 @@
+/// A stack of layers evaluated one after another.
+use crate::neural::combinator::MaxCombinatorNode;
+use crate::neural::layer::{Layer, LayerError, Wiring};
+use crate::neural::mapper_shared::SharedMapper;
+use crate::neural::node::{NodeError, SignalNode, SignalNodeBuilder};
+use crate::neural::normalizer::Normalizer;
+
+/// A feed-forward network: the outputs of each layer are the inputs of the next.
+/// A network without layers returns its input unchanged.
+#[derive(Default)]
+pub struct Network {
+    layers: Vec<Layer>,
+}
+
+impl Network {
+    /// Returns a builder that wires the layers together and validates them in `build`.
+    pub fn builder() -> NetworkBuilder {
+        NetworkBuilder::default()
+    }
+
+    /// Creates a network, checking that each layer's input width matches
+    /// the previous layer's output width.
+    pub fn try_new(layers: Vec<Layer>) -> Result<Self, NetworkError> {
+        check_layers(&layers)?;
+        Ok(Self { layers })
+    }
+
+    /// Checks the invariants of `try_new` again: that the widths of consecutive layers match
+    /// and that no name is used twice. A network can only break them through `node_mut`,
+    /// by renaming a node to a name another node has.
+    pub fn validate(&self) -> Result<(), NetworkError> {
+        check_layers(&self.layers)
+    }
+
+    /// Runs the input through every layer and returns the outputs of the last one.
+    pub fn forward(&self, input: &[u8]) -> Result<Vec<u8>, EvalError> {
+        self.check_input(input)?;
+        Ok(self
+            .layers
+            .iter()
+            .fold(input.to_vec(), |values, layer| layer.evaluate(&values)))
+    }
+
+    /// Runs the input through every layer and returns the outputs of each layer in order.
+    /// An empty network returns no vectors.
+    pub fn forward_trace(&self, input: &[u8]) -> Result<Vec<Vec<u8>>, EvalError> {
+        self.check_input(input)?;
+        let mut trace: Vec<Vec<u8>> = Vec::with_capacity(self.layers.len());
+        for layer in &self.layers {
+            let values = layer.evaluate(trace.last().map_or(input, Vec::as_slice));
+            trace.push(values);
+        }
+        Ok(trace)
+    }
+
+    fn check_input(&self, input: &[u8]) -> Result<(), EvalError> {
+        match self.input_width() {
+            Some(expected) if expected != input.len() => Err(EvalError::InputWidth {
+                expected,
+                actual: input.len(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the node called `name`.
+    pub fn node(&self, name: &str) -> Option<&SignalNode> {
+        let (layer, node) = self.position(name)?;
+        Some(&self.layers[layer].nodes()[node])
+    }
+
+    /// Returns the node called `name` for modification.
+    /// Use `replace_node` to swap in a different node, so that its input count is checked.
+    pub fn node_mut(&mut self, name: &str) -> Option<&mut SignalNode> {
+        let (layer, node) = self.position(name)?;
+        self.layers[layer].node_mut(node)
+    }
+
+    /// Replaces the node called `name`, after checking that `node` accepts the number of inputs
+    /// wired to it. The replacement keeps the name `name`.
+    pub fn replace_node(&mut self, name: &str, mut node: SignalNode) -> Result<(), ReplaceError> {
+        let (layer, index) = self
+            .position(name)
+            .ok_or_else(|| ReplaceError::NotFound(name.to_string()))?;
+        node.set_name(name);
+        self.layers[layer]
+            .replace_node(index, node)
+            .map_err(|error| ReplaceError::Layer {
+                name: name.to_string(),
+                layer,
+                error,
+            })?;
+        Ok(())
+    }
+
+    /// Returns the layer and node index of the node called `name`.
+    fn position(&self, name: &str) -> Option<(usize, usize)> {
+        self.layers.iter().enumerate().find_map(|(layer, l)| {
+            l.nodes()
+                .iter()
+                .position(|node| node.name() == Some(name))
+                .map(|node| (layer, node))
+        })
+    }
+
+    /// Returns the layers in evaluation order.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Returns the layers for rewriting by the optimizer. Callers must keep the widths consistent.
+    pub(crate) fn layers_mut(&mut self) -> &mut Vec<Layer> {
+        &mut self.layers
+    }
+
+    /// Returns the total number of nodes, which is the number of node evaluations per forward pass.
+    pub fn node_count(&self) -> usize {
+        self.layers.iter().map(Layer::len).sum()
+    }
+
+    /// Returns the number of layers.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns true if the network has no layers.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Returns the input width of the first layer, or `None` for an empty network.
+    pub fn input_width(&self) -> Option<usize> {
+        self.layers.first().map(Layer::input_width)
+    }
+
+    /// Returns the output width of the last layer, or `None` for an empty network.
+    pub fn output_width(&self) -> Option<usize> {
+        self.layers.last().map(Layer::output_width)
+    }
+}
+
+fn check_layers(layers: &[Layer]) -> Result<(), NetworkError> {
+    for (index, pair) in layers.windows(2).enumerate() {
+        let (expected, actual) = (pair[0].output_width(), pair[1].input_width());
+        if expected != actual {
+            return Err(NetworkError::WidthMismatch {
+                layer: index + 1,
+                expected,
+                actual,
+            });
+        }
+    }
+    if let Some(name) = duplicate_name(layers) {
+        return Err(NetworkError::DuplicateName(name));
+    }
+    Ok(())
+}
+
+/// Returns the first name that is given to more than one node.
+fn duplicate_name(layers: &[Layer]) -> Option<String> {
+    let mut seen = std::collections::HashSet::new();
+    layers
+        .iter()
+        .flat_map(|layer| layer.nodes())
+        .filter_map(SignalNode::name)
+        .find(|&name| !seen.insert(name))
+        .map(str::to_string)
+}
+
+/// A layer waiting to be built by `NetworkBuilder`.
+enum PendingLayer {
+    /// Nodes still to be built, wired to every output of the previous layer.
+    Dense(Vec<SignalNodeBuilder>),
+    /// Nodes with the given wiring.
+    Nodes(Vec<SignalNode>, Wiring),
+    /// A layer that was built by hand.
+    Built(Layer),
+    /// One single-input node per output of the previous layer, each applying the mapper.
+    OutputMapper(SharedMapper),
+    /// A pass-through layer that normalizes the outputs of the previous layer.
+    Normalizer(Normalizer),
+}
+
+/// Builds a `Network` one layer at a time. Each layer reads the outputs of the one before it,
+/// and the first reads the `input_width` inputs of the network.
+/// Nothing is validated until `build`, which reports the first layer and node at fault.
+#[derive(Default)]
+pub struct NetworkBuilder {
+    input_width: Option<usize>,
+    layers: Vec<PendingLayer>,
+}
+
+impl NetworkBuilder {
+    /// Sets the number of inputs of the network. Required unless the first layer is added
+    /// with `layer`, which knows its own input width.
+    pub fn input_width(mut self, width: usize) -> Self {
+        self.input_width = Some(width);
+        self
+    }
+
+    /// Adds a dense layer of `node_count` nodes, each configured by `configure`.
+    pub fn layer_dense(
+        mut self,
+        node_count: usize,
+        mut configure: impl FnMut(SignalNodeBuilder) -> SignalNodeBuilder,
+    ) -> Self {
+        let builders = (0..node_count)
+            .map(|_| configure(SignalNode::builder()))
+            .collect();
+        self.layers.push(PendingLayer::Dense(builders));
+        self
+    }
+
+    /// Adds a dense layer made of the given nodes.
+    pub fn layer_nodes(mut self, nodes: Vec<SignalNode>) -> Self {
+        self.layers.push(PendingLayer::Nodes(nodes, Wiring::Dense));
+        self
+    }
+
+    /// Adds a layer where node `i` reads the previous outputs listed in `wiring[i]`.
+    pub fn layer_sparse(mut self, nodes: Vec<SignalNode>, wiring: Vec<Vec<usize>>) -> Self {
+        self.layers
+            .push(PendingLayer::Nodes(nodes, Wiring::Sparse(wiring)));
+        self
+    }
+
+    /// Adds a layer that was built by hand.
+    pub fn layer(mut self, layer: Layer) -> Self {
+        self.layers.push(PendingLayer::Built(layer));
+        self
+    }
+
+    /// Passes every output of the network through `mapper`.
+    /// This adds a final layer with one single-input node per output, all sharing the table.
+    pub fn output_mapper(mut self, mapper: impl Into<SharedMapper>) -> Self {
+        self.layers.push(PendingLayer::OutputMapper(mapper.into()));
+        self
+    }
+
+    /// Normalizes the outputs of the previous layer as a whole.
+    /// This adds a pass-through layer with one single-input node per output.
+    pub fn normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.layers.push(PendingLayer::Normalizer(normalizer));
+        self
+    }
+
+    /// Builds the network, validating every node, every layer's wiring, and the widths
+    /// between layers.
+    pub fn build(self) -> Result<Network, BuildError> {
+        let mut width = self.input_width;
+        let mut layers = Vec::with_capacity(self.layers.len());
+        for (index, pending) in self.layers.into_iter().enumerate() {
+            let layer = match pending {
+                PendingLayer::Built(layer) => {
+                    if let Some(expected) = width
+                        && expected != layer.input_width()
+                    {
+                        return Err(BuildError::WidthMismatch {
+                            layer: index,
+                            expected,
+                            actual: layer.input_width(),
+                        });
+                    }
+                    layer
+                }
+                PendingLayer::Dense(builders) => {
+                    let nodes = builders
+                        .into_iter()
+                        .enumerate()
+                        .map(|(node, builder)| {
+                            builder.build().map_err(|error| BuildError::Node {
+                                layer: index,
+                                node,
+                                error,
+                            })
+                        })
+                        .collect::<Result<_, _>>()?;
+                    let width = width.ok_or(BuildError::MissingInputWidth)?;
+                    Layer::dense(nodes, width).map_err(|error| BuildError::Layer {
+                        layer: index,
+                        error,
+                    })?
+                }
+                PendingLayer::Nodes(nodes, wiring) => {
+                    let width = width.ok_or(BuildError::MissingInputWidth)?;
+                    Layer::new(nodes, wiring, width).map_err(|error| BuildError::Layer {
+                        layer: index,
+                        error,
+                    })?
+                }
+                PendingLayer::OutputMapper(mapper) => {
+                    let width = width.ok_or(BuildError::MissingInputWidth)?;
+                    let nodes = (0..width)
+                        .map(|_| {
+                            SignalNode::builder()
+                                .shared_mapper(mapper.clone())
+                                .combinator(Box::new(MaxCombinatorNode::new()))
+                                .build()
+                                .expect("a max node with a shared mapper is always valid")
+                        })
+                        .collect();
+                    let wiring = (0..width).map(|i| vec![i]).collect();
+                    Layer::sparse(nodes, wiring, width).map_err(|error| BuildError::Layer {
+                        layer: index,
+                        error,
+                    })?
+                }
+                PendingLayer::Normalizer(normalizer) => {
+                    let width = width.ok_or(BuildError::MissingInputWidth)?;
+                    Layer::splitter(width, 1).with_normalizer(normalizer)
+                }
+            };
+            width = Some(layer.output_width());
+            layers.push(layer);
+        }
+        if let Some(name) = duplicate_name(&layers) {
+            return Err(BuildError::DuplicateName(name));
+        }
+        Ok(Network { layers })
+    }
+}
+
+/// Error returned by `NetworkBuilder::build`. Layers are numbered from 0 in the order they were added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// The first layer needs `input_width` to know how many inputs it reads.
+    MissingInputWidth,
+    /// A node of a `layer_dense` layer could not be built.
+    Node {
+        layer: usize,
+        node: usize,
+        error: NodeError,
+    },
+    /// A layer's wiring or nodes are invalid.
+    Layer { layer: usize, error: LayerError },
+    /// A hand-built layer reads `actual` inputs but the layer before it produces `expected` outputs.
+    WidthMismatch {
+        layer: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// More than one node has this name.
+    DuplicateName(String),
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::MissingInputWidth => write!(f, "network has no input width"),
+            BuildError::Node { layer, node, error } => {
+                write!(f, "layer {layer}: node {node}: {error}")
+            }
+            BuildError::Layer { layer, error } => write!(f, "layer {layer}: {error}"),
+            BuildError::WidthMismatch {
+                layer,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "layer {layer} takes {actual} inputs, but {expected} values feed it"
+            ),
+            BuildError::DuplicateName(name) => write!(f, "more than one node is named {name:?}"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BuildError::Node { error, .. } => Some(error),
+            BuildError::Layer { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned when the layers of a `Network` do not fit together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkError {
+    /// Layer `layer` reads `actual` inputs but the layer before it produces `expected` outputs.
+    WidthMismatch {
+        layer: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// More than one node has this name.
+    DuplicateName(String),
+}
+
+impl std::fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::WidthMismatch {
+                layer,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "layer {layer} takes {actual} inputs, but layer {} produces {expected} outputs",
+                layer - 1
+            ),
+            NetworkError::DuplicateName(name) => {
+                write!(f, "more than one node is named {name:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+/// Error returned by `Network::replace_node`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplaceError {
+    /// No node has this name.
+    NotFound(String),
+    /// The replacement does not accept the inputs wired to the named node.
+    Layer {
+        name: String,
+        layer: usize,
+        error: LayerError,
+    },
+}
+
+impl std::fmt::Display for ReplaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplaceError::NotFound(name) => write!(f, "no node is named {name:?}"),
+            ReplaceError::Layer { name, layer, error } => {
+                write!(f, "cannot replace node {name:?}: layer {layer}: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplaceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplaceError::Layer { error, .. } => Some(error),
+            ReplaceError::NotFound(_) => None,
+        }
+    }
+}
+
+/// Error returned when a `Network` cannot evaluate an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// The input does not match the width of the first layer.
+    InputWidth { expected: usize, actual: usize },
+    /// Windows were requested with a stride of zero.
+    ZeroStride,
+    /// Windows were requested from a network without layers, which has no input width.
+    NoLayers,
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::InputWidth { expected, actual } => {
+                write!(f, "network expects {expected} inputs, got {actual}")
+            }
+            EvalError::ZeroStride => write!(f, "window stride must be at least 1"),
+            EvalError::NoLayers => write!(f, "network has no layers to take a window width from"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode};
+    use crate::neural::mapper::MapperNode;
+    use crate::neural::mapper_defaults::{
+        create_mapper_for_lowercase, create_mapper_for_uppercase,
+    };
+    use crate::neural::mapper_shared::MapperRef;
+    use crate::neural::node::SignalNode;
+    use crate::neural::traits::Combinator;
+
+    fn detector(mapper: MapperNode) -> SignalNode {
+        SignalNode::builder()
+            .shared_mapper(mapper)
+            .combinator(Box::new(MaxCombinatorNode::new()))
+            .build()
+            .unwrap()
+    }
+
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    /// Classifies a single byte as 0 (other), 1 (letter) or 2 (digit).
+    fn byte_classifier() -> Network {
+        let digits = MapperNode::new().with_range(b'0'..=b'9', 255);
+        // Layer 0: lowercase, uppercase and digit detectors.
+        let detect = Layer::dense(
+            vec![
+                detector(create_mapper_for_lowercase()),
+                detector(create_mapper_for_uppercase()),
+                detector(digits),
+            ],
+            1,
+        )
+        .unwrap();
+        // Layer 1: letter = lowercase or uppercase, digit passes through.
+        let merge = Layer::sparse(
+            vec![
+                node(MaxCombinatorNode::new()),
+                node(MaxCombinatorNode::new()),
+            ],
+            vec![vec![0, 1], vec![2]],
+            3,
+        )
+        .unwrap();
+        // Layer 2: scale the flags to class codes and add them.
+        let classify = Layer::dense(
+            vec![
+                SignalNode::builder()
+                    .input_mapper(MapperNode::new().with_range(255..=255, 1))
+                    .input_mapper(MapperNode::new().with_range(255..=255, 2))
+                    .combinator(Box::new(AdditionCombinatorNode::new()))
+                    .build()
+                    .unwrap(),
+            ],
+            2,
+        )
+        .unwrap();
+        Network::try_new(vec![detect, merge, classify]).unwrap()
+    }
+
+    #[test]
+    fn test_byte_classifier() {
+        let network = byte_classifier();
+        assert_eq!(network.len(), 3);
+        assert_eq!(network.input_width(), Some(1));
+        assert_eq!(network.output_width(), Some(1));
+        for (byte, class) in [
+            (b'a', 1),
+            (b'Z', 1),
+            (b'5', 2),
+            (b'0', 2),
+            (b' ', 0),
+            (b'~', 0),
+            (0, 0),
+        ] {
+            assert_eq!(
+                network.forward(&[byte]),
+                Ok(vec![class]),
+                "{:?}",
+                byte as char
+            );
+        }
+    }
+
+    #[test]
+    fn test_forward_trace() {
+        let network = byte_classifier();
+        let trace = network.forward_trace(b"Q").unwrap();
+        assert_eq!(trace, vec![vec![0, 255, 0], vec![255, 0], vec![1]]);
+        assert_eq!(trace.last(), network.forward(b"Q").ok().as_ref());
+    }
+
+    #[test]
+    fn test_empty_network_is_identity() {
+        let network = Network::try_new(vec![]).unwrap();
+        assert!(network.is_empty());
+        assert_eq!(network.input_width(), None);
+        assert_eq!(network.forward(&[1, 2, 3]), Ok(vec![1, 2, 3]));
+        assert_eq!(network.forward(&[]), Ok(vec![]));
+        assert_eq!(network.forward_trace(&[1, 2, 3]), Ok(vec![]));
+        assert!(Network::default().is_empty());
+    }
+
+    #[test]
+    fn test_single_layer() {
+        let layer = Layer::dense(vec![node(MaxCombinatorNode::new())], 2).unwrap();
+        let network = Network::try_new(vec![layer]).unwrap();
+        assert_eq!(network.forward(&[4, 9]), Ok(vec![9]));
+        assert_eq!(
+            network.forward(&[4]),
+            Err(EvalError::InputWidth {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_width_mismatch() {
+        let first = Layer::dense(vec![node(MaxCombinatorNode::new())], 2).unwrap();
+        let second = Layer::dense(vec![node(MaxCombinatorNode::new())], 1).unwrap();
+        let third = Layer::dense(vec![node(MaxCombinatorNode::new())], 3).unwrap();
+        let error = Network::try_new(vec![first, second, third]).err().unwrap();
+        assert_eq!(
+            error,
+            NetworkError::WidthMismatch {
+                layer: 2,
+                expected: 1,
+                actual: 3
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "layer 2 takes 3 inputs, but layer 1 produces 1 outputs"
+        );
+    }
+
+    #[test]
+    fn test_builder_matches_manual() {
+        let manual = byte_classifier();
+        let digits = MapperNode::new().with_range(b'0'..=b'9', 255);
+        let built = Network::builder()
+            .input_width(1)
+            .layer_nodes(vec![
+                detector(create_mapper_for_lowercase()),
+                detector(create_mapper_for_uppercase()),
+                detector(digits),
+            ])
+            .layer_sparse(
+                vec![
+                    node(MaxCombinatorNode::new()),
+                    node(MaxCombinatorNode::new()),
+                ],
+                vec![vec![0, 1], vec![2]],
+            )
+            .layer_dense(1, |node| {
+                node.input_mapper(MapperNode::new().with_range(255..=255, 1))
+                    .input_mapper(MapperNode::new().with_range(255..=255, 2))
+                    .combinator(Box::new(AdditionCombinatorNode::new()))
+            })
+            .build()
+            .unwrap();
+        assert_eq!(built.len(), manual.len());
+        for byte in 0..=255u8 {
+            assert_eq!(built.forward(&[byte]), manual.forward(&[byte]), "{byte}");
+        }
+    }
+
+    #[test]
+    fn test_builder_output_mapper() {
+        let network = Network::builder()
+            .input_width(3)
+            .layer_dense(2, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .output_mapper(MapperNode::new_transformation(|x| 255 - x as u8))
+            .build()
+            .unwrap();
+        assert_eq!(network.len(), 2);
+        assert_eq!(network.output_width(), Some(2));
+        assert_eq!(network.forward(&[1, 50, 7]), Ok(vec![205, 205]));
+    }
+
+    #[test]
+    fn test_builder_static_tables() {
+        static INVERT: [u8; 256] = {
+            let mut table = [0u8; 256];
+            let mut i = 0;
+            while i < 256 {
+                table[i] = 255 - i as u8;
+                i += 1;
+            }
+            table
+        };
+        let network = Network::builder()
+            .input_width(3)
+            .layer_dense(2, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+                    .shared_mapper(MapperRef::new(&INVERT))
+            })
+            .output_mapper(MapperRef::new(&INVERT))
+            .build()
+            .unwrap();
+        // Every node reads the static table itself instead of a copy.
+        for node in network.layers().iter().flat_map(Layer::nodes) {
+            assert_eq!(
+                node.input_mapper(0).unwrap().table().as_ptr(),
+                INVERT.as_ptr()
+            );
+        }
+        assert_eq!(network.forward(&[1, 50, 7]), Ok(vec![1, 1]));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut network = Network::builder()
+            .input_width(1)
+            .layer_dense(2, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .build()
+            .unwrap();
+        assert_eq!(network.validate(), Ok(()));
+        network.layers_mut()[0].node_mut(0).unwrap().set_name("a");
+        network.layers_mut()[0].node_mut(1).unwrap().set_name("a");
+        assert_eq!(
+            network.validate(),
+            Err(NetworkError::DuplicateName("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_builder_normalizer() {
+        let network = Network::builder()
+            .input_width(3)
+            .layer_sparse(
+                vec![
+                    node(MaxCombinatorNode::new()),
+                    node(MaxCombinatorNode::new()),
+                    node(MaxCombinatorNode::new()),
+                ],
+                vec![vec![0], vec![1], vec![2]],
+            )
+            .normalizer(Normalizer::sum())
+            .build()
+            .unwrap();
+        assert_eq!(network.len(), 2);
+        assert_eq!(network.output_width(), Some(3));
+        assert_eq!(network.forward(&[10, 30, 60]), Ok(vec![26, 77, 153]));
+        assert_eq!(network.forward(&[0, 0, 0]), Ok(vec![0, 0, 0]));
+        assert_eq!(network.layers()[1].normalizer(), Some(&Normalizer::sum()));
+        assert_eq!(
+            Network::builder()
+                .normalizer(Normalizer::linear())
+                .build()
+                .err(),
+            Some(BuildError::MissingInputWidth)
+        );
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        assert_eq!(
+            Network::builder()
+                .layer_dense(1, |node| node
+                    .combinator(Box::new(MaxCombinatorNode::new())))
+                .build()
+                .err(),
+            Some(BuildError::MissingInputWidth)
+        );
+
+        let missing = Network::builder()
+            .input_width(2)
+            .layer_dense(2, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .layer_dense(1, |node| node)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            missing,
+            BuildError::Node {
+                layer: 1,
+                node: 0,
+                error: NodeError::MissingCombinator
+            }
+        );
+        assert_eq!(
+            missing.to_string(),
+            "layer 1: node 0: node has no combinator"
+        );
+
+        let wiring = Network::builder()
+            .input_width(2)
+            .layer_sparse(
+                vec![
+                    node(MaxCombinatorNode::new()),
+                    node(MaxCombinatorNode::new()),
+                ],
+                vec![vec![0], vec![0, 2]],
+            )
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(
+            wiring,
+            BuildError::Layer {
+                layer: 0,
+                error: LayerError::IndexOutOfRange {
+                    node: 1,
+                    index: 2,
+                    ..
+                }
+            }
+        ));
+
+        let layer = Layer::dense(vec![node(MaxCombinatorNode::new())], 3).unwrap();
+        let width = Network::builder()
+            .input_width(2)
+            .layer(layer)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            width,
+            BuildError::WidthMismatch {
+                layer: 0,
+                expected: 2,
+                actual: 3
+            }
+        );
+    }
+
+    /// Two named detectors feeding a named sum.
+    fn named_network() -> Network {
+        Network::builder()
+            .input_width(2)
+            .layer_sparse(
+                vec![
+                    SignalNode::builder()
+                        .name("left")
+                        .combinator(Box::new(MaxCombinatorNode::new()))
+                        .build()
+                        .unwrap(),
+                    SignalNode::builder()
+                        .name("right")
+                        .combinator(Box::new(MaxCombinatorNode::new()))
+                        .build()
+                        .unwrap(),
+                ],
+                vec![vec![0], vec![1]],
+            )
+            .layer_dense(1, |node| {
+                node.name("sum")
+                    .combinator(Box::new(AdditionCombinatorNode::new()))
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_node_lookup() {
+        let network = named_network();
+        assert_eq!(
+            network.node("sum").unwrap().combinator().identifier(),
+            "addition"
+        );
+        assert_eq!(network.node("right").unwrap().name(), Some("right"));
+        assert!(network.node("missing").is_none());
+    }
+
+    #[test]
+    fn test_hot_swap_activation() {
+        let mut network = named_network();
+        assert_eq!(network.forward(&[10, 20]), Ok(vec![30]));
+        network
+            .node_mut("left")
+            .unwrap()
+            .set_activation(Some(MapperNode::new_transformation(|x| x as u8 / 2)));
+        assert_eq!(network.forward(&[10, 20]), Ok(vec![25]));
+        network
+            .node_mut("sum")
+            .unwrap()
+            .set_activation(Some(MapperNode::new().with_range(20..=255, 255)));
+        assert_eq!(network.forward(&[10, 20]), Ok(vec![255]));
+        assert_eq!(network.forward(&[10, 5]), Ok(vec![0]));
+    }
+
+    #[test]
+    fn test_replace_node() {
+        let mut network = named_network();
+        network
+            .replace_node("sum", node(MaxCombinatorNode::new()))
+            .unwrap();
+        assert_eq!(
+            network.node("sum").unwrap().combinator().identifier(),
+            "max"
+        );
+        assert_eq!(network.forward(&[10, 20]), Ok(vec![20]));
+
+        let three_inputs = SignalNode::builder()
+            .combinator(Box::new(MaxCombinatorNode::new()))
+            .input_count(3)
+            .build()
+            .unwrap();
+        let error = network.replace_node("left", three_inputs).err().unwrap();
+        assert!(matches!(
+            &error,
+            ReplaceError::Layer { name, layer: 0, error: LayerError::Node { node: 0, .. } } if name == "left"
+        ));
+        assert_eq!(
+            network.replace_node("nope", node(MaxCombinatorNode::new())),
+            Err(ReplaceError::NotFound("nope".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let named = |name: &str| {
+            SignalNode::builder()
+                .name(name)
+                .combinator(Box::new(MaxCombinatorNode::new()))
+                .build()
+                .unwrap()
+        };
+        let error = Network::builder()
+            .input_width(1)
+            .layer_nodes(vec![named("a"), named("b")])
+            .layer_nodes(vec![named("a")])
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(error, BuildError::DuplicateName("a".to_string()));
+
+        let first = Layer::dense(vec![named("x")], 1).unwrap();
+        let second = Layer::dense(vec![named("x")], 1).unwrap();
+        assert_eq!(
+            Network::try_new(vec![first, second]).err(),
+            Some(NetworkError::DuplicateName("x".to_string()))
+        );
+    }
+}
@@ -0,0 +1,489 @@
+/// Compact binary format for whole networks.
+///
+/// All integers are little-endian. The data starts with the magic bytes `SNNB` and the
+/// format version as a `u16`, followed by the layer count as a `u32` and then each layer:
+///
+/// - layer: `u32` input width, `u32` node count, `u8` wiring (0 dense, 1 sparse, plus
+///   `0x80` if the layer has a normalizer), for sparse wiring per node a `u32` index count and
+///   that many `u32` indices, then the nodes, then the normalizer string if flagged.
+/// - node: the combinator identifier as a string, a `u8` flag and the parameters string if
+///   the flag is 1, the input mappers as a `u8` (0 none, 1 one shared table, 2 a `u32` count
+///   and that many tables), a `u8` flag and the activation table if set, and a `u8` flag
+///   and the `u32` input count if set, and a `u8` flag and the name string if set.
+/// - string: `u16` byte length and UTF-8 bytes.
+/// - table: the 256 raw bytes of the mapper.
+use std::io::{Read, Write};
+
+use crate::neural::combinator::combinator_from_str;
+use crate::neural::layer::{Layer, Wiring};
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::Network;
+use crate::neural::network_format::{FORMAT_VERSION, NetworkFormatError, build_node};
+use crate::neural::node::{InputMappers, SignalNode};
+use crate::neural::normalizer::Normalizer;
+
+/// The bytes every binary network starts with.
+pub const MAGIC: [u8; 4] = *b"SNNB";
+
+/// Set in a layer's wiring byte when a normalizer string follows its nodes.
+const NORMALIZER_FLAG: u8 = 0x80;
+
+impl Network {
+    /// Writes the network in the binary format.
+    ///
+    /// Fails with `InvalidInput` if a combinator identifier or parameter string is longer
+    /// than 65535 bytes.
+    pub fn write_binary(&self, mut w: impl Write) -> std::io::Result<()> {
+        w.write_all(&MAGIC)?;
+        w.write_all(&(FORMAT_VERSION as u16).to_le_bytes())?;
+        write_len(&mut w, self.layers().len())?;
+        for layer in self.layers() {
+            write_layer(&mut w, layer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a network written by `write_binary`, validating every layer and node.
+    pub fn read_binary(mut r: impl Read) -> Result<Network, NetworkFormatError> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(NetworkFormatError::InvalidFormat(
+                "missing binary network magic bytes".to_string(),
+            ));
+        }
+        let version = u16::from_le_bytes(read_array(&mut r)?) as u64;
+        if version != FORMAT_VERSION {
+            return Err(NetworkFormatError::UnsupportedVersion(version));
+        }
+        let layer_count = read_len(&mut r)?;
+        let mut layers = Vec::new();
+        for index in 0..layer_count {
+            layers.push(read_layer(&mut r, index)?);
+        }
+        Ok(Network::try_new(layers)?)
+    }
+}
+
+pub(crate) fn write_len(w: &mut impl Write, len: usize) -> std::io::Result<()> {
+    let len = u32::try_from(len)
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "length exceeds u32"))?;
+    w.write_all(&len.to_le_bytes())
+}
+
+pub(crate) fn write_str(w: &mut impl Write, s: &str) -> std::io::Result<()> {
+    let len = u16::try_from(s.len())
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "string exceeds u16"))?;
+    w.write_all(&len.to_le_bytes())?;
+    w.write_all(s.as_bytes())
+}
+
+fn write_layer(w: &mut impl Write, layer: &Layer) -> std::io::Result<()> {
+    write_len(w, layer.input_width())?;
+    write_len(w, layer.len())?;
+    let flag = if layer.normalizer().is_some() {
+        NORMALIZER_FLAG
+    } else {
+        0
+    };
+    match layer.wiring() {
+        Wiring::Dense => w.write_all(&[flag])?,
+        Wiring::Sparse(wiring) => {
+            w.write_all(&[1 | flag])?;
+            for indices in wiring {
+                write_len(w, indices.len())?;
+                for &index in indices {
+                    write_len(w, index)?;
+                }
+            }
+        }
+    }
+    for node in layer.nodes() {
+        write_node(w, node)?;
+    }
+    if let Some(normalizer) = layer.normalizer() {
+        write_str(w, &normalizer.to_string())?;
+    }
+    Ok(())
+}
+
+fn write_node(w: &mut impl Write, node: &SignalNode) -> std::io::Result<()> {
+    write_str(w, &node.combinator().identifier())?;
+    match node.combinator().parameters() {
+        Some(args) => {
+            w.write_all(&[1])?;
+            write_str(w, &args)?;
+        }
+        None => w.write_all(&[0])?,
+    }
+    match node.input_mappers() {
+        InputMappers::Identity => w.write_all(&[0])?,
+        InputMappers::Shared(mapper) => {
+            w.write_all(&[1])?;
+            w.write_all(mapper.table())?;
+        }
+        InputMappers::PerInput(mappers) => {
+            w.write_all(&[2])?;
+            write_len(w, mappers.len())?;
+            for mapper in mappers {
+                w.write_all(mapper.table())?;
+            }
+        }
+    }
+    match node.activation() {
+        Some(activation) => {
+            w.write_all(&[1])?;
+            w.write_all(activation.table())?;
+        }
+        None => w.write_all(&[0])?,
+    }
+    match node.input_count() {
+        Some(count) => {
+            w.write_all(&[1])?;
+            write_len(w, count)?;
+        }
+        None => w.write_all(&[0])?,
+    }
+    match node.name() {
+        Some(name) => {
+            w.write_all(&[1])?;
+            write_str(w, name)?;
+        }
+        None => w.write_all(&[0])?,
+    }
+    Ok(())
+}
+
+pub(crate) fn read_array<const N: usize>(r: &mut impl Read) -> Result<[u8; N], NetworkFormatError> {
+    let mut bytes = [0; N];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub(crate) fn read_len(r: &mut impl Read) -> Result<usize, NetworkFormatError> {
+    Ok(u32::from_le_bytes(read_array(r)?) as usize)
+}
+
+pub(crate) fn read_flag(r: &mut impl Read, field: &str) -> Result<bool, NetworkFormatError> {
+    match read_array::<1>(r)? {
+        [0] => Ok(false),
+        [1] => Ok(true),
+        [other] => Err(NetworkFormatError::InvalidFormat(format!(
+            "invalid {field} flag {other}"
+        ))),
+    }
+}
+
+pub(crate) fn read_str(r: &mut impl Read) -> Result<String, NetworkFormatError> {
+    let len = u16::from_le_bytes(read_array(r)?) as usize;
+    let mut bytes = vec![0; len];
+    r.read_exact(&mut bytes)?;
+    String::from_utf8(bytes)
+        .map_err(|_| NetworkFormatError::InvalidFormat("string is not UTF-8".to_string()))
+}
+
+pub(crate) fn read_table(r: &mut impl Read) -> Result<MapperNode, NetworkFormatError> {
+    Ok(MapperNode::new_from(read_array(r)?))
+}
+
+fn read_layer(r: &mut impl Read, index: usize) -> Result<Layer, NetworkFormatError> {
+    let input_width = read_len(r)?;
+    let node_count = read_len(r)?;
+    // Counts come from untrusted data, so vectors grow as items are read instead of
+    // being allocated up front.
+    let [kind] = read_array::<1>(r)?;
+    let wiring = match kind & !NORMALIZER_FLAG {
+        0 => Wiring::Dense,
+        1 => {
+            let mut wiring = Vec::new();
+            for _ in 0..node_count {
+                let len = read_len(r)?;
+                let mut indices = Vec::new();
+                for _ in 0..len {
+                    indices.push(read_len(r)?);
+                }
+                wiring.push(indices);
+            }
+            Wiring::Sparse(wiring)
+        }
+        other => {
+            return Err(NetworkFormatError::InvalidFormat(format!(
+                "layer {index}: unknown wiring kind {other}"
+            )));
+        }
+    };
+    let mut nodes = Vec::new();
+    for node in 0..node_count {
+        nodes.push(read_node(r, index, node)?);
+    }
+    let layer =
+        Layer::new(nodes, wiring, input_width).map_err(|error| NetworkFormatError::Layer {
+            layer: index,
+            error,
+        })?;
+    if kind & NORMALIZER_FLAG == 0 {
+        return Ok(layer);
+    }
+    let normalizer = read_str(r)?
+        .parse::<Normalizer>()
+        .map_err(|error| NetworkFormatError::InvalidFormat(format!("layer {index}: {error}")))?;
+    Ok(layer.with_normalizer(normalizer))
+}
+
+fn read_node(
+    r: &mut impl Read,
+    layer: usize,
+    node: usize,
+) -> Result<SignalNode, NetworkFormatError> {
+    let identifier = read_str(r)?;
+    let description = if read_flag(r, "parameters")? {
+        format!("{identifier}:{}", read_str(r)?)
+    } else {
+        identifier
+    };
+    let combinator = combinator_from_str(&description)
+        .map_err(|error| NetworkFormatError::Combinator { layer, node, error })?;
+    let input_mappers = match read_array::<1>(r)? {
+        [0] => InputMappers::Identity,
+        [1] => InputMappers::Shared(read_table(r)?.into()),
+        [2] => {
+            let count = read_len(r)?;
+            let mut mappers = Vec::new();
+            for _ in 0..count {
+                mappers.push(read_table(r)?.into());
+            }
+            InputMappers::PerInput(mappers)
+        }
+        [other] => {
+            return Err(NetworkFormatError::InvalidFormat(format!(
+                "layer {layer}: node {node}: unknown input mapper kind {other}"
+            )));
+        }
+    };
+    let activation = match read_flag(r, "activation")? {
+        true => Some(read_table(r)?),
+        false => None,
+    };
+    let input_count = match read_flag(r, "input count")? {
+        true => Some(read_len(r)?),
+        false => None,
+    };
+    let name = match read_flag(r, "name")? {
+        true => Some(read_str(r)?),
+        false => None,
+    };
+    let mut node = build_node(
+        (layer, node),
+        combinator,
+        input_mappers,
+        activation,
+        input_count,
+    )?;
+    if let Some(name) = name {
+        node.set_name(name);
+    }
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{
+        AdditionCombinatorNode, MaxCombinatorNode, ThresholdCombinatorNode,
+    };
+    use crate::neural::mapper_defaults::create_mapper_for_character;
+    use crate::neural::rng::Rng;
+
+    fn sample_network() -> Network {
+        Network::builder()
+            .input_width(3)
+            .layer_dense(2, |node| {
+                node.shared_mapper(create_mapper_for_character())
+                    .combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .layer_sparse(
+                vec![
+                    SignalNode::builder()
+                        .name("halves")
+                        .input_mapper(MapperNode::new_transformation(|x| x as u8 / 2))
+                        .input_mapper(MapperNode::new_transformation(|x| x as u8 / 3))
+                        .combinator(Box::new(ThresholdCombinatorNode::new(20, 1, 200)))
+                        .activation(MapperNode::new_transformation(|x| 255 - x as u8))
+                        .build()
+                        .unwrap(),
+                    SignalNode::builder()
+                        .combinator(Box::new(AdditionCombinatorNode::new()))
+                        .input_count(3)
+                        .build()
+                        .unwrap(),
+                ],
+                vec![vec![1, 0], vec![0, 0, 1]],
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn to_bytes(network: &Network) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        network.write_binary(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let network = sample_network();
+        let bytes = to_bytes(&network);
+        assert_eq!(&bytes[..4], b"SNNB");
+        assert_eq!(&bytes[4..6], &[1, 0]);
+        let restored = Network::read_binary(bytes.as_slice()).unwrap();
+        assert_eq!(to_bytes(&restored), bytes);
+        #[cfg(feature = "json")]
+        assert_eq!(restored.to_json(), network.to_json());
+        assert!(restored.node("halves").is_some());
+        for input in [b"abc", b"A1 ", b"zz9", b"\0\0\0", b"~~~"] {
+            assert_eq!(restored.forward(input), network.forward(input), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalizer_round_trip() {
+        let network = Network::builder()
+            .input_width(3)
+            .layer_dense(2, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .normalizer(Normalizer::sum())
+            .layer(Layer::splitter(2, 1).with_normalizer(Normalizer::softmax(0)))
+            .build()
+            .unwrap();
+        let bytes = to_bytes(&network);
+        let restored = Network::read_binary(bytes.as_slice()).unwrap();
+        assert_eq!(to_bytes(&restored), bytes);
+        #[cfg(feature = "json")]
+        assert_eq!(restored.to_json(), network.to_json());
+        assert_eq!(restored.layers()[0].normalizer(), None);
+        assert_eq!(restored.layers()[1].normalizer(), Some(&Normalizer::sum()));
+        assert_eq!(
+            restored.layers()[2].normalizer(),
+            Some(&Normalizer::softmax(0))
+        );
+
+        let position = bytes.windows(3).position(|w| w == b"sum").unwrap();
+        let mut bad = bytes.clone();
+        bad[position..position + 3].copy_from_slice(b"sun");
+        assert!(matches!(
+            Network::read_binary(bad.as_slice()),
+            Err(NetworkFormatError::InvalidFormat(message)) if message.starts_with("layer 1: ")
+        ));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let bytes = to_bytes(&sample_network());
+        for len in [
+            0,
+            3,
+            5,
+            6,
+            9,
+            10,
+            20,
+            100,
+            300,
+            bytes.len() / 2,
+            bytes.len() - 1,
+        ] {
+            assert_eq!(
+                Network::read_binary(&bytes[..len]).err(),
+                Some(NetworkFormatError::Truncated),
+                "{len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrong_magic_and_version() {
+        let mut bytes = to_bytes(&sample_network());
+        bytes[4] = 9;
+        let error = Network::read_binary(bytes.as_slice()).err().unwrap();
+        assert_eq!(error, NetworkFormatError::UnsupportedVersion(9));
+        assert!(error.to_string().contains("version 9"));
+
+        bytes[0] = b'X';
+        assert!(matches!(
+            Network::read_binary(bytes.as_slice()),
+            Err(NetworkFormatError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            Network::read_binary(&b"{\"version\": 1}"[..]),
+            Err(NetworkFormatError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_data_does_not_panic() {
+        let bytes = to_bytes(&sample_network());
+        let mut rng = Rng::new(0x2545_F491_4F6C_DD1D);
+        for _ in 0..2000 {
+            let seed = rng.next_u64();
+            let mut corrupt = bytes.clone();
+            let position = 6 + (seed as usize) % (corrupt.len() - 6);
+            corrupt[position] = (seed >> 32) as u8;
+            let _ = Network::read_binary(corrupt.as_slice());
+        }
+
+        // A huge count must fail on missing data instead of allocating up front.
+        let mut huge = bytes[..6].to_vec();
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        huge.extend_from_slice(&[1, 0, 0, 0, 255, 255, 255, 255, 1]);
+        assert_eq!(
+            Network::read_binary(huge.as_slice()).err(),
+            Some(NetworkFormatError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_unknown_combinator_names_node() {
+        let network = Network::builder()
+            .input_width(1)
+            .layer_dense(1, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .build()
+            .unwrap();
+        let mut bytes = to_bytes(&network);
+        let position = bytes.windows(3).position(|w| w == b"max").unwrap();
+        bytes[position..position + 3].copy_from_slice(b"mox");
+        let error = Network::read_binary(bytes.as_slice()).err().unwrap();
+        assert!(matches!(
+            error,
+            NetworkFormatError::Combinator {
+                layer: 0,
+                node: 0,
+                ..
+            }
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_smaller_than_json() {
+        let network = sample_network();
+        assert!(to_bytes(&network).len() < network.to_json().len() * 3 / 4);
+
+        // Without tables the per-node overhead dominates.
+        let plain = Network::builder()
+            .input_width(8)
+            .layer_dense(16, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .layer_dense(4, |node| {
+                node.combinator(Box::new(AdditionCombinatorNode::new()))
+            })
+            .build()
+            .unwrap();
+        let binary = to_bytes(&plain).len();
+        let json = plain.to_json().len();
+        assert!(binary * 10 < json, "{binary} vs {json}");
+    }
+}
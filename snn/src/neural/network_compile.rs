@@ -0,0 +1,437 @@
+/// Flattening a network into contiguous arrays for fast repeated evaluation.
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::neural::combinator::{
+    ANDCombinatorNode, AdditionCombinatorNode, AverageCombinatorNode, MaxCombinatorNode,
+    MinCombinatorNode, ORCombinatorNode, SaturatingAdditionCombinatorNode,
+    WrappingAdditionCombinatorNode, XORCombinatorNode,
+};
+use crate::neural::layer::Wiring;
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::{EvalError, Network};
+use crate::neural::node::{InputMappers, SignalNode};
+use crate::neural::normalizer::Normalizer;
+use crate::neural::traits::Combinator;
+
+/// The operation a compiled node applies to its mapped inputs.
+/// Built-in combinators without parameters become op-codes; everything else is called
+/// through its trait object. As with the serialization formats, a combinator is recognised
+/// by its identifier.
+enum Op<'a> {
+    SaturatingSum,
+    WrappingSum,
+    Max,
+    Min,
+    Average,
+    Or,
+    And,
+    Xor,
+    Custom(&'a dyn Combinator),
+}
+
+impl<'a> Op<'a> {
+    fn new(combinator: &'a dyn Combinator) -> Self {
+        if combinator.parameters().is_some() {
+            return Op::Custom(combinator);
+        }
+        match combinator.identifier().as_str() {
+            AdditionCombinatorNode::IDENTIFIER | SaturatingAdditionCombinatorNode::IDENTIFIER => {
+                Op::SaturatingSum
+            }
+            WrappingAdditionCombinatorNode::IDENTIFIER => Op::WrappingSum,
+            MaxCombinatorNode::IDENTIFIER => Op::Max,
+            MinCombinatorNode::IDENTIFIER => Op::Min,
+            AverageCombinatorNode::IDENTIFIER => Op::Average,
+            ORCombinatorNode::IDENTIFIER => Op::Or,
+            ANDCombinatorNode::IDENTIFIER => Op::And,
+            XORCombinatorNode::IDENTIFIER => Op::Xor,
+            _ => Op::Custom(combinator),
+        }
+    }
+
+    fn apply(&self, inputs: &[u8]) -> u8 {
+        match self {
+            Op::SaturatingSum => inputs.iter().fold(0, |acc, &x| acc.saturating_add(x)),
+            Op::WrappingSum => inputs.iter().fold(0, |acc, &x| acc.wrapping_add(x)),
+            Op::Max => inputs.iter().copied().max().unwrap_or(0),
+            Op::Min => inputs.iter().copied().min().unwrap_or(0),
+            Op::Average if inputs.is_empty() => 0,
+            Op::Average => (inputs.iter().map(|&x| x as usize).sum::<usize>() / inputs.len()) as u8,
+            Op::Or => inputs.iter().fold(0, |acc, &x| acc | x),
+            Op::And => inputs.iter().fold(0xFF, |acc, &x| acc & x),
+            Op::Xor => inputs.iter().fold(0, |acc, &x| acc ^ x),
+            Op::Custom(combinator) => combinator.combine(inputs),
+        }
+    }
+}
+
+/// Where a compiled node reads its inputs in the previous layer's outputs.
+enum Sources {
+    /// A run of consecutive outputs, as for every node of a dense layer.
+    Contiguous(Range<usize>),
+    /// The output positions listed in this range of `CompiledNetwork::sources`.
+    Indexed(Range<usize>),
+}
+
+impl Sources {
+    fn len(&self) -> usize {
+        match self {
+            Sources::Contiguous(range) | Sources::Indexed(range) => range.len(),
+        }
+    }
+}
+
+/// The tables a compiled node applies to its inputs, as indices into `CompiledNetwork::tables`.
+enum Mappers {
+    Identity,
+    Shared(usize),
+    /// Input `i` goes through table `start + i`; inputs past the end are passed unchanged.
+    PerInput(Range<usize>),
+}
+
+/// One node of a compiled network.
+struct CompiledNode<'a> {
+    sources: Sources,
+    mappers: Mappers,
+    op: Op<'a>,
+    activation: Option<usize>,
+}
+
+/// A network flattened into contiguous tables, wiring and op-codes.
+/// Created by `Network::compile`; it borrows the network for its custom combinators.
+///
+/// Evaluating a compiled network gives the same results as `Network::forward`,
+/// without following per-node pointers or allocating on every call.
+pub struct CompiledNetwork<'a> {
+    input_width: Option<usize>,
+    layers: Vec<Range<usize>>,
+    /// The normalizer of each layer, if any.
+    normalizers: Vec<Option<&'a Normalizer>>,
+    nodes: Vec<CompiledNode<'a>>,
+    tables: Vec<[u8; 256]>,
+    sources: Vec<usize>,
+    /// The widest layer input or output, which sizes the value buffers in the scratch space.
+    max_width: usize,
+    /// The most inputs any node reads, which sizes the mapped-input buffer.
+    max_fan_in: usize,
+}
+
+impl Network {
+    /// Flattens the network into a `CompiledNetwork` for fast repeated evaluation.
+    /// Tables shared between nodes are stored once.
+    pub fn compile(&self) -> CompiledNetwork<'_> {
+        let mut compiled = CompiledNetwork {
+            input_width: self.input_width(),
+            layers: Vec::with_capacity(self.len()),
+            normalizers: Vec::with_capacity(self.len()),
+            nodes: Vec::with_capacity(self.node_count()),
+            tables: Vec::new(),
+            sources: Vec::new(),
+            max_width: self.input_width().unwrap_or(0),
+            max_fan_in: 0,
+        };
+        let mut table_ids = HashMap::new();
+        for layer in self.layers() {
+            let start = compiled.nodes.len();
+            for (index, node) in layer.nodes().iter().enumerate() {
+                let sources = match layer.wiring() {
+                    Wiring::Dense => Sources::Contiguous(0..layer.input_width()),
+                    Wiring::Sparse(wiring) => compiled.sources(&wiring[index]),
+                };
+                let node = compiled.compile_node(node, sources, &mut table_ids);
+                compiled.nodes.push(node);
+            }
+            compiled.layers.push(start..compiled.nodes.len());
+            compiled.normalizers.push(layer.normalizer());
+            compiled.max_width = compiled.max_width.max(layer.output_width());
+        }
+        compiled
+    }
+}
+
+impl<'a> CompiledNetwork<'a> {
+    /// Stores a sparse node's input positions, or just their range if they are consecutive.
+    fn sources(&mut self, indices: &[usize]) -> Sources {
+        match indices.first() {
+            Some(&first)
+                if indices
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &index)| index == first + i) =>
+            {
+                Sources::Contiguous(first..first + indices.len())
+            }
+            _ => {
+                let start = self.sources.len();
+                self.sources.extend(indices);
+                Sources::Indexed(start..self.sources.len())
+            }
+        }
+    }
+
+    fn compile_node(
+        &mut self,
+        node: &'a SignalNode,
+        sources: Sources,
+        table_ids: &mut HashMap<*const u8, usize>,
+    ) -> CompiledNode<'a> {
+        let mappers = match node.input_mappers() {
+            InputMappers::Identity => Mappers::Identity,
+            InputMappers::Shared(mapper) => Mappers::Shared(self.table_id(mapper, table_ids)),
+            // Per-input tables must be consecutive, so they are always stored anew.
+            InputMappers::PerInput(mappers) => {
+                let start = self.tables.len();
+                self.tables
+                    .extend(mappers.iter().map(|mapper| *mapper.table()));
+                Mappers::PerInput(start..self.tables.len())
+            }
+        };
+        let activation = node
+            .activation()
+            .map(|activation| self.table_id(activation, table_ids));
+        self.max_fan_in = self.max_fan_in.max(sources.len());
+        CompiledNode {
+            sources,
+            mappers,
+            op: Op::new(node.combinator()),
+            activation,
+        }
+    }
+
+    /// Returns the index of `mapper`'s table, storing it unless the same table was stored before.
+    fn table_id(
+        &mut self,
+        mapper: &MapperNode,
+        table_ids: &mut HashMap<*const u8, usize>,
+    ) -> usize {
+        *table_ids.entry(mapper.table().as_ptr()).or_insert_with(|| {
+            self.tables.push(*mapper.table());
+            self.tables.len() - 1
+        })
+    }
+
+    /// Returns the number of inputs the network expects, or `None` for an empty network.
+    pub fn input_width(&self) -> Option<usize> {
+        self.input_width
+    }
+
+    /// Returns the number of mapper tables stored. Shared tables count once.
+    pub fn table_count(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Copies the node's mapped inputs from `values` into `inputs`.
+    fn gather(&self, node: &CompiledNode, values: &[u8], inputs: &mut [u8]) {
+        match &node.sources {
+            Sources::Contiguous(range) => inputs.copy_from_slice(&values[range.clone()]),
+            Sources::Indexed(range) => {
+                for (input, &source) in inputs.iter_mut().zip(&self.sources[range.clone()]) {
+                    *input = values[source];
+                }
+            }
+        }
+        match &node.mappers {
+            Mappers::Identity => {}
+            Mappers::Shared(table) => {
+                let table = &self.tables[*table];
+                for input in inputs.iter_mut() {
+                    *input = table[*input as usize];
+                }
+            }
+            Mappers::PerInput(range) => {
+                for (input, table) in inputs.iter_mut().zip(&self.tables[range.clone()]) {
+                    *input = table[*input as usize];
+                }
+            }
+        }
+    }
+
+    /// Runs the input through the compiled network and returns the outputs of the last layer.
+    pub fn forward(&self, input: &[u8]) -> Result<Vec<u8>, EvalError> {
+        let mut output = Vec::new();
+        self.forward_into(input, &mut Vec::new(), &mut output)?;
+        Ok(output)
+    }
+
+    /// Runs the input through the compiled network, writing the outputs of the last layer
+    /// to `output`. `scratch` holds intermediate values; reusing it (and `output`) across
+    /// calls avoids allocating once it has grown to size.
+    pub fn forward_into(
+        &self,
+        input: &[u8],
+        scratch: &mut Vec<u8>,
+        output: &mut Vec<u8>,
+    ) -> Result<(), EvalError> {
+        if let Some(expected) = self.input_width
+            && expected != input.len()
+        {
+            return Err(EvalError::InputWidth {
+                expected,
+                actual: input.len(),
+            });
+        }
+        // Two value buffers that swap roles every layer, then room for one node's mapped inputs.
+        let width = self.max_width.max(input.len());
+        scratch.clear();
+        scratch.resize(2 * width + self.max_fan_in, 0);
+        let (mut current, rest) = scratch.split_at_mut(width);
+        let (mut next, gathered) = rest.split_at_mut(width);
+        current[..input.len()].copy_from_slice(input);
+        let mut width = input.len();
+        for (layer, normalizer) in self.layers.iter().zip(&self.normalizers) {
+            for (slot, node) in next.iter_mut().zip(&self.nodes[layer.clone()]) {
+                let inputs = &mut gathered[..node.sources.len()];
+                self.gather(node, current, inputs);
+                let combined = node.op.apply(inputs);
+                *slot = match node.activation {
+                    Some(table) => self.tables[table][combined as usize],
+                    None => combined,
+                };
+            }
+            width = layer.len();
+            if let Some(normalizer) = normalizer {
+                normalizer.normalize_in_place(&mut next[..width]);
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        output.clear();
+        output.extend_from_slice(&current[..width]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{
+        MedianCombinatorNode, ThresholdCombinatorNode, combinator_from_str,
+    };
+    use crate::neural::layer::Layer;
+    use crate::neural::mapper_shared::SharedMapper;
+    use crate::neural::rng::Rng;
+    use crate::neural::traits::FnCombinator;
+
+    fn random_table(rng: &mut Rng) -> MapperNode {
+        let mut table = [0; 256];
+        table.fill_with(|| rng.below(256) as u8);
+        MapperNode::new_from(table)
+    }
+
+    fn network(rng: &mut Rng) -> Network {
+        let shared = SharedMapper::new(random_table(rng));
+        let mixer = SignalNode::builder()
+            .input_mapper(random_table(rng))
+            .input_mapper(random_table(rng))
+            .input_mapper(random_table(rng))
+            .combinator(Box::new(ThresholdCombinatorNode::new(100, 0, 255)))
+            .build()
+            .unwrap();
+        let custom = SignalNode::builder()
+            .combinator(Box::new(FnCombinator(|inputs: &[u8]| {
+                inputs.iter().fold(7u8, |acc, &x| acc.rotate_left(3) ^ x)
+            })))
+            .activation(shared.clone())
+            .build()
+            .unwrap();
+        let identifiers = [
+            "addition", "add_wrap", "max", "min", "average", "or", "and", "xor",
+        ];
+        Network::builder()
+            .input_width(5)
+            .layer_dense(identifiers.len(), |n| {
+                let identifier = identifiers[rng.below(identifiers.len())];
+                n.shared_mapper(shared.clone())
+                    .combinator(combinator_from_str(identifier).unwrap())
+            })
+            .layer_sparse(
+                vec![mixer, custom],
+                vec![vec![0, 3, 7], vec![2, 3, 4, 5, 6]],
+            )
+            .layer(
+                Layer::dense(
+                    vec![
+                        SignalNode::builder()
+                            .combinator(Box::new(MedianCombinatorNode::new()))
+                            .build()
+                            .unwrap(),
+                        SignalNode::builder()
+                            .combinator(Box::new(AverageCombinatorNode::new()))
+                            .activation(random_table(rng))
+                            .build()
+                            .unwrap(),
+                    ],
+                    2,
+                )
+                .unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_matches_interpreted_forward() {
+        let mut rng = Rng::new(17);
+        for _ in 0..20 {
+            let network = network(&mut rng);
+            let compiled = network.compile();
+            let (mut scratch, mut output) = (Vec::new(), Vec::new());
+            for _ in 0..50 {
+                let input: Vec<u8> = (0..5).map(|_| rng.below(256) as u8).collect();
+                compiled
+                    .forward_into(&input, &mut scratch, &mut output)
+                    .unwrap();
+                assert_eq!(output, network.forward(&input).unwrap(), "{input:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalizers() {
+        let mut rng = Rng::new(29);
+        let network = Network::builder()
+            .input_width(5)
+            .normalizer(Normalizer::linear())
+            .layer_dense(3, |n| {
+                n.shared_mapper(random_table(&mut rng))
+                    .combinator(combinator_from_str("average").unwrap())
+            })
+            .layer(Layer::splitter(3, 2).with_normalizer(Normalizer::softmax(40)))
+            .normalizer(Normalizer::sum())
+            .build()
+            .unwrap();
+        let compiled = network.compile();
+        for _ in 0..100 {
+            let input: Vec<u8> = (0..5).map(|_| rng.below(256) as u8).collect();
+            assert_eq!(
+                compiled.forward(&input),
+                network.forward(&input),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_shared_tables_stored_once() {
+        let network = network(&mut Rng::new(3));
+        // One shared table, three per-input tables and one activation.
+        assert_eq!(network.compile().table_count(), 5);
+    }
+
+    #[test]
+    fn test_input_width_and_empty_network() {
+        let network = network(&mut Rng::new(5));
+        let compiled = network.compile();
+        assert_eq!(compiled.input_width(), Some(5));
+        assert_eq!(
+            compiled.forward(&[1, 2]),
+            Err(EvalError::InputWidth {
+                expected: 5,
+                actual: 2
+            })
+        );
+
+        let empty = Network::default();
+        assert_eq!(empty.compile().forward(&[4, 5, 6]), Ok(vec![4, 5, 6]));
+    }
+}
@@ -0,0 +1,502 @@
+/// Structural comparison of two networks, for reviewing what an optimization pass or a training
+/// run changed.
+use crate::neural::layer::Layer;
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::Network;
+use crate::neural::node::SignalNode;
+
+/// The most changed entries `NetworkDiff`'s `Display` lists for one mapper.
+pub const MAX_SHOWN_ENTRIES: usize = 8;
+
+/// Identifies a mapper table of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapperSlot {
+    /// The mapper applied to input `i`.
+    Input(usize),
+    /// The mapper applied to the node's output.
+    Activation,
+}
+
+/// One difference between two networks. Layers and nodes are numbered from 0, and `left` is
+/// always the network `diff` was called on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// The networks have a different number of layers; only the shared layers are compared.
+    LayerCount { left: usize, right: usize },
+    /// A layer reads a different number of inputs.
+    InputWidth {
+        layer: usize,
+        left: usize,
+        right: usize,
+    },
+    /// A layer has a different number of nodes; only the shared nodes are compared.
+    NodeCount {
+        layer: usize,
+        left: usize,
+        right: usize,
+    },
+    /// A layer normalizes its outputs differently.
+    Normalizer {
+        layer: usize,
+        left: Option<String>,
+        right: Option<String>,
+    },
+    /// A node reads different inputs of its layer.
+    Wiring {
+        layer: usize,
+        node: usize,
+        left: Vec<usize>,
+        right: Vec<usize>,
+    },
+    /// A node has a different combinator, described as `identifier` or `identifier:parameters`.
+    Combinator {
+        layer: usize,
+        node: usize,
+        left: String,
+        right: String,
+    },
+    /// A mapper table of a node maps some inputs differently. A missing mapper counts as the
+    /// identity. `entries` holds `(input, left output, right output)` for every changed input.
+    Mapper {
+        layer: usize,
+        node: usize,
+        slot: MapperSlot,
+        entries: Vec<(u8, u8, u8)>,
+    },
+}
+
+impl Change {
+    /// Returns true if the change makes the networks' shapes incompatible, so that one cannot
+    /// take the other's place.
+    pub fn is_structural(&self) -> bool {
+        matches!(
+            self,
+            Change::LayerCount { .. } | Change::InputWidth { .. } | Change::NodeCount { .. }
+        )
+    }
+}
+
+/// The differences between two networks, found by `Network::diff`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NetworkDiff {
+    pub changes: Vec<Change>,
+}
+
+impl NetworkDiff {
+    /// Returns true if no difference was found.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns true if the networks have the same shape: layer count, input widths and node
+    /// counts.
+    pub fn is_compatible(&self) -> bool {
+        !self.changes.iter().any(Change::is_structural)
+    }
+
+    /// Returns the total number of changed mapper entries across all nodes.
+    pub fn changed_entries(&self) -> usize {
+        self.changes
+            .iter()
+            .map(|change| match change {
+                Change::Mapper { entries, .. } => entries.len(),
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+impl Network {
+    /// Compares this network with `other` layer by layer and node by node. Wiring is compared
+    /// by the inputs each node reads, so dense wiring equals the sparse wiring that lists every
+    /// input. Node names are not compared. Networks of different shapes are compared as far as
+    /// they overlap, with the mismatch reported as a structural change.
+    pub fn diff(&self, other: &Network) -> NetworkDiff {
+        let mut changes = Vec::new();
+        let (left, right) = (self.layers(), other.layers());
+        if left.len() != right.len() {
+            changes.push(Change::LayerCount {
+                left: left.len(),
+                right: right.len(),
+            });
+        }
+        for (index, (left, right)) in left.iter().zip(right).enumerate() {
+            diff_layer(&mut changes, index, left, right);
+        }
+        NetworkDiff { changes }
+    }
+}
+
+fn diff_layer(changes: &mut Vec<Change>, layer: usize, left: &Layer, right: &Layer) {
+    if left.input_width() != right.input_width() {
+        changes.push(Change::InputWidth {
+            layer,
+            left: left.input_width(),
+            right: right.input_width(),
+        });
+    }
+    if left.len() != right.len() {
+        changes.push(Change::NodeCount {
+            layer,
+            left: left.len(),
+            right: right.len(),
+        });
+    }
+    let (left_normalizer, right_normalizer) = (left.normalizer(), right.normalizer());
+    if left_normalizer != right_normalizer {
+        changes.push(Change::Normalizer {
+            layer,
+            left: left_normalizer.map(ToString::to_string),
+            right: right_normalizer.map(ToString::to_string),
+        });
+    }
+    for node in 0..left.len().min(right.len()) {
+        let (left_sources, right_sources) = (left.sources(node), right.sources(node));
+        if left_sources != right_sources {
+            changes.push(Change::Wiring {
+                layer,
+                node,
+                left: left_sources.clone(),
+                right: right_sources.clone(),
+            });
+        }
+        let fan_in = left_sources.len().max(right_sources.len());
+        diff_node(
+            changes,
+            (layer, node),
+            &left.nodes()[node],
+            &right.nodes()[node],
+            fan_in,
+        );
+    }
+}
+
+fn diff_node(
+    changes: &mut Vec<Change>,
+    (layer, node): (usize, usize),
+    left: &SignalNode,
+    right: &SignalNode,
+    fan_in: usize,
+) {
+    let (left_combinator, right_combinator) = (describe(left), describe(right));
+    if left_combinator != right_combinator {
+        changes.push(Change::Combinator {
+            layer,
+            node,
+            left: left_combinator,
+            right: right_combinator,
+        });
+    }
+    let slots = (0..fan_in)
+        .map(|i| {
+            (
+                MapperSlot::Input(i),
+                left.input_mapper(i),
+                right.input_mapper(i),
+            )
+        })
+        .chain([(
+            MapperSlot::Activation,
+            left.activation(),
+            right.activation(),
+        )]);
+    for (slot, left, right) in slots {
+        let entries = match (left, right) {
+            (None, None) => continue,
+            (Some(left), Some(right)) => left.diff(right),
+            (Some(left), None) => left.diff(&identity()),
+            (None, Some(right)) => identity().diff(right),
+        };
+        if !entries.is_empty() {
+            changes.push(Change::Mapper {
+                layer,
+                node,
+                slot,
+                entries,
+            });
+        }
+    }
+}
+
+fn identity() -> MapperNode {
+    MapperNode::new_transformation(|x| x as u8)
+}
+
+/// Describes a node's combinator the way `combinator_from_str` reads it.
+fn describe(node: &SignalNode) -> String {
+    let combinator = node.combinator();
+    match combinator.parameters() {
+        Some(args) => format!("{}:{args}", combinator.identifier()),
+        None => combinator.identifier(),
+    }
+}
+
+impl std::fmt::Display for MapperSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapperSlot::Input(i) => write!(f, "input {i} mapper"),
+            MapperSlot::Activation => f.write_str("activation"),
+        }
+    }
+}
+
+impl std::fmt::Display for Change {
+    /// Writes one line per change, or for a mapper one line per changed entry, up to
+    /// `MAX_SHOWN_ENTRIES`, then a count of the rest.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |normalizer: &Option<String>| normalizer.clone().unwrap_or("none".to_string());
+        match self {
+            Change::LayerCount { left, right } => write!(f, "layer count {left} -> {right}"),
+            Change::InputWidth { layer, left, right } => {
+                write!(f, "layer {layer}: input width {left} -> {right}")
+            }
+            Change::NodeCount { layer, left, right } => {
+                write!(f, "layer {layer}: node count {left} -> {right}")
+            }
+            Change::Normalizer { layer, left, right } => {
+                write!(
+                    f,
+                    "layer {layer}: normalizer {} -> {}",
+                    show(left),
+                    show(right)
+                )
+            }
+            Change::Wiring {
+                layer,
+                node,
+                left,
+                right,
+            } => write!(
+                f,
+                "layer {layer} / node {node}: wiring {left:?} -> {right:?}"
+            ),
+            Change::Combinator {
+                layer,
+                node,
+                left,
+                right,
+            } => write!(
+                f,
+                "layer {layer} / node {node}: combinator {left} -> {right}"
+            ),
+            Change::Mapper {
+                layer,
+                node,
+                slot,
+                entries,
+            } => {
+                for (i, (input, left, right)) in entries.iter().take(MAX_SHOWN_ENTRIES).enumerate()
+                {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(
+                        f,
+                        "layer {layer} / node {node}: {slot}[{input:#04x}] {left:#04x} -> {right:#04x}"
+                    )?;
+                }
+                if entries.len() > MAX_SHOWN_ENTRIES {
+                    write!(
+                        f,
+                        "\nlayer {layer} / node {node}: {slot}: {} more entries changed",
+                        entries.len() - MAX_SHOWN_ENTRIES
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for NetworkDiff {
+    /// Writes every change on its own line, or `no differences`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode, MinCombinatorNode};
+    use crate::neural::layer::Wiring;
+    use crate::neural::normalizer::Normalizer;
+
+    fn stage(table: MapperNode) -> SignalNode {
+        SignalNode::builder()
+            .shared_mapper(table)
+            .combinator(Box::new(MaxCombinatorNode::new()))
+            .build()
+            .unwrap()
+    }
+
+    /// Two single-input stages over three inputs, then a node adding them.
+    fn network(table: MapperNode, wiring: Vec<Vec<usize>>) -> Network {
+        Network::builder()
+            .input_width(3)
+            .layer_sparse(vec![stage(identity()), stage(table)], wiring)
+            .layer_dense(1, |node| {
+                node.combinator(Box::new(AdditionCombinatorNode::new()))
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn halve() -> MapperNode {
+        MapperNode::new_transformation(|x| x as u8 / 2)
+    }
+
+    #[test]
+    fn test_identical_networks() {
+        let diff = network(halve(), vec![vec![0], vec![2]])
+            .diff(&network(halve(), vec![vec![0], vec![2]]));
+        assert!(diff.is_empty());
+        assert!(diff.is_compatible());
+        assert_eq!(diff.to_string(), "no differences\n");
+    }
+
+    #[test]
+    fn test_single_entry_mutation() {
+        let original = network(halve(), vec![vec![0], vec![2]]);
+        let mutated = network(halve().with_mapdata([(0x41, 0xFF)]), vec![vec![0], vec![2]]);
+        let diff = original.diff(&mutated);
+        assert_eq!(
+            diff.changes,
+            [Change::Mapper {
+                layer: 0,
+                node: 1,
+                slot: MapperSlot::Input(0),
+                entries: vec![(0x41, 0x20, 0xFF)]
+            }]
+        );
+        assert!(diff.is_compatible());
+        assert_eq!(diff.changed_entries(), 1);
+        assert_eq!(
+            diff.to_string(),
+            "layer 0 / node 1: input 0 mapper[0x41] 0x20 -> 0xff\n"
+        );
+    }
+
+    #[test]
+    fn test_rewired_copy() {
+        let original = network(halve(), vec![vec![0], vec![2]]);
+        let rewired = network(halve(), vec![vec![1], vec![2]]);
+        let diff = original.diff(&rewired);
+        assert_eq!(
+            diff.changes,
+            [Change::Wiring {
+                layer: 0,
+                node: 0,
+                left: vec![0],
+                right: vec![1]
+            }]
+        );
+        assert!(diff.is_compatible());
+        assert_eq!(diff.to_string(), "layer 0 / node 0: wiring [0] -> [1]\n");
+
+        // Dense wiring equals sparse wiring that lists every input in order.
+        let dense = Layer::new(vec![stage(halve())], Wiring::Dense, 1).unwrap();
+        let sparse = Layer::sparse(vec![stage(halve())], vec![vec![0]], 1).unwrap();
+        let diff = Network::try_new(vec![dense])
+            .unwrap()
+            .diff(&Network::try_new(vec![sparse]).unwrap());
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_combinators_activations_and_normalizers() {
+        let left = Network::builder()
+            .input_width(2)
+            .layer_dense(1, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .build()
+            .unwrap();
+        let right = Network::builder()
+            .input_width(2)
+            .layer_dense(1, |node| {
+                node.combinator(Box::new(MinCombinatorNode::new()))
+                    .activation(halve())
+            })
+            .normalizer(Normalizer::softmax(8))
+            .build()
+            .unwrap();
+        let diff = left.diff(&right);
+        assert!(!diff.is_compatible());
+        assert_eq!(diff.changed_entries(), 255);
+        let text = diff.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "layer count 1 -> 2",
+                "layer 0 / node 0: combinator max -> min",
+                "layer 0 / node 0: activation[0x01] 0x01 -> 0x00",
+                "layer 0 / node 0: activation[0x02] 0x02 -> 0x01",
+                "layer 0 / node 0: activation[0x03] 0x03 -> 0x01",
+                "layer 0 / node 0: activation[0x04] 0x04 -> 0x02",
+                "layer 0 / node 0: activation[0x05] 0x05 -> 0x02",
+                "layer 0 / node 0: activation[0x06] 0x06 -> 0x03",
+                "layer 0 / node 0: activation[0x07] 0x07 -> 0x03",
+                "layer 0 / node 0: activation[0x08] 0x08 -> 0x04",
+                "layer 0 / node 0: activation: 247 more entries changed",
+            ]
+        );
+
+        let normalized = |normalizer: Normalizer| {
+            Network::try_new(vec![Layer::splitter(2, 1).with_normalizer(normalizer)]).unwrap()
+        };
+        let diff = normalized(Normalizer::sum()).diff(&normalized(Normalizer::softmax(3)));
+        assert_eq!(diff.to_string(), "layer 0: normalizer sum -> softmax:3\n");
+        let plain = Network::try_new(vec![Layer::splitter(2, 1)]).unwrap();
+        assert_eq!(
+            plain.diff(&normalized(Normalizer::linear())).to_string(),
+            "layer 0: normalizer none -> linear\n"
+        );
+    }
+
+    #[test]
+    fn test_incompatible_shapes_do_not_panic() {
+        let small = Network::try_new(vec![Layer::splitter(2, 1)]).unwrap();
+        let large = Network::try_new(vec![Layer::splitter(3, 2), Layer::splitter(6, 1)]).unwrap();
+        let diff = small.diff(&large);
+        assert!(!diff.is_compatible());
+        assert_eq!(
+            diff.changes[..3],
+            [
+                Change::LayerCount { left: 1, right: 2 },
+                Change::InputWidth {
+                    layer: 0,
+                    left: 2,
+                    right: 3
+                },
+                Change::NodeCount {
+                    layer: 0,
+                    left: 2,
+                    right: 6
+                },
+            ]
+        );
+        // The second node of the splitter reads input 1 on the left, input 0 on the right.
+        assert_eq!(
+            diff.changes[3..],
+            [Change::Wiring {
+                layer: 0,
+                node: 1,
+                left: vec![1],
+                right: vec![0]
+            }]
+        );
+        assert!(Network::default().diff(&Network::default()).is_empty());
+        assert_eq!(
+            Network::default().diff(&small).to_string(),
+            "layer count 0 -> 1\n"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+/// Stable content digests for whole networks.
+///
+/// The digest covers, in order: the layer count; for each layer its input width, node count
+/// and wiring; for each node its combinator identifier and parameters, the digests of its
+/// input mapper and activation tables, and its input count; then the layer's normalizer if it
+/// has one, flagged by `0x80` in the wiring tag as in the binary format. Node names are not
+/// included.
+/// Counts and indices are fed as `u64`, strings as their `u64` length and UTF-8 bytes, and
+/// every optional field or variant starts with a one-byte tag, all through `Fnv1a`.
+use crate::neural::digest::{Fnv1a, short_id};
+use crate::neural::layer::{Layer, Wiring};
+use crate::neural::network::Network;
+use crate::neural::node::{InputMappers, SignalNode};
+
+impl Network {
+    /// Returns a digest of the network's structure, combinators and tables, as described in
+    /// the module documentation. Networks that differ only in node names have equal digests.
+    pub fn digest(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        write_len(&mut hasher, self.layers().len());
+        for layer in self.layers() {
+            write_layer(&mut hasher, layer);
+        }
+        hasher.finish()
+    }
+
+    /// Returns `short_id` of the digest.
+    pub fn short_id(&self) -> String {
+        short_id(self.digest())
+    }
+}
+
+fn write_len(hasher: &mut Fnv1a, len: usize) {
+    hasher.write_u64(len as u64);
+}
+
+fn write_str(hasher: &mut Fnv1a, s: &str) {
+    write_len(hasher, s.len());
+    hasher.write(s.as_bytes());
+}
+
+fn write_layer(hasher: &mut Fnv1a, layer: &Layer) {
+    write_len(hasher, layer.input_width());
+    write_len(hasher, layer.len());
+    let flag = if layer.normalizer().is_some() {
+        0x80
+    } else {
+        0
+    };
+    match layer.wiring() {
+        Wiring::Dense => hasher.write(&[flag]),
+        Wiring::Sparse(wiring) => {
+            hasher.write(&[1 | flag]);
+            for indices in wiring {
+                write_len(hasher, indices.len());
+                for &index in indices {
+                    write_len(hasher, index);
+                }
+            }
+        }
+    }
+    for node in layer.nodes() {
+        write_node(hasher, node);
+    }
+    if let Some(normalizer) = layer.normalizer() {
+        write_str(hasher, &normalizer.to_string());
+    }
+}
+
+fn write_node(hasher: &mut Fnv1a, node: &SignalNode) {
+    write_str(hasher, &node.combinator().identifier());
+    match node.combinator().parameters() {
+        Some(args) => {
+            hasher.write(&[1]);
+            write_str(hasher, &args);
+        }
+        None => hasher.write(&[0]),
+    }
+    match node.input_mappers() {
+        InputMappers::Identity => hasher.write(&[0]),
+        InputMappers::Shared(mapper) => {
+            hasher.write(&[1]);
+            hasher.write_u64(mapper.digest());
+        }
+        InputMappers::PerInput(mappers) => {
+            hasher.write(&[2]);
+            write_len(hasher, mappers.len());
+            for mapper in mappers {
+                hasher.write_u64(mapper.digest());
+            }
+        }
+    }
+    match node.activation() {
+        Some(activation) => {
+            hasher.write(&[1]);
+            hasher.write_u64(activation.digest());
+        }
+        None => hasher.write(&[0]),
+    }
+    match node.input_count() {
+        Some(count) => {
+            hasher.write(&[1]);
+            write_len(hasher, count);
+        }
+        None => hasher.write(&[0]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{MaxCombinatorNode, XORCombinatorNode};
+    use crate::neural::mapper::MapperNode;
+    use crate::neural::normalizer::Normalizer;
+
+    #[cfg(feature = "json")]
+    const UPPERCASE: &str = include_str!("../../tests/fixtures/uppercase.json");
+    const MAX3: &[u8] = include_bytes!("../../tests/fixtures/max3.snnb");
+
+    fn network(name: &str, fill: u8) -> Network {
+        Network::builder()
+            .input_width(2)
+            .layer_dense(1, |n| {
+                n.shared_mapper(MapperNode::new().with_fill(fill))
+                    .combinator(Box::new(XORCombinatorNode::new()))
+                    .name(name)
+            })
+            .layer_dense(1, |n| n.combinator(Box::new(MaxCombinatorNode::new())))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pinned_fixture_digests() {
+        #[cfg(feature = "json")]
+        {
+            let uppercase = Network::from_json(UPPERCASE).unwrap();
+            assert_eq!(uppercase.digest(), 0x78d9_902a_d77a_1b12);
+        }
+        let max3 = Network::read_binary(MAX3).unwrap();
+        assert_eq!(max3.digest(), 0xf676_4153_464d_3965);
+        assert_eq!(max3.short_id(), "f6764153");
+        assert_eq!(Network::default().digest(), 0xa8c7_f832_281a_39c5);
+    }
+
+    #[test]
+    fn test_equal_networks_have_equal_digests() {
+        #[cfg(feature = "json")]
+        {
+            let max3 = Network::read_binary(MAX3).unwrap();
+            let copy = Network::from_json(&max3.to_json()).unwrap();
+            assert_eq!(copy.digest(), max3.digest());
+        }
+        assert_eq!(network("a", 1).digest(), network("b", 1).digest());
+        assert_ne!(network("a", 1).digest(), network("a", 2).digest());
+    }
+
+    #[test]
+    fn test_normalizer_changes_digest() {
+        let with = |normalizer: Normalizer| {
+            Network::try_new(vec![Layer::splitter(2, 1).with_normalizer(normalizer)])
+                .unwrap()
+                .digest()
+        };
+        let plain = Network::try_new(vec![Layer::splitter(2, 1)])
+            .unwrap()
+            .digest();
+        assert_ne!(with(Normalizer::linear()), plain);
+        assert_ne!(with(Normalizer::linear()), with(Normalizer::sum()));
+        assert_ne!(with(Normalizer::softmax(1)), with(Normalizer::softmax(2)));
+        assert_eq!(with(Normalizer::softmax(2)), with(Normalizer::softmax(2)));
+    }
+}
@@ -0,0 +1,243 @@
+/// Graphviz DOT export for networks and layers.
+use std::fmt::Write;
+
+use crate::neural::layer::{Layer, Wiring};
+use crate::neural::network::Network;
+
+/// Settings for `Network::to_dot_with` and `Layer::to_dot_with`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotOptions {
+    /// Labels every edge with the input position it feeds on the target node.
+    pub edge_labels: bool,
+    /// The Graphviz `rankdir`, such as `"LR"` or `"TB"`.
+    pub rankdir: String,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            edge_labels: false,
+            rankdir: "LR".to_string(),
+        }
+    }
+}
+
+impl DotOptions {
+    /// Returns the options with edge labels turned on or off.
+    pub fn with_edge_labels(self, edge_labels: bool) -> Self {
+        Self {
+            edge_labels,
+            ..self
+        }
+    }
+
+    /// Returns the options with the given `rankdir`.
+    pub fn with_rankdir(self, rankdir: &str) -> Self {
+        Self {
+            rankdir: rankdir.to_string(),
+            ..self
+        }
+    }
+}
+
+impl Network {
+    /// Returns the network as a Graphviz DOT graph with the default options.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&DotOptions::default())
+    }
+
+    /// Returns the network as a Graphviz DOT graph. Inputs are drawn as inverted triangles,
+    /// nodes as boxes labelled with their combinator, and outputs as triangles.
+    /// Node `j` of layer `i` is called `l{i}n{j}`.
+    pub fn to_dot_with(&self, options: &DotOptions) -> String {
+        let mut out = header("network", options);
+        let mut sources = input_ports(&mut out, self.input_width().unwrap_or(0));
+        for (index, layer) in self.layers().iter().enumerate() {
+            sources = write_layer(&mut out, layer, index, &sources, options);
+        }
+        output_ports(&mut out, &sources);
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl Layer {
+    /// Returns the layer as a Graphviz DOT graph with the default options.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&DotOptions::default())
+    }
+
+    /// Returns the layer as a Graphviz DOT graph, drawn like a one-layer network.
+    pub fn to_dot_with(&self, options: &DotOptions) -> String {
+        let mut out = header("layer", options);
+        let sources = input_ports(&mut out, self.input_width());
+        let sources = write_layer(&mut out, self, 0, &sources, options);
+        output_ports(&mut out, &sources);
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn header(name: &str, options: &DotOptions) -> String {
+    let mut out = format!("digraph {name} {{\n");
+    writeln!(out, "  rankdir={};", quote(&options.rankdir)).unwrap();
+    out
+}
+
+fn input_ports(out: &mut String, width: usize) -> Vec<String> {
+    (0..width)
+        .map(|i| {
+            writeln!(out, "  in{i} [shape=invtriangle, label=\"in {i}\"];").unwrap();
+            format!("in{i}")
+        })
+        .collect()
+}
+
+fn output_ports(out: &mut String, sources: &[String]) {
+    for (i, source) in sources.iter().enumerate() {
+        writeln!(out, "  out{i} [shape=triangle, label=\"out {i}\"];").unwrap();
+        writeln!(out, "  {source} -> out{i};").unwrap();
+    }
+}
+
+/// Writes the nodes of a layer and the edges into them, returning the node names.
+fn write_layer(
+    out: &mut String,
+    layer: &Layer,
+    index: usize,
+    sources: &[String],
+    options: &DotOptions,
+) -> Vec<String> {
+    let mut names = Vec::with_capacity(layer.len());
+    for (n, node) in layer.nodes().iter().enumerate() {
+        let name = format!("l{index}n{n}");
+        writeln!(
+            out,
+            "  {name} [shape=box, label={}];",
+            quote(&node.combinator().describe())
+        )
+        .unwrap();
+        let inputs: Vec<usize> = match layer.wiring() {
+            Wiring::Dense => (0..sources.len()).collect(),
+            Wiring::Sparse(wiring) => wiring[n].clone(),
+        };
+        for (position, input) in inputs.into_iter().enumerate() {
+            if options.edge_labels {
+                writeln!(
+                    out,
+                    "  {} -> {name} [label=\"{position}\"];",
+                    sources[input]
+                )
+                .unwrap();
+            } else {
+                writeln!(out, "  {} -> {name};", sources[input]).unwrap();
+            }
+        }
+        names.push(name);
+    }
+    names
+}
+
+/// Returns `text` as a quoted DOT string.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{
+        AdditionCombinatorNode, MaxCombinatorNode, ThresholdCombinatorNode,
+    };
+    use crate::neural::combinator_compare::GreaterThanCombinatorNode;
+    use crate::neural::node::SignalNode;
+    use crate::neural::traits::Combinator;
+
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    fn sample_network() -> Network {
+        Network::builder()
+            .input_width(3)
+            .layer_nodes(vec![
+                node(MaxCombinatorNode::new()),
+                node(AdditionCombinatorNode::new()),
+            ])
+            .layer_sparse(
+                vec![node(GreaterThanCombinatorNode::new())],
+                vec![vec![1, 0]],
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn count(dot: &str, pattern: &str) -> usize {
+        dot.lines().filter(|line| line.contains(pattern)).count()
+    }
+
+    #[test]
+    fn test_network_dot() {
+        let dot = sample_network().to_dot();
+        assert!(dot.starts_with("digraph network {\n  rankdir=\"LR\";\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+        // 3 inputs, 3 nodes and 1 output.
+        assert_eq!(count(&dot, "[shape="), 7);
+        assert_eq!(count(&dot, "shape=invtriangle"), 3);
+        assert_eq!(count(&dot, "shape=box"), 3);
+        assert_eq!(count(&dot, "shape=triangle"), 1);
+        // 2 dense nodes reading 3 inputs, 1 node reading 2, and 1 output edge.
+        assert_eq!(count(&dot, " -> "), 9);
+        assert!(dot.contains("  l0n0 [shape=box, label=\"max\"];\n"));
+        assert!(dot.contains("  l1n0 [shape=box, label=\"greater_than\"];\n"));
+        assert!(dot.contains("  l0n1 -> l1n0;\n  l0n0 -> l1n0;\n"));
+        assert!(dot.contains("  l1n0 -> out0;\n"));
+        assert_eq!(count(&dot, "label=\"0\""), 0);
+    }
+
+    #[test]
+    fn test_dot_options() {
+        let options = DotOptions::default()
+            .with_edge_labels(true)
+            .with_rankdir("TB");
+        let dot = sample_network().to_dot_with(&options);
+        assert!(dot.contains("rankdir=\"TB\";"));
+        assert!(dot.contains("  l0n1 -> l1n0 [label=\"0\"];\n  l0n0 -> l1n0 [label=\"1\"];\n"));
+        // Edges to outputs carry no label.
+        assert_eq!(count(&dot, "[label="), 8);
+    }
+
+    #[test]
+    fn test_dot_labels_describe_parameters() {
+        let network = Network::builder()
+            .input_width(2)
+            .layer_nodes(vec![node(ThresholdCombinatorNode::new(300, 0, 255))])
+            .build()
+            .unwrap();
+        assert!(
+            network.to_dot().contains(
+                "  l0n0 [shape=box, label=\"threshold(cutoff=300, low=0, high=255)\"];\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_layer_dot() {
+        let network = sample_network();
+        let dot = network.layers()[1].to_dot();
+        assert!(dot.starts_with("digraph layer {"));
+        assert_eq!(count(&dot, "[shape="), 2 + 1 + 1);
+        assert_eq!(count(&dot, " -> "), 3);
+        assert!(dot.contains("  in1 -> l0n0;\n  in0 -> l0n0;\n"));
+    }
+
+    #[test]
+    fn test_empty_network_dot() {
+        let dot = Network::default().to_dot();
+        assert_eq!(dot, "digraph network {\n  rankdir=\"LR\";\n}\n");
+    }
+}
@@ -0,0 +1,247 @@
+/// Checks whether two networks give the same outputs.
+use crate::neural::network::{EvalError, Network};
+use crate::neural::rng::Rng;
+
+/// The seed `equivalent_to` uses when it samples inputs, so that reports are reproducible.
+pub const DEFAULT_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// The widest input that `equivalent_to` checks exhaustively.
+pub const MAX_EXHAUSTIVE_WIDTH: usize = 2;
+
+/// The result of comparing two networks with `Network::equivalent_to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EquivalenceReport {
+    /// The number of inputs both networks were run on.
+    pub inputs_checked: usize,
+    /// True if every possible input was checked, so equivalence is proven.
+    pub exhaustive: bool,
+    /// The first input on which the networks differ, if any.
+    pub difference: Option<Difference>,
+}
+
+/// An input on which two networks give different results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    pub input: Vec<u8>,
+    /// The result of the network `equivalent_to` was called on.
+    pub left: Result<Vec<u8>, EvalError>,
+    /// The result of the network passed to `equivalent_to`.
+    pub right: Result<Vec<u8>, EvalError>,
+}
+
+impl EquivalenceReport {
+    /// Returns true if no difference was found.
+    pub fn is_equivalent(&self) -> bool {
+        self.difference.is_none()
+    }
+}
+
+impl Network {
+    /// Compares the outputs of this network and `other`.
+    /// Networks with an input width of at most `MAX_EXHAUSTIVE_WIDTH` are run on every
+    /// possible input; wider ones on `max_inputs` inputs drawn from `DEFAULT_SEED`.
+    /// The input width is taken from this network, or from `other` if this one is empty.
+    pub fn equivalent_to(&self, other: &Network, max_inputs: usize) -> EquivalenceReport {
+        self.equivalent_to_seeded(other, max_inputs, DEFAULT_SEED)
+    }
+
+    /// Like `equivalent_to`, but samples wide inputs from `seed`.
+    pub fn equivalent_to_seeded(
+        &self,
+        other: &Network,
+        max_inputs: usize,
+        seed: u64,
+    ) -> EquivalenceReport {
+        let width = self.input_width().or(other.input_width()).unwrap_or(0);
+        let exhaustive = width <= MAX_EXHAUSTIVE_WIDTH;
+        let mut inputs_checked = 0;
+        let mut check = |input: &[u8]| -> Option<Difference> {
+            inputs_checked += 1;
+            let left = self.forward(input);
+            let right = other.forward(input);
+            (left != right).then(|| Difference {
+                input: input.to_vec(),
+                left,
+                right,
+            })
+        };
+        let difference = if exhaustive {
+            let count = 1usize << (8 * width);
+            (0..count).find_map(|n| check(&n.to_le_bytes()[..width]))
+        } else {
+            // Seed 0 becomes `DEFAULT_SEED`, the same constant `Rng` substitutes for it.
+            let mut rng = Rng::new(seed);
+            let mut input = vec![0; width];
+            (0..max_inputs).find_map(|_| {
+                rng.fill(&mut input);
+                check(&input)
+            })
+        };
+        EquivalenceReport {
+            inputs_checked,
+            exhaustive,
+            difference,
+        }
+    }
+}
+
+impl std::fmt::Display for EquivalenceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scope = if self.exhaustive {
+            "all"
+        } else {
+            "a sample of"
+        };
+        match &self.difference {
+            None => write!(f, "equivalent on {scope} {} inputs", self.inputs_checked),
+            Some(difference) => {
+                writeln!(
+                    f,
+                    "networks differ on input {:?}, found after {} inputs",
+                    difference.input, self.inputs_checked
+                )?;
+                match (&difference.left, &difference.right) {
+                    (Ok(left), Ok(right)) => {
+                        for i in 0..left.len().max(right.len()) {
+                            let (l, r) = (left.get(i), right.get(i));
+                            if l != r {
+                                writeln!(f, "  output {i}: {} != {}", show(l), show(r))?;
+                            }
+                        }
+                        Ok(())
+                    }
+                    (left, right) => {
+                        writeln!(f, "  left:  {}", show_result(left))?;
+                        writeln!(f, "  right: {}", show_result(right))
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn show(value: Option<&u8>) -> String {
+    value.map_or("missing".to_string(), u8::to_string)
+}
+
+fn show_result(result: &Result<Vec<u8>, EvalError>) -> String {
+    match result {
+        Ok(outputs) => format!("{outputs:?}"),
+        Err(error) => format!("error: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode, MinCombinatorNode};
+    use crate::neural::mapper::MapperNode;
+    use crate::neural::node::SignalNode;
+
+    fn halve() -> MapperNode {
+        MapperNode::new_transformation(|x| x as u8 / 2)
+    }
+
+    /// Halves two inputs and adds them, with the halving on the inputs.
+    fn mapped_inputs(activation: MapperNode) -> Network {
+        Network::builder()
+            .input_width(2)
+            .layer_dense(1, |node| {
+                node.shared_mapper(halve())
+                    .combinator(Box::new(AdditionCombinatorNode::new()))
+                    .activation(activation.clone())
+            })
+            .build()
+            .unwrap()
+    }
+
+    /// The same function built from separate halving nodes.
+    fn separate_layers() -> Network {
+        let stage = || {
+            SignalNode::builder()
+                .shared_mapper(halve())
+                .combinator(Box::new(MaxCombinatorNode::new()))
+                .build()
+                .unwrap()
+        };
+        Network::builder()
+            .input_width(2)
+            .layer_sparse(vec![stage(), stage()], vec![vec![0], vec![1]])
+            .layer_dense(1, |node| {
+                node.combinator(Box::new(AdditionCombinatorNode::new()))
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn identity() -> MapperNode {
+        MapperNode::new_transformation(|x| x as u8)
+    }
+
+    #[test]
+    fn test_equivalent_networks() {
+        let report = mapped_inputs(identity()).equivalent_to(&separate_layers(), 0);
+        assert!(report.is_equivalent());
+        assert!(report.exhaustive);
+        assert_eq!(report.inputs_checked, 65536);
+        assert_eq!(report.to_string(), "equivalent on all 65536 inputs");
+    }
+
+    #[test]
+    fn test_flipped_table_entry() {
+        let mut table = *identity().table();
+        table[77] = 78;
+        let flipped = mapped_inputs(MapperNode::new_from(table));
+        let report = separate_layers().equivalent_to(&flipped, 0);
+        assert!(!report.is_equivalent());
+        // The first input in enumeration order whose halves add up to 77: 154 / 2 + 0.
+        assert_eq!(
+            report.difference,
+            Some(Difference {
+                input: vec![154, 0],
+                left: Ok(vec![77]),
+                right: Ok(vec![78])
+            })
+        );
+        assert_eq!(report.inputs_checked, 155);
+        assert_eq!(
+            report.to_string(),
+            "networks differ on input [154, 0], found after 155 inputs\n  output 0: 77 != 78\n"
+        );
+    }
+
+    #[test]
+    fn test_sampled_wide_networks() {
+        let wide = |combinator: fn() -> Box<dyn crate::neural::traits::Combinator>| {
+            Network::builder()
+                .input_width(4)
+                .layer_dense(1, |node| node.combinator(combinator()))
+                .build()
+                .unwrap()
+        };
+        let max = wide(|| Box::new(MaxCombinatorNode::new()));
+        let report = max.equivalent_to(&wide(|| Box::new(MaxCombinatorNode::new())), 500);
+        assert!(report.is_equivalent());
+        assert!(!report.exhaustive);
+        assert_eq!(report.inputs_checked, 500);
+
+        let report = max.equivalent_to(&wide(|| Box::new(MinCombinatorNode::new())), 500);
+        let difference = report.difference.clone().unwrap();
+        assert_eq!(difference.input.len(), 4);
+        assert_ne!(difference.left, difference.right);
+        // The same seed finds the same counterexample.
+        assert_eq!(
+            max.equivalent_to(&wide(|| Box::new(MinCombinatorNode::new())), 500),
+            report
+        );
+    }
+
+    #[test]
+    fn test_width_mismatch_is_a_difference() {
+        let report = separate_layers().equivalent_to(&Network::default(), 0);
+        let difference = report.difference.unwrap();
+        assert_eq!(difference.input, vec![0, 0]);
+        assert_eq!(difference.left, Ok(vec![0]));
+        assert_eq!(difference.right, Ok(vec![0, 0]));
+    }
+}
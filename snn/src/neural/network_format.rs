@@ -0,0 +1,125 @@
+/// Pieces shared by the network file formats: the format version, the loading error and
+/// node assembly. The JSON format lives in `network_json` and the binary one in
+/// `network_binary`.
+use crate::neural::combinator::CombinatorParseError;
+#[cfg(feature = "json")]
+use crate::neural::combinator_json::JsonError;
+use crate::neural::layer::LayerError;
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::NetworkError;
+use crate::neural::node::{InputMappers, NodeError, SignalNode};
+use crate::neural::traits::Combinator;
+
+/// The version of the JSON and binary network formats this crate writes and reads.
+pub const FORMAT_VERSION: u64 = 1;
+
+/// Assembles a loaded node, reporting builder errors against its position.
+pub(crate) fn build_node(
+    (layer, node): (usize, usize),
+    combinator: Box<dyn Combinator>,
+    input_mappers: InputMappers,
+    activation: Option<MapperNode>,
+    input_count: Option<usize>,
+) -> Result<SignalNode, NetworkFormatError> {
+    let mut builder = SignalNode::builder().combinator(combinator);
+    match input_mappers {
+        InputMappers::Identity => {}
+        InputMappers::Shared(mapper) => builder = builder.shared_mapper(mapper),
+        InputMappers::PerInput(mappers) => {
+            for mapper in mappers {
+                builder = builder.input_mapper(mapper);
+            }
+        }
+    }
+    if let Some(activation) = activation {
+        builder = builder.activation(activation);
+    }
+    if let Some(count) = input_count {
+        builder = builder.input_count(count);
+    }
+    builder
+        .build()
+        .map_err(|error| NetworkFormatError::Node { layer, node, error })
+}
+
+/// Error returned when a stored network cannot be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkFormatError {
+    /// The text was not valid JSON.
+    #[cfg(feature = "json")]
+    Json(JsonError),
+    /// The data does not have the expected shape; the message names the field at fault.
+    InvalidFormat(String),
+    /// The data was written by a newer or unknown version of the format.
+    UnsupportedVersion(u64),
+    /// Binary data ended before the network was complete.
+    Truncated,
+    /// Reading binary data failed; holds the I/O error message.
+    Io(String),
+    /// A node names a combinator that does not exist or has invalid arguments.
+    Combinator {
+        layer: usize,
+        node: usize,
+        error: CombinatorParseError,
+    },
+    /// A node's mappers or input count do not fit together.
+    Node {
+        layer: usize,
+        node: usize,
+        error: NodeError,
+    },
+    /// A layer's wiring is invalid.
+    Layer { layer: usize, error: LayerError },
+    /// The layers do not fit together.
+    Network(NetworkError),
+}
+
+impl std::fmt::Display for NetworkFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "json")]
+            NetworkFormatError::Json(e) => write!(f, "{e}"),
+            NetworkFormatError::InvalidFormat(message) => {
+                write!(f, "invalid network format: {message}")
+            }
+            NetworkFormatError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported network format version {version}, this build reads version {FORMAT_VERSION}"
+            ),
+            NetworkFormatError::Truncated => write!(f, "network data ends unexpectedly"),
+            NetworkFormatError::Io(message) => write!(f, "failed to read network: {message}"),
+            NetworkFormatError::Combinator { layer, node, error } => {
+                write!(f, "layer {layer}: node {node}: {error}")
+            }
+            NetworkFormatError::Node { layer, node, error } => {
+                write!(f, "layer {layer}: node {node}: {error}")
+            }
+            NetworkFormatError::Layer { layer, error } => write!(f, "layer {layer}: {error}"),
+            NetworkFormatError::Network(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for NetworkFormatError {}
+
+#[cfg(feature = "json")]
+impl From<JsonError> for NetworkFormatError {
+    fn from(e: JsonError) -> Self {
+        NetworkFormatError::Json(e)
+    }
+}
+
+impl From<std::io::Error> for NetworkFormatError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => NetworkFormatError::Truncated,
+            _ => NetworkFormatError::Io(e.to_string()),
+        }
+    }
+}
+
+impl From<NetworkError> for NetworkFormatError {
+    fn from(e: NetworkError) -> Self {
+        NetworkFormatError::Network(e)
+    }
+}
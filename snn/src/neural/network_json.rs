@@ -0,0 +1,563 @@
+/// The JSON file format for whole networks.
+///
+/// The format stores a `version` and the layers in order. Each layer has its
+/// `input_width`, its `wiring` (`"dense"` or one list of input indices per node) and its
+/// `nodes`, plus a `normalizer` (`"linear"`, `"sum"` or `"softmax:<temperature>"`) when the
+/// layer has one. A node has a `combinator` in the tagged form of `combinator_json`, its
+/// `input_mappers` (`null` for none, `{"shared": table}` or a list with one table per input),
+/// an `activation` table or `null`, the fixed `input_count` or `null`, and an optional `name`.
+/// Every mapper table is its 256 bytes encoded as standard base64 with padding.
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "layers": [
+///     {
+///       "input_width": 2,
+///       "wiring": "dense",
+///       "nodes": [
+///         {
+///           "combinator": {"type": "threshold", "args": "300:0:255"},
+///           "input_mappers": null,
+///           "activation": null,
+///           "input_count": null
+///         }
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+use crate::neural::combinator_json::{
+    CombinatorDeserializeError, combinator_from_value, combinator_to_value,
+};
+use crate::neural::json::{self, Value};
+use crate::neural::layer::{Layer, Wiring};
+use crate::neural::mapper::MapperNode;
+use crate::neural::mapper_shared::SharedMapper;
+use crate::neural::network::Network;
+use crate::neural::network_format::{FORMAT_VERSION, NetworkFormatError, build_node};
+use crate::neural::node::{InputMappers, SignalNode};
+use crate::neural::normalizer::Normalizer;
+
+impl Network {
+    /// Serializes the network to JSON text.
+    pub fn to_json(&self) -> String {
+        let layers = self.layers().iter().map(layer_to_value).collect();
+        Value::Object(vec![
+            ("version".to_string(), FORMAT_VERSION.into()),
+            ("layers".to_string(), Value::Array(layers)),
+        ])
+        .to_json_pretty()
+    }
+
+    /// Loads a network from JSON text produced by `to_json`, validating every layer and node.
+    pub fn from_json(text: &str) -> Result<Network, NetworkFormatError> {
+        let value = json::parse(text)?;
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid("", "missing integer field \"version\""))?;
+        if version != FORMAT_VERSION {
+            return Err(NetworkFormatError::UnsupportedVersion(version));
+        }
+        let layers = value
+            .get("layers")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("", "missing array field \"layers\""))?;
+        let layers = layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| layer_from_value(index, layer))
+            .collect::<Result<_, _>>()?;
+        Ok(Network::try_new(layers)?)
+    }
+}
+
+fn layer_to_value(layer: &Layer) -> Value {
+    let wiring = match layer.wiring() {
+        Wiring::Dense => Value::from("dense"),
+        Wiring::Sparse(wiring) => Value::Array(
+            wiring
+                .iter()
+                .map(|indices| Value::Array(indices.iter().map(|&i| (i as u64).into()).collect()))
+                .collect(),
+        ),
+    };
+    let mut fields = vec![
+        (
+            "input_width".to_string(),
+            (layer.input_width() as u64).into(),
+        ),
+        ("wiring".to_string(), wiring),
+        (
+            "nodes".to_string(),
+            Value::Array(layer.nodes().iter().map(node_to_value).collect()),
+        ),
+    ];
+    if let Some(normalizer) = layer.normalizer() {
+        fields.push((
+            "normalizer".to_string(),
+            Value::from(normalizer.to_string()),
+        ));
+    }
+    Value::Object(fields)
+}
+
+fn node_to_value(node: &SignalNode) -> Value {
+    let input_mappers = match node.input_mappers() {
+        InputMappers::Identity => Value::Null,
+        InputMappers::Shared(mapper) => {
+            Value::Object(vec![("shared".to_string(), mapper_to_value(mapper))])
+        }
+        InputMappers::PerInput(mappers) => Value::Array(
+            mappers
+                .iter()
+                .map(|mapper| mapper_to_value(mapper))
+                .collect(),
+        ),
+    };
+    Value::Object(vec![
+        (
+            "combinator".to_string(),
+            combinator_to_value(node.combinator()),
+        ),
+        ("input_mappers".to_string(), input_mappers),
+        (
+            "activation".to_string(),
+            node.activation().map_or(Value::Null, mapper_to_value),
+        ),
+        (
+            "input_count".to_string(),
+            node.input_count()
+                .map_or(Value::Null, |count| (count as u64).into()),
+        ),
+        (
+            "name".to_string(),
+            node.name().map_or(Value::Null, Value::from),
+        ),
+    ])
+}
+
+fn mapper_to_value(mapper: &MapperNode) -> Value {
+    Value::String(base64_encode(mapper.table()))
+}
+
+fn layer_from_value(index: usize, value: &Value) -> Result<Layer, NetworkFormatError> {
+    let path = format!("layers[{index}]");
+    let input_width = value
+        .get("input_width")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| invalid(&path, "missing integer field \"input_width\""))?
+        as usize;
+    let wiring = match value.get("wiring") {
+        Some(Value::String(kind)) if kind == "dense" => Wiring::Dense,
+        Some(Value::Array(entries)) => Wiring::Sparse(
+            entries
+                .iter()
+                .map(|entry| {
+                    entry
+                        .as_array()
+                        .and_then(|indices| {
+                            indices
+                                .iter()
+                                .map(|i| i.as_u64().map(|i| i as usize))
+                                .collect::<Option<Vec<_>>>()
+                        })
+                        .ok_or_else(|| invalid(&path, "wiring entries must be lists of indices"))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        _ => {
+            return Err(invalid(
+                &path,
+                "field \"wiring\" must be \"dense\" or a list of index lists",
+            ));
+        }
+    };
+    let nodes = value
+        .get("nodes")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid(&path, "missing array field \"nodes\""))?
+        .iter()
+        .enumerate()
+        .map(|(node, value)| node_from_value(index, node, value))
+        .collect::<Result<_, _>>()?;
+    let normalizer = match value.get("normalizer") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(text)) => Some(
+            text.parse::<Normalizer>()
+                .map_err(|error| invalid(&path, &error.to_string()))?,
+        ),
+        Some(_) => return Err(invalid(&path, "field \"normalizer\" must be a string")),
+    };
+    let layer =
+        Layer::new(nodes, wiring, input_width).map_err(|error| NetworkFormatError::Layer {
+            layer: index,
+            error,
+        })?;
+    Ok(match normalizer {
+        Some(normalizer) => layer.with_normalizer(normalizer),
+        None => layer,
+    })
+}
+
+fn node_from_value(
+    layer: usize,
+    node: usize,
+    value: &Value,
+) -> Result<SignalNode, NetworkFormatError> {
+    let path = format!("layers[{layer}].nodes[{node}]");
+    let combinator = value
+        .get("combinator")
+        .ok_or_else(|| invalid(&path, "missing field \"combinator\""))?;
+    let combinator = combinator_from_value(combinator).map_err(|error| match error {
+        CombinatorDeserializeError::Parse(error) => {
+            NetworkFormatError::Combinator { layer, node, error }
+        }
+        error => invalid(&path, &error.to_string()),
+    })?;
+    let input_mappers = match value.get("input_mappers") {
+        None | Some(Value::Null) => InputMappers::Identity,
+        Some(Value::Array(tables)) => InputMappers::PerInput(
+            tables
+                .iter()
+                .map(|table| mapper_from_value(&path, table).map(SharedMapper::new))
+                .collect::<Result<_, _>>()?,
+        ),
+        Some(shared @ Value::Object(_)) => {
+            let table = shared
+                .get("shared")
+                .ok_or_else(|| invalid(&path, "missing field \"shared\" in \"input_mappers\""))?;
+            InputMappers::Shared(mapper_from_value(&path, table)?.into())
+        }
+        Some(_) => {
+            return Err(invalid(
+                &path,
+                "field \"input_mappers\" must be null, a list or {\"shared\": ...}",
+            ));
+        }
+    };
+    let activation = match value.get("activation") {
+        None | Some(Value::Null) => None,
+        Some(table) => Some(mapper_from_value(&path, table)?),
+    };
+    let input_count = match value.get("input_count") {
+        None | Some(Value::Null) => None,
+        Some(count) => Some(
+            count
+                .as_u64()
+                .ok_or_else(|| invalid(&path, "field \"input_count\" must be an integer"))?
+                as usize,
+        ),
+    };
+    let name = match value.get("name") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(name)) => Some(name.clone()),
+        Some(_) => return Err(invalid(&path, "field \"name\" must be a string")),
+    };
+    let mut node = build_node(
+        (layer, node),
+        combinator,
+        input_mappers,
+        activation,
+        input_count,
+    )?;
+    if let Some(name) = name {
+        node.set_name(name);
+    }
+    Ok(node)
+}
+
+fn mapper_from_value(path: &str, value: &Value) -> Result<MapperNode, NetworkFormatError> {
+    value
+        .as_str()
+        .and_then(base64_decode)
+        .and_then(|bytes| <[u8; 256]>::try_from(bytes).ok())
+        .map(MapperNode::new_from)
+        .ok_or_else(|| invalid(path, "mapper tables must be 256 bytes of base64"))
+}
+
+fn invalid(path: &str, message: &str) -> NetworkFormatError {
+    if path.is_empty() {
+        NetworkFormatError::InvalidFormat(message.to_string())
+    } else {
+        NetworkFormatError::InvalidFormat(format!("{path}: {message}"))
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as standard base64 with padding.
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes standard base64 with padding, returning `None` for malformed text.
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (index, chunk) in bytes.chunks(4).enumerate() {
+        let last = index == bytes.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+        let mut n = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let digit = BASE64_ALPHABET.iter().position(|&a| a == c)? as u32;
+            n = n << 6 | digit;
+        }
+        n <<= 6 * padding;
+        out.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{
+        AdditionCombinatorNode, CombinatorParseError, MaxCombinatorNode, ThresholdCombinatorNode,
+    };
+    use crate::neural::layer::LayerError;
+    use crate::neural::mapper_defaults::{
+        create_mapper_for_character, create_mapper_for_lowercase,
+    };
+    use crate::neural::network::NetworkError;
+
+    fn sample_network() -> Network {
+        Network::builder()
+            .input_width(3)
+            .layer_dense(2, |node| {
+                node.shared_mapper(create_mapper_for_character())
+                    .combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .layer_sparse(
+                vec![
+                    SignalNode::builder()
+                        .name("lower")
+                        .input_mapper(create_mapper_for_lowercase())
+                        .input_mapper(MapperNode::new_transformation(|x| x as u8 / 2))
+                        .combinator(Box::new(ThresholdCombinatorNode::new(20, 1, 200)))
+                        .activation(MapperNode::new_transformation(|x| 255 - x as u8))
+                        .build()
+                        .unwrap(),
+                    SignalNode::builder()
+                        .combinator(Box::new(AdditionCombinatorNode::new()))
+                        .input_count(3)
+                        .build()
+                        .unwrap(),
+                ],
+                vec![vec![1, 0], vec![0, 0, 1]],
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_base64() {
+        for (bytes, text) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foobar", "Zm9vYmFy"),
+            (&[0, 255, 128, 7], "AP+ABw=="),
+        ] {
+            assert_eq!(base64_encode(bytes), text);
+            assert_eq!(base64_decode(text).as_deref(), Some(bytes));
+        }
+        assert_eq!(base64_decode("Zg="), None);
+        assert_eq!(base64_decode("Zg==Zg=="), None);
+        assert_eq!(base64_decode("Z==="), None);
+        assert_eq!(base64_decode("Zm9*"), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let network = sample_network();
+        let text = network.to_json();
+        let restored = Network::from_json(&text).unwrap();
+        assert_eq!(restored.to_json(), text);
+        assert!(restored.node("lower").is_some());
+        for input in [b"abc", b"A1 ", b"zz9", b"\0\0\0", b"~~~"] {
+            assert_eq!(restored.forward(input), network.forward(input), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalizer_round_trip() {
+        let network = Network::builder()
+            .input_width(3)
+            .normalizer(Normalizer::softmax(24))
+            .layer_nodes(vec![
+                SignalNode::builder()
+                    .combinator(Box::new(MaxCombinatorNode::new()))
+                    .build()
+                    .unwrap(),
+            ])
+            .build()
+            .unwrap();
+        let text = network.to_json();
+        assert!(text.contains(r#""normalizer": "softmax:24""#), "{text}");
+        let restored = Network::from_json(&text).unwrap();
+        assert_eq!(
+            restored.layers()[0].normalizer(),
+            Some(&Normalizer::softmax(24))
+        );
+        assert_eq!(restored.layers()[1].normalizer(), None);
+        assert_eq!(restored.to_json(), text);
+
+        let bad = text.replace("softmax:24", "softmax:x");
+        assert!(matches!(
+            Network::from_json(&bad),
+            Err(NetworkFormatError::InvalidFormat(message))
+                if message == r#"layers[0]: invalid softmax temperature "x""#
+        ));
+    }
+
+    #[test]
+    fn test_fixture() {
+        let identity = base64_encode(&std::array::from_fn::<u8, 256, _>(|i| i as u8));
+        let doubled = base64_encode(&std::array::from_fn::<u8, 256, _>(|i| {
+            (i as u8).saturating_mul(2)
+        }));
+        let fixture = format!(
+            r#"{{
+                "version": 1,
+                "layers": [
+                    {{
+                        "input_width": 2,
+                        "wiring": "dense",
+                        "nodes": [
+                            {{
+                                "combinator": {{"type": "max"}},
+                                "input_mappers": {{"shared": "{doubled}"}},
+                                "activation": null,
+                                "input_count": null
+                            }},
+                            {{
+                                "combinator": {{"type": "threshold", "args": "100:0:255"}},
+                                "input_mappers": ["{identity}", "{doubled}"],
+                                "activation": null,
+                                "input_count": 2
+                            }}
+                        ]
+                    }},
+                    {{
+                        "input_width": 2,
+                        "wiring": [[1]],
+                        "nodes": [
+                            {{"combinator": {{"type": "min"}}, "activation": "{identity}"}}
+                        ]
+                    }}
+                ]
+            }}"#
+        );
+        let network = Network::from_json(&fixture).unwrap();
+        assert_eq!(network.len(), 2);
+        assert_eq!(
+            network.forward_trace(&[10, 30]),
+            Ok(vec![vec![60, 0], vec![0]])
+        );
+        assert_eq!(
+            network.forward_trace(&[40, 30]),
+            Ok(vec![vec![80, 255], vec![255]])
+        );
+        assert_eq!(network.forward(&[200, 0]), Ok(vec![255]));
+    }
+
+    fn minimal(version: &str, combinator: &str, wiring: &str) -> String {
+        format!(
+            r#"{{"version": {version}, "layers": [
+                {{"input_width": 1, "wiring": {wiring}, "nodes": [{{"combinator": {combinator}}}]}},
+                {{"input_width": 1, "wiring": "dense", "nodes": [{{"combinator": {{"type": "max"}}}}]}}
+            ]}}"#
+        )
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let max = r#"{"type": "max"}"#;
+        assert!(Network::from_json(&minimal("1", max, r#""dense""#)).is_ok());
+        assert_eq!(
+            Network::from_json(&minimal("2", max, r#""dense""#)).err(),
+            Some(NetworkFormatError::UnsupportedVersion(2))
+        );
+
+        let unknown = Network::from_json(&minimal("1", r#"{"type": "maxx"}"#, r#""dense""#))
+            .err()
+            .unwrap();
+        assert_eq!(
+            unknown,
+            NetworkFormatError::Combinator {
+                layer: 0,
+                node: 0,
+                error: CombinatorParseError::UnknownName("maxx".to_string())
+            }
+        );
+        assert!(unknown.to_string().starts_with("layer 0: node 0: "));
+
+        assert!(matches!(
+            Network::from_json(&minimal("1", max, "[[1]]")),
+            Err(NetworkFormatError::Layer {
+                layer: 0,
+                error: LayerError::IndexOutOfRange { .. }
+            })
+        ));
+        assert!(matches!(
+            Network::from_json(&minimal("1", max, "7")),
+            Err(NetworkFormatError::InvalidFormat(message)) if message.starts_with("layers[0]")
+        ));
+        assert!(matches!(
+            Network::from_json("{"),
+            Err(NetworkFormatError::Json(_))
+        ));
+
+        let bad_table = r#"{"version": 1, "layers": [{"input_width": 1, "wiring": "dense",
+            "nodes": [{"combinator": {"type": "max"}, "activation": "AAAA"}]}]}"#;
+        assert!(matches!(
+            Network::from_json(bad_table),
+            Err(NetworkFormatError::InvalidFormat(message)) if message.starts_with("layers[0].nodes[0]")
+        ));
+
+        let mismatch = r#"{"version": 1, "layers": [
+            {"input_width": 1, "wiring": "dense", "nodes": [{"combinator": {"type": "max"}}]},
+            {"input_width": 2, "wiring": "dense", "nodes": [{"combinator": {"type": "max"}}]}]}"#;
+        let duplicate = r#"{"version": 1, "layers": [{"input_width": 1, "wiring": "dense", "nodes": [
+            {"combinator": {"type": "max"}, "name": "a"},
+            {"combinator": {"type": "min"}, "name": "a"}]}]}"#;
+        assert_eq!(
+            Network::from_json(duplicate).err(),
+            Some(NetworkFormatError::Network(NetworkError::DuplicateName(
+                "a".to_string()
+            )))
+        );
+
+        assert!(matches!(
+            Network::from_json(mismatch),
+            Err(NetworkFormatError::Network(NetworkError::WidthMismatch {
+                layer: 1,
+                ..
+            }))
+        ));
+    }
+}
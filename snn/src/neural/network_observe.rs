@@ -0,0 +1,244 @@
+/// Watching every node evaluation of a forward pass.
+use crate::neural::layer::{Layer, Wiring};
+use crate::neural::network::{EvalError, Network};
+
+/// Receives every intermediate value of `Network::forward_observed`.
+pub trait EvalObserver {
+    /// Called after node `node_idx` of layer `layer_idx` maps `inputs` to `output`.
+    fn on_node(&mut self, layer_idx: usize, node_idx: usize, inputs: &[u8], output: u8);
+
+    /// Called after every node of layer `layer_idx` has been evaluated, with the outputs
+    /// normalized if the layer has a normalizer.
+    fn on_layer(&mut self, layer_idx: usize, outputs: &[u8]) {
+        let _ = (layer_idx, outputs);
+    }
+}
+
+impl Network {
+    /// Runs the input through every layer like `forward`, reporting each node evaluation to
+    /// `observer`. `forward` itself is unaffected, so only observed passes pay for the calls.
+    pub fn forward_observed(
+        &self,
+        input: &[u8],
+        observer: &mut dyn EvalObserver,
+    ) -> Result<Vec<u8>, EvalError> {
+        if let Some(expected) = self.input_width()
+            && expected != input.len()
+        {
+            return Err(EvalError::InputWidth {
+                expected,
+                actual: input.len(),
+            });
+        }
+        let mut values = input.to_vec();
+        for (layer_idx, layer) in self.layers().iter().enumerate() {
+            values = evaluate_layer(layer, layer_idx, &values, observer);
+            observer.on_layer(layer_idx, &values);
+        }
+        Ok(values)
+    }
+}
+
+fn evaluate_layer(
+    layer: &Layer,
+    layer_idx: usize,
+    inputs: &[u8],
+    observer: &mut dyn EvalObserver,
+) -> Vec<u8> {
+    let mut gathered = Vec::new();
+    let mut outputs = Vec::with_capacity(layer.len());
+    for (node_idx, node) in layer.nodes().iter().enumerate() {
+        let node_inputs = match layer.wiring() {
+            Wiring::Dense => inputs,
+            Wiring::Sparse(wiring) => {
+                gathered.clear();
+                gathered.extend(wiring[node_idx].iter().map(|&i| inputs[i]));
+                &gathered
+            }
+        };
+        let output = node.evaluate(node_inputs);
+        observer.on_node(layer_idx, node_idx, node_inputs, output);
+        outputs.push(output);
+    }
+    if let Some(normalizer) = layer.normalizer() {
+        normalizer.normalize_in_place(&mut outputs);
+    }
+    outputs
+}
+
+/// One node evaluation seen by an observer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeEvent {
+    pub layer: usize,
+    pub node: usize,
+    pub inputs: Vec<u8>,
+    pub output: u8,
+}
+
+/// Records every node evaluation, in evaluation order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordingObserver {
+    events: Vec<NodeEvent>,
+}
+
+impl RecordingObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorded evaluations.
+    pub fn events(&self) -> &[NodeEvent] {
+        &self.events
+    }
+
+    /// Returns the outputs of each layer, in the shape of `Network::forward_trace`.
+    pub fn layer_outputs(&self) -> Vec<Vec<u8>> {
+        let mut layers: Vec<Vec<u8>> = Vec::new();
+        for event in &self.events {
+            if layers.len() <= event.layer {
+                layers.resize(event.layer + 1, Vec::new());
+            }
+            layers[event.layer].push(event.output);
+        }
+        layers
+    }
+
+    /// Forgets every recorded evaluation.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl EvalObserver for RecordingObserver {
+    fn on_node(&mut self, layer_idx: usize, node_idx: usize, inputs: &[u8], output: u8) {
+        self.events.push(NodeEvent {
+            layer: layer_idx,
+            node: node_idx,
+            inputs: inputs.to_vec(),
+            output,
+        });
+    }
+}
+
+/// Records only the node evaluations whose output reaches a threshold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdObserver {
+    threshold: u8,
+    events: Vec<NodeEvent>,
+}
+
+impl ThresholdObserver {
+    /// Creates an observer that records outputs of at least `threshold`.
+    pub fn new(threshold: u8) -> Self {
+        Self {
+            threshold,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the recorded evaluations.
+    pub fn events(&self) -> &[NodeEvent] {
+        &self.events
+    }
+}
+
+impl EvalObserver for ThresholdObserver {
+    fn on_node(&mut self, layer_idx: usize, node_idx: usize, inputs: &[u8], output: u8) {
+        if output >= self.threshold {
+            self.events.push(NodeEvent {
+                layer: layer_idx,
+                node: node_idx,
+                inputs: inputs.to_vec(),
+                output,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode, MinCombinatorNode};
+    use crate::neural::node::SignalNode;
+    use crate::neural::traits::Combinator;
+
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    /// Layer 0 takes the max and min of each input pair, layer 1 adds all four.
+    fn network() -> Network {
+        Network::builder()
+            .input_width(4)
+            .layer_sparse(
+                vec![
+                    node(MaxCombinatorNode::new()),
+                    node(MinCombinatorNode::new()),
+                    node(MaxCombinatorNode::new()),
+                    node(MinCombinatorNode::new()),
+                ],
+                vec![vec![0, 1], vec![0, 1], vec![2, 3], vec![2, 3]],
+            )
+            .layer_dense(1, |n| n.combinator(Box::new(AdditionCombinatorNode::new())))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_recording_matches_trace() {
+        let network = network();
+        for input in [[1, 2, 3, 4], [40, 10, 90, 200]] {
+            let mut recorder = RecordingObserver::new();
+            let output = network.forward_observed(&input, &mut recorder);
+            assert_eq!(output, network.forward(&input));
+            assert_eq!(Ok(recorder.layer_outputs()), network.forward_trace(&input));
+        }
+
+        let mut recorder = RecordingObserver::new();
+        network
+            .forward_observed(&[1, 2, 3, 4], &mut recorder)
+            .unwrap();
+        assert_eq!(recorder.events().len(), 5);
+        assert_eq!(
+            recorder.events()[2],
+            NodeEvent {
+                layer: 0,
+                node: 2,
+                inputs: vec![3, 4],
+                output: 4
+            }
+        );
+        assert_eq!(recorder.events()[4].inputs, vec![2, 1, 4, 3]);
+        recorder.clear();
+        assert!(recorder.events().is_empty());
+    }
+
+    #[test]
+    fn test_threshold_observer() {
+        let mut observer = ThresholdObserver::new(100);
+        let output = network().forward_observed(&[40, 10, 90, 200], &mut observer);
+        assert_eq!(output, Ok(vec![255]));
+        let seen: Vec<(usize, usize, u8)> = observer
+            .events()
+            .iter()
+            .map(|e| (e.layer, e.node, e.output))
+            .collect();
+        assert_eq!(seen, vec![(0, 2, 200), (1, 0, 255)]);
+    }
+
+    #[test]
+    fn test_input_width() {
+        let mut recorder = RecordingObserver::new();
+        assert_eq!(
+            network().forward_observed(&[1], &mut recorder),
+            Err(EvalError::InputWidth {
+                expected: 4,
+                actual: 1
+            })
+        );
+        assert!(recorder.events().is_empty());
+    }
+}
@@ -0,0 +1,294 @@
+/// Optimization passes that rewrite a network without changing its outputs.
+use crate::neural::combinator::is_builtin;
+use crate::neural::layer::{Layer, Wiring};
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::Network;
+
+impl Network {
+    /// Returns the network with mapper stages fused. See `optimize_in_place`.
+    pub fn optimize(mut self) -> Network {
+        self.optimize_in_place();
+        self
+    }
+
+    /// Rewrites the network so it evaluates fewer nodes while giving the same outputs.
+    ///
+    /// A mapper layer is one where every node reads a single input, is unnamed, and uses a
+    /// built-in combinator, so that the whole node is a table lookup. Mapper layers are folded
+    /// into the input mappers of the layer after them. A trailing mapper layer whose node `i`
+    /// reads output `i` of the layer before it is folded into that layer's activation tables.
+    /// Input mappers and activations that map every value to itself are then removed.
+    /// Named nodes, nodes with other combinators and layers with a normalizer are not folded.
+    pub fn optimize_in_place(&mut self) {
+        let layers = self.layers_mut();
+        let mut index = 0;
+        while index + 1 < layers.len() {
+            if is_mapper_layer(&layers[index]) {
+                let mapper_layer = layers.remove(index);
+                let next = layers.remove(index);
+                layers.insert(index, fuse_forward(mapper_layer, next));
+            } else {
+                index += 1;
+            }
+        }
+        if layers.len() >= 2 && is_one_to_one(&layers[layers.len() - 1], &layers[layers.len() - 2])
+        {
+            let last = layers.pop().unwrap();
+            let previous = layers.last_mut().unwrap();
+            for (index, node) in last.nodes().iter().enumerate() {
+                previous
+                    .node_mut(index)
+                    .unwrap()
+                    .compose_activation(&node.to_table());
+            }
+        }
+        for layer in layers.iter_mut() {
+            for index in 0..layer.len() {
+                layer.node_mut(index).unwrap().remove_identity_mappers();
+            }
+        }
+    }
+}
+
+/// Returns true if every node of a non-empty layer without a normalizer is a pure function of
+/// a single input.
+fn is_mapper_layer(layer: &Layer) -> bool {
+    !layer.is_empty()
+        && layer.normalizer().is_none()
+        && layer.nodes().iter().enumerate().all(|(index, node)| {
+            layer.sources(index).len() == 1
+                && node.name().is_none()
+                && is_builtin(&node.combinator().identifier())
+        })
+}
+
+/// Returns true if `layer` is a mapper layer whose node `i` reads output `i` of `previous`,
+/// and `previous` has no normalizer to run between its activations and `layer`.
+fn is_one_to_one(layer: &Layer, previous: &Layer) -> bool {
+    is_mapper_layer(layer)
+        && previous.normalizer().is_none()
+        && layer.len() == previous.len()
+        && (0..layer.len()).all(|i| layer.sources(i) == [i])
+}
+
+/// Replaces `next`'s reads of `mapper_layer`'s outputs with reads of `mapper_layer`'s inputs,
+/// passed through the tables of the mapper nodes.
+fn fuse_forward(mapper_layer: Layer, next: Layer) -> Layer {
+    let sources: Vec<usize> = (0..mapper_layer.len())
+        .map(|i| mapper_layer.sources(i)[0])
+        .collect();
+    let tables: Vec<MapperNode> = mapper_layer
+        .nodes()
+        .iter()
+        .map(|node| node.to_table())
+        .collect();
+    let input_width = mapper_layer.input_width();
+    let wiring: Vec<Vec<usize>> = (0..next.len()).map(|i| next.sources(i)).collect();
+    let normalizer = next.normalizer().cloned();
+    let (mut nodes, _, _) = next.into_parts();
+    for (node, indices) in nodes.iter_mut().zip(&wiring) {
+        let before: Vec<MapperNode> = indices.iter().map(|&i| tables[i].clone()).collect();
+        node.compose_inputs(&before);
+    }
+    let wiring = wiring
+        .iter()
+        .map(|indices| indices.iter().map(|&i| sources[i]).collect())
+        .collect();
+    let layer = Layer::new(nodes, Wiring::Sparse(wiring), input_width)
+        .expect("fusing keeps every node's input count and wires only existing inputs");
+    match normalizer {
+        Some(normalizer) => layer.with_normalizer(normalizer),
+        None => layer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode, MinCombinatorNode};
+    use crate::neural::combinator_compare::GreaterThanCombinatorNode;
+    use crate::neural::node::SignalNode;
+    use crate::neural::normalizer::Normalizer;
+    use crate::neural::rng::Rng;
+    use crate::neural::traits::Combinator;
+
+    fn table(f: impl Fn(u8) -> u8) -> MapperNode {
+        MapperNode::new_transformation(|x| f(x as u8))
+    }
+
+    /// A single-input node applying `f` to its input.
+    fn stage(f: impl Fn(u8) -> u8) -> SignalNode {
+        SignalNode::builder()
+            .shared_mapper(table(f))
+            .combinator(Box::new(MaxCombinatorNode::new()))
+            .build()
+            .unwrap()
+    }
+
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    /// Width 1: three mapper stages, a fan-out, a comparator, and a trailing mapper stage.
+    fn chain_network() -> Network {
+        Network::builder()
+            .input_width(1)
+            .layer_nodes(vec![stage(|x| x.wrapping_mul(7))])
+            .layer_nodes(vec![stage(|x| x ^ 0x55)])
+            .layer_sparse(
+                vec![
+                    stage(|x| x / 2),
+                    stage(|x| x.saturating_add(40)),
+                    stage(|x| x),
+                ],
+                vec![vec![0], vec![0], vec![0]],
+            )
+            .layer_sparse(
+                vec![
+                    node(GreaterThanCombinatorNode::new()),
+                    node(AdditionCombinatorNode::new()),
+                ],
+                vec![vec![0, 1], vec![1, 2, 0]],
+            )
+            .layer_nodes(vec![stage(|x| 255 - x)])
+            .output_mapper(table(|x| x.rotate_left(3)))
+            .build()
+            .unwrap()
+    }
+
+    /// Width 3: a dense layer, a permuting mapper layer, and a combining layer.
+    fn wide_network() -> Network {
+        Network::builder()
+            .input_width(3)
+            .layer_sparse(
+                vec![
+                    stage(|x| x / 3),
+                    stage(|x| x),
+                    stage(|x| x.wrapping_add(100)),
+                ],
+                vec![vec![2], vec![0], vec![1]],
+            )
+            .layer_nodes(vec![
+                node(MaxCombinatorNode::new()),
+                node(MinCombinatorNode::new()),
+            ])
+            .layer_sparse(
+                vec![stage(|x| x.wrapping_mul(3)), stage(|x| x)],
+                vec![vec![1], vec![0]],
+            )
+            .layer_nodes(vec![node(AdditionCombinatorNode::new())])
+            .output_mapper(table(|x| x))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_chain_all_single_bytes() {
+        let original = chain_network();
+        let optimized = chain_network().optimize();
+        for x in 0..=255u8 {
+            assert_eq!(optimized.forward(&[x]), original.forward(&[x]), "{x}");
+        }
+        assert_eq!(original.len(), 6);
+        // The comparator layer and the max over its two outputs remain.
+        assert_eq!(optimized.len(), 2);
+        assert_eq!(original.node_count(), 9);
+        assert_eq!(optimized.node_count(), 3);
+    }
+
+    #[test]
+    fn test_wide_random_inputs() {
+        let original = wide_network();
+        let optimized = wide_network().optimize();
+        let mut rng = Rng::new(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..5000 {
+            let input = &rng.next_u64().to_le_bytes()[..3];
+            assert_eq!(
+                optimized.forward(input),
+                original.forward(input),
+                "{input:?}"
+            );
+        }
+        assert_eq!(original.node_count(), 3 + 2 + 2 + 1 + 1);
+        assert_eq!(optimized.node_count(), 2 + 1);
+        // The identity output mapper is folded in and then removed.
+        assert!(optimized.layers()[1].nodes()[0].activation().is_none());
+    }
+
+    #[test]
+    fn test_leaves_other_networks_untouched() {
+        let plain = || {
+            Network::builder()
+                .input_width(2)
+                .layer_nodes(vec![node(MaxCombinatorNode::new())])
+                .build()
+                .unwrap()
+        };
+        let optimized = plain().optimize();
+        assert_eq!(optimized.node_count(), plain().node_count());
+        #[cfg(feature = "json")]
+        assert_eq!(optimized.to_json(), plain().to_json());
+
+        // A single mapper layer has nothing to fuse with.
+        let single = Network::builder()
+            .input_width(1)
+            .layer_nodes(vec![stage(|x| x / 2)])
+            .build()
+            .unwrap();
+        assert_eq!(single.optimize().forward(&[9]), Ok(vec![4]));
+
+        // Named nodes are kept so they can still be looked up.
+        let named = || {
+            Network::builder()
+                .input_width(1)
+                .layer_dense(1, |node| {
+                    node.name("keep")
+                        .shared_mapper(table(|x| x / 2))
+                        .combinator(Box::new(MaxCombinatorNode::new()))
+                })
+                .layer_nodes(vec![stage(|x| x / 2)])
+                .layer_nodes(vec![node(AdditionCombinatorNode::new())])
+                .build()
+                .unwrap()
+        };
+        let optimized = named().optimize();
+        // The first layer is kept, the two after it are folded into its activation.
+        assert_eq!(optimized.len(), 1);
+        assert!(optimized.node("keep").is_some());
+        for x in 0..=255u8 {
+            assert_eq!(optimized.forward(&[x]), named().forward(&[x]));
+        }
+    }
+
+    #[test]
+    fn test_normalizers_stay_in_place() {
+        let network = || {
+            Network::builder()
+                .input_width(3)
+                .layer_sparse(
+                    vec![stage(|x| x / 2), stage(|x| x), stage(|x| 255 - x)],
+                    vec![vec![0], vec![1], vec![2]],
+                )
+                .normalizer(Normalizer::sum())
+                .output_mapper(table(|x| x.saturating_mul(2)))
+                .build()
+                .unwrap()
+        };
+        let optimized = network().optimize();
+        // The first mapper layer is folded into the normalizing layer, which keeps its
+        // normalizer; the output mapper must run after normalizing, so it stays.
+        assert_eq!(optimized.len(), 2);
+        assert_eq!(optimized.layers()[0].normalizer(), Some(&Normalizer::sum()));
+        assert_eq!(optimized.layers()[1].normalizer(), None);
+        for input in [[0, 0, 0], [10, 30, 60], [255, 1, 128], [200, 200, 0]] {
+            assert_eq!(
+                optimized.forward(&input),
+                network().forward(&input),
+                "{input:?}"
+            );
+        }
+    }
+}
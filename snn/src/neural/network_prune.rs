@@ -0,0 +1,436 @@
+/// Removing nodes that cannot affect a network's outputs.
+use crate::neural::combinator_routing::ConstantCombinatorNode;
+use crate::neural::layer::{Layer, Wiring};
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::Network;
+use crate::neural::node::SignalNode;
+use crate::neural::normalizer::Normalizer;
+
+/// The most inputs of a node that may vary for `prune` to try all their values.
+/// Each one multiplies the evaluations needed by 256.
+pub const MAX_FREE_INPUTS: usize = 1;
+
+/// What `Network::prune` changed. Layers and nodes are numbered as in the original network.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PruneReport {
+    /// The `(layer, node)` of every node removed because no output depends on it.
+    pub removed: Vec<(usize, usize)>,
+    /// The `(layer, node, value)` of every node replaced by a `constant` node without inputs.
+    pub folded: Vec<(usize, usize, u8)>,
+}
+
+impl PruneReport {
+    /// Returns true if nothing was removed or folded.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.folded.is_empty()
+    }
+}
+
+/// A layer taken apart so its nodes and wiring can be changed.
+struct Stage {
+    nodes: Vec<SignalNode>,
+    sources: Vec<Vec<usize>>,
+    input_width: usize,
+    dense: bool,
+    normalizer: Option<Normalizer>,
+    /// The original index of each remaining node.
+    original: Vec<usize>,
+}
+
+impl Network {
+    /// Returns the network with dead nodes removed and constant nodes folded. See
+    /// `prune_in_place`.
+    pub fn prune(mut self) -> (Network, PruneReport) {
+        let report = self.prune_in_place();
+        (self, report)
+    }
+
+    /// Rewrites the network without the nodes that cannot affect its outputs, giving the same
+    /// outputs for every input.
+    ///
+    /// A node is provably constant if its activation table is constant, or if trying every
+    /// value of its varying inputs gives a single output. An input varies unless it reads a
+    /// constant node of the layer before or passes through a constant input mapper, and at most
+    /// `MAX_FREE_INPUTS` may vary. Nodes whose combinator is not deterministic, such as noise,
+    /// are only folded when their activation table is constant. Constant nodes are replaced by
+    /// `constant` nodes that read no inputs. Then every node that no output reaches through the
+    /// wiring is removed, and the layer after it is rewired. The nodes of the last layer are the outputs and are never
+    /// removed. Named nodes are kept as they are so they can still be looked up, and so are
+    /// the nodes of a layer with a normalizer, whose outputs depend on each other.
+    pub fn prune_in_place(&mut self) -> PruneReport {
+        let mut report = PruneReport::default();
+        let mut stages: Vec<Stage> = std::mem::take(self.layers_mut())
+            .into_iter()
+            .map(|layer| {
+                let sources = (0..layer.len()).map(|i| layer.sources(i)).collect();
+                let normalizer = layer.normalizer().cloned();
+                let (nodes, wiring, input_width) = layer.into_parts();
+                Stage {
+                    original: (0..nodes.len()).collect(),
+                    nodes,
+                    sources,
+                    input_width,
+                    dense: wiring == Wiring::Dense,
+                    normalizer,
+                }
+            })
+            .collect();
+
+        let mut known = vec![None; stages.first().map_or(0, |stage| stage.input_width)];
+        for (layer, stage) in stages.iter_mut().enumerate() {
+            let mut outputs = vec![None; stage.nodes.len()];
+            for (index, output) in outputs.iter_mut().enumerate() {
+                let node = &stage.nodes[index];
+                *output = constant_value(node, &stage.sources[index], &known);
+                let Some(value) = *output else { continue };
+                let folded = node.combinator().identifier() == ConstantCombinatorNode::IDENTIFIER
+                    && stage.sources[index].is_empty();
+                if node.name().is_none() && stage.normalizer.is_none() && !folded {
+                    stage.nodes[index] = SignalNode::builder()
+                        .combinator(Box::new(ConstantCombinatorNode::new(value)))
+                        .build()
+                        .expect("a constant node always builds");
+                    stage.sources[index].clear();
+                    stage.dense = false;
+                    report.folded.push((layer, index, value));
+                }
+            }
+            // A normalizer mixes every output of the layer, so none of them stays constant.
+            known = match stage.normalizer {
+                Some(_) => vec![None; outputs.len()],
+                None => outputs,
+            };
+        }
+
+        let mut needed = vec![true; stages.last().map_or(0, |stage| stage.nodes.len())];
+        for layer in (0..stages.len()).rev() {
+            let stage = &mut stages[layer];
+            let mut reads = vec![false; stage.input_width];
+            for (index, keep) in needed.iter_mut().enumerate() {
+                *keep |= stage.normalizer.is_some() || stage.nodes[index].name().is_some();
+                if *keep {
+                    for &source in &stage.sources[index] {
+                        reads[source] = true;
+                    }
+                }
+            }
+            let mut keep = needed.iter();
+            stage.nodes.retain(|_| *keep.next().unwrap());
+            let mut keep = needed.iter();
+            stage.sources.retain(|_| *keep.next().unwrap());
+            let mut keep = needed.iter();
+            stage.original.retain(|&index| {
+                let kept = *keep.next().unwrap();
+                if !kept {
+                    report.removed.push((layer, index));
+                }
+                kept
+            });
+            needed = reads;
+        }
+        report.removed.sort_unstable();
+        report
+            .folded
+            .retain(|&(layer, node, _)| !report.removed.contains(&(layer, node)));
+
+        // Renumber each layer's reads to the nodes that remain in the layer before it.
+        for layer in 1..stages.len() {
+            let (before, after) = stages.split_at_mut(layer);
+            let (previous, stage) = (&before[layer - 1], &mut after[0]);
+            if previous.original.len() == stage.input_width {
+                continue;
+            }
+            let mut position = vec![usize::MAX; stage.input_width];
+            for (new, &old) in previous.original.iter().enumerate() {
+                position[old] = new;
+            }
+            for source in stage.sources.iter_mut().flatten() {
+                *source = position[*source];
+            }
+            stage.input_width = previous.original.len();
+            stage.dense = false;
+        }
+
+        *self.layers_mut() = stages
+            .into_iter()
+            .map(|stage| {
+                let wiring = if stage.dense {
+                    Wiring::Dense
+                } else {
+                    Wiring::Sparse(stage.sources)
+                };
+                let layer = Layer::new(stage.nodes, wiring, stage.input_width)
+                    .expect("pruning keeps every remaining node's inputs");
+                match stage.normalizer {
+                    Some(normalizer) => layer.with_normalizer(normalizer),
+                    None => layer,
+                }
+            })
+            .collect();
+        report
+    }
+}
+
+/// Returns the node's output if it is the same for every value of the inputs that vary.
+/// `known` holds the value of each input of the layer that is constant.
+fn constant_value(node: &SignalNode, sources: &[usize], known: &[Option<u8>]) -> Option<u8> {
+    if let Some(activation) = node.activation()
+        && is_constant(activation)
+    {
+        return Some(activation.transform(0));
+    }
+    // Probing a non-deterministic combinator would also advance its state.
+    if !node.combinator().is_deterministic() {
+        return None;
+    }
+    let mut inputs: Vec<u8> = sources.iter().map(|&i| known[i].unwrap_or(0)).collect();
+    let free: Vec<usize> = (0..sources.len())
+        .filter(|&i| known[sources[i]].is_none() && !node.input_mapper(i).is_some_and(is_constant))
+        .collect();
+    if free.len() > MAX_FREE_INPUTS {
+        return None;
+    }
+    let first = node.evaluate(&inputs);
+    for assignment in 1..1usize << (8 * free.len()) {
+        for (k, &i) in free.iter().enumerate() {
+            inputs[i] = (assignment >> (8 * k)) as u8;
+        }
+        if node.evaluate(&inputs) != first {
+            return None;
+        }
+    }
+    Some(first)
+}
+
+fn is_constant(mapper: &MapperNode) -> bool {
+    let table = mapper.table();
+    table.iter().all(|&x| x == table[0])
+}
+
+impl std::fmt::Display for PruneReport {
+    /// Writes a summary line, then one line per removed or folded node.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "removed {} nodes, folded {} nodes into constants",
+            self.removed.len(),
+            self.folded.len()
+        )?;
+        for (layer, node) in &self.removed {
+            writeln!(f, "layer {layer} / node {node}: removed")?;
+        }
+        for (layer, node, value) in &self.folded {
+            writeln!(f, "layer {layer} / node {node}: constant {value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode, MinCombinatorNode};
+    use crate::neural::noise::NoiseCombinatorNode;
+    use crate::neural::traits::Combinator;
+
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    fn mapped(mapper: MapperNode) -> SignalNode {
+        SignalNode::builder()
+            .shared_mapper(mapper)
+            .combinator(Box::new(MaxCombinatorNode::new()))
+            .build()
+            .unwrap()
+    }
+
+    /// Seven nodes, three of them prunable: node 2 of layer 0 and node 2 of layer 1 ignore
+    /// their inputs, and node 3 of layer 0 only feeds the constant node 2 of layer 1.
+    fn fixture() -> Network {
+        Network::builder()
+            .input_width(2)
+            .layer_sparse(
+                vec![
+                    mapped(MapperNode::new_transformation(|x| x as u8 / 2)),
+                    node(MinCombinatorNode::new()),
+                    mapped(MapperNode::new().with_fill(9)),
+                    node(AdditionCombinatorNode::new()),
+                ],
+                vec![vec![0], vec![0, 1], vec![1], vec![0, 1]],
+            )
+            .layer_sparse(
+                vec![
+                    node(AdditionCombinatorNode::new()),
+                    node(MaxCombinatorNode::new()),
+                    mapped(MapperNode::new()),
+                ],
+                vec![vec![0, 2], vec![1], vec![3]],
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fixture() {
+        let (pruned, report) = fixture().prune();
+        assert_eq!(report.removed, [(0, 3)]);
+        assert_eq!(report.folded, [(0, 2, 9), (1, 2, 0)]);
+        assert_eq!(fixture().node_count(), 7);
+        assert_eq!(pruned.node_count(), 6);
+        assert_eq!(pruned.layers()[1].input_width(), 3);
+        assert_eq!(pruned.output_width(), Some(3));
+        assert_eq!(
+            pruned.layers()[0].nodes()[2].combinator().identifier(),
+            "constant"
+        );
+        assert_eq!(
+            report.to_string(),
+            "removed 1 nodes, folded 2 nodes into constants\nlayer 0 / node 3: removed\n\
+             layer 0 / node 2: constant 9\nlayer 1 / node 2: constant 0\n"
+        );
+        let equivalence = pruned.equivalent_to(&fixture(), 0);
+        assert!(equivalence.exhaustive);
+        assert!(equivalence.is_equivalent(), "{equivalence}");
+
+        // Pruning again finds nothing left to do.
+        let (twice, report) = pruned.prune();
+        assert!(report.is_empty());
+        assert_eq!(twice.node_count(), 6);
+    }
+
+    /// Width 4, so equivalence is checked on sampled inputs.
+    fn wide() -> Network {
+        Network::builder()
+            .input_width(4)
+            .layer_dense(5, |node| {
+                node.combinator(Box::new(AdditionCombinatorNode::new()))
+            })
+            .layer_sparse(
+                vec![
+                    node(MaxCombinatorNode::new()),
+                    mapped(MapperNode::new().with_fill(200)),
+                    node(MinCombinatorNode::new()),
+                    node(MaxCombinatorNode::new()),
+                ],
+                vec![vec![0, 4], vec![1, 2], vec![3], vec![2]],
+            )
+            .layer_sparse(
+                vec![
+                    node(AdditionCombinatorNode::new()),
+                    SignalNode::builder()
+                        .combinator(Box::new(MinCombinatorNode::new()))
+                        .activation(MapperNode::new().with_fill(3))
+                        .build()
+                        .unwrap(),
+                ],
+                vec![vec![0, 1], vec![2]],
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_wide_network() {
+        let (pruned, report) = wide().prune();
+        // Node 2 of layer 1 only fed the constant output, and node 3 of layer 1 fed nothing;
+        // nodes 1 and 2 of layer 0 only fed those and the constant node 1 of layer 1.
+        assert_eq!(report.removed, [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3)]);
+        assert_eq!(report.folded, [(1, 1, 200), (2, 1, 3)]);
+        assert_eq!(pruned.node_count(), 2 + 2 + 2);
+        assert!(pruned.layers()[0].wiring() == &Wiring::Dense);
+        let equivalence = pruned.equivalent_to(&wide(), 2000);
+        assert!(!equivalence.exhaustive);
+        assert!(equivalence.is_equivalent(), "{equivalence}");
+    }
+
+    #[test]
+    fn test_named_and_normalized_nodes_are_kept() {
+        let network = || {
+            Network::builder()
+                .input_width(2)
+                .layer_dense(3, |node| {
+                    node.combinator(Box::new(MaxCombinatorNode::new()))
+                })
+                .layer_sparse(
+                    vec![
+                        SignalNode::builder()
+                            .name("unused")
+                            .shared_mapper(MapperNode::new())
+                            .combinator(Box::new(MaxCombinatorNode::new()))
+                            .build()
+                            .unwrap(),
+                        mapped(MapperNode::new().with_fill(1)),
+                        node(MaxCombinatorNode::new()),
+                    ],
+                    vec![vec![0], vec![1], vec![2]],
+                )
+                .normalizer(Normalizer::sum())
+                .layer_sparse(vec![node(MaxCombinatorNode::new())], vec![vec![2]])
+                .build()
+                .unwrap()
+        };
+        let (pruned, report) = network().prune();
+        // The normalizing layer keeps every node of the layer before it, so only the input of
+        // the folded node goes, while the named node keeps its input.
+        assert!(pruned.node("unused").is_some());
+        assert_eq!(report.removed, [(0, 1)]);
+        assert_eq!(report.folded, [(1, 1, 1)]);
+        assert_eq!(pruned.layers()[2].len(), 3);
+        assert!(pruned.equivalent_to(&network(), 0).is_equivalent());
+    }
+
+    #[test]
+    fn test_keeps_noise() {
+        let noisy = || NoiseCombinatorNode::new(Box::new(AdditionCombinatorNode::new()), 3, 1);
+        let network = || {
+            Network::builder()
+                .input_width(1)
+                .layer_sparse(
+                    vec![
+                        mapped(MapperNode::new().with_fill(5)),
+                        mapped(MapperNode::new().with_fill(7)),
+                    ],
+                    vec![vec![0], vec![0]],
+                )
+                .layer_sparse(
+                    vec![
+                        node(noisy()),
+                        SignalNode::builder()
+                            .combinator(Box::new(noisy()))
+                            .activation(MapperNode::new().with_fill(9))
+                            .build()
+                            .unwrap(),
+                    ],
+                    vec![vec![0, 1], vec![0, 1]],
+                )
+                .build()
+                .unwrap()
+        };
+        let (pruned, report) = network().prune();
+        // Only the noise node behind a constant activation table is folded.
+        assert_eq!(report.folded, [(0, 0, 5), (0, 1, 7), (1, 1, 9)]);
+        assert_eq!(
+            pruned.layers()[1].nodes()[0].combinator().identifier(),
+            "noise(addition)"
+        );
+        // Pruning did not advance the noise, so the outputs follow the same sequence.
+        let original = network();
+        let outputs: Vec<Vec<u8>> = (0..20).map(|_| pruned.forward(&[0]).unwrap()).collect();
+        for output in &outputs {
+            assert_eq!(output, &original.forward(&[0]).unwrap());
+            assert_eq!(output[1], 9);
+        }
+        assert!(outputs.iter().any(|output| output[0] != 12));
+    }
+
+    #[test]
+    fn test_empty_network() {
+        let (pruned, report) = Network::default().prune();
+        assert!(pruned.is_empty());
+        assert!(report.is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+/// Building networks with a random topology and random mapper tables.
+use crate::neural::combinator::{CombinatorParseError, combinator_from_str};
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::NetworkBuilder;
+use crate::neural::node::SignalNode;
+use crate::neural::rng::Rng;
+
+/// How `NetworkBuilder::random` wires each node to the previous layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomWiring {
+    /// Every node reads every output of the previous layer.
+    Dense,
+    /// Every node reads this many distinct outputs of the previous layer, chosen at random, or
+    /// all of them if there are fewer.
+    RandomK(usize),
+}
+
+/// How `NetworkBuilder::random` fills the per-input mapper tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableInit {
+    /// Every table maps each value to itself.
+    Identity,
+    /// Every entry is a random value.
+    Random,
+    /// Every table is a random permutation, so no information is lost.
+    RandomPermutation,
+}
+
+/// Settings for `NetworkBuilder::random`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomOptions {
+    /// How nodes are wired to the previous layer.
+    pub wiring: RandomWiring,
+    /// The combinators a node may get, as accepted by `combinator_from_str`. Each node picks
+    /// one of those that accept its number of inputs.
+    pub combinators: Vec<String>,
+    /// How the per-input mapper tables are filled.
+    pub tables: TableInit,
+}
+
+impl Default for RandomOptions {
+    fn default() -> Self {
+        Self {
+            wiring: RandomWiring::Dense,
+            combinators: ["max", "min", "average", "addition", "or", "and", "xor"]
+                .map(str::to_string)
+                .to_vec(),
+            tables: TableInit::Random,
+        }
+    }
+}
+
+impl RandomOptions {
+    /// Returns the options with the given wiring.
+    pub fn with_wiring(self, wiring: RandomWiring) -> Self {
+        Self { wiring, ..self }
+    }
+
+    /// Returns the options with the given eligible combinators.
+    pub fn with_combinators<S: Into<String>>(
+        self,
+        combinators: impl IntoIterator<Item = S>,
+    ) -> Self {
+        Self {
+            combinators: combinators.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    /// Returns the options with the given table initialization.
+    pub fn with_tables(self, tables: TableInit) -> Self {
+        Self { tables, ..self }
+    }
+}
+
+/// Error returned by `NetworkBuilder::random`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RandomNetworkError {
+    /// One of the eligible combinators could not be parsed.
+    Combinator(CombinatorParseError),
+    /// None of the eligible combinators accepts the number of inputs of a node.
+    NoCombinator {
+        layer: usize,
+        node: usize,
+        inputs: usize,
+    },
+}
+
+impl std::fmt::Display for RandomNetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RandomNetworkError::Combinator(error) => write!(f, "{error}"),
+            RandomNetworkError::NoCombinator {
+                layer,
+                node,
+                inputs,
+            } => write!(
+                f,
+                "layer {layer}: node {node}: no eligible combinator accepts {inputs} inputs"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RandomNetworkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RandomNetworkError::Combinator(error) => Some(error),
+            RandomNetworkError::NoCombinator { .. } => None,
+        }
+    }
+}
+
+impl From<CombinatorParseError> for RandomNetworkError {
+    fn from(error: CombinatorParseError) -> Self {
+        RandomNetworkError::Combinator(error)
+    }
+}
+
+impl NetworkBuilder {
+    /// Returns a builder for a network of `input_width` inputs and one layer per entry of
+    /// `layer_widths`, with wiring, combinators and mapper tables chosen at random as
+    /// `options` allow. Every node gets one mapper per input. The same arguments always give
+    /// the same network.
+    pub fn random(
+        input_width: usize,
+        layer_widths: &[usize],
+        seed: u64,
+        options: &RandomOptions,
+    ) -> Result<NetworkBuilder, RandomNetworkError> {
+        let arities = options
+            .combinators
+            .iter()
+            .map(|description| {
+                let combinator = combinator_from_str(description)?;
+                Ok((
+                    description,
+                    combinator.min_inputs(),
+                    combinator.max_inputs(),
+                ))
+            })
+            .collect::<Result<Vec<_>, CombinatorParseError>>()?;
+        let mut rng = Rng::new(seed);
+        let mut builder = NetworkBuilder::default().input_width(input_width);
+        let mut width = input_width;
+        for (layer, &node_count) in layer_widths.iter().enumerate() {
+            let mut nodes = Vec::with_capacity(node_count);
+            let mut wiring = Vec::with_capacity(node_count);
+            for node in 0..node_count {
+                let sources = match options.wiring {
+                    RandomWiring::Dense => (0..width).collect(),
+                    RandomWiring::RandomK(k) => choose_distinct(&mut rng, width, k),
+                };
+                let inputs = sources.len();
+                let eligible: Vec<&String> = arities
+                    .iter()
+                    .filter(|(_, min, max)| inputs >= *min && max.is_none_or(|max| inputs <= max))
+                    .map(|(description, _, _)| *description)
+                    .collect();
+                if eligible.is_empty() {
+                    return Err(RandomNetworkError::NoCombinator {
+                        layer,
+                        node,
+                        inputs,
+                    });
+                }
+                let description = eligible[rng.below(eligible.len())];
+                let mut node = SignalNode::builder().combinator(combinator_from_str(description)?);
+                for _ in 0..inputs {
+                    node = node.input_mapper(random_table(&mut rng, options.tables));
+                }
+                nodes.push(
+                    node.build()
+                        .expect("the combinator accepts the node's number of inputs"),
+                );
+                wiring.push(sources);
+            }
+            builder = match options.wiring {
+                RandomWiring::Dense => builder.layer_nodes(nodes),
+                RandomWiring::RandomK(_) => builder.layer_sparse(nodes, wiring),
+            };
+            width = node_count;
+        }
+        Ok(builder)
+    }
+}
+
+/// Returns `k` distinct indices below `n` in random order, or all of them if `k >= n`.
+fn choose_distinct(rng: &mut Rng, n: usize, k: usize) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..n).collect();
+    let k = k.min(n);
+    for i in 0..k {
+        let j = i + rng.below(n - i);
+        indices.swap(i, j);
+    }
+    indices.truncate(k);
+    indices
+}
+
+fn random_table(rng: &mut Rng, init: TableInit) -> MapperNode {
+    match init {
+        TableInit::Identity => MapperNode::new_transformation(|i| i as u8),
+        TableInit::Random => {
+            let table: Vec<u8> = (0..256).map(|_| rng.below(256) as u8).collect();
+            MapperNode::new_transformation(|i| table[i])
+        }
+        TableInit::RandomPermutation => {
+            let table: Vec<u8> = choose_distinct(rng, 256, 256)
+                .into_iter()
+                .map(|x| x as u8)
+                .collect();
+            MapperNode::new_transformation(|i| table[i])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::network::Network;
+
+    fn random(seed: u64, options: &RandomOptions) -> Network {
+        NetworkBuilder::random(4, &[6, 3, 2], seed, options)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_hundred_seeds() {
+        let options = RandomOptions::default();
+        let networks: Vec<Network> = (0..100).map(|seed| random(seed, &options)).collect();
+        for network in &networks {
+            assert_eq!(network.validate(), Ok(()));
+            assert_eq!(network.input_width(), Some(4));
+            assert_eq!(network.output_width(), Some(2));
+            assert_eq!(network.forward(&[1, 2, 3, 4]).unwrap().len(), 2);
+        }
+        let digests: std::collections::HashSet<_> = networks.iter().map(Network::digest).collect();
+        assert!(digests.len() >= 2);
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let options = RandomOptions::default()
+            .with_wiring(RandomWiring::RandomK(2))
+            .with_tables(TableInit::RandomPermutation);
+        assert_eq!(random(7, &options).digest(), random(7, &options).digest());
+        assert_ne!(random(7, &options).digest(), random(8, &options).digest());
+    }
+
+    #[test]
+    fn test_wiring_and_tables() {
+        let options = RandomOptions::default()
+            .with_wiring(RandomWiring::RandomK(2))
+            .with_tables(TableInit::RandomPermutation);
+        let network = random(3, &options);
+        for layer in network.layers() {
+            for (index, node) in layer.nodes().iter().enumerate() {
+                let sources = layer.sources(index);
+                assert_eq!(sources.len(), 2);
+                assert_ne!(sources[0], sources[1]);
+                assert!((0..2).all(|i| node.input_mapper(i).unwrap().is_bijective()));
+            }
+        }
+        // A layer narrower than k is read whole.
+        let network = NetworkBuilder::random(1, &[2], 3, &options.with_tables(TableInit::Identity))
+            .unwrap()
+            .build()
+            .unwrap();
+        let layer = &network.layers()[0];
+        assert_eq!(layer.sources(0), [0]);
+        assert!(layer.nodes()[0].input_mapper(0).unwrap().is_identity());
+    }
+
+    #[test]
+    fn test_combinators_follow_arity() {
+        let options = RandomOptions::default()
+            .with_wiring(RandomWiring::RandomK(2))
+            .with_combinators(["greater_than", "max"]);
+        let network = random(5, &options);
+        assert_eq!(network.validate(), Ok(()));
+        // With three inputs, only max is eligible.
+        let network = NetworkBuilder::random(3, &[4], 5, &options.with_wiring(RandomWiring::Dense))
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(
+            network.layers()[0]
+                .nodes()
+                .iter()
+                .all(|node| node.combinator().identifier() == "max")
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let options = RandomOptions::default().with_combinators(["no_such_combinator"]);
+        assert!(matches!(
+            NetworkBuilder::random(2, &[2], 1, &options),
+            Err(RandomNetworkError::Combinator(_))
+        ));
+        let options = RandomOptions::default().with_combinators(["greater_than"]);
+        assert_eq!(
+            NetworkBuilder::random(3, &[2], 1, &options).err(),
+            Some(RandomNetworkError::NoCombinator {
+                layer: 0,
+                node: 0,
+                inputs: 3,
+            })
+        );
+    }
+}
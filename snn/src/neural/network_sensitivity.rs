@@ -0,0 +1,289 @@
+/// Finding which inputs of a network each output depends on.
+use crate::neural::network::Network;
+use crate::neural::network_equivalence::MAX_EXHAUSTIVE_WIDTH;
+use crate::neural::rng::Rng;
+
+/// For every pair of input and output position, how often and how far the output moved when
+/// only that input changed. Created by `Network::sensitivity`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SensitivityMatrix {
+    input_width: usize,
+    output_width: usize,
+    exhaustive: bool,
+    trials: usize,
+    /// Indexed by `input * output_width + output`.
+    changed: Vec<usize>,
+    /// Indexed like `changed`.
+    max_delta: Vec<u8>,
+}
+
+impl Network {
+    /// Measures which outputs depend on which inputs.
+    ///
+    /// Networks with an input width of at most `MAX_EXHAUSTIVE_WIDTH` are run on every possible
+    /// input: a trial fixes the other inputs and runs all 256 values of one input, and counts as
+    /// a change for each output that does not stay constant. Wider networks are run on `samples`
+    /// random inputs drawn from `seed`, and a trial changes one input of a sample to another
+    /// random value. A dependence found this way is certain; a missing one is only as likely as
+    /// the sampling makes it.
+    pub fn sensitivity(&self, samples: usize, seed: u64) -> SensitivityMatrix {
+        let input_width = self.input_width().unwrap_or(0);
+        let output_width = self.output_width().unwrap_or(input_width);
+        let mut matrix = SensitivityMatrix {
+            input_width,
+            output_width,
+            exhaustive: input_width <= MAX_EXHAUSTIVE_WIDTH,
+            trials: 0,
+            changed: vec![0; input_width * output_width],
+            max_delta: vec![0; input_width * output_width],
+        };
+        let forward = |input: &[u8]| {
+            self.forward(input)
+                .expect("inputs have the network's input width")
+        };
+        if matrix.exhaustive {
+            let count = 1usize << (8 * input_width);
+            let outputs: Vec<Vec<u8>> = (0..count)
+                .map(|n| forward(&n.to_le_bytes()[..input_width]))
+                .collect();
+            matrix.trials = count / 256;
+            for input in 0..input_width {
+                let stride = 1 << (8 * input);
+                for base in (0..count).filter(|n| (n / stride) % 256 == 0) {
+                    let mut min = vec![u8::MAX; output_width];
+                    let mut max = vec![0; output_width];
+                    for value in 0..256 {
+                        for (output, &x) in outputs[base + value * stride].iter().enumerate() {
+                            min[output] = min[output].min(x);
+                            max[output] = max[output].max(x);
+                        }
+                    }
+                    for (output, (&min, &max)) in min.iter().zip(&max).enumerate() {
+                        matrix.record(input, output, max - min);
+                    }
+                }
+            }
+        } else {
+            let mut rng = Rng::new(seed);
+            matrix.trials = samples;
+            for _ in 0..samples {
+                let base: Vec<u8> = (0..input_width).map(|_| rng.below(256) as u8).collect();
+                let expected = forward(&base);
+                let mut perturbed = base.clone();
+                for input in 0..input_width {
+                    // XOR with a non-zero value always gives a different byte.
+                    perturbed[input] ^= 1 + rng.below(255) as u8;
+                    let actual = forward(&perturbed);
+                    for (output, (&a, &b)) in expected.iter().zip(&actual).enumerate() {
+                        matrix.record(input, output, a.abs_diff(b));
+                    }
+                    perturbed[input] = base[input];
+                }
+            }
+        }
+        matrix
+    }
+}
+
+impl SensitivityMatrix {
+    fn record(&mut self, input: usize, output: usize, delta: u8) {
+        let index = input * self.output_width + output;
+        if delta > 0 {
+            self.changed[index] += 1;
+            self.max_delta[index] = self.max_delta[index].max(delta);
+        }
+    }
+
+    /// Returns the number of inputs of the network.
+    pub fn input_width(&self) -> usize {
+        self.input_width
+    }
+
+    /// Returns the number of outputs of the network.
+    pub fn output_width(&self) -> usize {
+        self.output_width
+    }
+
+    /// Returns true if every input was checked, so that the matrix is exact.
+    pub fn is_exhaustive(&self) -> bool {
+        self.exhaustive
+    }
+
+    /// Returns the number of trials run for each input.
+    pub fn trials(&self) -> usize {
+        self.trials
+    }
+
+    /// Returns the number of trials of `input` in which `output` changed.
+    ///
+    /// # Panics
+    /// Panics if `input` or `output` is out of range.
+    pub fn changed_count(&self, input: usize, output: usize) -> usize {
+        self.changed[self.index(input, output)]
+    }
+
+    /// Returns the largest change of `output` seen in a single trial of `input`.
+    ///
+    /// # Panics
+    /// Panics if `input` or `output` is out of range.
+    pub fn max_delta(&self, input: usize, output: usize) -> u8 {
+        self.max_delta[self.index(input, output)]
+    }
+
+    /// Returns true if changing `input` was seen to change `output`.
+    ///
+    /// # Panics
+    /// Panics if `input` or `output` is out of range.
+    pub fn affects(&self, input: usize, output: usize) -> bool {
+        self.changed_count(input, output) > 0
+    }
+
+    /// Returns the inputs that were not seen to affect any output, in ascending order.
+    pub fn dead_inputs(&self) -> Vec<usize> {
+        (0..self.input_width)
+            .filter(|&input| (0..self.output_width).all(|output| !self.affects(input, output)))
+            .collect()
+    }
+
+    /// Returns the outputs that no input was seen to affect, in ascending order.
+    pub fn constant_outputs(&self) -> Vec<usize> {
+        (0..self.output_width)
+            .filter(|&output| (0..self.input_width).all(|input| !self.affects(input, output)))
+            .collect()
+    }
+
+    fn index(&self, input: usize, output: usize) -> usize {
+        assert!(
+            input < self.input_width && output < self.output_width,
+            "input {input} or output {output} out of range"
+        );
+        input * self.output_width + output
+    }
+}
+
+impl std::fmt::Display for SensitivityMatrix {
+    /// Writes a summary line, then one row per input with `#` for every output it affects and
+    /// `.` for the others.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.exhaustive {
+            "exhaustive"
+        } else {
+            "sampled"
+        };
+        writeln!(
+            f,
+            "{} inputs, {} outputs, {} {kind} trials per input",
+            self.input_width, self.output_width, self.trials
+        )?;
+        for input in 0..self.input_width {
+            let mut row = String::with_capacity(self.output_width);
+            for output in 0..self.output_width {
+                row.push(if self.affects(input, output) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            writeln!(f, "input {input}: {row}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode};
+    use crate::neural::mapper::MapperNode;
+    use crate::neural::node::SignalNode;
+
+    fn node(mapper: Option<MapperNode>, activation: Option<MapperNode>) -> SignalNode {
+        let mut builder = SignalNode::builder().combinator(Box::new(MaxCombinatorNode::new()));
+        if let Some(mapper) = mapper {
+            builder = builder.shared_mapper(mapper);
+        }
+        if let Some(activation) = activation {
+            builder = builder.activation(activation);
+        }
+        builder.build().unwrap()
+    }
+
+    /// Output 0 halves input 0 and ignores input 1; output 1 adds both.
+    fn two_inputs() -> Network {
+        Network::builder()
+            .input_width(2)
+            .layer_sparse(
+                vec![
+                    node(Some(MapperNode::new_transformation(|x| x as u8 / 2)), None),
+                    SignalNode::builder()
+                        .combinator(Box::new(AdditionCombinatorNode::new()))
+                        .build()
+                        .unwrap(),
+                ],
+                vec![vec![0], vec![0, 1]],
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_exhaustive() {
+        let matrix = two_inputs().sensitivity(0, 1);
+        assert!(matrix.is_exhaustive());
+        assert_eq!(matrix.trials(), 256);
+        assert!(matrix.affects(0, 0));
+        assert!(!matrix.affects(1, 0));
+        assert!(matrix.affects(0, 1) && matrix.affects(1, 1));
+        assert_eq!(matrix.changed_count(0, 0), 256);
+        assert_eq!(matrix.max_delta(0, 0), 127);
+        assert_eq!(matrix.max_delta(1, 0), 0);
+        assert_eq!(matrix.max_delta(1, 1), 255);
+        assert!(matrix.dead_inputs().is_empty());
+        assert!(matrix.constant_outputs().is_empty());
+        assert_eq!(
+            matrix.to_string(),
+            "2 inputs, 2 outputs, 256 exhaustive trials per input\ninput 0: ##\ninput 1: .#\n"
+        );
+    }
+
+    #[test]
+    fn test_sampled_dead_inputs_and_constant_outputs() {
+        // Output 0 reads inputs 0 and 2, output 1 is constant, output 2 passes input 1 through
+        // and input 3 is never read.
+        let network = Network::builder()
+            .input_width(4)
+            .layer_sparse(
+                vec![
+                    node(None, None),
+                    node(None, Some(MapperNode::new().with_fill(7))),
+                    node(None, None),
+                ],
+                vec![vec![0, 2], vec![1], vec![1]],
+            )
+            .build()
+            .unwrap();
+        let matrix = network.sensitivity(200, 42);
+        assert!(!matrix.is_exhaustive());
+        assert_eq!(matrix.trials(), 200);
+        assert_eq!(matrix.dead_inputs(), [3]);
+        assert_eq!(matrix.constant_outputs(), [1]);
+        assert!(matrix.affects(0, 0) && matrix.affects(2, 0) && matrix.affects(1, 2));
+        assert!(!matrix.affects(1, 0) && !matrix.affects(0, 2));
+        // Every perturbation of the passed-through input shows.
+        assert_eq!(matrix.changed_count(1, 2), 200);
+        assert_eq!(network.sensitivity(200, 42), matrix);
+    }
+
+    #[test]
+    fn test_empty_network() {
+        let matrix = Network::default().sensitivity(10, 1);
+        assert_eq!(matrix.input_width(), 0);
+        assert!(matrix.dead_inputs().is_empty() && matrix.constant_outputs().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_out_of_range() {
+        two_inputs().sensitivity(0, 1).affects(2, 0);
+    }
+}
@@ -0,0 +1,257 @@
+/// Counting what a network is made of.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::mem::{size_of, size_of_val};
+
+use crate::neural::layer::{Layer, Wiring};
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::Network;
+use crate::neural::node::{InputMappers, SignalNode};
+
+impl Network {
+    /// Returns the number of layers. The same as `len`.
+    pub fn layer_count(&self) -> usize {
+        self.len()
+    }
+
+    /// Returns the total number of mapper table entries, 256 for every input mapper and
+    /// activation table in the network.
+    pub fn parameter_count(&self) -> usize {
+        self.layers().iter().map(layer_parameters).sum()
+    }
+
+    /// Returns how many nodes use each combinator, by identifier.
+    pub fn combinator_histogram(&self) -> BTreeMap<String, usize> {
+        let mut histogram = BTreeMap::new();
+        for node in self.layers().iter().flat_map(Layer::nodes) {
+            *histogram.entry(node.combinator().identifier()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Returns the number of bytes the network occupies: the network itself, its layers,
+    /// nodes, wiring, tables, names and combinators. Spare capacity, allocator overhead
+    /// and memory a combinator allocates on its own are not counted.
+    /// A table shared between nodes through `SharedMapper` is counted once.
+    pub fn memory_footprint_bytes(&self) -> usize {
+        let tables: BTreeSet<*const u8> = self
+            .layers()
+            .iter()
+            .flat_map(Layer::nodes)
+            .flat_map(node_tables)
+            .collect();
+        size_of::<Network>()
+            + self
+                .layers()
+                .iter()
+                .map(|layer| size_of::<Layer>() + layer_heap_bytes(layer))
+                .sum::<usize>()
+            + tables.len() * TABLE_ALLOCATION_BYTES
+    }
+
+    /// Returns a table with one row per layer, followed by totals and the combinators used,
+    /// as `Combinator::describe` names them.
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "{:<6} {:<7} {:>6} {:>7} {:>8} {:>11}",
+            "layer", "wiring", "nodes", "inputs", "outputs", "parameters"
+        )
+        .unwrap();
+        for (index, layer) in self.layers().iter().enumerate() {
+            let wiring = match layer.wiring() {
+                Wiring::Dense => "dense",
+                Wiring::Sparse(_) => "sparse",
+            };
+            writeln!(
+                out,
+                "{:<6} {:<7} {:>6} {:>7} {:>8} {:>11}",
+                index,
+                wiring,
+                layer.len(),
+                layer.input_width(),
+                layer.output_width(),
+                layer_parameters(layer)
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "total: {} layers, {} nodes, {} parameters, {} bytes",
+            self.layer_count(),
+            self.node_count(),
+            self.parameter_count(),
+            self.memory_footprint_bytes()
+        )
+        .unwrap();
+        let mut histogram = BTreeMap::new();
+        for node in self.layers().iter().flat_map(Layer::nodes) {
+            *histogram.entry(node.combinator().describe()).or_insert(0) += 1;
+        }
+        let combinators: Vec<String> = histogram
+            .into_iter()
+            .map(|(description, count)| format!("{description} x{count}"))
+            .collect();
+        writeln!(out, "combinators: {}", combinators.join(", ")).unwrap();
+        out
+    }
+}
+
+fn layer_parameters(layer: &Layer) -> usize {
+    let tables: usize = layer.nodes().iter().map(SignalNode::mapper_count).sum();
+    tables * MapperNode::new().len()
+}
+
+fn layer_heap_bytes(layer: &Layer) -> usize {
+    let wiring = match layer.wiring() {
+        Wiring::Dense => 0,
+        Wiring::Sparse(wiring) => wiring
+            .iter()
+            .map(|indices| size_of::<Vec<usize>>() + size_of_val(indices.as_slice()))
+            .sum(),
+    };
+    wiring
+        + layer
+            .nodes()
+            .iter()
+            .map(|node| size_of::<SignalNode>() + node_heap_bytes(node))
+            .sum::<usize>()
+}
+
+/// The bytes behind one `SharedMapper`: the table and its two reference counts.
+const TABLE_ALLOCATION_BYTES: usize = size_of::<MapperNode>() + 2 * size_of::<usize>();
+
+/// Returns the address of every table the node uses, so shared tables can be counted once.
+fn node_tables(node: &SignalNode) -> Vec<*const u8> {
+    let mut tables: Vec<*const u8> = match node.input_mappers() {
+        InputMappers::Identity => Vec::new(),
+        InputMappers::Shared(mapper) => vec![mapper.table().as_ptr()],
+        InputMappers::PerInput(mappers) => mappers
+            .iter()
+            .map(|mapper| mapper.table().as_ptr())
+            .collect(),
+    };
+    tables.extend(
+        node.activation()
+            .map(|activation| activation.table().as_ptr()),
+    );
+    tables
+}
+
+/// Returns the bytes the node owns outside its tables.
+fn node_heap_bytes(node: &SignalNode) -> usize {
+    let mappers = match node.input_mappers() {
+        InputMappers::Identity | InputMappers::Shared(_) => 0,
+        InputMappers::PerInput(mappers) => size_of_val(mappers.as_slice()),
+    };
+    mappers + size_of_val(node.combinator()) + node.name().map_or(0, str::len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode};
+    use crate::neural::mapper_shared::SharedMapper;
+
+    /// Two dense max nodes with a shared mapper, then a sparse layer of an adder with two
+    /// per-input mappers and an activation, and a named max node without tables.
+    fn network() -> Network {
+        let adder = SignalNode::builder()
+            .input_mapper(MapperNode::new())
+            .input_mapper(MapperNode::new())
+            .combinator(Box::new(AdditionCombinatorNode::new()))
+            .activation(MapperNode::new())
+            .build()
+            .unwrap();
+        let named = SignalNode::builder()
+            .name("pass")
+            .combinator(Box::new(MaxCombinatorNode::new()))
+            .build()
+            .unwrap();
+        Network::builder()
+            .input_width(3)
+            .layer_dense(2, |n| {
+                n.shared_mapper(MapperNode::new())
+                    .combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .layer_sparse(vec![adder, named], vec![vec![0, 1], vec![1]])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_counts() {
+        let network = network();
+        assert_eq!(network.layer_count(), 2);
+        assert_eq!(network.node_count(), 4);
+        assert_eq!(network.input_width(), Some(3));
+        assert_eq!(network.output_width(), Some(2));
+        // Two shared mappers, two per-input mappers and one activation.
+        assert_eq!(network.parameter_count(), 5 * 256);
+        let histogram: Vec<(String, usize)> = network.combinator_histogram().into_iter().collect();
+        assert_eq!(
+            histogram,
+            vec![("addition".to_string(), 1), ("max".to_string(), 3)]
+        );
+    }
+
+    #[test]
+    fn test_memory_footprint() {
+        let table = size_of::<MapperNode>() + 2 * size_of::<usize>();
+        let wiring = 2 * size_of::<Vec<usize>>() + 3 * size_of::<usize>();
+        let expected = size_of::<Network>()
+            + 2 * size_of::<Layer>()
+            + 4 * size_of::<SignalNode>()
+            + 5 * table
+            + 2 * size_of::<SharedMapper>()
+            + wiring
+            + "pass".len();
+        assert_eq!(network().memory_footprint_bytes(), expected);
+        assert_eq!(
+            Network::default().memory_footprint_bytes(),
+            size_of::<Network>()
+        );
+    }
+
+    #[test]
+    fn test_shared_tables_counted_once() {
+        let build = |shared: bool| {
+            let table = SharedMapper::new(MapperNode::new().with_fill(1));
+            Network::builder()
+                .input_width(1)
+                .layer_dense(3, |n| {
+                    let mapper = if shared {
+                        table.clone()
+                    } else {
+                        SharedMapper::new(table.clone().into_inner())
+                    };
+                    n.activation(mapper)
+                        .combinator(Box::new(MaxCombinatorNode::new()))
+                })
+                .build()
+                .unwrap()
+        };
+        let table = size_of::<MapperNode>() + 2 * size_of::<usize>();
+        assert_eq!(
+            build(false).memory_footprint_bytes() - build(true).memory_footprint_bytes(),
+            2 * table
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        let network = network();
+        let bytes = network.memory_footprint_bytes();
+        assert_eq!(
+            network.describe(),
+            format!(
+                "layer  wiring   nodes  inputs  outputs  parameters\n\
+                 0      dense        2       3        2         512\n\
+                 1      sparse       2       2        2         768\n\
+                 total: 2 layers, 4 nodes, 1280 parameters, {bytes} bytes\n\
+                 combinators: addition x1, max x3\n"
+            )
+        );
+    }
+}
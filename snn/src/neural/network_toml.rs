@@ -0,0 +1,559 @@
+/// Loading networks from a human-edited TOML configuration.
+///
+/// The configuration names its mapper tables once under `[mappers]` and lets nodes refer to
+/// them by name, so every node using a name shares one table. A mapper is either a `preset`
+/// accepted by `mapper_from_str`, with `args` for the presets that take parameters, or an
+/// `expr` accepted by `MapperNode::from_expr`.
+///
+/// Each `[[layers]]` entry has its `nodes`, an optional `wiring` (`"dense"`, the default, or
+/// one list of input indices per node) and an optional `normalizer` as in the JSON format. A
+/// node has a `combinator`, given as text such as `"threshold:300:0:255"` or as a table with
+/// a `name` and a list of `args`, and optionally an `input_mapper` name applied to every
+/// input, a list of `input_mappers` names with one per input, an `activation` name, a fixed
+/// `input_count` and a `name`.
+///
+/// ```toml
+/// input_width = 3
+///
+/// [mappers]
+/// lower = { preset = "for_lowercase" }
+/// relu = { preset = "relu", args = [64] }
+/// half = { expr = "x / 2" }
+///
+/// [[layers]]
+/// [[layers.nodes]]
+/// combinator = "max"
+/// input_mapper = "lower"
+/// [[layers.nodes]]
+/// combinator = { name = "threshold", args = [300, 0, 255] }
+/// input_mappers = ["lower", "half", "lower"]
+/// activation = "relu"
+/// ```
+use crate::neural::combinator::{CombinatorParseError, combinator_from_str};
+use crate::neural::json::Value;
+use crate::neural::layer::{Layer, LayerError, Wiring};
+use crate::neural::mapper::MapperNode;
+use crate::neural::mapper_expr::ExprError;
+use crate::neural::mapper_registry::{MapperParseError, try_mapper_from_str};
+use crate::neural::mapper_shared::SharedMapper;
+use crate::neural::network::{Network, NetworkError};
+use crate::neural::node::{NodeError, SignalNode};
+use crate::neural::normalizer::Normalizer;
+use crate::neural::toml::{self, TomlError};
+
+/// Error returned by `Network::from_toml`. Every variant but `Syntax` and `Network` carries
+/// the key path of the offending entry, such as `layers[0].nodes[1].input_mapper`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TomlConfigError {
+    /// The text is not valid TOML.
+    Syntax(TomlError),
+    /// An entry is missing, unknown or has the wrong type.
+    Invalid { path: String, message: String },
+    /// A mapper names a preset that does not exist.
+    UnknownPreset { path: String, name: String },
+    /// A node names a mapper that is not defined under `[mappers]`.
+    UnknownMapper { path: String, name: String },
+    /// A mapper expression could not be parsed or evaluated.
+    Expression { path: String, error: ExprError },
+    /// A combinator does not exist or has invalid arguments.
+    Combinator {
+        path: String,
+        error: CombinatorParseError,
+    },
+    /// A node's mappers or input count do not fit together.
+    Node { path: String, error: NodeError },
+    /// A layer's wiring is invalid.
+    Layer { path: String, error: LayerError },
+    /// The layers do not fit together.
+    Network(NetworkError),
+}
+
+impl std::fmt::Display for TomlConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TomlConfigError::Syntax(e) => write!(f, "{e}"),
+            TomlConfigError::Invalid { path, message } => write!(f, "{path}: {message}"),
+            TomlConfigError::UnknownPreset { path, name } => {
+                write!(f, "{path}: unknown mapper preset {name:?}")
+            }
+            TomlConfigError::UnknownMapper { path, name } => {
+                write!(f, "{path}: unknown mapper {name:?}")
+            }
+            TomlConfigError::Expression { path, error } => write!(f, "{path}: {error}"),
+            TomlConfigError::Combinator { path, error } => write!(f, "{path}: {error}"),
+            TomlConfigError::Node { path, error } => write!(f, "{path}: {error}"),
+            TomlConfigError::Layer { path, error } => write!(f, "{path}: {error}"),
+            TomlConfigError::Network(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for TomlConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TomlConfigError::Syntax(e) => Some(e),
+            TomlConfigError::Expression { error, .. } => Some(error),
+            TomlConfigError::Combinator { error, .. } => Some(error),
+            TomlConfigError::Node { error, .. } => Some(error),
+            TomlConfigError::Layer { error, .. } => Some(error),
+            TomlConfigError::Network(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<TomlError> for TomlConfigError {
+    fn from(e: TomlError) -> Self {
+        TomlConfigError::Syntax(e)
+    }
+}
+
+impl From<NetworkError> for TomlConfigError {
+    fn from(e: NetworkError) -> Self {
+        TomlConfigError::Network(e)
+    }
+}
+
+impl Network {
+    /// Loads a network from a TOML configuration. See the `network_toml` module for the
+    /// schema.
+    pub fn from_toml(text: &str) -> Result<Network, TomlConfigError> {
+        let root = toml::parse(text)?;
+        check_keys("", &root, &["input_width", "mappers", "layers"])?;
+        let mut width =
+            root.get("input_width")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| invalid("input_width", "missing integer"))? as usize;
+        let mappers = match root.get("mappers") {
+            None => Vec::new(),
+            Some(Value::Object(entries)) => entries
+                .iter()
+                .map(|(name, value)| {
+                    let mapper = mapper_from_value(&format!("mappers.{name}"), value)?;
+                    Ok((name.as_str(), SharedMapper::new(mapper)))
+                })
+                .collect::<Result<_, TomlConfigError>>()?,
+            Some(_) => return Err(invalid("mappers", "must be a table")),
+        };
+        let layers = root
+            .get("layers")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("layers", "missing array of tables"))?;
+        let mut built = Vec::with_capacity(layers.len());
+        for (index, value) in layers.iter().enumerate() {
+            let layer = layer_from_value(&format!("layers[{index}]"), value, width, &mappers)?;
+            width = layer.output_width();
+            built.push(layer);
+        }
+        Ok(Network::try_new(built)?)
+    }
+}
+
+fn layer_from_value(
+    path: &str,
+    value: &Value,
+    input_width: usize,
+    mappers: &[(&str, SharedMapper)],
+) -> Result<Layer, TomlConfigError> {
+    check_keys(path, value, &["nodes", "wiring", "normalizer"])?;
+    let nodes = value
+        .get("nodes")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid(&format!("{path}.nodes"), "missing array of tables"))?
+        .iter()
+        .enumerate()
+        .map(|(index, node)| node_from_value(&format!("{path}.nodes[{index}]"), node, mappers))
+        .collect::<Result<Vec<_>, _>>()?;
+    let wiring_path = format!("{path}.wiring");
+    let wiring = match value.get("wiring") {
+        None => Wiring::Dense,
+        Some(Value::String(kind)) if kind == "dense" => Wiring::Dense,
+        Some(Value::Array(entries)) => Wiring::Sparse(
+            entries
+                .iter()
+                .map(|entry| {
+                    entry
+                        .as_array()
+                        .and_then(|indices| {
+                            indices
+                                .iter()
+                                .map(|i| i.as_u64().map(|i| i as usize))
+                                .collect::<Option<Vec<_>>>()
+                        })
+                        .ok_or_else(|| invalid(&wiring_path, "entries must be lists of indices"))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        Some(_) => {
+            return Err(invalid(
+                &wiring_path,
+                "must be \"dense\" or a list of index lists",
+            ));
+        }
+    };
+    let normalizer_path = format!("{path}.normalizer");
+    let normalizer = match value.get("normalizer") {
+        None => None,
+        Some(Value::String(text)) => Some(
+            text.parse::<Normalizer>()
+                .map_err(|error| invalid(&normalizer_path, &error.to_string()))?,
+        ),
+        Some(_) => return Err(invalid(&normalizer_path, "must be a string")),
+    };
+    let layer = Layer::new(nodes, wiring, input_width).map_err(|error| TomlConfigError::Layer {
+        path: path.to_string(),
+        error,
+    })?;
+    Ok(match normalizer {
+        Some(normalizer) => layer.with_normalizer(normalizer),
+        None => layer,
+    })
+}
+
+fn node_from_value(
+    path: &str,
+    value: &Value,
+    mappers: &[(&str, SharedMapper)],
+) -> Result<SignalNode, TomlConfigError> {
+    check_keys(
+        path,
+        value,
+        &[
+            "combinator",
+            "input_mapper",
+            "input_mappers",
+            "activation",
+            "input_count",
+            "name",
+        ],
+    )?;
+    let combinator_path = format!("{path}.combinator");
+    let description = match value.get("combinator") {
+        Some(Value::String(text)) => text.clone(),
+        Some(table @ Value::Object(_)) => combinator_description(&combinator_path, table)?,
+        Some(_) => return Err(invalid(&combinator_path, "must be a string or a table")),
+        None => return Err(invalid(&combinator_path, "missing")),
+    };
+    let combinator =
+        combinator_from_str(&description).map_err(|error| TomlConfigError::Combinator {
+            path: combinator_path,
+            error,
+        })?;
+    let mut builder = SignalNode::builder().combinator(combinator);
+    if let Some(name) = value.get("input_mapper") {
+        builder = builder.shared_mapper(lookup(&format!("{path}.input_mapper"), name, mappers)?);
+    }
+    if let Some(names) = value.get("input_mappers") {
+        let names_path = format!("{path}.input_mappers");
+        let names = names
+            .as_array()
+            .ok_or_else(|| invalid(&names_path, "must be a list of mapper names"))?;
+        for (index, name) in names.iter().enumerate() {
+            builder =
+                builder.input_mapper(lookup(&format!("{names_path}[{index}]"), name, mappers)?);
+        }
+    }
+    if let Some(name) = value.get("activation") {
+        builder = builder.activation(lookup(&format!("{path}.activation"), name, mappers)?);
+    }
+    if let Some(count) = value.get("input_count") {
+        let count = count
+            .as_u64()
+            .ok_or_else(|| invalid(&format!("{path}.input_count"), "must be an integer"))?;
+        builder = builder.input_count(count as usize);
+    }
+    if let Some(name) = value.get("name") {
+        let name = name
+            .as_str()
+            .ok_or_else(|| invalid(&format!("{path}.name"), "must be a string"))?;
+        builder = builder.name(name);
+    }
+    builder.build().map_err(|error| TomlConfigError::Node {
+        path: path.to_string(),
+        error,
+    })
+}
+
+/// Turns a combinator table with a `name` and optional `args` into the text form accepted by
+/// `combinator_from_str`.
+fn combinator_description(path: &str, value: &Value) -> Result<String, TomlConfigError> {
+    check_keys(path, value, &["name", "args"])?;
+    let mut description = value
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid(&format!("{path}.name"), "missing string"))?
+        .to_string();
+    if let Some(args) = value.get("args") {
+        let args_path = format!("{path}.args");
+        let args = args
+            .as_array()
+            .ok_or_else(|| invalid(&args_path, "must be a list"))?;
+        for (index, arg) in args.iter().enumerate() {
+            let arg = match arg {
+                Value::String(text) => text.clone(),
+                Value::Number(n) => n.to_string(),
+                _ => {
+                    return Err(invalid(
+                        &format!("{args_path}[{index}]"),
+                        "must be a number or a string",
+                    ));
+                }
+            };
+            description.push(':');
+            description.push_str(&arg);
+        }
+    }
+    Ok(description)
+}
+
+fn mapper_from_value(path: &str, value: &Value) -> Result<MapperNode, TomlConfigError> {
+    check_keys(path, value, &["preset", "args", "expr"])?;
+    match (value.get("preset"), value.get("expr")) {
+        (Some(_), Some(_)) => Err(invalid(path, "set either \"preset\" or \"expr\", not both")),
+        (None, None) => Err(invalid(path, "missing \"preset\" or \"expr\"")),
+        (None, Some(expr)) => {
+            let expr_path = format!("{path}.expr");
+            if value.get("args").is_some() {
+                return Err(invalid(
+                    &format!("{path}.args"),
+                    "only presets take arguments",
+                ));
+            }
+            let expr = expr
+                .as_str()
+                .ok_or_else(|| invalid(&expr_path, "must be a string"))?;
+            MapperNode::from_expr(expr).map_err(|error| TomlConfigError::Expression {
+                path: expr_path,
+                error,
+            })
+        }
+        (Some(name), None) => {
+            let preset_path = format!("{path}.preset");
+            let name = name
+                .as_str()
+                .ok_or_else(|| invalid(&preset_path, "must be a string"))?;
+            let args_path = format!("{path}.args");
+            let mut description = name.to_string();
+            match value.get("args") {
+                None => {}
+                Some(Value::Array(args)) => {
+                    for arg in args {
+                        let arg = arg
+                            .as_u64()
+                            .ok_or_else(|| invalid(&args_path, "arguments must be integers"))?;
+                        description.push_str(&format!(":{arg}"));
+                    }
+                }
+                Some(_) => return Err(invalid(&args_path, "must be a list")),
+            }
+            try_mapper_from_str(&description).map_err(|error| match error {
+                MapperParseError::UnknownName(_) => TomlConfigError::UnknownPreset {
+                    path: preset_path,
+                    name: name.to_string(),
+                },
+                MapperParseError::InvalidArguments { reason, .. } => invalid(&args_path, &reason),
+            })
+        }
+    }
+}
+
+/// Returns the shared table a node refers to by name.
+fn lookup(
+    path: &str,
+    name: &Value,
+    mappers: &[(&str, SharedMapper)],
+) -> Result<SharedMapper, TomlConfigError> {
+    let name = name
+        .as_str()
+        .ok_or_else(|| invalid(path, "must be a mapper name"))?;
+    mappers
+        .iter()
+        .find(|(defined, _)| *defined == name)
+        .map(|(_, mapper)| mapper.clone())
+        .ok_or_else(|| TomlConfigError::UnknownMapper {
+            path: path.to_string(),
+            name: name.to_string(),
+        })
+}
+
+/// Requires `value` to be a table with no keys outside `allowed`, so typos are reported
+/// instead of ignored.
+fn check_keys(path: &str, value: &Value, allowed: &[&str]) -> Result<(), TomlConfigError> {
+    let Value::Object(entries) = value else {
+        return Err(invalid(path, "must be a table"));
+    };
+    match entries
+        .iter()
+        .find(|(key, _)| !allowed.contains(&key.as_str()))
+    {
+        Some((key, _)) if path.is_empty() => Err(invalid(key, "unknown key")),
+        Some((key, _)) => Err(invalid(&format!("{path}.{key}"), "unknown key")),
+        None => Ok(()),
+    }
+}
+
+fn invalid(path: &str, message: &str) -> TomlConfigError {
+    TomlConfigError::Invalid {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::mapper_registry::{available_mappers, mapper_from_str};
+
+    const FIXTURE: &str = r#"
+# Flags lowercase letters, then thresholds their sum.
+input_width = 3
+
+[mappers]
+lower = { preset = "for_lowercase" }
+relu = { preset = "relu", args = [64] }
+half = { expr = "x / 2" }
+
+[[layers]]
+
+[[layers.nodes]]
+name = "any_lower"
+combinator = "max"
+input_mapper = "lower"
+
+[[layers.nodes]]
+name = "weighted"
+combinator = { name = "threshold", args = [300, 0, 255] }
+input_mappers = ["lower", "half", "lower"]
+activation = "relu"
+
+[[layers]]
+wiring = [[0, 1]]
+nodes = [{ combinator = "addition" }]
+"#;
+
+    #[test]
+    fn test_fixture() {
+        let network = Network::from_toml(FIXTURE).unwrap();
+        assert_eq!(network.input_width(), Some(3));
+        assert_eq!(network.output_width(), Some(1));
+
+        let any_lower = network.node("any_lower").unwrap();
+        let weighted = network.node("weighted").unwrap();
+        // Every use of "lower" shares one table.
+        let table = |mapper: Option<&MapperNode>| mapper.unwrap().table().as_ptr();
+        assert_eq!(
+            table(any_lower.input_mapper(0)),
+            table(weighted.input_mapper(0))
+        );
+        assert_eq!(
+            table(weighted.input_mapper(0)),
+            table(weighted.input_mapper(2))
+        );
+        assert!(*weighted.input_mapper(1).unwrap() == MapperNode::from_expr("x / 2").unwrap());
+        assert!(
+            *weighted.activation().unwrap()
+                == crate::neural::mapper_defaults::create_mapper_relu(64)
+        );
+        assert_eq!(
+            weighted.combinator().describe(),
+            "threshold(cutoff=300, low=0, high=255)"
+        );
+
+        // "a" and "b" are lowercase (255 each) and 200 / 2 = 100, so the sum clears 300.
+        let trace = network.forward_trace(b"a\xC8b").unwrap();
+        assert_eq!(trace[0], [255, 255]);
+        assert_eq!(network.forward(b"a\xC8b").unwrap(), [255]);
+        assert_eq!(network.forward(b"ABC").unwrap(), [0]);
+    }
+
+    fn error(text: &str) -> TomlConfigError {
+        Network::from_toml(text).err().unwrap()
+    }
+
+    #[test]
+    fn test_unknown_names() {
+        assert_eq!(
+            error(
+                "input_width = 1\n[mappers]\nbad = { preset = \"nope\" }\n[[layers]]\nnodes = []\n"
+            ),
+            TomlConfigError::UnknownPreset {
+                path: "mappers.bad.preset".to_string(),
+                name: "nope".to_string(),
+            }
+        );
+        let unknown = error(
+            "input_width = 2\n\
+             [mappers]\n\
+             id = { expr = 'x' }\n\
+             [[layers]]\n\
+             [[layers.nodes]]\n\
+             combinator = 'max'\n\
+             [[layers.nodes]]\n\
+             combinator = 'max'\n\
+             input_mappers = ['id', 'missing']\n",
+        );
+        assert_eq!(
+            unknown,
+            TomlConfigError::UnknownMapper {
+                path: "layers[0].nodes[1].input_mappers[1]".to_string(),
+                name: "missing".to_string(),
+            }
+        );
+        assert_eq!(
+            unknown.to_string(),
+            "layers[0].nodes[1].input_mappers[1]: unknown mapper \"missing\""
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            error("input_width = "),
+            TomlConfigError::Syntax(_)
+        ));
+        assert_eq!(
+            error("input_width = 1\nlayer = []"),
+            invalid("layer", "unknown key")
+        );
+        assert_eq!(
+            error("input_width = 1\n[mappers]\nr = { preset = 'relu' }\n[[layers]]\nnodes = []"),
+            invalid("mappers.r.args", "takes 1 arguments, got 0")
+        );
+        assert!(matches!(
+            error("input_width = 1\n[mappers]\nr = { expr = 'x +' }\n[[layers]]\nnodes = []"),
+            TomlConfigError::Expression { path, .. } if path == "mappers.r.expr"
+        ));
+        assert!(matches!(
+            error("input_width = 1\n[[layers]]\nnodes = [{ combinator = 'nope' }]"),
+            TomlConfigError::Combinator { path, .. } if path == "layers[0].nodes[0].combinator"
+        ));
+        assert!(matches!(
+            error("input_width = 1\n[[layers]]\nnodes = [{ combinator = 'max', colour = 1 }]"),
+            TomlConfigError::Invalid { path, .. } if path == "layers[0].nodes[0].colour"
+        ));
+        assert!(matches!(
+            error("input_width = 1\n[[layers]]\nwiring = [[3]]\nnodes = [{ combinator = 'max' }]"),
+            TomlConfigError::Layer { path, .. } if path == "layers[0]"
+        ));
+    }
+
+    #[test]
+    fn test_presets_build() {
+        for info in available_mappers() {
+            let args = vec![2; info.parameters.len()];
+            let text = format!(
+                "input_width = 1\n[mappers]\nm = {{ preset = '{}', args = {args:?} }}\n\
+                 [[layers]]\nnodes = [{{ combinator = 'max', input_mapper = 'm' }}]\n",
+                info.name
+            );
+            let network = Network::from_toml(&text).unwrap();
+            let mapper = network.layers()[0].nodes()[0].input_mapper(0).unwrap();
+            let description = format!("{}:{}", info.name, vec!["2"; args.len()].join(":"));
+            assert!(
+                Some(mapper) == mapper_from_str(&description).as_ref(),
+                "{}",
+                info.name
+            );
+        }
+    }
+}
@@ -0,0 +1,225 @@
+/// Sliding-window evaluation of a network over a byte sequence.
+use crate::neural::error::SnnError;
+use crate::neural::network::{EvalError, Network};
+
+/// What `evaluate_windows_with` does with a window that runs past the end of the data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindowTail {
+    /// Only windows that fit entirely in the data are evaluated.
+    #[default]
+    Drop,
+    /// Every window that starts inside the data is evaluated, with zeros after the end.
+    ZeroPad,
+}
+
+impl Network {
+    /// Evaluates the network on every window of `input_width` bytes, starting at 0 and moving
+    /// `stride` bytes each time, and returns the outputs of each window.
+    /// Windows that would run past the end of `data` are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride` is zero or the network has no layers.
+    pub fn evaluate_windows(&self, data: &[u8], stride: usize) -> Vec<Vec<u8>> {
+        self.evaluate_windows_with(data, stride, WindowTail::Drop)
+    }
+
+    /// Like `evaluate_windows`, with `tail` deciding what happens to the last, partial windows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride` is zero or the network has no layers.
+    pub fn evaluate_windows_with(
+        &self,
+        data: &[u8],
+        stride: usize,
+        tail: WindowTail,
+    ) -> Vec<Vec<u8>> {
+        self.try_evaluate_windows(data, stride, tail)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Like `evaluate_windows_with`, returning an error instead of panicking when `stride`
+    /// is zero or the network has no layers.
+    pub fn try_evaluate_windows(
+        &self,
+        data: &[u8],
+        stride: usize,
+        tail: WindowTail,
+    ) -> Result<Vec<Vec<u8>>, SnnError> {
+        let mut outputs = Vec::new();
+        self.each_window(data, stride, tail, |output| outputs.push(output))?;
+        Ok(outputs)
+    }
+
+    /// Like `evaluate_windows_with`, but writes the outputs of all windows one after another
+    /// into `out`, replacing its contents, and returns the number of windows.
+    /// Reusing `out` between calls avoids allocating a vector per window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride` is zero or the network has no layers.
+    pub fn evaluate_windows_into(
+        &self,
+        data: &[u8],
+        stride: usize,
+        tail: WindowTail,
+        out: &mut Vec<u8>,
+    ) -> usize {
+        out.clear();
+        let mut count = 0;
+        self.each_window(data, stride, tail, |output| {
+            out.extend_from_slice(&output);
+            count += 1;
+        })
+        .unwrap_or_else(|e| panic!("{e}"));
+        count
+    }
+
+    fn each_window(
+        &self,
+        data: &[u8],
+        stride: usize,
+        tail: WindowTail,
+        mut emit: impl FnMut(Vec<u8>),
+    ) -> Result<(), EvalError> {
+        if stride == 0 {
+            return Err(EvalError::ZeroStride);
+        }
+        let width = self.input_width().ok_or(EvalError::NoLayers)?;
+        let mut padded = vec![0; width];
+        let mut start = 0;
+        while start < data.len() {
+            let window = match data.get(start..start + width) {
+                Some(window) => window,
+                None if tail == WindowTail::ZeroPad => {
+                    let rest = &data[start..];
+                    padded[..rest.len()].copy_from_slice(rest);
+                    padded[rest.len()..].fill(0);
+                    &padded
+                }
+                None => break,
+            };
+            emit(self.forward(window)?);
+            start += stride;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{MaxCombinatorNode, MinCombinatorNode};
+    use crate::neural::layer::Layer;
+    use crate::neural::node::SignalNode;
+    use crate::neural::traits::Combinator;
+
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    /// Returns the largest and smallest byte of a 3-byte window.
+    fn max_min() -> Network {
+        let layer = Layer::dense(
+            vec![
+                node(MaxCombinatorNode::new()),
+                node(MinCombinatorNode::new()),
+            ],
+            3,
+        )
+        .unwrap();
+        Network::try_new(vec![layer]).unwrap()
+    }
+
+    #[test]
+    fn test_stride_one() {
+        let outputs = max_min().evaluate_windows(b"abcdefg", 1);
+        let expected: Vec<Vec<u8>> = vec![
+            b"ca".to_vec(),
+            b"db".to_vec(),
+            b"ec".to_vec(),
+            b"fd".to_vec(),
+            b"ge".to_vec(),
+        ];
+        assert_eq!(outputs, expected);
+        // Windows that stop exactly at the end are not padded.
+        assert_eq!(
+            max_min().evaluate_windows_with(b"abcdefg", 1, WindowTail::ZeroPad)[..5],
+            expected[..]
+        );
+    }
+
+    #[test]
+    fn test_stride_three() {
+        let network = max_min();
+        assert_eq!(
+            network.evaluate_windows(b"abcdefg", 3),
+            vec![b"ca".to_vec(), b"fd".to_vec()]
+        );
+        assert_eq!(
+            network.evaluate_windows_with(b"abcdefg", 3, WindowTail::ZeroPad),
+            vec![b"ca".to_vec(), b"fd".to_vec(), vec![b'g', 0]]
+        );
+        assert_eq!(
+            network.evaluate_windows(b"abcdef", 3),
+            vec![b"ca".to_vec(), b"fd".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_short_input() {
+        let network = max_min();
+        assert!(network.evaluate_windows(b"ab", 1).is_empty());
+        assert_eq!(
+            network.evaluate_windows_with(b"ab", 1, WindowTail::ZeroPad),
+            vec![vec![b'b', 0], vec![b'b', 0]]
+        );
+        assert!(
+            network
+                .evaluate_windows_with(b"", 1, WindowTail::ZeroPad)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_invalid_windows() {
+        assert_eq!(
+            max_min()
+                .try_evaluate_windows(b"abc", 0, WindowTail::Drop)
+                .err()
+                .unwrap()
+                .to_string(),
+            "evaluation error: window stride must be at least 1"
+        );
+        assert_eq!(
+            Network::default().try_evaluate_windows(b"abc", 1, WindowTail::Drop),
+            Err(SnnError::Eval(EvalError::NoLayers))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "window stride must be at least 1")]
+    fn test_zero_stride_panics() {
+        max_min().evaluate_windows(b"abc", 0);
+    }
+
+    #[test]
+    fn test_into_reuses_buffer() {
+        let network = max_min();
+        let mut out = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        assert_eq!(
+            network.evaluate_windows_into(b"abcdefg", 3, WindowTail::Drop, &mut out),
+            2
+        );
+        assert_eq!(out, b"cafd");
+        assert_eq!(
+            network.evaluate_windows_into(b"ab", 3, WindowTail::Drop, &mut out),
+            0
+        );
+        assert!(out.is_empty());
+    }
+}
@@ -0,0 +1,461 @@
+/// Nodes that wire input mappers, a combinator and an activation table into a single neuron.
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::neural::mapper::MapperNode;
+use crate::neural::mapper_shared::SharedMapper;
+use crate::neural::traits::{ArityError, Combinator, check_arity};
+
+/// The mappers applied to a node's inputs before they are combined.
+pub(crate) enum InputMappers {
+    /// Inputs are passed to the combinator unchanged.
+    Identity,
+    /// The same mapper is applied to every input.
+    Shared(SharedMapper),
+    /// Input `i` goes through mapper `i`.
+    PerInput(Vec<SharedMapper>),
+}
+
+/// A neuron that maps each input, combines the mapped values, and passes the result
+/// through an optional activation table.
+pub struct SignalNode {
+    input_mappers: InputMappers,
+    combinator: Box<dyn Combinator>,
+    activation: Option<SharedMapper>,
+    input_count: Option<usize>,
+    name: Option<String>,
+}
+
+impl SignalNode {
+    /// Returns a builder for a node. Only the combinator is required.
+    pub fn builder() -> SignalNodeBuilder {
+        SignalNodeBuilder::default()
+    }
+
+    /// Evaluates the node: map every input, combine, then activate.
+    /// Inputs past the end of the per-input mappers are passed to the combinator unchanged;
+    /// use `try_evaluate` to reject an unexpected number of inputs instead.
+    pub fn evaluate(&self, inputs: &[u8]) -> u8 {
+        let combined = match &self.input_mappers {
+            InputMappers::Identity => self.combinator.combine(inputs),
+            InputMappers::Shared(mapper) => {
+                let mapped: Vec<u8> = inputs.iter().map(|&x| mapper.transform(x)).collect();
+                self.combinator.combine(&mapped)
+            }
+            InputMappers::PerInput(mappers) => {
+                let mapped: Vec<u8> = inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &x)| match mappers.get(i) {
+                        Some(mapper) => mapper.transform(x),
+                        None => x,
+                    })
+                    .collect();
+                self.combinator.combine(&mapped)
+            }
+        };
+        match &self.activation {
+            Some(activation) => activation.transform(combined),
+            None => combined,
+        }
+    }
+
+    /// Evaluates the node after checking the number of inputs against `input_count`
+    /// and the combinator's arity.
+    pub fn try_evaluate(&self, inputs: &[u8]) -> Result<u8, NodeError> {
+        self.check_input_count(inputs.len())?;
+        Ok(self.evaluate(inputs))
+    }
+
+    /// Checks whether the node accepts `count` inputs.
+    pub fn check_input_count(&self, count: usize) -> Result<(), NodeError> {
+        if let Some(expected) = self.input_count
+            && expected != count
+        {
+            return Err(NodeError::InputCount {
+                expected,
+                actual: count,
+            });
+        }
+        check_arity(self.combinator.as_ref(), count)?;
+        Ok(())
+    }
+
+    /// Returns the number of inputs the node expects, if it is fixed.
+    /// This is the declared count, or else the number of per-input mappers.
+    pub fn input_count(&self) -> Option<usize> {
+        self.input_count
+    }
+
+    /// Returns the combinator.
+    pub fn combinator(&self) -> &dyn Combinator {
+        self.combinator.as_ref()
+    }
+
+    /// Returns the activation table, if any.
+    pub fn activation(&self) -> Option<&MapperNode> {
+        self.activation.as_deref()
+    }
+
+    /// Replaces the activation table, or removes it with `None`.
+    pub fn set_activation(&mut self, activation: Option<MapperNode>) {
+        self.activation = activation.map(SharedMapper::new);
+    }
+
+    /// Returns the node's name, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Names the node so that it can be found with `Network::node`.
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = Some(name.into());
+    }
+
+    /// Returns the node as a single table, `evaluate(&[x])` for every `x`.
+    #[cfg(feature = "std")]
+    pub(crate) fn to_table(&self) -> MapperNode {
+        MapperNode::new_transformation(|x| self.evaluate(&[x as u8]))
+    }
+
+    /// Applies `before[i]` to input `i` ahead of the node's own input mappers.
+    #[cfg(feature = "std")]
+    pub(crate) fn compose_inputs(&mut self, before: &[MapperNode]) {
+        let mappers = before
+            .iter()
+            .enumerate()
+            .map(|(i, first)| match self.input_mapper(i) {
+                Some(then) => SharedMapper::new(MapperNode::new_transformation(|x| {
+                    then.transform(first.transform(x as u8))
+                })),
+                None => SharedMapper::new(first.clone()),
+            })
+            .collect();
+        self.input_mappers = InputMappers::PerInput(mappers);
+        self.input_count = Some(before.len());
+    }
+
+    /// Applies `after` to the node's output, after its own activation.
+    #[cfg(feature = "std")]
+    pub(crate) fn compose_activation(&mut self, after: &MapperNode) {
+        self.activation = Some(SharedMapper::new(match &self.activation {
+            Some(first) => {
+                MapperNode::new_transformation(|x| after.transform(first.transform(x as u8)))
+            }
+            None => after.clone(),
+        }));
+    }
+
+    /// Drops input mappers and activation tables that map every value to itself.
+    #[cfg(feature = "std")]
+    pub(crate) fn remove_identity_mappers(&mut self) {
+        let identity = match &self.input_mappers {
+            InputMappers::Identity => false,
+            InputMappers::Shared(mapper) => mapper.is_identity(),
+            InputMappers::PerInput(mappers) => mappers.iter().all(|mapper| mapper.is_identity()),
+        };
+        if identity {
+            // Per-input mappers also fixed the input count, which is kept.
+            if let InputMappers::PerInput(mappers) = &self.input_mappers {
+                self.input_count = Some(mappers.len());
+            }
+            self.input_mappers = InputMappers::Identity;
+        }
+        if self
+            .activation
+            .as_ref()
+            .is_some_and(|activation| activation.is_identity())
+        {
+            self.activation = None;
+        }
+    }
+
+    /// Returns how the node maps its inputs.
+    #[cfg(feature = "std")]
+    pub(crate) fn input_mappers(&self) -> &InputMappers {
+        &self.input_mappers
+    }
+
+    /// Returns every table of the node for modification: the input mappers, then the activation.
+    /// Tables shared with other nodes are copied first, so changes stay local to this node.
+    #[cfg(feature = "std")]
+    pub(crate) fn mappers_mut(&mut self) -> Vec<&mut MapperNode> {
+        let mut mappers: Vec<&mut MapperNode> = match &mut self.input_mappers {
+            InputMappers::Identity => Vec::new(),
+            InputMappers::Shared(mapper) => alloc::vec![mapper.make_mut()],
+            InputMappers::PerInput(mappers) => {
+                mappers.iter_mut().map(SharedMapper::make_mut).collect()
+            }
+        };
+        mappers.extend(self.activation.as_mut().map(SharedMapper::make_mut));
+        mappers
+    }
+
+    /// Returns the number of mapper tables the node holds: input mappers and activation.
+    pub fn mapper_count(&self) -> usize {
+        let inputs = match &self.input_mappers {
+            InputMappers::Identity => 0,
+            InputMappers::Shared(_) => 1,
+            InputMappers::PerInput(mappers) => mappers.len(),
+        };
+        inputs + usize::from(self.activation.is_some())
+    }
+
+    /// Returns the mapper applied to input `index`, or `None` if that input is passed unchanged.
+    pub fn input_mapper(&self, index: usize) -> Option<&MapperNode> {
+        match &self.input_mappers {
+            InputMappers::Identity => None,
+            InputMappers::Shared(mapper) => Some(mapper),
+            InputMappers::PerInput(mappers) => mappers.get(index).map(|mapper| &**mapper),
+        }
+    }
+}
+
+/// Builds a `SignalNode`. See `SignalNode::builder`.
+#[derive(Default)]
+pub struct SignalNodeBuilder {
+    input_mappers: Vec<SharedMapper>,
+    shared_mapper: Option<SharedMapper>,
+    combinator: Option<Box<dyn Combinator>>,
+    activation: Option<SharedMapper>,
+    input_count: Option<usize>,
+    name: Option<String>,
+}
+
+impl SignalNodeBuilder {
+    /// Names the node so that it can be found with `Network::node`.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Adds the mapper for the next input. Calling this `n` times fixes the node to `n` inputs.
+    /// Passing a `SharedMapper` lets many nodes use one table without copying it.
+    pub fn input_mapper(mut self, mapper: impl Into<SharedMapper>) -> Self {
+        self.input_mappers.push(mapper.into());
+        self
+    }
+
+    /// Sets one mapper that is applied to every input.
+    pub fn shared_mapper(mut self, mapper: impl Into<SharedMapper>) -> Self {
+        self.shared_mapper = Some(mapper.into());
+        self
+    }
+
+    /// Sets the combinator.
+    pub fn combinator(mut self, combinator: Box<dyn Combinator>) -> Self {
+        self.combinator = Some(combinator);
+        self
+    }
+
+    /// Sets the activation table applied to the combined value.
+    pub fn activation(mut self, activation: impl Into<SharedMapper>) -> Self {
+        self.activation = Some(activation.into());
+        self
+    }
+
+    /// Fixes the number of inputs the node accepts.
+    pub fn input_count(mut self, count: usize) -> Self {
+        self.input_count = Some(count);
+        self
+    }
+
+    /// Builds the node, checking that the configuration is consistent.
+    pub fn build(self) -> Result<SignalNode, NodeError> {
+        let combinator = self.combinator.ok_or(NodeError::MissingCombinator)?;
+        let input_mappers = match (self.shared_mapper, self.input_mappers.is_empty()) {
+            (Some(_), false) => return Err(NodeError::ConflictingMappers),
+            (Some(mapper), true) => InputMappers::Shared(mapper),
+            (None, true) => InputMappers::Identity,
+            (None, false) => InputMappers::PerInput(self.input_mappers),
+        };
+        let per_input = match &input_mappers {
+            InputMappers::PerInput(mappers) => Some(mappers.len()),
+            _ => None,
+        };
+        let input_count = match (self.input_count, per_input) {
+            (Some(expected), Some(actual)) if expected != actual => {
+                return Err(NodeError::InputCount { expected, actual });
+            }
+            (declared, per_input) => declared.or(per_input),
+        };
+        if let Some(count) = input_count {
+            check_arity(combinator.as_ref(), count)?;
+        }
+        Ok(SignalNode {
+            input_mappers,
+            combinator,
+            activation: self.activation,
+            input_count,
+            name: self.name,
+        })
+    }
+}
+
+/// Error returned when a `SignalNode` is misconfigured or given the wrong number of inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeError {
+    /// The builder was not given a combinator.
+    MissingCombinator,
+    /// The builder was given both a shared mapper and per-input mappers.
+    ConflictingMappers,
+    /// The number of inputs does not match the number the node expects.
+    InputCount { expected: usize, actual: usize },
+    /// The number of inputs is outside the combinator's arity.
+    Arity(ArityError),
+}
+
+impl From<ArityError> for NodeError {
+    fn from(error: ArityError) -> Self {
+        NodeError::Arity(error)
+    }
+}
+
+impl core::fmt::Display for NodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NodeError::MissingCombinator => write!(f, "node has no combinator"),
+            NodeError::ConflictingMappers => {
+                write!(f, "node has both a shared mapper and per-input mappers")
+            }
+            NodeError::InputCount { expected, actual } => {
+                write!(f, "node expects {expected} inputs, got {actual}")
+            }
+            NodeError::Arity(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl core::error::Error for NodeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            NodeError::Arity(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{
+        AdditionCombinatorNode, MedianCombinatorNode, ThresholdCombinatorNode,
+    };
+    use crate::neural::combinator_compare::GreaterThanCombinatorNode;
+    use crate::neural::mapper_defaults::{
+        create_mapper_for_letters, create_mapper_for_lowercase, create_mapper_for_uppercase,
+    };
+
+    /// Fires 255 when at least two of three characters are ASCII letters.
+    fn mostly_letters() -> SignalNode {
+        SignalNode::builder()
+            .shared_mapper(create_mapper_for_letters().with_threshold(1, 0, 1))
+            .combinator(Box::new(AdditionCombinatorNode::new()))
+            .activation(MapperNode::new_transformation(|x| x as u8).with_threshold(2, 0, 255))
+            .input_count(3)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_mostly_letters() {
+        let node = mostly_letters();
+        for (text, expected) in [
+            (b"abc", 255),
+            (b"aB1", 255),
+            (b"1bC", 255),
+            (b"a12", 0),
+            (b"?!.", 0),
+            (b"Z 9", 0),
+            (b"XY ", 255),
+        ] {
+            assert_eq!(node.evaluate(text), expected, "{:?}", text);
+            assert_eq!(node.try_evaluate(text), Ok(expected));
+        }
+        assert_eq!(
+            node.try_evaluate(b"abcd"),
+            Err(NodeError::InputCount {
+                expected: 3,
+                actual: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_per_input_mappers() {
+        // Median of three binary votes is the majority: lowercase, uppercase, any letter.
+        let node = SignalNode::builder()
+            .input_mapper(create_mapper_for_lowercase())
+            .input_mapper(create_mapper_for_uppercase())
+            .input_mapper(create_mapper_for_letters().with_threshold(1, 0, 255))
+            .combinator(Box::new(MedianCombinatorNode::new()))
+            .build()
+            .unwrap();
+        assert_eq!(node.input_count(), Some(3));
+        assert!(node.activation().is_none());
+        assert_eq!(node.evaluate(b"aBc"), 255);
+        assert_eq!(node.evaluate(b"Abc"), 0);
+        assert_eq!(node.evaluate(b"a1c"), 255);
+        assert_eq!(node.evaluate(b"a1!"), 0);
+        assert!(node.try_evaluate(b"aB").is_err());
+    }
+
+    #[test]
+    fn test_identity_inputs() {
+        let node = SignalNode::builder()
+            .combinator(Box::new(ThresholdCombinatorNode::new(300, 0, 255)))
+            .build()
+            .unwrap();
+        assert_eq!(node.input_count(), None);
+        assert!(node.input_mapper(0).is_none());
+        assert_eq!(node.evaluate(&[200, 99]), 0);
+        assert_eq!(node.evaluate(&[200, 100]), 255);
+        assert_eq!(node.evaluate(&[100, 100, 100]), 255);
+    }
+
+    #[test]
+    fn test_name() {
+        let mut node = SignalNode::builder()
+            .name("sum")
+            .combinator(Box::new(AdditionCombinatorNode::new()))
+            .build()
+            .unwrap();
+        assert_eq!(node.name(), Some("sum"));
+        node.set_name("total");
+        assert_eq!(node.name(), Some("total"));
+        node.set_activation(Some(MapperNode::new()));
+        assert_eq!(node.evaluate(&[1, 2]), 0);
+        node.set_activation(None);
+        assert_eq!(node.evaluate(&[1, 2]), 3);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert_eq!(
+            SignalNode::builder().build().err(),
+            Some(NodeError::MissingCombinator)
+        );
+        let conflicting = SignalNode::builder()
+            .shared_mapper(MapperNode::new())
+            .input_mapper(MapperNode::new())
+            .combinator(Box::new(AdditionCombinatorNode::new()))
+            .build();
+        assert_eq!(conflicting.err(), Some(NodeError::ConflictingMappers));
+        let mismatched = SignalNode::builder()
+            .input_mapper(MapperNode::new())
+            .combinator(Box::new(AdditionCombinatorNode::new()))
+            .input_count(2)
+            .build();
+        assert_eq!(
+            mismatched.err(),
+            Some(NodeError::InputCount {
+                expected: 2,
+                actual: 1
+            })
+        );
+        let arity = SignalNode::builder()
+            .combinator(Box::new(GreaterThanCombinatorNode::new()))
+            .input_count(3)
+            .build();
+        assert!(matches!(arity.err(), Some(NodeError::Arity(_))));
+    }
+}
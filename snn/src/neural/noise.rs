@@ -0,0 +1,229 @@
+/// Combinators and mappers that add reproducible pseudo-random noise to their outputs.
+use alloc::{boxed::Box, format, string::String};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::neural::mapper::MapperNode;
+use crate::neural::rng::{nonzero_seed, xorshift64};
+use crate::neural::traits::{Combinator, Mapper};
+
+/// A xorshift64 generator shared between calls through an atomic, so that noisy nodes can
+/// keep `&self` methods and stay `Sync`.
+#[derive(Debug)]
+struct NoiseSource {
+    seed: u64,
+    state: AtomicU64,
+}
+
+impl NoiseSource {
+    fn new(seed: u64) -> Self {
+        let seed = nonzero_seed(seed);
+        Self {
+            seed,
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    fn next(&self) -> u64 {
+        let previous = self
+            .state
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| {
+                Some(xorshift64(x))
+            })
+            .unwrap();
+        xorshift64(previous)
+    }
+
+    /// Adds an offset in `-amplitude..=amplitude` to `value`, saturating at 0 and 255.
+    fn perturb(&self, value: u8, amplitude: u8) -> u8 {
+        if amplitude == 0 {
+            return value;
+        }
+        let span = 2 * amplitude as u64 + 1;
+        let offset = (self.next() % span) as i16 - amplitude as i16;
+        (value as i16 + offset).clamp(0, 255) as u8
+    }
+
+    fn reset(&self) {
+        self.state.store(self.seed, Ordering::Relaxed);
+    }
+}
+
+/// A combinator node that adds a pseudo-random offset in `-amplitude..=amplitude` to the
+/// result of an inner combinator, saturating at 0 and 255.
+/// The offsets follow a fixed sequence determined by the seed.
+pub struct NoiseCombinatorNode {
+    combinator: Box<dyn Combinator>,
+    amplitude: u8,
+    noise: NoiseSource,
+}
+
+impl NoiseCombinatorNode {
+    pub const IDENTIFIER: &'static str = "noise";
+    pub fn new(combinator: Box<dyn Combinator>, amplitude: u8, seed: u64) -> Self {
+        Self {
+            combinator,
+            amplitude,
+            noise: NoiseSource::new(seed),
+        }
+    }
+
+    /// Returns the inner combinator.
+    pub fn combinator(&self) -> &dyn Combinator {
+        self.combinator.as_ref()
+    }
+
+    /// Returns the largest offset that can be added or subtracted.
+    pub fn amplitude(&self) -> u8 {
+        self.amplitude
+    }
+
+    /// Restarts the noise sequence from the seed.
+    pub fn reset(&self) {
+        self.noise.reset();
+    }
+}
+
+impl Combinator for NoiseCombinatorNode {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        self.noise
+            .perturb(self.combinator.combine(inputs), self.amplitude)
+    }
+    fn identifier(&self) -> String {
+        format!("{}({})", Self::IDENTIFIER, self.combinator.identifier())
+    }
+    fn describe(&self) -> String {
+        format!(
+            "{}({}, amplitude={})",
+            Self::IDENTIFIER,
+            self.combinator.describe(),
+            self.amplitude
+        )
+    }
+    fn min_inputs(&self) -> usize {
+        self.combinator.min_inputs()
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        self.combinator.max_inputs()
+    }
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+}
+
+/// A mapper that adds a pseudo-random offset in `-amplitude..=amplitude` to each output of a
+/// lookup table, saturating at 0 and 255.
+/// The offsets follow a fixed sequence determined by the seed.
+pub struct DitherMapper {
+    mapper: MapperNode,
+    amplitude: u8,
+    noise: NoiseSource,
+}
+
+impl DitherMapper {
+    pub fn new(mapper: MapperNode, amplitude: u8, seed: u64) -> Self {
+        Self {
+            mapper,
+            amplitude,
+            noise: NoiseSource::new(seed),
+        }
+    }
+
+    /// Returns the table being perturbed.
+    pub fn mapper(&self) -> &MapperNode {
+        &self.mapper
+    }
+
+    /// Returns the largest offset that can be added or subtracted.
+    pub fn amplitude(&self) -> u8 {
+        self.amplitude
+    }
+
+    /// Restarts the noise sequence from the seed.
+    pub fn reset(&self) {
+        self.noise.reset();
+    }
+}
+
+impl Mapper for DitherMapper {
+    fn transform(&self, input: u8) -> u8 {
+        self.noise
+            .perturb(self.mapper.transform(input), self.amplitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::AverageCombinatorNode;
+
+    fn identity() -> MapperNode {
+        MapperNode::new_transformation(|x| x as u8)
+    }
+
+    fn noisy_average(amplitude: u8, seed: u64) -> NoiseCombinatorNode {
+        NoiseCombinatorNode::new(Box::new(AverageCombinatorNode::new()), amplitude, seed)
+    }
+
+    #[test]
+    fn test_zero_amplitude_is_noop() {
+        let node = noisy_average(0, 42);
+        assert_eq!(node.identifier(), "noise(average)");
+        for inputs in [&[0u8][..], &[255], &[10, 20, 33]] {
+            assert_eq!(
+                node.combine(inputs),
+                AverageCombinatorNode::new().combine(inputs)
+            );
+        }
+        let dither = DitherMapper::new(identity(), 0, 42);
+        assert!((0..=255).all(|x| dither.transform(x) == x));
+    }
+
+    #[test]
+    fn test_outputs_stay_within_bounds() {
+        let node = noisy_average(5, 7);
+        let mut seen = [false; 11];
+        for _ in 0..1000 {
+            let out = node.combine(&[100]);
+            assert!((95..=105).contains(&out), "{out}");
+            seen[(out - 95) as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s), "every offset should occur");
+
+        // Offsets saturate instead of wrapping at the ends of the range.
+        let dither = DitherMapper::new(identity(), 200, 7);
+        for _ in 0..1000 {
+            assert!(dither.transform(0) <= 200);
+            assert!(dither.transform(255) >= 55);
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let sequence = |node: &NoiseCombinatorNode| -> Vec<u8> {
+            (0..100).map(|i| node.combine(&[i as u8])).collect()
+        };
+        let a = noisy_average(20, 1234);
+        let b = noisy_average(20, 1234);
+        let first = sequence(&a);
+        assert_eq!(first, sequence(&b));
+        assert_ne!(first, sequence(&noisy_average(20, 4321)));
+
+        a.reset();
+        assert_eq!(sequence(&a), first);
+
+        let dither = DitherMapper::new(identity(), 3, 99);
+        let mut x = [50u8; 64];
+        let mut y = [50u8; 64];
+        dither.transform_in_place(&mut x);
+        dither.reset();
+        dither.transform_in_place(&mut y);
+        assert_eq!(x, y);
+    }
+
+    #[test]
+    fn test_zero_seed_still_produces_noise() {
+        let node = noisy_average(10, 0);
+        let outputs: Vec<u8> = (0..50).map(|_| node.combine(&[128])).collect();
+        assert!(outputs.iter().any(|&x| x != outputs[0]));
+    }
+}
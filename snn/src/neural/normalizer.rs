@@ -0,0 +1,243 @@
+/// Normalization of a whole vector of node outputs, such as the scores of a classification layer.
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::neural::mapper_defaults::exp2_fixed;
+
+/// `log2(e)` in 16.16 fixed point.
+const LOG2_E: i64 = 94_548;
+/// 1.0 in the 16.16 fixed point of the softmax weights.
+const ONE: u32 = 1 << 16;
+
+/// How a `Normalizer` rescales a vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizerMode {
+    /// Scales every value so the largest becomes 255.
+    Linear,
+    /// Scales every value in proportion to the total, so the outputs sum to 255 within rounding.
+    Sum,
+    /// Weights value `x` by `e^((x - max) / temperature)` and scales the weights like `Sum`,
+    /// so the largest value dominates more the lower the temperature. A temperature of 0 splits
+    /// 255 between the largest values only.
+    Softmax(u8),
+}
+
+/// Rescales a vector of values as a whole. Each output is rounded to the nearest value, and a
+/// vector of zeros stays zeros for `Linear` and `Sum`.
+///
+/// Attach one to a layer with `Layer::with_normalizer`, or add one between layers with
+/// `NetworkBuilder::normalizer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Normalizer {
+    mode: NormalizerMode,
+    /// For `Softmax`, entry `d` is `e^(-d / temperature)` in 16.16 fixed point.
+    weights: [u32; 256],
+}
+
+impl Normalizer {
+    pub fn new(mode: NormalizerMode) -> Self {
+        let mut weights = [0; 256];
+        match mode {
+            NormalizerMode::Softmax(0) => weights[0] = ONE,
+            NormalizerMode::Softmax(temperature) => {
+                for (distance, weight) in weights.iter_mut().enumerate() {
+                    *weight = exp2_fixed(-(distance as i64 * LOG2_E) / temperature as i64) as u32;
+                }
+            }
+            NormalizerMode::Linear | NormalizerMode::Sum => {}
+        }
+        Self { mode, weights }
+    }
+
+    /// Creates a `NormalizerMode::Linear` normalizer.
+    pub fn linear() -> Self {
+        Self::new(NormalizerMode::Linear)
+    }
+
+    /// Creates a `NormalizerMode::Sum` normalizer.
+    pub fn sum() -> Self {
+        Self::new(NormalizerMode::Sum)
+    }
+
+    /// Creates a `NormalizerMode::Softmax` normalizer.
+    pub fn softmax(temperature: u8) -> Self {
+        Self::new(NormalizerMode::Softmax(temperature))
+    }
+
+    /// Returns the mode.
+    pub fn mode(&self) -> NormalizerMode {
+        self.mode
+    }
+
+    /// Returns the normalized copy of `values`.
+    pub fn normalize(&self, values: &[u8]) -> Vec<u8> {
+        let mut values = values.to_vec();
+        self.normalize_in_place(&mut values);
+        values
+    }
+
+    /// Normalizes `values` in place.
+    pub fn normalize_in_place(&self, values: &mut [u8]) {
+        match self.mode {
+            NormalizerMode::Linear => {
+                let max = values.iter().copied().max().unwrap_or(0) as u64;
+                scale(values, |x| x as u64, max);
+            }
+            NormalizerMode::Sum => {
+                let total = values.iter().map(|&x| x as u64).sum();
+                scale(values, |x| x as u64, total);
+            }
+            NormalizerMode::Softmax(_) => {
+                let max = values.iter().copied().max().unwrap_or(0);
+                let weight = |x: u8| self.weights[(max - x) as usize] as u64;
+                let total = values.iter().map(|&x| weight(x)).sum();
+                scale(values, weight, total);
+            }
+        }
+    }
+}
+
+/// Replaces each value `x` with `255 * weight(x) / total`, rounded, or 0 if `total` is 0.
+fn scale(values: &mut [u8], weight: impl Fn(u8) -> u64, total: u64) {
+    if total == 0 {
+        values.fill(0);
+        return;
+    }
+    // The weights are computed before any value is overwritten, as they may depend on others.
+    for x in values.iter_mut() {
+        *x = ((2 * 255 * weight(*x) + total) / (2 * total)) as u8;
+    }
+}
+
+impl core::fmt::Display for Normalizer {
+    /// Writes `linear`, `sum` or `softmax:<temperature>`, the form `parse` accepts.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.mode {
+            NormalizerMode::Linear => f.write_str("linear"),
+            NormalizerMode::Sum => f.write_str("sum"),
+            NormalizerMode::Softmax(temperature) => write!(f, "softmax:{temperature}"),
+        }
+    }
+}
+
+impl core::str::FromStr for Normalizer {
+    type Err = NormalizerParseError;
+
+    /// Parses `linear`, `sum` or `softmax:<temperature>` with a temperature from 0 to 255.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "linear" => Ok(Normalizer::linear()),
+            None if s == "sum" => Ok(Normalizer::sum()),
+            Some(("softmax", temperature)) => temperature
+                .parse()
+                .map(Normalizer::softmax)
+                .map_err(|_| NormalizerParseError::InvalidTemperature(temperature.to_string())),
+            _ => Err(NormalizerParseError::UnknownMode(s.to_string())),
+        }
+    }
+}
+
+/// Error returned when a normalizer description cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NormalizerParseError {
+    /// The description is not `linear`, `sum` or `softmax:<temperature>`.
+    UnknownMode(String),
+    /// The softmax temperature is not a number from 0 to 255.
+    InvalidTemperature(String),
+}
+
+impl core::fmt::Display for NormalizerParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NormalizerParseError::UnknownMode(s) => write!(f, "unknown normalizer {s:?}"),
+            NormalizerParseError::InvalidTemperature(s) => {
+                write!(f, "invalid softmax temperature {s:?}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for NormalizerParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear() {
+        let linear = Normalizer::linear();
+        // 50 * 255 / 100 = 127.5.
+        assert_eq!(linear.normalize(&[0, 50, 100]), [0, 128, 255]);
+        assert_eq!(linear.normalize(&[3]), [255]);
+        assert_eq!(linear.normalize(&[0, 0, 0]), [0, 0, 0]);
+        assert_eq!(linear.normalize(&[]), Vec::<u8>::new());
+        assert_eq!(linear.normalize(&[255, 1]), [255, 1]);
+    }
+
+    #[test]
+    fn test_sum() {
+        let sum = Normalizer::sum();
+        // 25.5, 76.5 and 153 round to a total of 256.
+        assert_eq!(sum.normalize(&[10, 30, 60]), [26, 77, 153]);
+        assert_eq!(sum.normalize(&[200, 200, 0]), [128, 128, 0]);
+        assert_eq!(sum.normalize(&[7]), [255]);
+        assert_eq!(sum.normalize(&[0, 0]), [0, 0]);
+        let total: u32 = sum
+            .normalize(&[1, 2, 3, 4, 5, 6, 7, 8])
+            .iter()
+            .map(|&x| x as u32)
+            .sum();
+        assert!((251..=259).contains(&total), "{total}");
+    }
+
+    #[test]
+    fn test_softmax() {
+        // e^-1 = 0.3679, so the weights are 0.3679 and 1: 255 * 0.2689 = 68.6 and 186.4.
+        assert_eq!(Normalizer::softmax(32).normalize(&[0, 32]), [69, 186]);
+        // e^-0.5 = 0.6065: 255 * 0.6065 / 2.6065 = 59.3, 255 / 2.6065 = 97.8.
+        assert_eq!(
+            Normalizer::softmax(64).normalize(&[10, 42, 42]),
+            [59, 98, 98]
+        );
+        // Equal values, zeros included, share 255 evenly.
+        assert_eq!(Normalizer::softmax(16).normalize(&[0, 0, 0, 0]), [64; 4]);
+        assert_eq!(Normalizer::softmax(0).normalize(&[3, 9, 9]), [0, 128, 128]);
+        assert_eq!(Normalizer::softmax(1).normalize(&[0, 200]), [0, 255]);
+        assert_eq!(Normalizer::softmax(5).normalize(&[]), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_lower_temperature_sharpens() {
+        let input = [100, 120, 140];
+        let soft = Normalizer::softmax(200).normalize(&input);
+        let sharp = Normalizer::softmax(10).normalize(&input);
+        assert!(sharp[2] > soft[2]);
+        assert!(sharp[0] < soft[0]);
+        assert!(soft.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for normalizer in [
+            Normalizer::linear(),
+            Normalizer::sum(),
+            Normalizer::softmax(0),
+            Normalizer::softmax(32),
+        ] {
+            let text = normalizer.to_string();
+            assert_eq!(text.parse::<Normalizer>(), Ok(normalizer));
+        }
+        assert_eq!(Normalizer::softmax(32).to_string(), "softmax:32");
+        assert_eq!(
+            "max".parse::<Normalizer>(),
+            Err(NormalizerParseError::UnknownMode("max".to_string()))
+        );
+        assert_eq!(
+            "softmax:256".parse::<Normalizer>(),
+            Err(NormalizerParseError::InvalidTemperature("256".to_string()))
+        );
+        assert!("linear:3".parse::<Normalizer>().is_err());
+    }
+}
@@ -0,0 +1,195 @@
+/// Partial mapper module for byte-to-byte mappings where not every input has a valid output.
+use alloc::vec::Vec;
+
+use crate::neural::mapper::MapperNode;
+
+/// The length of the mapping array (256 for all possible u8 values).
+const MAX_LENGTH: usize = u8::MAX as usize + 1;
+/// The number of 64-bit words needed to hold one validity bit per entry.
+const MASK_WORDS: usize = MAX_LENGTH / 64;
+
+/// A lookup table mapper that tracks which inputs have a defined output.
+/// Unlike `MapperNode`, an undefined input is distinguishable from one that maps to 0.
+#[derive(Clone)]
+pub struct PartialMapperNode {
+    /// The transformation table: maps each u8 value to another u8 value.
+    tf: [u8; MAX_LENGTH],
+    /// One bit per entry in `tf`, set when the entry is defined.
+    mask: [u64; MASK_WORDS],
+}
+
+impl PartialMapperNode {
+    /// Creates a new `PartialMapperNode` with every entry undefined.
+    pub const fn new() -> PartialMapperNode {
+        PartialMapperNode {
+            tf: [0u8; MAX_LENGTH],
+            mask: [0u64; MASK_WORDS],
+        }
+    }
+
+    /// Returns whether the given input has a defined output.
+    pub const fn is_defined(&self, input: u8) -> bool {
+        self.mask[input as usize / 64] & (1 << (input as usize % 64)) != 0
+    }
+
+    /// Returns the number of defined entries.
+    pub const fn defined_count(&self) -> usize {
+        let mut count = 0;
+        let mut i = 0;
+        while i < MASK_WORDS {
+            count += self.mask[i].count_ones() as usize;
+            i += 1;
+        }
+        count
+    }
+
+    /// Transforms an input byte using the mapping table.
+    ///
+    /// # Arguments
+    /// * `input` - The input byte to transform.
+    ///
+    /// # Returns
+    /// The mapped output byte, or `None` if the input has no defined mapping.
+    pub const fn try_transform(&self, input: u8) -> Option<u8> {
+        if self.is_defined(input) {
+            Some(self.tf[input as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Transforms every byte of the input slice.
+    ///
+    /// # Returns
+    /// The transformed bytes, or `Err(index)` with the position of the first undefined byte.
+    pub fn transform_slice_strict(&self, input: &[u8]) -> Result<Vec<u8>, usize> {
+        input
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| self.try_transform(b).ok_or(i))
+            .collect()
+    }
+
+    /// Returns a new `PartialMapperNode` with the given key-value pairs defined.
+    ///
+    /// # Arguments
+    /// * `mapdata` - An iterator of (u8, u8) pairs to define in the mapping.
+    pub fn with_mapdata<I>(&self, mapdata: I) -> PartialMapperNode
+    where
+        I: IntoIterator<Item = (u8, u8)>,
+    {
+        let mut result = self.clone();
+        for (k, v) in mapdata {
+            result.define(k, v);
+        }
+        result
+    }
+
+    /// Returns a new `PartialMapperNode` with all keys in the given range defined as the specified value.
+    ///
+    /// # Arguments
+    /// * `range` - An iterator of u8 values to define in the mapping.
+    /// * `value` - The value to set for each key in the range.
+    pub fn with_range<R>(&self, range: R, value: u8) -> PartialMapperNode
+    where
+        R: IntoIterator<Item = u8>,
+    {
+        self.with_mapdata(range.into_iter().map(|k| (k, value)))
+    }
+
+    /// Returns a new `PartialMapperNode` with the given keys marked as undefined.
+    ///
+    /// # Arguments
+    /// * `keys` - An iterator of u8 values to remove from the mapping.
+    pub fn without<I>(&self, keys: I) -> PartialMapperNode
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut result = self.clone();
+        for k in keys {
+            result.tf[k as usize] = 0;
+            result.mask[k as usize / 64] &= !(1 << (k as usize % 64));
+        }
+        result
+    }
+
+    /// Converts this partial mapper into a plain `MapperNode`.
+    ///
+    /// # Arguments
+    /// * `default` - The value used for every undefined entry.
+    pub fn to_mapper(&self, default: u8) -> MapperNode {
+        MapperNode::new_transformation(|i| self.try_transform(i as u8).unwrap_or(default))
+    }
+
+    fn define(&mut self, key: u8, value: u8) {
+        self.tf[key as usize] = value;
+        self.mask[key as usize / 64] |= 1 << (key as usize % 64);
+    }
+}
+
+impl Default for PartialMapperNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex_decoder() -> PartialMapperNode {
+        PartialMapperNode::new()
+            .with_mapdata((b'0'..=b'9').map(|c| (c, c - b'0')))
+            .with_mapdata((b'a'..=b'f').map(|c| (c, c - b'a' + 10)))
+            .with_mapdata((b'A'..=b'F').map(|c| (c, c - b'A' + 10)))
+    }
+
+    #[test]
+    fn test_new_is_undefined() {
+        let mapper = PartialMapperNode::new();
+        for i in 0u8..=u8::MAX {
+            assert_eq!(mapper.try_transform(i), None);
+        }
+        assert_eq!(mapper.defined_count(), 0);
+    }
+
+    #[test]
+    fn test_hex_decoding() {
+        let hex = hex_decoder();
+        assert_eq!(hex.try_transform(b'0'), Some(0));
+        assert_eq!(hex.try_transform(b'9'), Some(9));
+        assert_eq!(hex.try_transform(b'a'), Some(10));
+        assert_eq!(hex.try_transform(b'F'), Some(15));
+        assert_eq!(hex.try_transform(b'g'), None);
+        assert_eq!(hex.try_transform(b' '), None);
+        assert_eq!(hex.defined_count(), 22);
+    }
+
+    #[test]
+    fn test_transform_slice_strict() {
+        let hex = hex_decoder();
+        assert_eq!(hex.transform_slice_strict(b"1aF"), Ok(vec![1, 10, 15]));
+        assert_eq!(hex.transform_slice_strict(b"12g4"), Err(2));
+        assert_eq!(hex.transform_slice_strict(b""), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_with_range_and_without() {
+        let mapper = PartialMapperNode::new()
+            .with_range(0..=255, 1)
+            .without([0, 64, 255]);
+        assert_eq!(mapper.defined_count(), 253);
+        assert_eq!(mapper.try_transform(0), None);
+        assert_eq!(mapper.try_transform(64), None);
+        assert_eq!(mapper.try_transform(255), None);
+        assert_eq!(mapper.try_transform(63), Some(1));
+    }
+
+    #[test]
+    fn test_to_mapper() {
+        let mapper = hex_decoder().to_mapper(0xFF);
+        assert_eq!(mapper.transform(b'0'), 0);
+        assert_eq!(mapper.transform(b'c'), 12);
+        assert_eq!(mapper.transform(b'g'), 0xFF);
+    }
+}
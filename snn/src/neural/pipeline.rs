@@ -0,0 +1,436 @@
+/// Multi-threaded streaming of data through a network, enabled by the `parallel` feature.
+///
+/// The stream is cut into records of the network's input width, and whole records are grouped
+/// into chunks. Each worker thread evaluates chunks on its own copy of the network, and the
+/// outputs are written in the order the chunks were read, so the result is byte-identical to
+/// `Network::evaluate_windows_into` with a stride of the input width.
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::neural::network::{EvalError, Network};
+use crate::neural::network_format::NetworkFormatError;
+use crate::neural::network_windows::WindowTail;
+
+/// The default number of bytes per chunk, before rounding down to whole records.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A chunk of input and its position in the stream.
+type Job = (usize, Vec<u8>);
+/// A chunk's output, or `Err` if evaluating it panicked.
+type Done = (usize, Result<Vec<u8>, ()>);
+
+/// Streams data through a network on several threads. See the module documentation.
+///
+/// Networks are not shared between threads: every worker builds its own copy, so combinators
+/// that keep state between calls see only the chunks their worker was given.
+pub struct Pipeline {
+    factory: Box<dyn Fn() -> Network + Send + Sync>,
+    input_width: usize,
+    chunk_size: usize,
+    threads: usize,
+    tail: WindowTail,
+}
+
+impl Pipeline {
+    /// Creates a pipeline that evaluates copies of `network`, handing each worker about
+    /// `chunk_size` bytes at a time. Uses one worker per available CPU and drops a final,
+    /// partial record.
+    ///
+    /// The copies are made through the binary format, so every combinator must be a built-in
+    /// or registered one; use `from_factory` for networks with other combinators.
+    pub fn new(network: &Network, chunk_size: usize) -> Result<Pipeline, PipelineError> {
+        let mut bytes = Vec::new();
+        network
+            .write_binary(&mut bytes)
+            .map_err(|e| PipelineError::Io(e.to_string()))?;
+        // Checks that the network can be rebuilt before any worker needs to.
+        Network::read_binary(bytes.as_slice())?;
+        Pipeline::from_factory(
+            move || Network::read_binary(bytes.as_slice()).expect("checked in Pipeline::new"),
+            chunk_size,
+        )
+    }
+
+    /// Creates a pipeline whose workers each evaluate a network built by `factory`.
+    /// Every call must build the same network.
+    pub fn from_factory(
+        factory: impl Fn() -> Network + Send + Sync + 'static,
+        chunk_size: usize,
+    ) -> Result<Pipeline, PipelineError> {
+        let input_width = factory()
+            .input_width()
+            .ok_or(PipelineError::Eval(EvalError::NoLayers))?;
+        Ok(Pipeline {
+            factory: Box::new(factory),
+            input_width,
+            chunk_size: (chunk_size / input_width).max(1) * input_width,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            tail: WindowTail::Drop,
+        })
+    }
+
+    /// Sets the number of worker threads. Zero is treated as one.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Sets what happens to a final record shorter than the input width.
+    pub fn with_tail(mut self, tail: WindowTail) -> Self {
+        self.tail = tail;
+        self
+    }
+
+    /// Returns the number of bytes in each chunk: a whole number of records.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Reads `r` to the end, evaluates every record and writes the outputs to `w` in order.
+    pub fn process_reader(
+        &self,
+        mut r: impl Read,
+        mut w: impl Write,
+    ) -> Result<PipelineStats, PipelineError> {
+        let started = Instant::now();
+        let (jobs, queue) = mpsc::sync_channel(self.threads);
+        let queue = Mutex::new(Some(queue));
+        let (done, results) = mpsc::channel();
+        let mut stats = PipelineStats::default();
+        let outcome = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|_| {
+                    let done = done.clone();
+                    let queue = &queue;
+                    scope.spawn(move || self.work(queue, done))
+                })
+                .collect();
+            drop(done);
+            let outcome = self.feed(&mut r, &mut w, jobs, &results, &mut stats);
+            stats.threads = workers
+                .into_iter()
+                .map(|worker| worker.join().expect("worker panics are caught"))
+                .collect();
+            outcome
+        });
+        outcome?;
+        stats.elapsed = started.elapsed();
+        Ok(stats)
+    }
+
+    /// Reads chunks and hands them to the workers, writing results as they come back in order.
+    /// Returning drops `jobs`, which stops the workers.
+    fn feed(
+        &self,
+        r: &mut impl Read,
+        w: &mut impl Write,
+        jobs: SyncSender<Job>,
+        results: &Receiver<Done>,
+        stats: &mut PipelineStats,
+    ) -> Result<(), PipelineError> {
+        let io = |e: std::io::Error| PipelineError::Io(e.to_string());
+        let mut pending = BTreeMap::new();
+        let (mut sent, mut written) = (0, 0);
+        loop {
+            let chunk = read_chunk(r, self.chunk_size).map_err(io)?;
+            let last = chunk.len() < self.chunk_size;
+            if !chunk.is_empty() {
+                stats.bytes_in += chunk.len() as u64;
+                jobs.send((sent, chunk))
+                    .map_err(|_| PipelineError::WorkerPanicked)?;
+                sent += 1;
+            }
+            // Keep at most two chunks per worker in flight; at the end, wait for all of them.
+            while written < sent && (last || sent - written > 2 * self.threads) {
+                let (index, output) = results.recv().map_err(|_| PipelineError::WorkerPanicked)?;
+                pending.insert(index, output.map_err(|()| PipelineError::WorkerPanicked)?);
+                while let Some(output) = pending.remove(&written) {
+                    w.write_all(&output).map_err(io)?;
+                    stats.bytes_out += output.len() as u64;
+                    written += 1;
+                }
+            }
+            if last {
+                break;
+            }
+        }
+        stats.chunks = sent;
+        w.flush().map_err(io)
+    }
+
+    /// Evaluates chunks from `queue` until it is closed. A panic is reported for the chunk at
+    /// fault and closes the queue, which stops every worker and fails the next `feed` send.
+    fn work(&self, queue: &Mutex<Option<Receiver<Job>>>, done: Sender<Done>) -> ThreadStats {
+        let mut stats = ThreadStats::default();
+        let close = || queue.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let Ok(network) = panic::catch_unwind(AssertUnwindSafe(&self.factory)) else {
+            close();
+            return stats;
+        };
+        let compiled = network.compile();
+        let (mut scratch, mut output, mut padded) = (Vec::new(), Vec::new(), Vec::new());
+        loop {
+            let job = match &*queue.lock().unwrap_or_else(|e| e.into_inner()) {
+                Some(queue) => queue.recv(),
+                None => return stats,
+            };
+            let Ok((index, chunk)) = job else {
+                return stats;
+            };
+            let started = Instant::now();
+            let evaluated = panic::catch_unwind(AssertUnwindSafe(|| {
+                let mut out = Vec::new();
+                for record in chunk.chunks(self.input_width) {
+                    let record = match (record.len() == self.input_width, self.tail) {
+                        (true, _) => record,
+                        (false, WindowTail::Drop) => break,
+                        (false, WindowTail::ZeroPad) => {
+                            padded.clear();
+                            padded.extend_from_slice(record);
+                            padded.resize(self.input_width, 0);
+                            &padded
+                        }
+                    };
+                    compiled
+                        .forward_into(record, &mut scratch, &mut output)
+                        .expect("records match the input width");
+                    out.extend_from_slice(&output);
+                }
+                out
+            }));
+            stats.busy += started.elapsed();
+            stats.chunks += 1;
+            stats.bytes += chunk.len() as u64;
+            let failed = evaluated.is_err();
+            if done.send((index, evaluated.map_err(|_| ()))).is_err() || failed {
+                close();
+                return stats;
+            }
+        }
+    }
+}
+
+/// Reads until the chunk holds `size` bytes or the reader is exhausted.
+fn read_chunk(r: &mut impl Read, size: usize) -> std::io::Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(size);
+    r.take(size as u64).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+/// What a `Pipeline::process_reader` call did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PipelineStats {
+    /// The number of bytes read.
+    pub bytes_in: u64,
+    /// The number of bytes written.
+    pub bytes_out: u64,
+    /// The number of chunks the input was cut into.
+    pub chunks: usize,
+    /// The wall-clock time of the whole call.
+    pub elapsed: Duration,
+    /// One entry per worker thread.
+    pub threads: Vec<ThreadStats>,
+}
+
+impl PipelineStats {
+    /// Returns the input bytes processed per second of wall-clock time, or 0 if no time passed.
+    pub fn throughput(&self) -> f64 {
+        per_second(self.bytes_in, self.elapsed)
+    }
+}
+
+/// The work one pipeline thread did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThreadStats {
+    /// The number of chunks the thread evaluated.
+    pub chunks: usize,
+    /// The number of input bytes in those chunks.
+    pub bytes: u64,
+    /// The time spent evaluating, not counting waiting for work.
+    pub busy: Duration,
+}
+
+impl ThreadStats {
+    /// Returns the input bytes evaluated per second of busy time, or 0 if the thread did no work.
+    pub fn throughput(&self) -> f64 {
+        per_second(self.bytes, self.busy)
+    }
+}
+
+fn per_second(bytes: u64, time: Duration) -> f64 {
+    if time.is_zero() {
+        0.0
+    } else {
+        bytes as f64 / time.as_secs_f64()
+    }
+}
+
+/// Error returned when a `Pipeline` cannot be created or cannot process a stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineError {
+    /// Reading the input or writing the output failed; holds the I/O error message.
+    Io(String),
+    /// The network could not be copied for the workers.
+    Format(NetworkFormatError),
+    /// The network cannot evaluate records, for example because it has no layers.
+    Eval(EvalError),
+    /// A worker thread panicked while building its network or evaluating a chunk.
+    WorkerPanicked,
+}
+
+impl std::fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PipelineError::Io(message) => write!(f, "pipeline I/O failed: {message}"),
+            PipelineError::Format(e) => write!(f, "cannot copy the network for workers: {e}"),
+            PipelineError::Eval(e) => write!(f, "{e}"),
+            PipelineError::WorkerPanicked => write!(f, "a pipeline worker panicked"),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PipelineError::Format(e) => Some(e),
+            PipelineError::Eval(e) => Some(e),
+            PipelineError::Io(_) | PipelineError::WorkerPanicked => None,
+        }
+    }
+}
+
+impl From<NetworkFormatError> for PipelineError {
+    fn from(e: NetworkFormatError) -> Self {
+        PipelineError::Format(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, XORCombinatorNode};
+    use crate::neural::mapper::MapperNode;
+    use crate::neural::rng::Rng;
+    use crate::neural::traits::FnCombinator;
+
+    fn network() -> Network {
+        Network::builder()
+            .input_width(3)
+            .layer_dense(2, |n| {
+                n.shared_mapper(MapperNode::new_transformation(|x| (x as u8).rotate_left(3)))
+                    .combinator(Box::new(XORCombinatorNode::new()))
+            })
+            .layer_dense(1, |n| n.combinator(Box::new(AdditionCombinatorNode::new())))
+            .build()
+            .unwrap()
+    }
+
+    fn serial(network: &Network, data: &[u8], tail: WindowTail) -> Vec<u8> {
+        let mut out = Vec::new();
+        let width = network.input_width().unwrap();
+        network.evaluate_windows_into(data, width, tail, &mut out);
+        out
+    }
+
+    #[test]
+    fn test_matches_serial_on_large_input() {
+        let network = network();
+        let data = Rng::new(0x5EED).bytes(3 * 1024 * 1024 + 2);
+        for tail in [WindowTail::Drop, WindowTail::ZeroPad] {
+            let pipeline = Pipeline::new(&network, 10_000)
+                .unwrap()
+                .with_threads(4)
+                .with_tail(tail);
+            let mut out = Vec::new();
+            let stats = pipeline.process_reader(data.as_slice(), &mut out).unwrap();
+            assert_eq!(out, serial(&network, &data, tail), "{tail:?}");
+            assert_eq!(stats.bytes_in, data.len() as u64);
+            assert_eq!(stats.bytes_out, out.len() as u64);
+            assert_eq!(stats.chunks, data.len().div_ceil(pipeline.chunk_size()));
+            assert_eq!(stats.threads.len(), 4);
+            let per_thread: u64 = stats.threads.iter().map(|t| t.bytes).sum();
+            assert_eq!(per_thread, data.len() as u64);
+        }
+    }
+
+    #[test]
+    fn test_input_smaller_than_one_chunk() {
+        let network = network();
+        for tail in [WindowTail::Drop, WindowTail::ZeroPad] {
+            let pipeline = Pipeline::new(&network, DEFAULT_CHUNK_SIZE)
+                .unwrap()
+                .with_tail(tail);
+            assert_eq!(pipeline.chunk_size() % 3, 0);
+            for len in [0, 1, 3, 8, 100] {
+                let data = Rng::new(len as u64 + 1).bytes(len);
+                let mut out = Vec::new();
+                let stats = pipeline.process_reader(data.as_slice(), &mut out).unwrap();
+                assert_eq!(out, serial(&network, &data, tail), "length {len}, {tail:?}");
+                assert_eq!(stats.chunks, usize::from(len > 0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_combinators_need_a_factory() {
+        let build = || {
+            Network::builder()
+                .input_width(2)
+                .layer_dense(1, |n| {
+                    n.combinator(Box::new(FnCombinator(|inputs: &[u8]| {
+                        inputs[0].wrapping_sub(inputs[1])
+                    })))
+                })
+                .build()
+                .unwrap()
+        };
+        assert!(matches!(
+            Pipeline::new(&build(), 16),
+            Err(PipelineError::Format(_))
+        ));
+
+        let pipeline = Pipeline::from_factory(build, 16).unwrap().with_threads(3);
+        let data = Rng::new(9).bytes(1001);
+        let mut out = Vec::new();
+        pipeline.process_reader(data.as_slice(), &mut out).unwrap();
+        assert_eq!(out, serial(&build(), &data, WindowTail::Drop));
+    }
+
+    #[test]
+    fn test_worker_panic_is_reported() {
+        let build = || {
+            Network::builder()
+                .input_width(1)
+                .layer_dense(1, |n| {
+                    n.combinator(Box::new(FnCombinator(|inputs: &[u8]| {
+                        assert!(inputs[0] != 7, "seven");
+                        inputs[0]
+                    })))
+                })
+                .build()
+                .unwrap()
+        };
+        let pipeline = Pipeline::from_factory(build, 4).unwrap().with_threads(2);
+        let data: Vec<u8> = (0..100).collect();
+        let result = pipeline.process_reader(data.as_slice(), Vec::new());
+        assert_eq!(result, Err(PipelineError::WorkerPanicked));
+    }
+
+    #[test]
+    fn test_empty_network_is_rejected() {
+        assert_eq!(
+            Pipeline::new(&Network::default(), 16).err(),
+            Some(PipelineError::Eval(EvalError::NoLayers))
+        );
+    }
+}
@@ -0,0 +1,61 @@
+/// Replaces seed 0, from which xorshift would only ever produce zeros, with a fixed odd
+/// constant.
+pub(crate) const fn nonzero_seed(seed: u64) -> u64 {
+    if seed == 0 {
+        0x9E37_79B9_7F4A_7C15
+    } else {
+        seed
+    }
+}
+
+/// Advances a xorshift64 state by one step. Training, sampled equivalence checks and noisy
+/// nodes all draw from this, so their sequences are reproducible without extra dependencies.
+pub(crate) const fn xorshift64(mut x: u64) -> u64 {
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    x
+}
+
+/// A `xorshift64` generator that owns its state.
+#[cfg(any(feature = "std", test))]
+pub(crate) struct Rng(pub(crate) u64);
+
+#[cfg(any(feature = "std", test))]
+impl Rng {
+    /// Creates a generator. Seed 0 is replaced as described at `nonzero_seed`.
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(nonzero_seed(seed))
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = xorshift64(self.0);
+        self.0
+    }
+
+    /// Returns true with the given probability.
+    #[cfg(feature = "std")]
+    pub(crate) fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    /// Returns a value in `0..n`. `n` must not be zero.
+    #[cfg(feature = "std")]
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Fills `bytes` with bits 24 to 31 of successive outputs.
+    #[cfg(feature = "std")]
+    pub(crate) fn fill(&mut self, bytes: &mut [u8]) {
+        for byte in bytes {
+            *byte = (self.next_u64() >> 24) as u8;
+        }
+    }
+
+    /// Returns `len` pseudo-random bytes, for test data.
+    #[cfg(test)]
+    pub(crate) fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| (self.next_u64() >> 24) as u8).collect()
+    }
+}
@@ -0,0 +1,518 @@
+/// Networks that keep state between time steps through delay elements.
+use std::collections::VecDeque;
+
+use crate::neural::error::SnnError;
+use crate::neural::network::EvalError;
+use crate::neural::node::{NodeError, SignalNode};
+use crate::neural::topology::topological_order;
+use crate::neural::traits::StatefulCombinator;
+
+/// A delay element: outputs the value it was given `delay` ticks earlier.
+/// Before that many values have been pushed it outputs its initial value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelayNode {
+    buffer: VecDeque<u8>,
+    delay: usize,
+    initial: u8,
+}
+
+impl DelayNode {
+    /// Creates a delay of `delay` ticks that starts out holding zeros.
+    ///
+    /// # Panics
+    /// Panics if `delay` is zero; a delay of zero ticks would be a plain wire.
+    pub fn new(delay: usize) -> Self {
+        assert!(delay > 0, "a delay must be at least one tick");
+        Self {
+            buffer: VecDeque::from(vec![0; delay]),
+            delay,
+            initial: 0,
+        }
+    }
+
+    /// Returns the delay holding `initial` until it has been given `delay` values.
+    pub fn with_initial(self, initial: u8) -> Self {
+        let mut delay = Self { initial, ..self };
+        delay.reset();
+        delay
+    }
+
+    /// Returns the number of ticks a value is held.
+    pub fn delay(&self) -> usize {
+        self.delay
+    }
+
+    /// Returns the value that leaves the delay this tick.
+    pub fn output(&self) -> u8 {
+        self.buffer[0]
+    }
+
+    /// Drops the oldest value and stores `value`.
+    pub fn push(&mut self, value: u8) {
+        self.buffer.pop_front();
+        self.buffer.push_back(value);
+    }
+
+    /// Refills the delay with its initial value.
+    pub fn reset(&mut self) {
+        self.buffer.iter_mut().for_each(|x| *x = self.initial);
+    }
+
+    /// Returns the held values, the one leaving next first.
+    pub fn values(&self) -> impl ExactSizeIterator<Item = u8> + '_ {
+        self.buffer.iter().copied()
+    }
+}
+
+/// Where an element of a `StatefulNetwork` reads a value from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The external input at this index.
+    External(usize),
+    /// The output of the element with this id, as returned by `add_node` or `add_delay`.
+    Element(usize),
+}
+
+/// An element of a `StatefulNetwork`.
+enum Element {
+    Node(Box<SignalNode>, Vec<Source>),
+    Delay(DelayNode, Source),
+    Stateful(Box<dyn StatefulCombinator>, Vec<Source>),
+}
+
+/// The runtime state of one element of a `StatefulNetwork`, as saved in a checkpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ElementState {
+    /// Nodes keep no state between ticks.
+    Node,
+    /// The values a delay holds, the one leaving next first.
+    Delay(Vec<u8>),
+    /// What a stateful combinator's `save_state` returned.
+    Stateful(Vec<u8>),
+}
+
+/// A network of signal nodes and delays that is advanced one tick at a time.
+/// Nodes may feed back into earlier nodes through a delay; every cycle must pass through one.
+pub struct StatefulNetwork {
+    elements: Vec<Element>,
+    outputs: Vec<Source>,
+    external_width: usize,
+    /// Node ids in evaluation order.
+    order: Vec<usize>,
+    /// The value of every element during the current tick.
+    values: Vec<u8>,
+}
+
+impl StatefulNetwork {
+    /// Returns a builder for a network with `external_width` external inputs.
+    pub fn builder(external_width: usize) -> StatefulNetworkBuilder {
+        StatefulNetworkBuilder {
+            external_width,
+            elements: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Advances one tick: delays output what they held, nodes are evaluated on this tick's
+    /// values, the outputs are read, and finally every delay stores its input.
+    ///
+    /// # Panics
+    /// Panics if `external_inputs` does not have `external_width` values.
+    pub fn tick(&mut self, external_inputs: &[u8]) -> Vec<u8> {
+        self.try_tick(external_inputs)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Like `tick`, returning an error instead of panicking on the wrong number of
+    /// external inputs. The state is left untouched on error.
+    pub fn try_tick(&mut self, external_inputs: &[u8]) -> Result<Vec<u8>, SnnError> {
+        if external_inputs.len() != self.external_width {
+            return Err(EvalError::InputWidth {
+                expected: self.external_width,
+                actual: external_inputs.len(),
+            }
+            .into());
+        }
+        for (id, element) in self.elements.iter().enumerate() {
+            if let Element::Delay(delay, _) = element {
+                self.values[id] = delay.output();
+            }
+        }
+        let mut gathered = Vec::new();
+        for &id in &self.order {
+            let (Element::Node(_, sources) | Element::Stateful(_, sources)) = &self.elements[id]
+            else {
+                continue;
+            };
+            gathered.clear();
+            gathered.extend(
+                sources
+                    .iter()
+                    .map(|&s| read(&self.values, external_inputs, s)),
+            );
+            self.values[id] = match &mut self.elements[id] {
+                Element::Node(node, _) => node.evaluate(&gathered),
+                Element::Stateful(combinator, _) => combinator.step(&gathered),
+                Element::Delay(..) => unreachable!("delays are not in the evaluation order"),
+            };
+        }
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|&s| read(&self.values, external_inputs, s))
+            .collect();
+        for element in &mut self.elements {
+            if let Element::Delay(delay, source) = element {
+                delay.push(read(&self.values, external_inputs, *source));
+            }
+        }
+        Ok(outputs)
+    }
+
+    /// Clears the state of every delay and stateful combinator.
+    pub fn reset(&mut self) {
+        for element in &mut self.elements {
+            match element {
+                Element::Delay(delay, _) => delay.reset(),
+                Element::Stateful(combinator, _) => combinator.reset(),
+                Element::Node(..) => {}
+            }
+        }
+        self.values.iter_mut().for_each(|x| *x = 0);
+    }
+
+    /// Returns the state of every element, in id order.
+    pub(crate) fn element_states(&self) -> Vec<ElementState> {
+        self.elements
+            .iter()
+            .map(|element| match element {
+                Element::Node(..) => ElementState::Node,
+                Element::Delay(delay, _) => ElementState::Delay(delay.values().collect()),
+                Element::Stateful(combinator, _) => ElementState::Stateful(combinator.save_state()),
+            })
+            .collect()
+    }
+
+    /// Restores states returned by `element_states` of a network built the same way.
+    /// On a mismatch the network is left unchanged and the first mismatch is described.
+    pub(crate) fn restore_element_states(&mut self, states: &[ElementState]) -> Result<(), String> {
+        if states.len() != self.elements.len() {
+            return Err(format!(
+                "state has {} elements, the network has {}",
+                states.len(),
+                self.elements.len()
+            ));
+        }
+        for (id, (element, state)) in self.elements.iter().zip(states).enumerate() {
+            match (element, state) {
+                (Element::Node(..), ElementState::Node) => {}
+                (Element::Delay(delay, _), ElementState::Delay(values))
+                    if values.len() == delay.delay() => {}
+                (Element::Stateful(..), ElementState::Stateful(_)) => {}
+                _ => return Err(format!("element {id} does not match the saved state")),
+            }
+        }
+        // Stateful combinators may still reject their state, so keep the current state to
+        // roll back to.
+        let previous = self.element_states();
+        for (id, state) in states.iter().enumerate() {
+            let loaded = match (&mut self.elements[id], state) {
+                (Element::Delay(delay, _), ElementState::Delay(values)) => {
+                    delay.buffer = values.iter().copied().collect();
+                    true
+                }
+                (Element::Stateful(combinator, _), ElementState::Stateful(bytes)) => {
+                    combinator.load_state(bytes)
+                }
+                _ => true,
+            };
+            if !loaded {
+                self.restore_element_states(&previous)
+                    .expect("the previous state fits the network");
+                return Err(format!("element {id} rejected the saved state"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of external inputs.
+    pub fn external_width(&self) -> usize {
+        self.external_width
+    }
+
+    /// Returns the number of outputs.
+    pub fn output_width(&self) -> usize {
+        self.outputs.len()
+    }
+}
+
+fn read(values: &[u8], external: &[u8], source: Source) -> u8 {
+    match source {
+        Source::External(index) => external[index],
+        Source::Element(id) => values[id],
+    }
+}
+
+/// Builds a `StatefulNetwork`. See `StatefulNetwork::builder`.
+pub struct StatefulNetworkBuilder {
+    external_width: usize,
+    elements: Vec<Element>,
+    outputs: Vec<Source>,
+}
+
+impl StatefulNetworkBuilder {
+    /// Adds a node reading `inputs` and returns its id.
+    /// Ids are handed out in order, so an element may read one added after it.
+    pub fn add_node(&mut self, node: SignalNode, inputs: Vec<Source>) -> usize {
+        self.elements.push(Element::Node(Box::new(node), inputs));
+        self.elements.len() - 1
+    }
+
+    /// Adds a stateful combinator reading `inputs` and returns its id. It is stepped once per
+    /// tick, like a node is evaluated, and reset with the network.
+    pub fn add_stateful(
+        &mut self,
+        combinator: impl StatefulCombinator + 'static,
+        inputs: Vec<Source>,
+    ) -> usize {
+        self.elements
+            .push(Element::Stateful(Box::new(combinator), inputs));
+        self.elements.len() - 1
+    }
+
+    /// Adds a delay reading `input` and returns its id.
+    pub fn add_delay(&mut self, delay: DelayNode, input: Source) -> usize {
+        self.elements.push(Element::Delay(delay, input));
+        self.elements.len() - 1
+    }
+
+    /// Adds an output of the network.
+    pub fn add_output(&mut self, source: Source) {
+        self.outputs.push(source);
+    }
+
+    /// Builds the network, checking every source, every node's input count, and that
+    /// every feedback loop passes through a delay.
+    pub fn build(self) -> Result<StatefulNetwork, StatefulBuildError> {
+        let check = |source: Source| match source {
+            Source::External(index) if index >= self.external_width => {
+                Err(StatefulBuildError::UnknownSource(source))
+            }
+            Source::Element(id) if id >= self.elements.len() => {
+                Err(StatefulBuildError::UnknownSource(source))
+            }
+            _ => Ok(()),
+        };
+        for (id, element) in self.elements.iter().enumerate() {
+            match element {
+                Element::Node(node, sources) => {
+                    sources.iter().try_for_each(|&s| check(s))?;
+                    node.check_input_count(sources.len())
+                        .map_err(|error| StatefulBuildError::Node { id, error })?;
+                }
+                Element::Delay(_, source) => check(*source)?,
+                Element::Stateful(_, sources) => sources.iter().try_for_each(|&s| check(s))?,
+            }
+        }
+        self.outputs.iter().try_for_each(|&s| check(s))?;
+
+        // Delays output last tick's value, so only reads between nodes and stateful
+        // combinators order the evaluation.
+        let elements = &self.elements;
+        let order = topological_order(elements.len(), |id| match &elements[id] {
+            Element::Node(_, sources) | Element::Stateful(_, sources) => sources
+                .iter()
+                .filter_map(|&s| match s {
+                    Source::Element(dep) if !matches!(elements[dep], Element::Delay(..)) => {
+                        Some(dep)
+                    }
+                    _ => None,
+                })
+                .collect(),
+            Element::Delay(..) => Vec::new(),
+        })
+        .map_err(StatefulBuildError::CombinationalCycle)?;
+        Ok(StatefulNetwork {
+            values: vec![0; self.elements.len()],
+            elements: self.elements,
+            outputs: self.outputs,
+            external_width: self.external_width,
+            order,
+        })
+    }
+}
+
+/// Error returned by `StatefulNetworkBuilder::build`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatefulBuildError {
+    /// A source names an external input or element that does not exist.
+    UnknownSource(Source),
+    /// A node does not accept the number of inputs wired to it.
+    Node { id: usize, error: NodeError },
+    /// These nodes read each other in a loop without a delay in between;
+    /// each reads the next and the last reads the first.
+    CombinationalCycle(Vec<usize>),
+}
+
+impl std::fmt::Display for StatefulBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatefulBuildError::UnknownSource(source) => {
+                write!(f, "source {source:?} does not exist")
+            }
+            StatefulBuildError::Node { id, error } => write!(f, "element {id}: {error}"),
+            StatefulBuildError::CombinationalCycle(ids) => {
+                write!(f, "nodes {ids:?} form a cycle without a delay")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StatefulBuildError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{AdditionCombinatorNode, MaxCombinatorNode};
+    use crate::neural::combinator_stateful::EmaCombinatorNode;
+    use crate::neural::traits::Combinator;
+
+    fn node(combinator: impl Combinator + 'static) -> SignalNode {
+        SignalNode::builder()
+            .combinator(Box::new(combinator))
+            .build()
+            .unwrap()
+    }
+
+    /// `acc = input + acc(t - 1)`, saturating.
+    fn accumulator() -> StatefulNetwork {
+        let mut builder = StatefulNetwork::builder(1);
+        // The delay reads the sum, which is added after it.
+        let delay = builder.add_delay(DelayNode::new(1), Source::Element(1));
+        let sum = builder.add_node(
+            node(AdditionCombinatorNode::new()),
+            vec![Source::External(0), Source::Element(delay)],
+        );
+        builder.add_output(Source::Element(sum));
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_stateful_combinator() {
+        let mut builder = StatefulNetwork::builder(1);
+        // The node reads the combinator added after it, so it is evaluated after it too.
+        let total = builder.add_node(
+            node(AdditionCombinatorNode::new()),
+            vec![Source::External(0), Source::Element(1)],
+        );
+        let ema = builder.add_stateful(EmaCombinatorNode::new(1, 2), vec![Source::External(0)]);
+        builder.add_output(Source::Element(ema));
+        builder.add_output(Source::Element(total));
+        let mut network = builder.build().unwrap();
+        assert_eq!(network.tick(&[100]), vec![100, 200]);
+        assert_eq!(network.tick(&[0]), vec![50, 50]);
+
+        let states = network.element_states();
+        assert_eq!(states[0], ElementState::Node);
+        assert_eq!(network.tick(&[0]), vec![25, 25]);
+        network.restore_element_states(&states).unwrap();
+        assert_eq!(network.tick(&[0]), vec![25, 25]);
+        assert!(
+            network
+                .restore_element_states(&[ElementState::Node, ElementState::Stateful(vec![1])])
+                .is_err()
+        );
+        assert_eq!(network.tick(&[0]), vec![13, 13]);
+
+        network.reset();
+        assert_eq!(network.tick(&[8]), vec![8, 16]);
+    }
+
+    #[test]
+    fn test_feedback_accumulator() {
+        let mut network = accumulator();
+        let outputs: Vec<u8> = (1..=10).map(|x| network.tick(&[x])[0]).collect();
+        // 1, 1+2, 1+2+3, ...
+        assert_eq!(outputs, vec![1, 3, 6, 10, 15, 21, 28, 36, 45, 55]);
+        for _ in 0..5 {
+            network.tick(&[100]);
+        }
+        assert_eq!(network.tick(&[0]), vec![255]);
+
+        network.reset();
+        assert_eq!(network.tick(&[7]), vec![7]);
+        assert_eq!(network.tick(&[7]), vec![14]);
+    }
+
+    #[test]
+    fn test_try_tick_input_width() {
+        let mut network = accumulator();
+        network.tick(&[5]);
+        let error = network.try_tick(&[1, 2]).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "evaluation error: network expects 1 inputs, got 2"
+        );
+        assert_eq!(network.tick(&[1]), vec![6]);
+    }
+
+    #[test]
+    fn test_delay_node() {
+        let mut delay = DelayNode::new(3).with_initial(9);
+        let mut outputs = Vec::new();
+        for x in 1..=5 {
+            outputs.push(delay.output());
+            delay.push(x);
+        }
+        assert_eq!(outputs, vec![9, 9, 9, 1, 2]);
+        delay.reset();
+        assert_eq!(delay.output(), 9);
+        assert_eq!(delay.delay(), 3);
+    }
+
+    #[test]
+    fn test_two_tap_delay_line() {
+        let mut builder = StatefulNetwork::builder(1);
+        let first = builder.add_delay(DelayNode::new(1), Source::External(0));
+        let second = builder.add_delay(DelayNode::new(1), Source::Element(first));
+        builder.add_output(Source::External(0));
+        builder.add_output(Source::Element(first));
+        builder.add_output(Source::Element(second));
+        let mut network = builder.build().unwrap();
+        assert_eq!(network.output_width(), 3);
+        assert_eq!(network.tick(&[5]), vec![5, 0, 0]);
+        assert_eq!(network.tick(&[6]), vec![6, 5, 0]);
+        assert_eq!(network.tick(&[7]), vec![7, 6, 5]);
+    }
+
+    #[test]
+    fn test_combinational_cycle_rejected() {
+        let mut builder = StatefulNetwork::builder(1);
+        let a = builder.add_node(
+            node(MaxCombinatorNode::new()),
+            vec![Source::External(0), Source::Element(2)],
+        );
+        let b = builder.add_node(node(MaxCombinatorNode::new()), vec![Source::Element(a)]);
+        builder.add_node(node(MaxCombinatorNode::new()), vec![Source::Element(b)]);
+        assert_eq!(
+            builder.build().err(),
+            Some(StatefulBuildError::CombinationalCycle(vec![0, 2, 1]))
+        );
+    }
+
+    #[test]
+    fn test_unknown_source() {
+        let mut builder = StatefulNetwork::builder(1);
+        builder.add_node(node(MaxCombinatorNode::new()), vec![Source::External(1)]);
+        assert_eq!(
+            builder.build().err(),
+            Some(StatefulBuildError::UnknownSource(Source::External(1)))
+        );
+        let mut builder = StatefulNetwork::builder(1);
+        builder.add_delay(DelayNode::new(1), Source::Element(5));
+        assert_eq!(
+            builder.build().err(),
+            Some(StatefulBuildError::UnknownSource(Source::Element(5)))
+        );
+    }
+}
@@ -0,0 +1,398 @@
+/// Randomized law checks for combinators, enabled by the `test-util` feature.
+///
+/// Each check draws inputs from a seeded generator, so a failing seed reproduces exactly, and
+/// respects the combinator's `min_inputs` and `max_inputs`, trying at most 8 inputs at a time.
+/// A check that no valid input count can exercise reports 0 cases checked.
+///
+/// The built-ins, checked this way with their registry examples:
+/// - Every built-in is commutative except `weighted`, `argmax`, `argmin`, `bit_pack`,
+///   `abs_diff`, `greater_than` and `mux`. That includes `median` and the wrapping and saturating
+///   arithmetic, since overflow does not depend on input order.
+/// - Associative pairwise: the addition and multiplication variants, `max`, `min`, `majority`,
+///   `bit_majority`, `or`, `and`, `xor`, `xnor`, `mode`, `any_bit`, `percentile`, `nth` and
+///   `constant`. `nand`, `nor` and the means are not.
+/// - Idempotent on duplicates: `max`, `min`, `or`, `and`, `nand`, `nor`, `range`, `argmax`,
+///   `argmin`, `any_bit`, `percentile`, `equal` and `constant`. Sums, counts and averages are
+///   not.
+use std::fmt;
+
+use crate::neural::rng::Rng;
+use crate::neural::traits::Combinator;
+
+/// The most inputs a check passes to a combinator at once.
+const MAX_GENERATED_INPUTS: usize = 8;
+
+/// A property a combinator may satisfy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Law {
+    /// Reordering the inputs does not change the output.
+    Commutative,
+    /// `f([f([a, b]), c]) == f([a, f([b, c])])` for any three values.
+    AssociativePairwise,
+    /// Repeating some of the inputs does not change the output.
+    IdempotentOnDuplicates,
+    /// An empty input gives a fixed value.
+    EmptyInput,
+}
+
+impl fmt::Display for Law {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Law::Commutative => "commutative",
+            Law::AssociativePairwise => "associative",
+            Law::IdempotentOnDuplicates => "idempotent on duplicates",
+            Law::EmptyInput => "empty input",
+        })
+    }
+}
+
+/// Two calls that a law says must agree, but did not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample {
+    /// The inputs of the first call.
+    pub inputs: Vec<u8>,
+    /// The output of the first call.
+    pub output: u8,
+    /// The inputs of the second call. For `Law::EmptyInput` this is empty as well.
+    pub other_inputs: Vec<u8>,
+    /// The output of the second call. For `Law::EmptyInput` this is the required value.
+    pub other_output: u8,
+}
+
+/// The outcome of checking one law against one combinator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LawReport {
+    /// The law that was checked.
+    pub law: Law,
+    /// The identifier of the combinator.
+    pub combinator: String,
+    /// The number of cases tried before stopping.
+    pub checked: usize,
+    /// The first case that broke the law, if any.
+    pub counterexample: Option<Counterexample>,
+}
+
+impl LawReport {
+    /// Returns true if no counterexample was found.
+    pub fn holds(&self) -> bool {
+        self.counterexample.is_none()
+    }
+}
+
+impl fmt::Display for LawReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.counterexample {
+            None => write!(
+                f,
+                "{} is {} ({} cases)",
+                self.combinator, self.law, self.checked
+            ),
+            Some(c) => write!(
+                f,
+                "{} is not {}: {:?} gives {} but {:?} gives {}",
+                self.combinator, self.law, c.inputs, c.output, c.other_inputs, c.other_output
+            ),
+        }
+    }
+}
+
+/// Checks that shuffling random inputs never changes the output.
+pub fn check_commutative(combinator: &dyn Combinator, seed: u64, iterations: usize) -> LawReport {
+    let mut rng = Rng::new(seed);
+    check(
+        combinator,
+        Law::Commutative,
+        iterations,
+        |rng| {
+            let (lo, hi) = input_counts(combinator)?;
+            let inputs = random_inputs(rng, lo.max(2), hi)?;
+            let mut shuffled = inputs.clone();
+            for i in (1..shuffled.len()).rev() {
+                shuffled.swap(i, rng.below(i + 1));
+            }
+            Some((inputs, shuffled))
+        },
+        &mut rng,
+    )
+}
+
+/// Checks that grouping three random values in pairs either way gives the same output.
+/// Needs a combinator that accepts two inputs.
+pub fn check_associative_pairwise(
+    combinator: &dyn Combinator,
+    seed: u64,
+    iterations: usize,
+) -> LawReport {
+    let mut rng = Rng::new(seed);
+    check(
+        combinator,
+        Law::AssociativePairwise,
+        iterations,
+        |rng| {
+            let (lo, hi) = input_counts(combinator)?;
+            if lo > 2 || hi < 2 {
+                return None;
+            }
+            let [a, b, c] = [0; 3].map(|_| rng.next_u64() as u8);
+            let left = vec![combinator.combine(&[a, b]), c];
+            let right = vec![a, combinator.combine(&[b, c])];
+            Some((left, right))
+        },
+        &mut rng,
+    )
+}
+
+/// Checks that appending copies of some of the random inputs never changes the output.
+pub fn check_idempotent_on_duplicates(
+    combinator: &dyn Combinator,
+    seed: u64,
+    iterations: usize,
+) -> LawReport {
+    let mut rng = Rng::new(seed);
+    check(
+        combinator,
+        Law::IdempotentOnDuplicates,
+        iterations,
+        |rng| {
+            let (lo, hi) = input_counts(combinator)?;
+            let inputs = random_inputs(rng, lo, hi - 1)?;
+            let mut duplicated = inputs.clone();
+            for _ in 0..1 + rng.below(hi - inputs.len()) {
+                duplicated.push(inputs[rng.below(inputs.len())]);
+            }
+            Some((inputs, duplicated))
+        },
+        &mut rng,
+    )
+}
+
+/// Checks that combining no inputs gives `value`.
+pub fn check_empty_input_is(combinator: &dyn Combinator, value: u8) -> LawReport {
+    let output = combinator.combine(&[]);
+    LawReport {
+        law: Law::EmptyInput,
+        combinator: combinator.identifier(),
+        checked: 1,
+        counterexample: (output != value).then(|| Counterexample {
+            inputs: Vec::new(),
+            output,
+            other_inputs: Vec::new(),
+            other_output: value,
+        }),
+    }
+}
+
+/// Runs `iterations` cases from `case`, each a pair of inputs whose outputs must agree.
+/// `case` returns `None` when the combinator's arity rules the law out.
+fn check(
+    combinator: &dyn Combinator,
+    law: Law,
+    iterations: usize,
+    mut case: impl FnMut(&mut Rng) -> Option<(Vec<u8>, Vec<u8>)>,
+    rng: &mut Rng,
+) -> LawReport {
+    let mut report = LawReport {
+        law,
+        combinator: combinator.identifier(),
+        checked: 0,
+        counterexample: None,
+    };
+    for _ in 0..iterations {
+        let Some((inputs, other_inputs)) = case(rng) else {
+            break;
+        };
+        report.checked += 1;
+        let (output, other_output) = (
+            combinator.combine(&inputs),
+            combinator.combine(&other_inputs),
+        );
+        if output != other_output {
+            report.counterexample = Some(Counterexample {
+                inputs,
+                output,
+                other_inputs,
+                other_output,
+            });
+            break;
+        }
+    }
+    report
+}
+
+/// Returns the range of input counts to generate, or `None` if it is empty.
+fn input_counts(combinator: &dyn Combinator) -> Option<(usize, usize)> {
+    let lo = combinator.min_inputs().max(1);
+    let hi = combinator
+        .max_inputs()
+        .unwrap_or(MAX_GENERATED_INPUTS)
+        .min(MAX_GENERATED_INPUTS);
+    (lo <= hi).then_some((lo, hi))
+}
+
+/// Returns between `lo` and `hi` random bytes, or `None` if `lo > hi`.
+fn random_inputs(rng: &mut Rng, lo: usize, hi: usize) -> Option<Vec<u8>> {
+    if lo > hi {
+        return None;
+    }
+    let len = lo + rng.below(hi - lo + 1);
+    // Draw from a few values half the time, so that equal inputs are common.
+    let narrow = rng.chance(0.5);
+    Some(
+        (0..len)
+            .map(|_| match narrow {
+                true => [0, 1, 127, 128, 254, 255][rng.below(6)],
+                false => rng.next_u64() as u8,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::{BUILTINS, FnCombinatorNode, combinator_from_str};
+
+    /// For each built-in's example, whether it is commutative, associative and idempotent on
+    /// duplicates: `y` holds, `n` fails, `-` cannot be checked for its arity.
+    const EXPECTED: &[(&str, &str)] = &[
+        ("addition", "yyn"),
+        ("multiply", "yyn"),
+        ("add_sat", "yyn"),
+        ("add_wrap", "yyn"),
+        ("sub_sat", "nnn"),
+        ("multiply_sat", "yyn"),
+        ("multiply_wrap", "yyn"),
+        ("max", "yyy"),
+        ("min", "yyy"),
+        ("average", "ynn"),
+        ("weighted", "nnn"),
+        ("threshold", "ynn"),
+        ("median", "ynn"),
+        ("majority", "yyn"),
+        ("bit_majority", "yyn"),
+        ("or", "yyy"),
+        ("and", "yyy"),
+        ("xor", "yyn"),
+        ("nand", "yny"),
+        ("nor", "yny"),
+        ("xnor", "yyn"),
+        ("mode", "yyn"),
+        ("range", "yny"),
+        ("stddev", "ynn"),
+        ("geometric_mean", "ynn"),
+        ("harmonic_mean", "ynn"),
+        ("argmax", "nny"),
+        ("argmin", "nny"),
+        ("topk", "ynn"),
+        ("trimmed", "ynn"),
+        ("popcount", "ynn"),
+        ("parity", "ynn"),
+        ("any_bit", "yyy"),
+        ("bit_pack", "nnn"),
+        ("count_above", "ynn"),
+        ("count_eq", "ynn"),
+        ("count_nonzero", "ynn"),
+        ("percentile", "yyy"),
+        ("nth", "yyn"),
+        ("abs_diff", "nnn"),
+        ("greater_than", "nn-"),
+        ("equal", "yny"),
+        ("log_sum", "ynn"),
+        ("constant", "yyy"),
+        ("mux", "n--"),
+        ("first", "nyy"),
+        ("last", "nyn"),
+        ("select", "n-y"),
+    ];
+
+    fn flag(report: &LawReport) -> char {
+        match (report.checked, report.holds()) {
+            (0, _) => '-',
+            (_, true) => 'y',
+            (_, false) => 'n',
+        }
+    }
+
+    #[test]
+    fn test_builtin_laws() {
+        assert_eq!(EXPECTED.len(), BUILTINS.len());
+        for (builtin, (identifier, expected)) in BUILTINS.iter().zip(EXPECTED) {
+            assert_eq!(builtin.identifier, *identifier);
+            let combinator = combinator_from_str(builtin.example).unwrap();
+            let combinator = combinator.as_ref();
+            let flags: String = [
+                check_commutative(combinator, 7, 1000),
+                check_associative_pairwise(combinator, 7, 1000),
+                check_idempotent_on_duplicates(combinator, 7, 1000),
+            ]
+            .iter()
+            .map(flag)
+            .collect();
+            assert_eq!(flags, *expected, "{}", builtin.example);
+        }
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let and = combinator_from_str("and").unwrap();
+        assert!(check_empty_input_is(and.as_ref(), 255).holds());
+        let report = check_empty_input_is(and.as_ref(), 0);
+        assert_eq!(
+            report.counterexample,
+            Some(Counterexample {
+                inputs: vec![],
+                output: 255,
+                other_inputs: vec![],
+                other_output: 0,
+            })
+        );
+        assert_eq!(
+            report.to_string(),
+            "and is not empty input: [] gives 255 but [] gives 0"
+        );
+    }
+
+    #[test]
+    fn test_broken_combinator_is_caught() {
+        // Max, except that two inputs give the first one.
+        let broken = FnCombinatorNode::new("broken_max", |inputs| match inputs {
+            [first, _] => *first,
+            _ => inputs.iter().copied().max().unwrap_or(0),
+        });
+        let report = check_commutative(&broken, 1, 1000);
+        let counterexample = report.counterexample.clone().unwrap();
+        assert_eq!(
+            broken.combine(&counterexample.inputs),
+            counterexample.output
+        );
+        assert_eq!(
+            broken.combine(&counterexample.other_inputs),
+            counterexample.other_output
+        );
+        assert_ne!(counterexample.output, counterexample.other_output);
+        let mut sorted = counterexample.inputs.clone();
+        let mut other_sorted = counterexample.other_inputs.clone();
+        sorted.sort();
+        other_sorted.sort();
+        assert_eq!(sorted, other_sorted);
+        assert_eq!(sorted.len(), 2);
+        assert!(
+            report
+                .to_string()
+                .starts_with("broken_max is not commutative: ")
+        );
+
+        let max = combinator_from_str("max").unwrap();
+        let report = check_commutative(max.as_ref(), 1, 1000);
+        assert_eq!(report.checked, 1000);
+        assert_eq!(report.to_string(), "max is commutative (1000 cases)");
+    }
+
+    #[test]
+    fn test_same_seed_same_report() {
+        let average = combinator_from_str("average").unwrap();
+        let a = check_associative_pairwise(average.as_ref(), 3, 1000);
+        assert_eq!(a, check_associative_pairwise(average.as_ref(), 3, 1000));
+        assert!(!a.holds());
+    }
+}
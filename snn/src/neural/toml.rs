@@ -0,0 +1,499 @@
+/// Minimal TOML reader used by the configuration formats in this crate.
+///
+/// Supports comments, bare, quoted and dotted keys, `[table]` and `[[array of tables]]`
+/// headers, basic and literal strings, integers, floats, booleans, arrays and inline tables.
+/// Multi-line strings and dates are not supported. A document is read into a JSON `Value`,
+/// with integers and floats both becoming numbers and tables keeping their key order.
+use crate::neural::json::{MAX_DEPTH, Value};
+
+/// Error returned when TOML text cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TomlError {
+    /// The line where parsing failed, counting from 1.
+    pub line: usize,
+    /// What was expected or found.
+    pub message: String,
+}
+
+impl std::fmt::Display for TomlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid TOML at line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+type Table = Vec<(String, Value)>;
+
+/// Parses a complete TOML document into an object.
+pub fn parse(text: &str) -> Result<Value, TomlError> {
+    let mut parser = Parser {
+        bytes: text.as_bytes(),
+        text,
+        pos: 0,
+    };
+    let mut root = Table::new();
+    let mut current: Vec<String> = Vec::new();
+    loop {
+        parser.blank_lines();
+        match parser.bytes.get(parser.pos) {
+            None => return Ok(Value::Object(root)),
+            Some(b'[') if parser.bytes.get(parser.pos + 1) == Some(&b'[') => {
+                parser.pos += 2;
+                let path = parser.key()?;
+                parser.expect("]]")?;
+                let (last, parents) = path.split_last().expect("keys are never empty");
+                let table = parser.descend(&mut root, parents)?;
+                match entry(table, last) {
+                    Some(Value::Array(items)) => items.push(Value::Object(Table::new())),
+                    Some(_) => return Err(parser.error(&format!("{last:?} is not an array"))),
+                    None => table.push((
+                        last.clone(),
+                        Value::Array(vec![Value::Object(Table::new())]),
+                    )),
+                }
+                current = path;
+            }
+            Some(b'[') => {
+                parser.pos += 1;
+                let path = parser.key()?;
+                parser.expect("]")?;
+                parser.descend(&mut root, &path)?;
+                current = path;
+            }
+            Some(_) => {
+                let key = parser.key()?;
+                parser.expect("=")?;
+                let value = parser.value(0)?;
+                let (last, parents) = key.split_last().expect("keys are never empty");
+                let path: Vec<String> = current.iter().chain(parents).cloned().collect();
+                let table = parser.descend(&mut root, &path)?;
+                insert(table, last, value).map_err(|message| parser.error(&message))?;
+            }
+        }
+        parser.end_of_line()?;
+    }
+}
+
+/// Returns the value stored under `key` in `table`.
+fn entry<'t>(table: &'t mut Table, key: &str) -> Option<&'t mut Value> {
+    table.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+/// Adds `key` to `table`, failing if it is already there.
+fn insert(table: &mut Table, key: &str, value: Value) -> Result<(), String> {
+    if table.iter().any(|(k, _)| k == key) {
+        return Err(format!("duplicate key {key:?}"));
+    }
+    table.push((key.to_string(), value));
+    Ok(())
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> TomlError {
+        TomlError {
+            line: self.text[..self.pos].matches('\n').count() + 1,
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns the table at `path` below `root`, creating missing tables on the way. An array
+    /// of tables on the path stands for its last table.
+    fn descend<'t>(
+        &self,
+        root: &'t mut Table,
+        path: &[String],
+    ) -> Result<&'t mut Table, TomlError> {
+        let mut table = root;
+        for key in path {
+            if entry(table, key).is_none() {
+                table.push((key.clone(), Value::Object(Table::new())));
+            }
+            table = match entry(table, key) {
+                Some(Value::Object(entries)) => entries,
+                Some(Value::Array(items)) => match items.last_mut() {
+                    Some(Value::Object(entries)) => entries,
+                    _ => return Err(self.error(&format!("{key:?} is not a table"))),
+                },
+                _ => return Err(self.error(&format!("{key:?} is not a table"))),
+            };
+        }
+        Ok(table)
+    }
+
+    /// Skips spaces and tabs.
+    fn spaces(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips a comment up to the end of the line.
+    fn comment(&mut self) {
+        if self.bytes.get(self.pos) == Some(&b'#') {
+            while !matches!(self.bytes.get(self.pos), None | Some(b'\n')) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines and comments.
+    fn blank_lines(&mut self) {
+        loop {
+            self.spaces();
+            self.comment();
+            match self.bytes.get(self.pos) {
+                Some(b'\n') => self.pos += 1,
+                Some(b'\r') if self.bytes.get(self.pos + 1) == Some(&b'\n') => self.pos += 2,
+                _ => return,
+            }
+        }
+    }
+
+    /// Requires that nothing but a comment follows on the current line.
+    fn end_of_line(&mut self) -> Result<(), TomlError> {
+        self.spaces();
+        self.comment();
+        match self.bytes.get(self.pos) {
+            None | Some(b'\n') => Ok(()),
+            Some(b'\r') if self.bytes.get(self.pos + 1) == Some(&b'\n') => Ok(()),
+            _ => Err(self.error("expected the end of the line")),
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), TomlError> {
+        self.spaces();
+        if self.text[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {token:?}")))
+        }
+    }
+
+    /// Parses a bare, quoted or dotted key into its parts.
+    fn key(&mut self) -> Result<Vec<String>, TomlError> {
+        let mut parts = Vec::new();
+        loop {
+            self.spaces();
+            let part = match self.bytes.get(self.pos) {
+                Some(b'"') => self.basic_string()?,
+                Some(b'\'') => self.literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while self
+                        .bytes
+                        .get(self.pos)
+                        .is_some_and(|&c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-')
+                    {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err(self.error("expected a key"));
+                    }
+                    self.text[start..self.pos].to_string()
+                }
+            };
+            parts.push(part);
+            self.spaces();
+            if self.bytes.get(self.pos) != Some(&b'.') {
+                return Ok(parts);
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Parses a value nested inside `depth` arrays and inline tables, failing past
+    /// `MAX_DEPTH` like the JSON reader.
+    fn value(&mut self, depth: usize) -> Result<Value, TomlError> {
+        self.spaces();
+        match self.bytes.get(self.pos) {
+            None => Err(self.error("expected a value")),
+            Some(b'"') => Ok(Value::String(self.basic_string()?)),
+            Some(b'\'') => Ok(Value::String(self.literal_string()?)),
+            Some(b'[' | b'{') if depth >= MAX_DEPTH => {
+                Err(self.error(&format!("nesting deeper than {MAX_DEPTH} levels")))
+            }
+            Some(b'[') => self.array(depth + 1),
+            Some(b'{') => self.inline_table(depth + 1),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'+' | b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, TomlError> {
+        self.expect(word)?;
+        Ok(value)
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, TomlError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.blank_lines();
+            if self.bytes.get(self.pos) == Some(&b']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value(depth)?);
+            self.blank_lines();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {}
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn inline_table(&mut self, depth: usize) -> Result<Value, TomlError> {
+        self.pos += 1;
+        let mut table = Table::new();
+        self.spaces();
+        if self.bytes.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(table));
+        }
+        loop {
+            let key = self.key()?;
+            self.expect("=")?;
+            let value = self.value(depth)?;
+            let (last, parents) = key.split_last().expect("keys are never empty");
+            let target = self.descend(&mut table, parents)?;
+            insert(target, last, value).map_err(|message| self.error(&message))?;
+            self.spaces();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(table));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, TomlError> {
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|&c| c.is_ascii_alphanumeric() || matches!(c, b'_' | b'.' | b'+' | b'-'))
+        {
+            self.pos += 1;
+        }
+        let text = self.text[start..self.pos].replace('_', "");
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => (-1.0, digits),
+            None => (1.0, text.strip_prefix('+').unwrap_or(&text)),
+        };
+        let radix = match digits.get(..2) {
+            Some("0x") => Some(16),
+            Some("0o") => Some(8),
+            Some("0b") => Some(2),
+            _ => None,
+        };
+        let parsed = match radix {
+            Some(radix) => u64::from_str_radix(&digits[2..], radix)
+                .ok()
+                .map(|n| n as f64),
+            None => digits.parse::<f64>().ok().filter(|n| n.is_finite()),
+        };
+        match parsed {
+            Some(n) => Ok(Value::Number(sign * n)),
+            None => {
+                self.pos = start;
+                Err(self.error(&format!("invalid number {text:?}")))
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, TomlError> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let start = self.pos;
+            while !matches!(self.bytes.get(self.pos), None | Some(b'"' | b'\\' | b'\n')) {
+                self.pos += 1;
+            }
+            out.push_str(&self.text[start..self.pos]);
+            match self.bytes.get(self.pos) {
+                None | Some(b'\n') => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                _ => {
+                    self.pos += 1;
+                    let escaped = match self.bytes.get(self.pos) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(&c @ (b'u' | b'U')) => {
+                            let len = if c == b'u' { 4 } else { 8 };
+                            let hex = self
+                                .text
+                                .get(self.pos + 1..self.pos + 1 + len)
+                                .ok_or_else(|| self.error("truncated unicode escape"))?;
+                            let code = u32::from_str_radix(hex, 16)
+                                .map_err(|_| self.error("invalid unicode escape"))?;
+                            self.pos += len;
+                            char::from_u32(code)
+                                .ok_or_else(|| self.error("unsupported unicode escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.pos += 1;
+                    out.push(escaped);
+                }
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, TomlError> {
+        self.pos += 1;
+        let start = self.pos;
+        while !matches!(self.bytes.get(self.pos), None | Some(b'\'' | b'\n')) {
+            self.pos += 1;
+        }
+        if self.bytes.get(self.pos) != Some(&b'\'') {
+            return Err(self.error("unterminated string"));
+        }
+        self.pos += 1;
+        Ok(self.text[start..self.pos - 1].to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values() {
+        let value = parse(
+            "# a comment\n\
+             name = \"a \\\"quoted\\\" \\u00e9\" # trailing\n\
+             path = 'C:\\raw'\n\
+             count = 1_000\n\
+             hex = 0xff\n\
+             negative = -2.5\n\
+             flag = true\n\
+             list = [1, 2,\n  3, # inside\n]\n\
+             point = { x = 1, y.z = 'deep' }\n\
+             dotted.key = 7\n",
+        )
+        .unwrap();
+        assert_eq!(
+            value.get("name").and_then(Value::as_str),
+            Some("a \"quoted\" é")
+        );
+        assert_eq!(value.get("path").and_then(Value::as_str), Some("C:\\raw"));
+        assert_eq!(value.get("count").and_then(Value::as_u64), Some(1000));
+        assert_eq!(value.get("hex").and_then(Value::as_u64), Some(255));
+        assert_eq!(value.get("negative"), Some(&Value::Number(-2.5)));
+        assert_eq!(value.get("flag"), Some(&Value::Bool(true)));
+        assert_eq!(
+            value.get("list").and_then(Value::as_array).unwrap().len(),
+            3
+        );
+        let point = value.get("point").unwrap();
+        assert_eq!(point.get("x").and_then(Value::as_u64), Some(1));
+        assert_eq!(
+            point
+                .get("y")
+                .and_then(|y| y.get("z"))
+                .and_then(Value::as_str),
+            Some("deep")
+        );
+        assert_eq!(
+            value
+                .get("dotted")
+                .and_then(|d| d.get("key"))
+                .and_then(Value::as_u64),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn test_tables_and_arrays_of_tables() {
+        let value = parse(
+            "top = 1\n\
+             [server]\n\
+             port = 80\n\
+             [server.tls]\n\
+             enabled = false\n\
+             \n\
+             [[layers]]\n\
+             width = 2\n\
+             [[layers.nodes]]\n\
+             kind = 'a'\n\
+             [[layers.nodes]]\n\
+             kind = 'b'\n\
+             [[layers]]\n\
+             width = 3\n",
+        )
+        .unwrap();
+        assert_eq!(value.get("top").and_then(Value::as_u64), Some(1));
+        let server = value.get("server").unwrap();
+        assert_eq!(server.get("port").and_then(Value::as_u64), Some(80));
+        assert_eq!(
+            server.get("tls").and_then(|t| t.get("enabled")),
+            Some(&Value::Bool(false))
+        );
+        let layers = value.get("layers").and_then(Value::as_array).unwrap();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[1].get("width").and_then(Value::as_u64), Some(3));
+        let nodes = layers[0].get("nodes").and_then(Value::as_array).unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[1].get("kind").and_then(Value::as_str), Some("b"));
+        assert!(layers[1].get("nodes").is_none());
+    }
+
+    #[test]
+    fn test_errors() {
+        let line = |text: &str| parse(text).err().unwrap().line;
+        assert_eq!(line("a = 1\na = 2\n"), 2);
+        assert_eq!(line("a = 1\n\nb = \n"), 3);
+        assert_eq!(line("a = \"open\n"), 1);
+        assert_eq!(line("a = 1 b = 2"), 1);
+        assert_eq!(line("a = [1 2]"), 1);
+        assert_eq!(line("[table\n"), 1);
+        assert_eq!(line("a = 1\n[a]\n"), 2);
+        assert_eq!(line("x = 0xZZ"), 1);
+        assert_eq!(line("= 1"), 1);
+        assert_eq!(
+            parse("a = 1\na = 2").err().unwrap().to_string(),
+            "invalid TOML at line 2: duplicate key \"a\""
+        );
+        assert_eq!(parse(""), Ok(Value::Object(vec![])));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |open: &str, inner: &str, close: &str, depth: usize| {
+            format!("a = {}{inner}{}", open.repeat(depth), close.repeat(depth))
+        };
+        assert!(parse(&nested("[", "1", "]", MAX_DEPTH)).is_ok());
+        assert!(parse(&nested("{b = ", "1", "}", MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse(&nested("[", "1", "]", MAX_DEPTH + 1))
+                .err()
+                .unwrap()
+                .to_string(),
+            format!("invalid TOML at line 1: nesting deeper than {MAX_DEPTH} levels")
+        );
+        assert!(parse(&nested("{b = ", "1", "}", MAX_DEPTH + 1)).is_err());
+        assert!(parse(&format!("a = {}", "[".repeat(200_000))).is_err());
+    }
+}
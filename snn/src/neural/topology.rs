@@ -0,0 +1,84 @@
+/// Returns the nodes `0..count` ordered so that every node comes after the nodes it depends on.
+/// `dependencies(i)` lists the nodes that node `i` reads.
+/// If the dependencies form a cycle, returns the nodes of one cycle instead, in dependency order:
+/// each node depends on the next, and the last depends on the first.
+pub(crate) fn topological_order(
+    count: usize,
+    dependencies: impl Fn(usize) -> Vec<usize>,
+) -> Result<Vec<usize>, Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Active,
+        Done,
+    }
+    let mut state = vec![State::New; count];
+    let mut order = Vec::with_capacity(count);
+    for root in 0..count {
+        if state[root] != State::New {
+            continue;
+        }
+        // Each frame holds a node and its remaining dependencies, so deep graphs do not
+        // overflow the call stack.
+        let mut stack = vec![(root, dependencies(root).into_iter())];
+        state[root] = State::Active;
+        while let Some((node, remaining)) = stack.last_mut() {
+            let node = *node;
+            match remaining.next() {
+                Some(next) => match state[next] {
+                    State::New => {
+                        state[next] = State::Active;
+                        stack.push((next, dependencies(next).into_iter()));
+                    }
+                    State::Active => {
+                        let start = stack.iter().position(|(n, _)| *n == next).unwrap();
+                        return Err(stack[start..].iter().map(|(n, _)| *n).collect());
+                    }
+                    State::Done => {}
+                },
+                None => {
+                    state[node] = State::Done;
+                    order.push(node);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_respects_dependencies() {
+        // 0 reads 2 and 3, 2 reads 1, 3 reads 1.
+        let deps = [vec![2, 3], vec![], vec![1], vec![1]];
+        let order = topological_order(4, |i| deps[i].clone()).unwrap();
+        let position = |n: usize| order.iter().position(|&x| x == n).unwrap();
+        assert_eq!(order.len(), 4);
+        for (node, reads) in deps.iter().enumerate() {
+            for &dep in reads {
+                assert!(position(dep) < position(node));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycle() {
+        // 0 reads 1, 1 reads 2, 2 reads 0, 3 reads 0.
+        let deps = [vec![1], vec![2], vec![0], vec![0]];
+        assert_eq!(
+            topological_order(4, |i| deps[i].clone()),
+            Err(vec![0, 1, 2])
+        );
+        assert_eq!(topological_order(1, |_| vec![0]), Err(vec![0]));
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        let order = topological_order(100_000, |i| if i > 0 { vec![i - 1] } else { vec![] });
+        assert_eq!(order.unwrap()[..3], [0, 1, 2]);
+    }
+}
@@ -0,0 +1,308 @@
+/// Training networks by searching over their mapper tables.
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::Network;
+use crate::neural::rng::Rng;
+
+/// Examples of the outputs a network should give for some inputs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dataset {
+    examples: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Dataset {
+    /// Creates an empty dataset.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an example: the network should give `expected` for `input`.
+    pub fn push(&mut self, input: Vec<u8>, expected: Vec<u8>) {
+        self.examples.push((input, expected));
+    }
+
+    /// Returns the number of examples.
+    pub fn len(&self) -> usize {
+        self.examples.len()
+    }
+
+    /// Returns true if there are no examples.
+    pub fn is_empty(&self) -> bool {
+        self.examples.is_empty()
+    }
+
+    /// Returns the examples as `(input, expected)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.examples
+            .iter()
+            .map(|(input, expected)| (input.as_slice(), expected.as_slice()))
+    }
+
+    /// Returns the total loss of `network` over every example.
+    /// An example the network cannot evaluate counts as `u64::MAX`.
+    pub fn loss(&self, network: &Network, loss: &impl Loss) -> u64 {
+        self.iter()
+            .map(|(input, expected)| match network.forward(input) {
+                Ok(output) => loss.loss(&output, expected),
+                Err(_) => u64::MAX,
+            })
+            .fold(0, u64::saturating_add)
+    }
+}
+
+impl FromIterator<(Vec<u8>, Vec<u8>)> for Dataset {
+    fn from_iter<I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>>(iter: I) -> Self {
+        Self {
+            examples: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Measures how far an output is from the expected one. Zero means a perfect match.
+pub trait Loss {
+    fn loss(&self, output: &[u8], expected: &[u8]) -> u64;
+}
+
+/// The sum of the absolute differences between the output and expected bytes.
+/// Each byte missing from either side counts as 255.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AbsoluteLoss;
+
+impl Loss for AbsoluteLoss {
+    fn loss(&self, output: &[u8], expected: &[u8]) -> u64 {
+        let differences: u64 = output
+            .iter()
+            .zip(expected)
+            .map(|(&a, &b)| a.abs_diff(b) as u64)
+            .sum();
+        differences + 255 * output.len().abs_diff(expected.len()) as u64
+    }
+}
+
+/// 0 if the output equals the expected bytes exactly, 1 otherwise.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExactMatchLoss;
+
+impl Loss for ExactMatchLoss {
+    fn loss(&self, output: &[u8], expected: &[u8]) -> u64 {
+        u64::from(output != expected)
+    }
+}
+
+/// The outcome of a training run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrainReport {
+    /// The loss before training.
+    pub initial_loss: u64,
+    /// The loss after training.
+    pub final_loss: u64,
+    /// The number of mutations tried.
+    pub iterations: usize,
+    /// The number of mutations kept.
+    pub accepted: usize,
+}
+
+/// Trains a network by changing one mapper table entry at a time and keeping the change
+/// only if it lowers the loss.
+/// Only tables the network already has are changed: input mappers and activations.
+/// Nodes that pass their inputs through unmapped and have no activation are left alone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HillClimber<L = AbsoluteLoss> {
+    loss: L,
+}
+
+impl<L: Loss> HillClimber<L> {
+    pub fn new(loss: L) -> Self {
+        Self { loss }
+    }
+
+    /// Tries up to `budget` mutations on `network`, stopping early once the loss on `dataset`
+    /// reaches zero. The same seed always makes the same mutations.
+    pub fn train(
+        &self,
+        network: &mut Network,
+        dataset: &Dataset,
+        budget: usize,
+        seed: u64,
+    ) -> TrainReport {
+        self.train_resumable(network, dataset, budget, &mut TrainerState::new(seed))
+    }
+
+    /// Like `train`, but continues from `state` and leaves it where this run stopped, so a
+    /// run split over several calls, or saved in a checkpoint in between, makes the same
+    /// mutations as one uninterrupted run.
+    pub fn train_resumable(
+        &self,
+        network: &mut Network,
+        dataset: &Dataset,
+        budget: usize,
+        state: &mut TrainerState,
+    ) -> TrainReport {
+        let mut rng = Rng::new(state.rng_state);
+        let initial_loss = dataset.loss(network, &self.loss);
+        let mut report = TrainReport {
+            initial_loss,
+            final_loss: initial_loss,
+            iterations: 0,
+            accepted: 0,
+        };
+        let table_count = mappers_mut(network).len();
+        if table_count == 0 {
+            return report;
+        }
+        while report.iterations < budget && report.final_loss > 0 {
+            report.iterations += 1;
+            let table = rng.below(table_count);
+            let (entry, previous) = mutate_entry(mappers_mut(network).swap_remove(table), &mut rng);
+            let loss = dataset.loss(network, &self.loss);
+            if loss < report.final_loss {
+                report.final_loss = loss;
+                report.accepted += 1;
+            } else {
+                mappers_mut(network).swap_remove(table).table_mut()[entry] = previous;
+            }
+        }
+        state.rng_state = rng.0;
+        state.generation += report.iterations as u64;
+        report
+    }
+}
+
+/// Where a training run left off: its random generator and how many steps it has taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrainerState {
+    rng_state: u64,
+    /// The number of steps taken so far: mutations for `HillClimber`.
+    pub generation: u64,
+}
+
+impl TrainerState {
+    /// Returns the state of a run that has not started yet.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng_state: Rng::new(seed).0,
+            generation: 0,
+        }
+    }
+
+    /// Restores a state saved with `rng_state` and `generation`.
+    pub fn from_parts(rng_state: u64, generation: u64) -> Self {
+        Self {
+            rng_state: Rng::new(rng_state).0,
+            generation,
+        }
+    }
+
+    /// Returns the internal state of the random generator.
+    pub fn rng_state(&self) -> u64 {
+        self.rng_state
+    }
+}
+
+/// Returns every mapper table in the network, layer by layer and node by node.
+pub(crate) fn mappers_mut(network: &mut Network) -> Vec<&mut MapperNode> {
+    network
+        .layers_mut()
+        .iter_mut()
+        .flat_map(|layer| layer.nodes_mut())
+        .flat_map(|node| node.mappers_mut())
+        .collect()
+}
+
+/// Changes one random entry of `mapper` and returns the entry and its previous value.
+/// Half of the time the entry gets a new random value; otherwise it moves up or down by a
+/// power of two up to 16, which lets a loss that measures distance guide the search.
+pub(crate) fn mutate_entry(mapper: &mut MapperNode, rng: &mut Rng) -> (usize, u8) {
+    let entry = rng.below(256);
+    let table = mapper.table_mut();
+    let previous = table[entry];
+    let step = 1 << rng.below(5);
+    table[entry] = match rng.below(4) {
+        0 | 1 => rng.next_u64() as u8,
+        2 => previous.saturating_add(step),
+        _ => previous.saturating_sub(step),
+    };
+    (entry, previous)
+}
+
+/// Returns a table that takes the entries before a random cut point from `a` and the rest
+/// from `b`.
+pub(crate) fn crossover(a: &MapperNode, b: &MapperNode, rng: &mut Rng) -> MapperNode {
+    let cut = rng.below(257);
+    let mut child = a.clone();
+    child.table_mut()[cut..].copy_from_slice(&b.table()[cut..]);
+    child
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::MaxCombinatorNode;
+
+    /// A single node whose activation table starts out all zeros.
+    fn blank_network() -> Network {
+        Network::builder()
+            .input_width(1)
+            .layer_dense(1, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+                    .activation(MapperNode::new())
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn increment_dataset() -> Dataset {
+        (0..=250u8)
+            .step_by(10)
+            .map(|x| (vec![x], vec![x + 1]))
+            .collect()
+    }
+
+    #[test]
+    fn test_learns_increment() {
+        let mut network = blank_network();
+        let dataset = increment_dataset();
+        let report = HillClimber::new(AbsoluteLoss).train(&mut network, &dataset, 200_000, 7);
+        assert!(report.initial_loss > 0);
+        assert_eq!(report.final_loss, 0);
+        assert!(report.accepted > 0);
+        assert!(report.iterations <= 200_000);
+        for x in (0..=250u8).step_by(10) {
+            assert_eq!(network.forward(&[x]), Ok(vec![x + 1]));
+        }
+        assert_eq!(dataset.loss(&network, &ExactMatchLoss), 0);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let dataset = increment_dataset();
+        let run = |seed| {
+            let mut network = blank_network();
+            HillClimber::new(AbsoluteLoss).train(&mut network, &dataset, 500, seed)
+        };
+        assert_eq!(run(3), run(3));
+        assert!(run(3).final_loss <= run(3).initial_loss);
+    }
+
+    #[test]
+    fn test_losses() {
+        assert_eq!(AbsoluteLoss.loss(&[1, 10], &[4, 5]), 8);
+        assert_eq!(AbsoluteLoss.loss(&[1], &[1, 0]), 255);
+        assert_eq!(ExactMatchLoss.loss(&[1, 2], &[1, 2]), 0);
+        assert_eq!(ExactMatchLoss.loss(&[1, 2], &[1, 3]), 1);
+    }
+
+    #[test]
+    fn test_nothing_to_train() {
+        let mut network = Network::builder()
+            .input_width(1)
+            .layer_dense(1, |node| {
+                node.combinator(Box::new(MaxCombinatorNode::new()))
+            })
+            .build()
+            .unwrap();
+        let dataset: Dataset = [(vec![1], vec![2])].into_iter().collect();
+        let report = HillClimber::new(AbsoluteLoss).train(&mut network, &dataset, 100, 1);
+        assert_eq!(report.iterations, 0);
+        assert_eq!(report.final_loss, 1);
+    }
+}
@@ -0,0 +1,246 @@
+/// Loading datasets from CSV and byte streams, and splitting them for training and testing.
+use std::io::{BufRead, BufReader, Read};
+
+use crate::neural::rng::Rng;
+use crate::neural::training::Dataset;
+
+impl Dataset {
+    /// Reads a dataset with one example per line: `input_cols` input bytes followed by the
+    /// expected output bytes, separated by commas. Values are decimal or `0x`-prefixed hex.
+    /// Blank lines and lines starting with `#` are skipped. Every row must have the same
+    /// number of columns, with at least one expected byte.
+    pub fn from_csv(reader: impl Read, input_cols: usize) -> Result<Dataset, DatasetError> {
+        let mut dataset = Dataset::new();
+        let mut columns = None;
+        for (index, line) in BufReader::new(reader).lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.map_err(|e| DatasetError::Io(e.to_string()))?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values = line
+                .split(',')
+                .enumerate()
+                .map(|(column, text)| parse_byte(text.trim(), line_number, column + 1))
+                .collect::<Result<Vec<u8>, _>>()?;
+            let expected = *columns.get_or_insert(values.len().max(input_cols + 1));
+            if values.len() != expected {
+                return Err(DatasetError::ColumnCount {
+                    line: line_number,
+                    expected,
+                    actual: values.len(),
+                });
+            }
+            let (input, output) = values.split_at(input_cols);
+            dataset.push(input.to_vec(), output.to_vec());
+        }
+        Ok(dataset)
+    }
+
+    /// Builds one example per window of `window` bytes of `input`, moving one byte at a time.
+    /// The expected output of each window is the byte of `expected` at the window's last
+    /// position, so the two streams are read in parallel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero or the streams differ in length.
+    pub fn from_bytes(input: &[u8], expected: &[u8], window: usize) -> Dataset {
+        assert!(window > 0, "window must be at least one byte");
+        assert_eq!(
+            input.len(),
+            expected.len(),
+            "input and expected streams differ in length"
+        );
+        input
+            .windows(window)
+            .zip(&expected[window - 1..])
+            .map(|(input, &expected)| (input.to_vec(), vec![expected]))
+            .collect()
+    }
+
+    /// Shuffles the examples with `seed` and splits them into a training set holding
+    /// `ratio` of them, rounded to the nearest example, and a test set holding the rest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is not between 0 and 1.
+    pub fn split(&self, ratio: f64, seed: u64) -> (Dataset, Dataset) {
+        assert!(
+            (0.0..=1.0).contains(&ratio),
+            "split ratio {ratio} is not between 0 and 1"
+        );
+        let mut rng = Rng::new(seed);
+        let mut examples: Vec<(Vec<u8>, Vec<u8>)> = self
+            .iter()
+            .map(|(input, expected)| (input.to_vec(), expected.to_vec()))
+            .collect();
+        for i in (1..examples.len()).rev() {
+            examples.swap(i, rng.below(i + 1));
+        }
+        let test = examples.split_off((examples.len() as f64 * ratio).round() as usize);
+        (examples.into_iter().collect(), test.into_iter().collect())
+    }
+}
+
+fn parse_byte(text: &str, line: usize, column: usize) -> Result<u8, DatasetError> {
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    let value = parsed.map_err(|_| DatasetError::InvalidValue {
+        line,
+        column,
+        text: text.to_string(),
+    })?;
+    u8::try_from(value).map_err(|_| DatasetError::OutOfRange {
+        line,
+        column,
+        value,
+    })
+}
+
+/// Error returned when a dataset cannot be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatasetError {
+    /// Reading failed.
+    Io(String),
+    /// A value is not a decimal or `0x`-prefixed hex number. Lines and columns count from 1.
+    InvalidValue {
+        line: usize,
+        column: usize,
+        text: String,
+    },
+    /// A value is above 255.
+    OutOfRange {
+        line: usize,
+        column: usize,
+        value: u64,
+    },
+    /// A row has a different number of columns than the first, or no expected bytes.
+    ColumnCount {
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatasetError::Io(message) => write!(f, "failed to read dataset: {message}"),
+            DatasetError::InvalidValue { line, column, text } => {
+                write!(f, "line {line}, column {column}: {text:?} is not a byte")
+            }
+            DatasetError::OutOfRange {
+                line,
+                column,
+                value,
+            } => write!(f, "line {line}, column {column}: {value} is above 255"),
+            DatasetError::ColumnCount {
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "line {line}: expected {expected} columns, found {actual}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DatasetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "\
+# a, b -> a | b
+0, 0, 0
+0x0F, 0xF0, 0xFF
+
+1,2,3
+";
+
+    #[test]
+    fn test_from_csv() {
+        let dataset = Dataset::from_csv(FIXTURE.as_bytes(), 2).unwrap();
+        let examples: Vec<(&[u8], &[u8])> = dataset.iter().collect();
+        assert_eq!(
+            examples,
+            vec![
+                (&[0, 0][..], &[0][..]),
+                (&[15, 240][..], &[255][..]),
+                (&[1, 2][..], &[3][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bad_rows() {
+        let bad_row = format!("{FIXTURE}4, five, 6\n");
+        assert_eq!(
+            Dataset::from_csv(bad_row.as_bytes(), 2),
+            Err(DatasetError::InvalidValue {
+                line: 6,
+                column: 2,
+                text: "five".to_string()
+            })
+        );
+        assert_eq!(
+            Dataset::from_csv("1,2,3\n1,256,3\n".as_bytes(), 2),
+            Err(DatasetError::OutOfRange {
+                line: 2,
+                column: 2,
+                value: 256
+            })
+        );
+        assert_eq!(
+            Dataset::from_csv("1,2,3\n1,2\n".as_bytes(), 2),
+            Err(DatasetError::ColumnCount {
+                line: 2,
+                expected: 3,
+                actual: 2
+            })
+        );
+        // Rows need at least one expected byte.
+        assert_eq!(
+            Dataset::from_csv("1,2\n".as_bytes(), 2),
+            Err(DatasetError::ColumnCount {
+                line: 1,
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let dataset = Dataset::from_bytes(b"abcd", b"wxyz", 2);
+        let examples: Vec<(&[u8], &[u8])> = dataset.iter().collect();
+        assert_eq!(
+            examples,
+            vec![
+                (&b"ab"[..], &b"x"[..]),
+                (&b"bc"[..], &b"y"[..]),
+                (&b"cd"[..], &b"z"[..]),
+            ]
+        );
+        assert!(Dataset::from_bytes(b"ab", b"xy", 3).is_empty());
+    }
+
+    #[test]
+    fn test_split() {
+        let dataset: Dataset = (0..10u8).map(|x| (vec![x], vec![x])).collect();
+        let (train, test) = dataset.split(0.8, 4);
+        assert_eq!((train.len(), test.len()), (8, 2));
+        let mut all: Vec<u8> = train.iter().chain(test.iter()).map(|(i, _)| i[0]).collect();
+        assert_ne!(all, (0..10).collect::<Vec<u8>>());
+        all.sort();
+        assert_eq!(all, (0..10).collect::<Vec<u8>>());
+        assert_eq!(dataset.split(0.8, 4), (train, test));
+        assert_eq!(dataset.split(0.0, 1).0.len(), 0);
+        assert_eq!(dataset.split(1.0, 1).1.len(), 0);
+    }
+}
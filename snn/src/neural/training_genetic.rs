@@ -0,0 +1,367 @@
+/// Training networks by evolving a population of mapper tables.
+use crate::neural::mapper::MapperNode;
+use crate::neural::network::Network;
+use crate::neural::rng::Rng;
+use crate::neural::training::{
+    AbsoluteLoss, Dataset, Loss, TrainerState, crossover, mappers_mut, mutate_entry,
+};
+
+/// The losses of one generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationStats {
+    /// The generation, counting the starting population as 0.
+    pub generation: usize,
+    /// The lowest loss in the population.
+    pub best_loss: u64,
+    /// The mean loss of the population, rounded down.
+    pub mean_loss: u64,
+}
+
+/// The outcome of `GeneticTrainer::train`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvolutionReport {
+    /// The loss of the network before training.
+    pub initial_loss: u64,
+    /// The loss of the best individual, which was written into the network.
+    pub final_loss: u64,
+    /// The losses of every generation that was evaluated, in order.
+    pub history: Vec<GenerationStats>,
+}
+
+/// Where an evolution run left off: its random generator, the generation it reached and
+/// that generation's population, which has been evaluated but not yet bred.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EvolutionState {
+    /// The random generator and the number of generations bred so far.
+    pub trainer: TrainerState,
+    pub(crate) population: Vec<Vec<MapperNode>>,
+}
+
+impl EvolutionState {
+    /// Returns the state of a run that has not started yet.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            trainer: TrainerState::new(seed),
+            population: Vec::new(),
+        }
+    }
+
+    /// Returns the current population, each individual being a full set of mapper tables.
+    /// It is empty until the run has started.
+    pub fn population(&self) -> &[Vec<MapperNode>] {
+        &self.population
+    }
+}
+
+/// Trains a network by evolving a population of candidate tables.
+/// Every individual is a full set of the network's mapper tables, so all of them share
+/// the network's topology and combinators. Each generation keeps the `elitism` best
+/// individuals unchanged and fills the rest with children of two tournament winners:
+/// every table is crossed over with the matching table of the other parent, and each
+/// child table then gets a single-entry mutation with probability `mutation_rate`.
+#[derive(Debug, Clone, Copy)]
+pub struct GeneticTrainer<L = AbsoluteLoss> {
+    loss: L,
+    population_size: usize,
+    elitism: usize,
+    tournament_size: usize,
+    mutation_rate: f64,
+}
+
+impl<L: Loss> GeneticTrainer<L> {
+    /// Creates a trainer with a population of 32, 2 elites, tournaments of 3,
+    /// and a mutation rate of 0.5.
+    pub fn new(loss: L) -> Self {
+        Self {
+            loss,
+            population_size: 32,
+            elitism: 2,
+            tournament_size: 3,
+            mutation_rate: 0.5,
+        }
+    }
+
+    /// Sets the number of individuals in each generation.
+    ///
+    /// # Panics
+    /// Panics if `size` is zero.
+    pub fn with_population_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "the population needs at least one individual");
+        self.population_size = size;
+        self
+    }
+
+    /// Sets how many of the best individuals are carried over unchanged.
+    /// Values above the population size keep the whole population.
+    pub fn with_elitism(mut self, count: usize) -> Self {
+        self.elitism = count;
+        self
+    }
+
+    /// Sets how many random individuals compete to become each parent.
+    ///
+    /// # Panics
+    /// Panics if `size` is zero.
+    pub fn with_tournament_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "a tournament needs at least one individual");
+        self.tournament_size = size;
+        self
+    }
+
+    /// Sets the probability that each table of a child is mutated.
+    ///
+    /// # Panics
+    /// Panics if `rate` is not between 0 and 1.
+    pub fn with_mutation_rate(mut self, rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "mutation rate {rate} is not between 0 and 1"
+        );
+        self.mutation_rate = rate;
+        self
+    }
+
+    /// Evolves the tables of `network` for up to `generations` generations, stopping early
+    /// once an individual reaches zero loss on `dataset`, and writes the best individual
+    /// into the network. The starting population is the network's own tables plus
+    /// mutated copies of them. The same seed always gives the same result.
+    pub fn train(
+        &self,
+        network: &mut Network,
+        dataset: &Dataset,
+        generations: usize,
+        seed: u64,
+    ) -> EvolutionReport {
+        self.train_resumable(
+            network,
+            dataset,
+            generations,
+            &mut EvolutionState::new(seed),
+        )
+    }
+
+    /// Like `train`, but continues from `state` and leaves it where this run stopped, so a
+    /// run split over several calls, or saved in a checkpoint in between, evolves the same
+    /// population as one uninterrupted run. The generations in the history count from the
+    /// start of the whole run.
+    ///
+    /// # Panics
+    /// Panics if the population in `state` was evolved for a network with a different
+    /// number of mapper tables.
+    pub fn train_resumable(
+        &self,
+        network: &mut Network,
+        dataset: &Dataset,
+        generations: usize,
+        state: &mut EvolutionState,
+    ) -> EvolutionReport {
+        let mut rng = Rng::new(state.trainer.rng_state());
+        let initial_loss = dataset.loss(network, &self.loss);
+        let original: Vec<MapperNode> = mappers_mut(network)
+            .into_iter()
+            .map(|m| m.clone())
+            .collect();
+        if original.is_empty() {
+            return EvolutionReport {
+                initial_loss,
+                final_loss: initial_loss,
+                history: Vec::new(),
+            };
+        }
+
+        let mut population = core::mem::take(&mut state.population);
+        if population.is_empty() {
+            population.push(original.clone());
+            while population.len() < self.population_size {
+                let mut individual = original.clone();
+                self.mutate(&mut individual, &mut rng);
+                population.push(individual);
+            }
+        }
+        assert!(
+            population
+                .iter()
+                .all(|individual| individual.len() == original.len()),
+            "the population was evolved for a network with a different number of tables"
+        );
+
+        let start = state.trainer.generation as usize;
+        let mut history = Vec::new();
+        let mut generation = start;
+        loop {
+            let losses: Vec<u64> = population
+                .iter()
+                .map(|individual| {
+                    load(network, individual);
+                    dataset.loss(network, &self.loss)
+                })
+                .collect();
+            let mut ranking: Vec<usize> = (0..population.len()).collect();
+            ranking.sort_by_key(|&i| losses[i]);
+            let best_loss = losses[ranking[0]];
+            let total: u128 = losses.iter().map(|&l| l as u128).sum();
+            history.push(GenerationStats {
+                generation,
+                best_loss,
+                mean_loss: (total / losses.len() as u128) as u64,
+            });
+            if best_loss == 0 || generation == start + generations {
+                load(network, &population[ranking[0]]);
+                state.trainer = TrainerState::from_parts(rng.0, generation as u64);
+                state.population = population;
+                return EvolutionReport {
+                    initial_loss,
+                    final_loss: best_loss,
+                    history,
+                };
+            }
+
+            let mut next: Vec<Vec<MapperNode>> = ranking
+                .iter()
+                .take(self.elitism)
+                .map(|&i| population[i].clone())
+                .collect();
+            while next.len() < self.population_size {
+                let first = &population[self.tournament(&losses, &mut rng)];
+                let second = &population[self.tournament(&losses, &mut rng)];
+                let mut child: Vec<MapperNode> = first
+                    .iter()
+                    .zip(second)
+                    .map(|(a, b)| crossover(a, b, &mut rng))
+                    .collect();
+                self.mutate(&mut child, &mut rng);
+                next.push(child);
+            }
+            population = next;
+            generation += 1;
+        }
+    }
+
+    /// Returns the index of the best of `tournament_size` random individuals.
+    /// Ties go to the one drawn first.
+    fn tournament(&self, losses: &[u64], rng: &mut Rng) -> usize {
+        let mut winner = rng.below(losses.len());
+        for _ in 1..self.tournament_size {
+            let challenger = rng.below(losses.len());
+            if losses[challenger] < losses[winner] {
+                winner = challenger;
+            }
+        }
+        winner
+    }
+
+    fn mutate(&self, individual: &mut [MapperNode], rng: &mut Rng) {
+        for table in individual {
+            if rng.chance(self.mutation_rate) {
+                mutate_entry(table, rng);
+            }
+        }
+    }
+}
+
+/// Copies the tables of `individual` into the network.
+fn load(network: &mut Network, individual: &[MapperNode]) {
+    for (mapper, table) in mappers_mut(network).into_iter().zip(individual) {
+        mapper.clone_from(table);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::AdditionCombinatorNode;
+
+    /// Two inputs that are each 0 or 1, with blank input mappers and activation.
+    fn blank_network() -> Network {
+        Network::builder()
+            .input_width(2)
+            .layer_dense(1, |node| {
+                node.input_mapper(MapperNode::new())
+                    .input_mapper(MapperNode::new())
+                    .combinator(Box::new(AdditionCombinatorNode::new()))
+                    .activation(MapperNode::new())
+            })
+            .build()
+            .unwrap()
+    }
+
+    /// 255 when exactly one input is 1, otherwise 0.
+    fn xor_dataset() -> Dataset {
+        [
+            (vec![0, 0], vec![0]),
+            (vec![0, 1], vec![255]),
+            (vec![1, 0], vec![255]),
+            (vec![1, 1], vec![0]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn trainer() -> GeneticTrainer {
+        GeneticTrainer::new(AbsoluteLoss)
+            .with_population_size(64)
+            .with_elitism(4)
+            .with_tournament_size(3)
+            .with_mutation_rate(1.0)
+    }
+
+    #[test]
+    fn test_evolves_xor() {
+        let mut network = blank_network();
+        let dataset = xor_dataset();
+        let report = trainer().train(&mut network, &dataset, 1000, 11);
+        assert_eq!(report.initial_loss, 510);
+        assert_eq!(report.final_loss, 0);
+        assert!(report.history.len() <= 1001);
+        assert_eq!(report.history.last().unwrap().best_loss, 0);
+        for (input, expected) in dataset.iter() {
+            assert_eq!(network.forward(input).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted_run() {
+        let dataset = xor_dataset();
+        let trainer = trainer().with_mutation_rate(0.1);
+        let mut uninterrupted = blank_network();
+        let whole = trainer.train(&mut uninterrupted, &dataset, 6, 3);
+        assert_eq!(whole.history.len(), 7);
+
+        let mut network = blank_network();
+        let mut state = EvolutionState::new(3);
+        let first = trainer.train_resumable(&mut network, &dataset, 2, &mut state);
+        assert_eq!(state.trainer.generation, 2);
+        assert_eq!(state.population().len(), 64);
+        let second = trainer.train_resumable(&mut network, &dataset, 4, &mut state);
+        assert_eq!(state.trainer.generation, 6);
+        assert_eq!(second.initial_loss, first.final_loss);
+        assert_eq!(second.final_loss, whole.final_loss);
+        assert_eq!(first.history[..], whole.history[..3]);
+        assert_eq!(second.history[..], whole.history[2..]);
+        assert!(
+            mappers_mut(&mut network)
+                .into_iter()
+                .eq(mappers_mut(&mut uninterrupted))
+        );
+    }
+
+    #[test]
+    fn test_history_and_determinism() {
+        let dataset = xor_dataset();
+        let run = |seed| trainer().train(&mut blank_network(), &dataset, 5, seed);
+        let report = run(5);
+        assert_eq!(report, run(5));
+        assert!(report.history.len() <= 6);
+        for (generation, stats) in report.history.iter().enumerate() {
+            assert_eq!(stats.generation, generation);
+            assert!(stats.best_loss <= stats.mean_loss);
+        }
+        // Elites keep the best loss from getting worse.
+        assert!(
+            report
+                .history
+                .windows(2)
+                .all(|w| w[1].best_loss <= w[0].best_loss)
+        );
+    }
+}
@@ -1,8 +1,59 @@
+use alloc::{
+    boxed::Box,
+    format,
+    rc::Rc,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
 
 /// Trait for mapping an input value to an output value.
 pub trait Mapper {
     /// Maps the input value to an output value.
     fn transform(&self, input: u8) -> u8;
+
+    /// Maps every value of `input` into the matching position of `out`.
+    ///
+    /// # Panics
+    /// Panics if `input` and `out` have different lengths.
+    fn transform_slice(&self, input: &[u8], out: &mut [u8]) {
+        assert_eq!(input.len(), out.len(), "input and output lengths differ");
+        for (o, &i) in out.iter_mut().zip(input) {
+            *o = self.transform(i);
+        }
+    }
+
+    /// Maps every value of `buf` in place.
+    fn transform_in_place(&self, buf: &mut [u8]) {
+        for b in buf.iter_mut() {
+            *b = self.transform(*b);
+        }
+    }
+}
+
+/// Trait for mapping a 16-bit input value to a 16-bit output value, the wide counterpart of
+/// `Mapper`.
+pub trait WideMapper {
+    /// Maps the input value to an output value.
+    fn transform(&self, input: u16) -> u16;
+
+    /// Maps every value of `input` into the matching position of `out`.
+    ///
+    /// # Panics
+    /// Panics if `input` and `out` have different lengths.
+    fn transform_slice(&self, input: &[u16], out: &mut [u16]) {
+        assert_eq!(input.len(), out.len(), "input and output lengths differ");
+        for (o, &i) in out.iter_mut().zip(input) {
+            *o = self.transform(i);
+        }
+    }
+
+    /// Maps every value of `buf` in place.
+    fn transform_in_place(&self, buf: &mut [u16]) {
+        for b in buf.iter_mut() {
+            *b = self.transform(*b);
+        }
+    }
 }
 
 /// Trait for combining multiple input values into a single output value.
@@ -11,4 +62,479 @@ pub trait Combinator {
     fn combine(&self, inputs: &[u8]) -> u8;
 
     fn identifier(&self) -> String;
+
+    /// Returns the argument string that recreates this combinator's configuration,
+    /// in the format accepted after the colon by `combinator_from_str`.
+    /// Combinators without configuration return `None`.
+    fn parameters(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns a readable summary of the combinator and its configuration for logs and labels,
+    /// such as `threshold(cutoff=300, low=0, high=255)`. By default this is the identifier,
+    /// followed by `parameters` in parentheses when there are any.
+    fn describe(&self) -> String {
+        match self.parameters() {
+            Some(parameters) => format!("{}({parameters})", self.identifier()),
+            None => self.identifier(),
+        }
+    }
+
+    /// Returns the fewest inputs this combinator produces a meaningful result for.
+    fn min_inputs(&self) -> usize {
+        0
+    }
+
+    /// Returns the most inputs this combinator accepts, or `None` when unbounded.
+    fn max_inputs(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns false if `combine` can give different outputs for the same inputs, as noisy
+    /// combinators do. Passes such as `Network::prune` only fold deterministic combinators.
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    /// Combines the inputs after checking their count against `min_inputs` and `max_inputs`.
+    fn combine_checked(&self, inputs: &[u8]) -> Result<u8, ArityError> {
+        check_arity(self, inputs.len())?;
+        Ok(self.combine(inputs))
+    }
+}
+
+/// Trait for reductions whose exact result may not fit in a byte, such as a sum of many inputs.
+/// Wrap one in `combinator_wide::Narrowed` to use it as a `Combinator`.
+pub trait WideCombinator {
+    /// Combines a slice of input values into a single wide output value.
+    fn combine_wide(&self, inputs: &[u8]) -> u32;
+}
+
+/// A combinator that can consume its inputs one at a time instead of from a slice.
+/// Feeding every input to `push` and then calling `finish` gives the same result as `combine`.
+pub trait StreamingCombinator: Combinator {
+    /// The running state kept between inputs.
+    type Accumulator;
+
+    /// Returns the state for an empty input sequence.
+    fn begin(&self) -> Self::Accumulator;
+
+    /// Adds one input to the running state.
+    fn push(&self, acc: &mut Self::Accumulator, value: u8);
+
+    /// Returns the combined value of every input pushed so far.
+    fn finish(&self, acc: Self::Accumulator) -> u8;
+
+    /// Combines every value yielded by `values` without collecting them first.
+    fn combine_iter<I: IntoIterator<Item = u8>>(&self, values: I) -> u8
+    where
+        Self: Sized,
+    {
+        let mut acc = self.begin();
+        for value in values {
+            self.push(&mut acc, value);
+        }
+        self.finish(acc)
+    }
+}
+
+/// A combinator whose output depends on the inputs of earlier calls as well as the current one.
+pub trait StatefulCombinator {
+    /// Combines the inputs of one time step and updates the internal state.
+    fn step(&mut self, inputs: &[u8]) -> u8;
+
+    /// Forgets every earlier step.
+    fn reset(&mut self);
+
+    /// Returns the state kept between steps as bytes, so it can be saved in a checkpoint.
+    fn save_state(&self) -> Vec<u8>;
+
+    /// Restores a state returned by `save_state`. Returns false and leaves the state
+    /// unchanged if `state` is not one this combinator could have saved.
+    fn load_state(&mut self, state: &[u8]) -> bool;
+}
+
+/// Checks whether `combinator` accepts `count` inputs.
+pub fn check_arity<C: Combinator + ?Sized>(combinator: &C, count: usize) -> Result<(), ArityError> {
+    let min = combinator.min_inputs();
+    let max = combinator.max_inputs();
+    if count < min || max.is_some_and(|max| count > max) {
+        return Err(ArityError {
+            identifier: combinator.identifier(),
+            min,
+            max,
+            actual: count,
+        });
+    }
+    Ok(())
+}
+
+/// Error returned when a combinator receives a number of inputs outside its bounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArityError {
+    /// The identifier of the combinator.
+    pub identifier: String,
+    /// The fewest inputs accepted.
+    pub min: usize,
+    /// The most inputs accepted, if bounded.
+    pub max: Option<usize>,
+    /// The number of inputs received.
+    pub actual: usize,
+}
+
+impl core::fmt::Display for ArityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(
+                f,
+                "combinator {:?} takes exactly {} inputs, got {}",
+                self.identifier, self.min, self.actual
+            ),
+            Some(max) => write!(
+                f,
+                "combinator {:?} takes {} to {} inputs, got {}",
+                self.identifier, self.min, max, self.actual
+            ),
+            None => write!(
+                f,
+                "combinator {:?} takes at least {} inputs, got {}",
+                self.identifier, self.min, self.actual
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ArityError {}
+
+// Pointer types forward to the value they point at, so `&MapperNode`, `Box<dyn Mapper>` and friends
+// can be passed wherever a `Mapper` is expected.
+//
+// A blanket `impl<F: Fn(u8) -> u8> Mapper for F` would conflict with these, since `&F` and `Box<F>`
+// are closures themselves. Closures are wrapped in `FnMapper` / `FnCombinator` instead.
+
+impl<M: Mapper + ?Sized> Mapper for &M {
+    fn transform(&self, input: u8) -> u8 {
+        (**self).transform(input)
+    }
+    fn transform_slice(&self, input: &[u8], out: &mut [u8]) {
+        (**self).transform_slice(input, out)
+    }
+    fn transform_in_place(&self, buf: &mut [u8]) {
+        (**self).transform_in_place(buf)
+    }
+}
+
+impl<M: Mapper + ?Sized> Mapper for Box<M> {
+    fn transform(&self, input: u8) -> u8 {
+        (**self).transform(input)
+    }
+    fn transform_slice(&self, input: &[u8], out: &mut [u8]) {
+        (**self).transform_slice(input, out)
+    }
+    fn transform_in_place(&self, buf: &mut [u8]) {
+        (**self).transform_in_place(buf)
+    }
+}
+
+impl<M: Mapper + ?Sized> Mapper for Rc<M> {
+    fn transform(&self, input: u8) -> u8 {
+        (**self).transform(input)
+    }
+    fn transform_slice(&self, input: &[u8], out: &mut [u8]) {
+        (**self).transform_slice(input, out)
+    }
+    fn transform_in_place(&self, buf: &mut [u8]) {
+        (**self).transform_in_place(buf)
+    }
+}
+
+impl<M: Mapper + ?Sized> Mapper for Arc<M> {
+    fn transform(&self, input: u8) -> u8 {
+        (**self).transform(input)
+    }
+    fn transform_slice(&self, input: &[u8], out: &mut [u8]) {
+        (**self).transform_slice(input, out)
+    }
+    fn transform_in_place(&self, buf: &mut [u8]) {
+        (**self).transform_in_place(buf)
+    }
+}
+
+impl<C: Combinator + ?Sized> Combinator for &C {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        (**self).combine(inputs)
+    }
+    fn identifier(&self) -> String {
+        (**self).identifier()
+    }
+    fn parameters(&self) -> Option<String> {
+        (**self).parameters()
+    }
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+    fn min_inputs(&self) -> usize {
+        (**self).min_inputs()
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        (**self).max_inputs()
+    }
+    fn is_deterministic(&self) -> bool {
+        (**self).is_deterministic()
+    }
+}
+
+impl<C: Combinator + ?Sized> Combinator for Box<C> {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        (**self).combine(inputs)
+    }
+    fn identifier(&self) -> String {
+        (**self).identifier()
+    }
+    fn parameters(&self) -> Option<String> {
+        (**self).parameters()
+    }
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+    fn min_inputs(&self) -> usize {
+        (**self).min_inputs()
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        (**self).max_inputs()
+    }
+    fn is_deterministic(&self) -> bool {
+        (**self).is_deterministic()
+    }
+}
+
+impl<C: Combinator + ?Sized> Combinator for Rc<C> {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        (**self).combine(inputs)
+    }
+    fn identifier(&self) -> String {
+        (**self).identifier()
+    }
+    fn parameters(&self) -> Option<String> {
+        (**self).parameters()
+    }
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+    fn min_inputs(&self) -> usize {
+        (**self).min_inputs()
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        (**self).max_inputs()
+    }
+    fn is_deterministic(&self) -> bool {
+        (**self).is_deterministic()
+    }
+}
+
+impl<C: Combinator + ?Sized> Combinator for Arc<C> {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        (**self).combine(inputs)
+    }
+    fn identifier(&self) -> String {
+        (**self).identifier()
+    }
+    fn parameters(&self) -> Option<String> {
+        (**self).parameters()
+    }
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+    fn min_inputs(&self) -> usize {
+        (**self).min_inputs()
+    }
+    fn max_inputs(&self) -> Option<usize> {
+        (**self).max_inputs()
+    }
+    fn is_deterministic(&self) -> bool {
+        (**self).is_deterministic()
+    }
+}
+
+/// A `Mapper` backed by a closure.
+#[derive(Clone, Copy)]
+pub struct FnMapper<F>(pub F);
+
+impl<F: Fn(u8) -> u8> Mapper for FnMapper<F> {
+    fn transform(&self, input: u8) -> u8 {
+        (self.0)(input)
+    }
+}
+
+/// A `Combinator` backed by a closure.
+#[derive(Clone, Copy)]
+pub struct FnCombinator<F>(pub F);
+
+impl<F> FnCombinator<F> {
+    pub const IDENTIFIER: &'static str = "fn";
+}
+
+impl<F: Fn(&[u8]) -> u8> Combinator for FnCombinator<F> {
+    fn combine(&self, inputs: &[u8]) -> u8 {
+        (self.0)(inputs)
+    }
+    fn identifier(&self) -> String {
+        Self::IDENTIFIER.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::combinator::AdditionCombinatorNode;
+    #[cfg(feature = "std")]
+    use crate::neural::io::MappedReader;
+    use crate::neural::iter::MapperIterExt;
+    use crate::neural::mapper::MapperNode;
+    #[cfg(feature = "std")]
+    use std::io::Read;
+
+    fn apply(mapper: &dyn Mapper, input: u8) -> u8 {
+        mapper.transform(input)
+    }
+
+    fn reduce(combinator: &dyn Combinator, inputs: &[u8]) -> u8 {
+        combinator.combine(inputs)
+    }
+
+    #[test]
+    fn test_fn_mapper() {
+        let double = FnMapper(|x: u8| x.wrapping_mul(2));
+        assert_eq!(apply(&double, 21), 42);
+        assert_eq!(apply(&&double, 21), 42);
+        assert_eq!(apply(&Box::new(double), 21), 42);
+        assert_eq!(apply(&Arc::new(double), 21), 42);
+
+        let doubled: Vec<u8> = [1u8, 2, 3].into_iter().mapped(&double).collect();
+        assert_eq!(doubled, vec![2, 4, 6]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_mapped_reader_accepts_trait_mappers() {
+        let double = FnMapper(|x: u8| x.wrapping_mul(2));
+        let mut reader = MappedReader::new(&b"abc"[..], double);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, vec![b'a' * 2, b'b' * 2, b'c' * 2]);
+
+        let node = MapperNode::new().with_fill(7);
+        let mut reader = MappedReader::new(&b"ab"[..], &node);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, vec![7, 7]);
+    }
+
+    #[test]
+    fn test_default_and_overridden_batch_methods() {
+        let f = |x: u8| x.wrapping_mul(13).wrapping_add(7);
+        let closure: &dyn Mapper = &FnMapper(f);
+        let node = MapperNode::new_transformation(|x| f(x as u8));
+        let table: &dyn Mapper = &node;
+
+        let input: Vec<u8> = (0u8..=u8::MAX).chain(0u8..100).collect();
+        let mut by_closure = vec![0; input.len()];
+        let mut by_table = vec![0; input.len()];
+        closure.transform_slice(&input, &mut by_closure);
+        table.transform_slice(&input, &mut by_table);
+        assert_eq!(by_closure, by_table);
+        assert!(input.iter().zip(&by_closure).all(|(&i, &o)| f(i) == o));
+
+        let mut in_place_closure = input.clone();
+        let mut in_place_table = input.clone();
+        closure.transform_in_place(&mut in_place_closure);
+        table.transform_in_place(&mut in_place_table);
+        assert_eq!(in_place_closure, by_closure);
+        assert_eq!(in_place_table, by_closure);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_default_transform_slice_length_mismatch() {
+        let mut out = [0u8; 1];
+        FnMapper(|x: u8| x).transform_slice(&[1, 2], &mut out);
+    }
+
+    #[test]
+    fn test_pointer_mappers() {
+        let node = MapperNode::new().with_fill(7);
+        let boxed: Box<dyn Mapper> = Box::new(MapperNode::new().with_fill(7));
+        let shared: Arc<dyn Mapper> = Arc::new(MapperNode::new().with_fill(7));
+        let local: Rc<dyn Mapper> = Rc::new(MapperNode::new().with_fill(7));
+        assert_eq!(apply(&&node, 1), 7);
+        assert_eq!(apply(&boxed, 1), 7);
+        assert_eq!(apply(&shared, 1), 7);
+        assert_eq!(apply(&local, 1), 7);
+    }
+
+    #[test]
+    fn test_fn_combinator() {
+        let max = FnCombinator(|inputs: &[u8]| inputs.iter().copied().max().unwrap_or(0));
+        assert_eq!(reduce(&max, &[3, 9, 4]), 9);
+        assert_eq!(max.identifier(), "fn");
+        let boxed: Box<dyn Combinator> = Box::new(max);
+        assert_eq!(reduce(&boxed, &[3, 9, 4]), 9);
+    }
+
+    /// A combinator that only accepts exactly two inputs.
+    struct Difference;
+
+    impl Combinator for Difference {
+        fn combine(&self, inputs: &[u8]) -> u8 {
+            inputs[0].abs_diff(inputs[1])
+        }
+        fn identifier(&self) -> String {
+            "difference".to_string()
+        }
+        fn min_inputs(&self) -> usize {
+            2
+        }
+        fn max_inputs(&self) -> Option<usize> {
+            Some(2)
+        }
+    }
+
+    #[test]
+    fn test_default_arity_accepts_anything() {
+        let max = FnCombinator(|inputs: &[u8]| inputs.iter().copied().max().unwrap_or(0));
+        assert_eq!(max.min_inputs(), 0);
+        assert_eq!(max.max_inputs(), None);
+        assert_eq!(max.combine_checked(&[]), Ok(0));
+        assert_eq!(max.combine_checked(&[1; 1000]), Ok(1));
+    }
+
+    #[test]
+    fn test_overridden_arity() {
+        let boxed: Box<dyn Combinator> = Box::new(Difference);
+        assert_eq!(boxed.combine_checked(&[9, 4]), Ok(5));
+        assert_eq!(
+            boxed.combine_checked(&[9]),
+            Err(ArityError {
+                identifier: "difference".to_string(),
+                min: 2,
+                max: Some(2),
+                actual: 1
+            })
+        );
+        let error = boxed.combine_checked(&[1, 2, 3]).unwrap_err();
+        assert_eq!(error.actual, 3);
+        assert_eq!(
+            error.to_string(),
+            "combinator \"difference\" takes exactly 2 inputs, got 3"
+        );
+    }
+
+    #[test]
+    fn test_pointer_combinators() {
+        let boxed: Box<dyn Combinator> = Box::new(AdditionCombinatorNode::new());
+        let shared: Arc<dyn Combinator> = Arc::new(AdditionCombinatorNode::new());
+        assert_eq!(reduce(&boxed, &[1, 2]), 3);
+        assert_eq!(reduce(&shared, &[1, 2]), 3);
+        assert_eq!(boxed.identifier(), AdditionCombinatorNode::IDENTIFIER);
+    }
 }
@@ -0,0 +1,108 @@
+//! Drives the `snn-run` binary on the fixture files.
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+fn fixture(name: &str) -> String {
+    format!("{FIXTURES}/{name}")
+}
+
+fn snn_run(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_snn-run"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The binary may exit on a bad argument before reading stdin, closing the pipe early.
+    let _ = child.stdin.take().unwrap().write_all(stdin);
+    child.wait_with_output().unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn single_byte_mode_from_stdin() {
+    let output = snn_run(&["--network", &fixture("uppercase.json")], b"Hello, world!");
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(output.stdout, b"HELLO, WORLD!");
+}
+
+#[test]
+fn window_mode_from_file() {
+    let network = fixture("max3.snnb");
+    let input = fixture("hello.txt");
+    let output = snn_run(
+        &["--network", &network, "--input", &input, "--window", "3"],
+        b"",
+    );
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(output.stdout, b"llooowwwrrl");
+
+    let output = snn_run(
+        &[
+            "--network",
+            &network,
+            "--input",
+            &input,
+            "--window",
+            "3",
+            "--stride",
+            "3",
+            "--pad",
+        ],
+        b"",
+    );
+    assert_eq!(output.stdout, b"lowr!");
+}
+
+#[test]
+fn trace_goes_to_stderr() {
+    let output = snn_run(&["--network", &fixture("uppercase.json"), "--trace"], b"ab");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"AB");
+    assert_eq!(stderr(&output), "[97] -> [65]\n[98] -> [66]\n");
+}
+
+#[test]
+fn missing_network_file() {
+    let output = snn_run(&["--network", &fixture("missing.json")], b"x");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).starts_with("snn-run: cannot read network "));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn malformed_network() {
+    let output = snn_run(&["--network", &fixture("malformed.json")], b"x");
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stderr(&output).contains("invalid network"));
+}
+
+#[test]
+fn width_mismatch() {
+    let output = snn_run(&["--network", &fixture("max3.snnb")], b"x");
+    assert_eq!(output.status.code(), Some(5));
+    assert_eq!(
+        stderr(&output),
+        "snn-run: network takes 3 inputs; use --window 3 to feed it windows\n"
+    );
+    let output = snn_run(&["--network", &fixture("max3.snnb"), "--window", "2"], b"x");
+    assert_eq!(output.status.code(), Some(5));
+}
+
+#[test]
+fn bad_arguments() {
+    assert_eq!(snn_run(&[], b"").status.code(), Some(2));
+    assert_eq!(snn_run(&["--bogus"], b"").status.code(), Some(2));
+    let output = snn_run(&["--network", "x", "--window", "0"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--window needs a positive number"));
+    let help = snn_run(&["--help"], b"");
+    assert_eq!(help.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&help.stdout).starts_with("usage: snn-run"));
+}